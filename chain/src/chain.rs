@@ -20,7 +20,7 @@ use ckb_types::{
             ResolvedTransaction,
         },
         hardfork::HardForks,
-        service::{Request, DEFAULT_CHANNEL_SIZE},
+        service::{ChainReorg, Request, DEFAULT_CHANNEL_SIZE},
         BlockExt, BlockNumber, BlockView, Cycle, HeaderView,
     },
     packed::{Byte32, ProposalShortId},
@@ -386,8 +386,8 @@ impl ChainService {
 
     fn insert_block(&mut self, block: Arc<BlockView>, switch: Switch) -> Result<bool, Error> {
         let db_txn = Arc::new(self.shared.store().begin_transaction());
-        let txn_snapshot = db_txn.get_snapshot();
-        let _snapshot_tip_hash = db_txn.get_update_for_tip_hash(&txn_snapshot);
+        let txn_snapshot = self.shared.store().get_snapshot();
+        let _snapshot_tip_hash = db_txn.get_update_for_tip_hash();
 
         // insert_block are assumed be executed in single thread
         if txn_snapshot.block_exists(&block.header().hash()) {
@@ -519,6 +519,10 @@ impl ChainService {
                 }
             }
 
+            if fork.has_detached() {
+                self.notify_chain_reorg(&fork);
+            }
+
             let block_ref: &BlockView = &block;
             self.shared
                 .notify_controller()
@@ -552,6 +556,28 @@ impl ChainService {
         Ok(true)
     }
 
+    fn notify_chain_reorg(&self, fork: &ForkChanges) {
+        let common_ancestor = fork
+            .detached_blocks()
+            .front()
+            .and_then(|blk| self.shared.store().get_block_header(&blk.parent_hash()))
+            .expect("common ancestor of detached blocks stored");
+        let reorg = ChainReorg {
+            common_ancestor,
+            detached_blocks: fork
+                .detached_blocks()
+                .iter()
+                .map(|blk| blk.hash())
+                .collect(),
+            attached_blocks: fork
+                .attached_blocks()
+                .iter()
+                .map(|blk| blk.hash())
+                .collect(),
+        };
+        self.shared.notify_controller().notify_chain_reorg(reorg);
+    }
+
     pub(crate) fn update_proposal_table(&mut self, fork: &ForkChanges) {
         for blk in fork.detached_blocks() {
             self.proposal_table.remove(blk.header().number());
@@ -511,3 +511,59 @@ fn test_fork_proposal_table() {
         proposals.gap()
     );
 }
+
+// 0--1--2
+// \
+//  \
+//   1--2--3
+#[test]
+fn test_notify_chain_reorg() {
+    let builder = SharedBuilder::with_temp_db();
+    let (shared, mut pack) = builder.consensus(Consensus::default()).build().unwrap();
+    let mut chain_service = ChainService::new(shared.clone(), pack.take_proposal_table());
+    let mut reorg_receiver = shared
+        .async_handle()
+        .block_on(shared.notify_controller().subscribe_chain_reorg("test"));
+    let genesis = shared
+        .store()
+        .get_block_header(&shared.store().get_block_hash(0).unwrap())
+        .unwrap();
+
+    let mock_store = MockStore::new(&genesis, shared.store());
+    let mut fork1 = MockChain::new(genesis.clone(), shared.consensus());
+    let mut fork2 = MockChain::new(genesis.clone(), shared.consensus());
+    for _ in 0..2 {
+        fork1.gen_empty_block_with_diff(100u64, &mock_store);
+    }
+    for _ in 0..2 {
+        fork2.gen_empty_block_with_diff(90u64, &mock_store);
+    }
+    fork2.gen_empty_block_with_diff(100u64, &mock_store);
+
+    // fork1 total_difficulty 200, fork2 total_difficulty 280
+    for blk in fork1.blocks().iter().chain(fork2.blocks()) {
+        chain_service
+            .process_block(Arc::new(blk.clone()), Switch::DISABLE_ALL)
+            .unwrap();
+    }
+
+    let reorg = reorg_receiver.blocking_recv().unwrap();
+    assert_eq!(reorg.common_ancestor, genesis);
+    assert_eq!(
+        reorg.detached_blocks,
+        fork1
+            .blocks()
+            .iter()
+            .map(|blk| blk.hash())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        reorg.attached_blocks,
+        fork2
+            .blocks()
+            .iter()
+            .map(|blk| blk.hash())
+            .collect::<Vec<_>>()
+    );
+    assert!(reorg_receiver.try_recv().is_err());
+}
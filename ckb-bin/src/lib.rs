@@ -133,9 +133,17 @@ fn run_app_inner(
         cli::CMD_REPLAY => subcommand::replay(setup.replay(matches)?, handle.clone()),
         cli::CMD_EXPORT => subcommand::export(setup.export(matches)?, handle.clone()),
         cli::CMD_IMPORT => subcommand::import(setup.import(matches)?, handle.clone()),
+        cli::CMD_EXPORT_CELL_SNAPSHOT => {
+            subcommand::export_cell_snapshot(setup.export_cell_snapshot(matches)?, handle.clone())
+        }
+        cli::CMD_FREEZER => subcommand::freezer(setup.freezer(matches)?, handle.clone()),
+        cli::CMD_BACKUP => subcommand::backup(setup.backup(matches)?, handle.clone()),
+        cli::CMD_DB_STATS => subcommand::db_stats(setup.db_stats()?, handle.clone()),
+        cli::CMD_COMPACT => subcommand::compact(setup.compact(matches)?, handle.clone()),
         cli::CMD_STATS => subcommand::stats(setup.stats(matches)?, handle.clone()),
         cli::CMD_RESET_DATA => subcommand::reset_data(setup.reset_data(matches)?),
         cli::CMD_MIGRATE => subcommand::migrate(setup.migrate(matches)?),
+        cli::CMD_PEER_STORE => subcommand::peer_store(setup.peer_store(matches)?),
         #[cfg(not(target_os = "windows"))]
         cli::CMD_DAEMON => subcommand::daemon(setup.daemon(matches)?),
         _ => unreachable!(),
@@ -169,9 +177,15 @@ fn is_silent_logging(cmd: &str) -> Silent {
         cmd,
         cli::CMD_EXPORT
             | cli::CMD_IMPORT
+            | cli::CMD_EXPORT_CELL_SNAPSHOT
+            | cli::CMD_FREEZER
+            | cli::CMD_BACKUP
+            | cli::CMD_DB_STATS
+            | cli::CMD_COMPACT
             | cli::CMD_STATS
             | cli::CMD_MIGRATE
             | cli::CMD_RESET_DATA
+            | cli::CMD_PEER_STORE
             | cli::CMD_DAEMON
     )
 }
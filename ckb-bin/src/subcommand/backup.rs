@@ -0,0 +1,40 @@
+use ckb_app_config::{BackupArgs, ExitCode};
+use ckb_async_runtime::Handle;
+use ckb_shared::SharedBuilder;
+use ckb_store::{backup_chain_db, BACKUP_ANCIENT_DIR, BACKUP_DB_DIR};
+
+pub fn backup(args: BackupArgs, async_handle: Handle) -> Result<(), ExitCode> {
+    let builder = SharedBuilder::new(
+        &args.config.bin_name,
+        args.config.root_dir.as_path(),
+        &args.config.db,
+        Some(args.config.ancient.clone()),
+        async_handle,
+        args.consensus,
+    )?
+    .store_config(args.config.store);
+    let (shared, _) = builder.build()?;
+
+    let manifest = backup_chain_db(shared.store(), &args.target).map_err(|err| {
+        eprintln!("Backup error: {err}");
+        ExitCode::Failure
+    })?;
+    println!(
+        "Backed up the chain at block {} {:#x} to {}",
+        manifest.tip_number,
+        manifest.tip_hash,
+        args.target.display()
+    );
+    println!("To restore the node, point the following options in ckb.toml to the backup");
+    println!(
+        "    [db] path = \"{}\"",
+        args.target.join(BACKUP_DB_DIR).display()
+    );
+    if manifest.frozen_number > 0 {
+        println!(
+            "    ancient = \"{}\"",
+            args.target.join(BACKUP_ANCIENT_DIR).display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use ckb_app_config::{CompactArgs, ExitCode};
+use ckb_async_runtime::Handle;
+use ckb_shared::SharedBuilder;
+use ckb_store::CompactionTask;
+use std::time::Duration;
+
+pub fn compact(args: CompactArgs, async_handle: Handle) -> Result<(), ExitCode> {
+    let mut task = CompactionTask::new(&args.columns)
+        .map_err(|err| {
+            eprintln!("Compaction error: {err}");
+            ExitCode::Cli
+        })?
+        .key_range(args.start_key, args.end_key);
+    if let Some(throttle) = args.throttle {
+        task = task.throttle(Duration::from_millis(throttle));
+    }
+
+    let builder = SharedBuilder::new(
+        &args.config.bin_name,
+        args.config.root_dir.as_path(),
+        &args.config.db,
+        None,
+        async_handle,
+        args.consensus,
+    )?;
+    let (shared, _) = builder.build()?;
+
+    let mut last_column = None;
+    let progress = task
+        .run(shared.store(), |progress| {
+            if let Some(column) = progress.current_column {
+                if last_column != Some(column) {
+                    println!(
+                        "Compacting {} ({}/{} steps)",
+                        column, progress.completed_steps, progress.total_steps
+                    );
+                    last_column = Some(column);
+                }
+            }
+            true
+        })
+        .map_err(|err| {
+            eprintln!("Compaction error: {err}");
+            ExitCode::Failure
+        })?;
+
+    let elapsed = progress
+        .finished_at
+        .unwrap_or(progress.started_at)
+        .saturating_sub(progress.started_at);
+    println!(
+        "Compacted {} column families in {}ms",
+        progress.columns.len(),
+        elapsed
+    );
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use ckb_app_config::{DBStatsArgs, ExitCode};
+use ckb_async_runtime::Handle;
+use ckb_shared::SharedBuilder;
+use ckb_store::DBStats;
+
+pub fn db_stats(args: DBStatsArgs, async_handle: Handle) -> Result<(), ExitCode> {
+    let builder = SharedBuilder::new(
+        &args.config.bin_name,
+        args.config.root_dir.as_path(),
+        &args.config.db,
+        Some(args.config.ancient.clone()),
+        async_handle,
+        args.consensus,
+    )?
+    .store_config(args.config.store);
+    let (shared, _) = builder.build()?;
+
+    let stats = DBStats::collect(shared.store()).map_err(|err| {
+        eprintln!("Collect database statistics error: {err}");
+        ExitCode::Failure
+    })?;
+
+    println!(
+        "{:>3} {:<32} {:>16} {:>16} {:>16} {:>12}",
+        "id", "column family", "keys", "live sst size", "pending bytes", "compression"
+    );
+    for cf in &stats.columns {
+        let ratio = cf
+            .compression_ratio()
+            .map(|ratio| format!("{ratio:.2}"))
+            .unwrap_or_else(|| "-".to_owned());
+        println!(
+            "{:>3} {:<32} {:>16} {:>16} {:>16} {:>12}",
+            cf.col,
+            cf.name(),
+            cf.estimate_num_keys,
+            cf.live_sst_files_size,
+            cf.estimate_pending_compaction_bytes,
+            ratio
+        );
+    }
+
+    if stats.freezer.is_empty() {
+        println!("\nThe freezer is disabled");
+        return Ok(());
+    }
+    println!(
+        "\n{:<16} {:>12} {:>8} {:>16} {:>16}",
+        "freezer table", "items", "files", "data size", "index size"
+    );
+    for table in &stats.freezer {
+        println!(
+            "{:<16} {:>12} {:>8} {:>16} {:>16}",
+            table.name, table.number, table.files, table.data_size, table.index_size
+        );
+    }
+    Ok(())
+}
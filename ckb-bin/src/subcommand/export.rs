@@ -13,8 +13,12 @@ pub fn export(args: ExportArgs, async_handle: Handle) -> Result<(), ExitCode> {
         args.consensus,
     )?;
     let (shared, _) = builder.build()?;
-    Export::new(shared, args.target).execute().map_err(|err| {
-        eprintln!("Export error: {err:?}");
-        ExitCode::Failure
-    })
+    Export::new(shared, args.target, args.chain_spec_hash)
+        .format(args.format)
+        .range(args.from, args.to)
+        .execute()
+        .map_err(|err| {
+            eprintln!("Export error: {err:?}");
+            ExitCode::Failure
+        })
 }
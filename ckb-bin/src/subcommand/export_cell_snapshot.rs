@@ -0,0 +1,63 @@
+use ckb_app_config::{BlockNumberOrHash, ExitCode, ExportCellSnapshotArgs};
+use ckb_async_runtime::Handle;
+use ckb_shared::SharedBuilder;
+use ckb_store::export_cell_snapshot as export;
+use std::fs;
+
+pub fn export_cell_snapshot(
+    args: ExportCellSnapshotArgs,
+    async_handle: Handle,
+) -> Result<(), ExitCode> {
+    let builder = SharedBuilder::new(
+        &args.config.bin_name,
+        args.config.root_dir.as_path(),
+        &args.config.db,
+        None,
+        async_handle,
+        args.consensus,
+    )?;
+    let (shared, _) = builder.build()?;
+
+    // Keeps the bodies of the blocks which are never frozen or pruned.
+    let bodies_from = shared
+        .ancient_block_number()
+        .map(|number| number + 1)
+        .unwrap_or(1);
+    let snapshot = shared.snapshot();
+    // The database keeps the live cells of the tip block only.
+    if let Some(block) = args.block {
+        let is_tip = match &block {
+            BlockNumberOrHash::Number(number) => *number == snapshot.tip_number(),
+            BlockNumberOrHash::Hash(hash) => *hash == snapshot.tip_hash(),
+        };
+        if !is_tip {
+            eprintln!(
+                "The cell snapshot can only be exported at the tip block {} {}, \
+                 but the block {} is specified",
+                snapshot.tip_number(),
+                snapshot.tip_hash(),
+                block
+            );
+            return Err(ExitCode::Failure);
+        }
+    }
+    fs::create_dir_all(&args.target)?;
+    let path = args.target.join(format!(
+        "{}-{}.cells",
+        shared.consensus().id,
+        snapshot.tip_number()
+    ));
+
+    let info = export(snapshot.as_ref(), bodies_from, &path).map_err(|err| {
+        eprintln!("Export cell snapshot error: {err}");
+        ExitCode::Failure
+    })?;
+    println!(
+        "Exported the cell snapshot at block {} {} to {}",
+        info.number,
+        info.block_hash,
+        path.display()
+    );
+    println!("Snapshot hash: {}", info.hash);
+    Ok(())
+}
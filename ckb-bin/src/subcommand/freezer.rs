@@ -0,0 +1,104 @@
+use ckb_app_config::{ExitCode, FreezerArgs};
+use ckb_async_runtime::Handle;
+use ckb_freezer::Freezer;
+use ckb_shared::SharedBuilder;
+use ckb_store::ChainStore;
+
+pub fn freezer(args: FreezerArgs, async_handle: Handle) -> Result<(), ExitCode> {
+    let path = args.config.ancient.clone();
+    if !args.repair {
+        let check = Freezer::check(path).map_err(|err| {
+            eprintln!("Freezer check error: {err}");
+            ExitCode::Failure
+        })?;
+        if check.is_intact() {
+            println!("The freezer is intact, {} blocks", check.last_good);
+            return Ok(());
+        }
+        if let Some(corruption) = check.corruption {
+            eprintln!("The freezer is corrupted at {corruption}");
+            eprintln!(
+                "The blocks above {} will be dropped by `ckb freezer repair`",
+                check.last_good
+            );
+        }
+        if let Some(corruption) = check.meta_corruption {
+            eprintln!("The freezer is corrupted at {corruption}");
+            eprintln!(
+                "The headers, block exts, transaction hashes and block epochs above {} will be dropped by `ckb freezer repair`",
+                check.meta_last_good
+            );
+        }
+        return Err(ExitCode::Failure);
+    }
+
+    let check = Freezer::repair(path.clone()).map_err(|err| {
+        eprintln!("Freezer repair error: {err}");
+        ExitCode::Failure
+    })?;
+    if check.is_intact() {
+        println!("The freezer is intact, {} blocks", check.last_good);
+        return Ok(());
+    }
+    if let Some(ref corruption) = check.meta_corruption {
+        println!("The freezer is corrupted at {corruption}");
+        println!(
+            "Truncated the headers, block exts, transaction hashes and block epochs to block {}",
+            check.meta_last_good
+        );
+        // they are wiped out from the database after freezing and can't be recovered
+        eprintln!(
+            "The headers and block exts of [{}, {}) are lost, resync the chain with `ckb reset-data --database`",
+            check.meta_last_good + 1,
+            check.meta_number
+        );
+    }
+    let corruption = match check.corruption {
+        // only the meta tables are corrupted
+        None => return Err(ExitCode::Failure),
+        Some(corruption) => corruption,
+    };
+    println!("The freezer is corrupted at {corruption}");
+    println!("Truncated the freezer to block {}", check.last_good);
+
+    if !args.config.store.freezer_enable {
+        println!("The freezer is disabled, skip re-freezing the dropped blocks");
+        return Ok(());
+    }
+
+    // The frozen blocks are wiped out from the database after freezing, only the blocks dropped
+    // before the wiping can be re-frozen. The headers are served by the intact meta tables.
+    let builder = SharedBuilder::new(
+        &args.config.bin_name,
+        args.config.root_dir.as_path(),
+        &args.config.db,
+        Some(path),
+        async_handle,
+        args.consensus,
+    )?
+    .store_config(args.config.store);
+    let (shared, _) = builder.build()?;
+    let store = shared.store();
+    let freezer = store.freezer().expect("freezer enabled");
+    freezer
+        .freeze(check.number, |number| {
+            store
+                .get_block_hash(number)
+                .and_then(|hash| store.get_unfrozen_block(&hash))
+        })
+        .map_err(|err| {
+            eprintln!("Freezer re-freeze error: {err}");
+            ExitCode::Failure
+        })?;
+
+    let frozen = freezer.number();
+    if frozen < check.number {
+        eprintln!(
+            "The blocks [{}, {}) are lost, resync the chain with `ckb reset-data --database`",
+            frozen, check.number
+        );
+        return Err(ExitCode::Failure);
+    }
+    println!("Re-froze the blocks [{}, {})", check.last_good + 1, frozen);
+    Ok(())
+}
@@ -1,26 +1,38 @@
+mod backup;
+mod compact;
 #[cfg(not(target_os = "windows"))]
 mod daemon;
+mod db_stats;
 mod export;
+mod export_cell_snapshot;
+mod freezer;
 mod import;
 mod init;
 mod list_hashes;
 mod migrate;
 mod miner;
 mod peer_id;
+mod peer_store;
 mod replay;
 mod reset_data;
 mod run;
 mod stats;
 
+pub use self::backup::backup;
+pub use self::compact::compact;
 #[cfg(not(target_os = "windows"))]
 pub use self::daemon::{check_process, daemon};
+pub use self::db_stats::db_stats;
 pub use self::export::export;
+pub use self::export_cell_snapshot::export_cell_snapshot;
+pub use self::freezer::freezer;
 pub use self::import::import;
 pub use self::init::init;
 pub use self::list_hashes::list_hashes;
 pub use self::migrate::migrate;
 pub use self::miner::miner;
 pub use self::peer_id::peer_id;
+pub use self::peer_store::peer_store;
 pub use self::replay::replay;
 pub use self::reset_data::reset_data;
 pub use self::run::run;
@@ -0,0 +1,455 @@
+use ckb_app_config::{ExitCode, PeerStoreAction, PeerStoreArgs};
+use ckb_network::{
+    extract_peer_id, is_onion_addr,
+    multiaddr::Multiaddr,
+    multiaddr_to_socketaddr,
+    peer_store::{
+        types::{AddrInfo, BannedAddr},
+        PeerStore, Score,
+    },
+    Flags,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::BufReader, path::Path};
+
+const DAY_MS: u64 = 24 * 3600 * 1000;
+
+const FLAG_NAMES: [(&str, Flags); 7] = [
+    ("compatibility", Flags::COMPATIBILITY),
+    ("discovery", Flags::DISCOVERY),
+    ("sync", Flags::SYNC),
+    ("relay", Flags::RELAY),
+    ("light-client", Flags::LIGHT_CLIENT),
+    ("block-filter", Flags::BLOCK_FILTER),
+    ("pruned", Flags::PRUNED),
+];
+
+/// The JSON format shared by `ckb peer-store dump` and `ckb peer-store import`
+#[derive(Serialize, Deserialize)]
+struct PeerStoreDump {
+    #[serde(default)]
+    addrs: Vec<AddrEntry>,
+    #[serde(default)]
+    banned_addrs: Vec<BannedAddr>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AddrEntry {
+    addr: Multiaddr,
+    #[serde(default = "default_flag_names")]
+    flags: Vec<String>,
+    // the default score is used if it's absent
+    #[serde(default)]
+    score: Option<Score>,
+    // the addresses never connected are dialed by the feeler first
+    #[serde(default)]
+    last_connected_at_ms: u64,
+    // the dialing history is not imported
+    #[serde(default, skip_deserializing)]
+    last_tried_at_ms: u64,
+    #[serde(default, skip_deserializing)]
+    attempts_count: u32,
+}
+
+fn default_flag_names() -> Vec<String> {
+    flag_names(Flags::COMPATIBILITY)
+}
+
+fn flag_names(flags: Flags) -> Vec<String> {
+    FLAG_NAMES
+        .iter()
+        .filter(|(_, flag)| flags.contains(*flag))
+        .map(|(name, _)| (*name).to_owned())
+        .collect()
+}
+
+fn parse_flags(names: &[String]) -> Result<Flags, String> {
+    names.iter().try_fold(Flags::empty(), |flags, name| {
+        FLAG_NAMES
+            .iter()
+            .find(|(flag_name, _)| *flag_name == name.as_str())
+            .map(|(_, flag)| flags | *flag)
+            .ok_or_else(|| format!("unknown flag {name:?}"))
+    })
+}
+
+pub fn peer_store(args: PeerStoreArgs) -> Result<(), ExitCode> {
+    let path = args.peer_store_path;
+    let mut peer_store = PeerStore::load_from_dir_or_default(&path);
+    let now_ms = ckb_systemtime::unix_time_as_millis();
+
+    match args.action {
+        PeerStoreAction::Dump => dump(&peer_store, now_ms),
+        PeerStoreAction::Import { source } => {
+            import(&mut peer_store, &source, now_ms)?;
+            save(&peer_store, &path)
+        }
+        PeerStoreAction::Prune {
+            older_than_days,
+            without_flags,
+        } => {
+            let required_flags = parse_flags(&without_flags).map_err(|err| {
+                eprintln!("Args Error: {err}");
+                ExitCode::Cli
+            })?;
+            let removed_addrs = prune(&mut peer_store, older_than_days, required_flags, now_ms);
+            println!(
+                "Removed {} addresses, {} addresses left",
+                removed_addrs.len(),
+                peer_store.addr_manager().count()
+            );
+            save(&peer_store, &path)
+        }
+    }
+}
+
+/// Removes the addresses not connected in the last `older_than_days` days, or without all the
+/// `required_flags`
+fn prune(
+    peer_store: &mut PeerStore,
+    older_than_days: Option<u64>,
+    required_flags: Flags,
+    now_ms: u64,
+) -> Vec<AddrInfo> {
+    let connected_after_ms =
+        older_than_days.map(|days| now_ms.saturating_sub(days.saturating_mul(DAY_MS)));
+    peer_store.mut_addr_manager().retain(|addr_info| {
+        connected_after_ms.map_or(true, |after_ms| addr_info.last_connected_at_ms >= after_ms)
+            && Flags::from_bits_truncate(addr_info.flags).contains(required_flags)
+    })
+}
+
+impl PeerStoreDump {
+    fn new(peer_store: &PeerStore, now_ms: u64) -> Self {
+        let score_config = peer_store.score_config();
+        let mut addrs: Vec<AddrEntry> = peer_store
+            .addr_manager()
+            .addrs_iter()
+            .map(|addr_info| AddrEntry {
+                addr: addr_info.addr.clone(),
+                flags: flag_names(Flags::from_bits_truncate(addr_info.flags)),
+                score: Some(score_config.decay(
+                    addr_info.score,
+                    addr_info.score_updated_at_ms,
+                    now_ms,
+                )),
+                last_connected_at_ms: addr_info.last_connected_at_ms,
+                last_tried_at_ms: addr_info.last_tried_at_ms,
+                attempts_count: addr_info.attempts_count,
+            })
+            .collect();
+        addrs.sort_by(|a, b| b.last_connected_at_ms.cmp(&a.last_connected_at_ms));
+        PeerStoreDump {
+            addrs,
+            banned_addrs: peer_store.ban_list().get_banned_addrs(),
+        }
+    }
+}
+
+fn dump(peer_store: &PeerStore, now_ms: u64) -> Result<(), ExitCode> {
+    let dump = PeerStoreDump::new(peer_store, now_ms);
+    let json = serde_json::to_string_pretty(&dump).map_err(|err| {
+        eprintln!("Peer store dump error: {err}");
+        ExitCode::Failure
+    })?;
+    println!("{json}");
+    Ok(())
+}
+
+fn import(peer_store: &mut PeerStore, source: &Path, now_ms: u64) -> Result<(), ExitCode> {
+    let file = File::open(source).map_err(|err| {
+        eprintln!("Failed to open {}: {err}", source.display());
+        ExitCode::IO
+    })?;
+    let dump: PeerStoreDump = serde_json::from_reader(BufReader::new(file)).map_err(|err| {
+        eprintln!("Failed to parse {}: {err}", source.display());
+        ExitCode::Config
+    })?;
+    let counts = import_dump(peer_store, dump, now_ms).map_err(|err| {
+        eprintln!("{err}");
+        ExitCode::Config
+    })?;
+    println!(
+        "Imported {} addresses and {} bans, skipped {} addresses",
+        counts.imported, counts.banned, counts.skipped
+    );
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ImportCounts {
+    imported: usize,
+    banned: usize,
+    skipped: usize,
+}
+
+/// Adds the addresses and the unexpired bans of the dump to the peer store
+fn import_dump(
+    peer_store: &mut PeerStore,
+    dump: PeerStoreDump,
+    now_ms: u64,
+) -> Result<ImportCounts, String> {
+    let mut counts = ImportCounts::default();
+    for banned_addr in dump.banned_addrs {
+        if banned_addr.ban_until > now_ms {
+            peer_store.mut_ban_list().ban(banned_addr);
+            counts.banned += 1;
+        }
+    }
+
+    let default_score = peer_store.score_config().default_score;
+    for entry in dump.addrs {
+        let flags = parse_flags(&entry.flags)
+            .map_err(|err| format!("Invalid address {}: {err}", entry.addr))?;
+        let is_dialable =
+            multiaddr_to_socketaddr(&entry.addr).is_some() || is_onion_addr(&entry.addr);
+        if !is_dialable
+            || extract_peer_id(&entry.addr).is_none()
+            || peer_store.is_addr_banned(&entry.addr)
+        {
+            eprintln!("Skip the address {}", entry.addr);
+            counts.skipped += 1;
+            continue;
+        }
+        let mut addr_info = AddrInfo::new(
+            entry.addr,
+            entry.last_connected_at_ms.min(now_ms),
+            entry.score.unwrap_or(default_score),
+            flags.bits(),
+        );
+        addr_info.score_updated_at_ms = now_ms;
+        peer_store.mut_addr_manager().add(addr_info);
+        counts.imported += 1;
+    }
+    Ok(counts)
+}
+
+fn save(peer_store: &PeerStore, path: &Path) -> Result<(), ExitCode> {
+    peer_store.dump_to_dir(path).map_err(|err| {
+        eprintln!("Failed to save the peer store to {}: {err}", path.display());
+        ExitCode::IO
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_network::PeerId;
+
+    const HOUR_MS: u64 = 3600 * 1000;
+
+    fn new_addr(ip: &str) -> Multiaddr {
+        format!("/ip4/{ip}/tcp/8115/p2p/{}", PeerId::random().to_base58())
+            .parse()
+            .unwrap()
+    }
+
+    fn add_addr(
+        peer_store: &mut PeerStore,
+        addr: &Multiaddr,
+        last_connected_at_ms: u64,
+        score: Score,
+        flags: Flags,
+        score_updated_at_ms: u64,
+    ) {
+        let mut addr_info = AddrInfo::new(addr.clone(), last_connected_at_ms, score, flags.bits());
+        addr_info.score_updated_at_ms = score_updated_at_ms;
+        peer_store.mut_addr_manager().add(addr_info);
+    }
+
+    fn banned_addr(address: &str, ban_until: u64) -> BannedAddr {
+        BannedAddr {
+            address: address.parse().unwrap(),
+            ban_until,
+            ban_reason: "test".to_owned(),
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_dump_import_round_trip() {
+        let now_ms = ckb_systemtime::unix_time_as_millis();
+        let mut peer_store = PeerStore::default();
+        let sync = new_addr("1.1.1.1");
+        let relay = new_addr("2.2.2.2");
+        let never_connected = new_addr("3.3.3.3");
+        add_addr(
+            &mut peer_store,
+            &sync,
+            now_ms - HOUR_MS,
+            150,
+            Flags::SYNC | Flags::DISCOVERY,
+            now_ms,
+        );
+        // the score decays towards the default score by half in 6 hours
+        add_addr(
+            &mut peer_store,
+            &relay,
+            now_ms - 2 * HOUR_MS,
+            180,
+            Flags::RELAY,
+            now_ms - 6 * HOUR_MS,
+        );
+        add_addr(
+            &mut peer_store,
+            &never_connected,
+            0,
+            100,
+            Flags::COMPATIBILITY,
+            now_ms,
+        );
+        peer_store
+            .mut_ban_list()
+            .ban(banned_addr("4.4.4.4/32", now_ms + HOUR_MS));
+        peer_store
+            .mut_ban_list()
+            .ban(banned_addr("5.5.0.0/16", now_ms + 2 * HOUR_MS));
+
+        let json = serde_json::to_string(&PeerStoreDump::new(&peer_store, now_ms)).unwrap();
+        let dump: PeerStoreDump = serde_json::from_str(&json).unwrap();
+        // the most recently connected first
+        let addrs: Vec<_> = dump.addrs.iter().map(|entry| entry.addr.clone()).collect();
+        assert_eq!(
+            addrs,
+            vec![sync.clone(), relay.clone(), never_connected.clone()]
+        );
+
+        let mut imported = PeerStore::default();
+        let counts = import_dump(&mut imported, dump, now_ms).unwrap();
+        assert_eq!(
+            counts,
+            ImportCounts {
+                imported: 3,
+                banned: 2,
+                skipped: 0,
+            }
+        );
+
+        for (addr, last_connected_at_ms, score, flags) in [
+            (&sync, now_ms - HOUR_MS, 150, Flags::SYNC | Flags::DISCOVERY),
+            (&relay, now_ms - 2 * HOUR_MS, 140, Flags::RELAY),
+            (&never_connected, 0, 100, Flags::COMPATIBILITY),
+        ] {
+            let addr_info = imported.addr_manager().get(addr).expect("imported");
+            assert_eq!(addr_info.last_connected_at_ms, last_connected_at_ms);
+            assert_eq!(addr_info.score, score);
+            assert_eq!(addr_info.score_updated_at_ms, now_ms);
+            assert_eq!(Flags::from_bits_truncate(addr_info.flags), flags);
+            // the dialing history is not imported
+            assert_eq!(addr_info.attempts_count, 0);
+        }
+
+        let mut banned_addrs = imported.ban_list().get_banned_addrs();
+        banned_addrs.sort_by_key(|banned_addr| banned_addr.ban_until);
+        assert_eq!(
+            banned_addrs,
+            vec![
+                banned_addr("4.4.4.4/32", now_ms + HOUR_MS),
+                banned_addr("5.5.0.0/16", now_ms + 2 * HOUR_MS),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_skips_invalid_entries() {
+        let now_ms = ckb_systemtime::unix_time_as_millis();
+        let banned = new_addr("5.5.5.5");
+        let json = format!(
+            r#"{{
+                "addrs": [
+                    {{ "addr": "{}", "last_connected_at_ms": {} }},
+                    {{ "addr": "/ip4/6.6.6.6/tcp/8115" }},
+                    {{ "addr": "{}" }}
+                ],
+                "banned_addrs": [
+                    {{ "address": "5.5.0.0/16", "ban_until": {}, "ban_reason": "", "created_at": 0 }},
+                    {{ "address": "7.7.7.7/32", "ban_until": {}, "ban_reason": "", "created_at": 0 }}
+                ]
+            }}"#,
+            new_addr("1.1.1.1"),
+            now_ms + HOUR_MS,
+            banned,
+            now_ms + HOUR_MS,
+            now_ms - 1,
+        );
+        let dump: PeerStoreDump = serde_json::from_str(&json).unwrap();
+        let mut peer_store = PeerStore::default();
+        let counts = import_dump(&mut peer_store, dump, now_ms).unwrap();
+        // the address without peer id and the banned address are skipped, the expired ban is
+        // dropped
+        assert_eq!(
+            counts,
+            ImportCounts {
+                imported: 1,
+                banned: 1,
+                skipped: 2,
+            }
+        );
+        let addr_info = peer_store.addr_manager().addrs_iter().next().unwrap();
+        // the defaults of the absent fields, and the time in the future is capped
+        assert_eq!(addr_info.last_connected_at_ms, now_ms);
+        assert_eq!(addr_info.score, peer_store.score_config().default_score);
+        assert_eq!(
+            Flags::from_bits_truncate(addr_info.flags),
+            Flags::COMPATIBILITY
+        );
+
+        let dump: PeerStoreDump = serde_json::from_str(
+            r#"{ "addrs": [{ "addr": "/ip4/1.1.1.1/tcp/8115", "flags": ["unknown"] }] }"#,
+        )
+        .unwrap();
+        assert!(import_dump(&mut PeerStore::default(), dump, now_ms).is_err());
+    }
+
+    #[test]
+    fn test_prune() {
+        let now_ms = 30 * DAY_MS;
+        let mut peer_store = PeerStore::default();
+        let recent_sync = new_addr("1.1.1.1");
+        let recent = new_addr("2.2.2.2");
+        let old_sync = new_addr("3.3.3.3");
+        let never_connected = new_addr("4.4.4.4");
+        add_addr(
+            &mut peer_store,
+            &recent_sync,
+            now_ms - DAY_MS,
+            100,
+            Flags::SYNC | Flags::RELAY,
+            0,
+        );
+        add_addr(
+            &mut peer_store,
+            &recent,
+            now_ms - 7 * DAY_MS,
+            100,
+            Flags::RELAY,
+            0,
+        );
+        add_addr(
+            &mut peer_store,
+            &old_sync,
+            now_ms - 7 * DAY_MS - 1,
+            100,
+            Flags::SYNC,
+            0,
+        );
+        add_addr(&mut peer_store, &never_connected, 0, 100, Flags::SYNC, 0);
+
+        let removed: Vec<_> = prune(&mut peer_store, Some(7), Flags::empty(), now_ms)
+            .into_iter()
+            .map(|addr_info| addr_info.addr)
+            .collect();
+        assert_eq!(removed.len(), 2);
+        assert!(removed.contains(&old_sync));
+        assert!(removed.contains(&never_connected));
+
+        let removed = prune(&mut peer_store, None, Flags::SYNC, now_ms);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].addr, recent);
+        assert_eq!(peer_store.addr_manager().count(), 1);
+        assert!(peer_store.addr_manager().get(&recent_sync).is_some());
+
+        // nothing is removed without criteria
+        assert!(prune(&mut peer_store, None, Flags::empty(), now_ms).is_empty());
+    }
+}
@@ -1,16 +1,51 @@
 use ckb_app_config::{ExitCode, ReplayArgs};
-use ckb_async_runtime::Handle;
+use ckb_async_runtime::{tokio::sync::RwLock, Handle};
 use ckb_chain::chain::ChainService;
 use ckb_chain_iter::ChainIterator;
+use ckb_chain_spec::consensus::Consensus;
+use ckb_dao::DaoCalculator;
+use ckb_error::{Error, InternalErrorKind};
+use ckb_freezer::Freezer;
 use ckb_instrument::{ProgressBar, ProgressStyle};
+use ckb_merkle_mountain_range::leaf_index_to_mmr_size;
 use ckb_shared::{Shared, SharedBuilder};
-use ckb_store::ChainStore;
+use ckb_store::{data_loader_wrapper::AsDataLoader, ChainStore, ReadOnlyChainDB};
+use ckb_types::{
+    core::{
+        cell::{
+            resolve_transaction, BlockCellProvider, CellMetaBuilder, CellProvider, CellStatus,
+            OverlayCellProvider, ResolvedTransaction,
+        },
+        BlockNumber, BlockView, Cycle, TransactionInfo,
+    },
+    packed::OutPoint,
+    prelude::*,
+    utilities::merkle_mountain_range::ChainRootMMR,
+    H256,
+};
+use ckb_verification::cache::{init_cache, TxVerificationCache};
+use ckb_verification_contextual::{ContextualBlockVerifier, VerifyContext};
 use ckb_verification_traits::Switch;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 const MIN_PROFILING_TIME: u64 = 5;
 
 pub fn replay(args: ReplayArgs, async_handle: Handle) -> Result<(), ExitCode> {
+    if let Some((from, to)) = args.verify {
+        return verify(args, from, to, async_handle);
+    }
+
+    let tmp_target = args.tmp_target.ok_or_else(|| {
+        eprintln!("Replay error: {:?}", "--tmp-target is required");
+        ExitCode::Cli
+    })?;
     let shared_builder = SharedBuilder::new(
         &args.config.bin_name,
         args.config.root_dir.as_path(),
@@ -23,14 +58,14 @@ pub fn replay(args: ReplayArgs, async_handle: Handle) -> Result<(), ExitCode> {
         .tx_pool_config(args.config.tx_pool.clone())
         .build()?;
 
-    if !args.tmp_target.is_dir() {
+    if !tmp_target.is_dir() {
         eprintln!(
             "Replay error: {:?}",
             "The specified path does not exist or not directory"
         );
         return Err(ExitCode::Failure);
     }
-    let tmp_db_dir = tempfile::tempdir_in(args.tmp_target).map_err(|err| {
+    let tmp_db_dir = tempfile::tempdir_in(tmp_target).map_err(|err| {
         eprintln!("Replay error: {err:?}");
         ExitCode::Failure
     })?;
@@ -166,3 +201,415 @@ fn sanity_check(shared: Shared, mut chain: ChainService, full_verification: bool
 
     println!("Finishing replay; please wait...");
 }
+
+/// A difference between the stored block and the verifying replay.
+#[derive(Serialize)]
+struct ReplayMismatch {
+    number: BlockNumber,
+    hash: H256,
+    #[serde(flatten)]
+    kind: MismatchKind,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum MismatchKind {
+    Cycles {
+        tx_index: usize,
+        expected: Cycle,
+        actual: Cycle,
+    },
+    Fee {
+        tx_index: usize,
+        expected: u64,
+        actual: u64,
+    },
+    Dao {
+        expected: H256,
+        actual: H256,
+    },
+    Error {
+        message: String,
+    },
+}
+
+fn verify(
+    args: ReplayArgs,
+    from: Option<u64>,
+    to: Option<u64>,
+    async_handle: Handle,
+) -> Result<(), ExitCode> {
+    let config = args.config;
+    // Both the database and the freezer are opened in read-only mode, so the verifying replay can
+    // share them with a running node.
+    let freezer = if config.store.freezer_enable {
+        Some(
+            Freezer::open_read_only(config.ancient.clone()).map_err(|err| {
+                eprintln!("Replay error: failed to open the freezer: {err}");
+                ExitCode::Failure
+            })?,
+        )
+    } else {
+        None
+    };
+    let store = ReadOnlyChainDB::open(&config.db.path, freezer, config.store)
+        .map_err(|err| {
+            eprintln!("Replay error: {err}");
+            ExitCode::Failure
+        })?
+        .ok_or_else(|| {
+            eprintln!(
+                "Replay error: the database {} does not exist",
+                config.db.path.display()
+            );
+            ExitCode::Failure
+        })?;
+    let store = Arc::new(store);
+    let consensus = Arc::new(args.consensus);
+
+    let tip_number = store
+        .get_tip_header()
+        .map(|header| header.number())
+        .unwrap_or_default();
+    let pruned_number = store.get_pruned_block_number().unwrap_or_default();
+    let from = [1, pruned_number, from.unwrap_or(1)]
+        .into_iter()
+        .max()
+        .expect("not empty");
+    let to = to
+        .map(|v| std::cmp::min(v, tip_number))
+        .unwrap_or(tip_number);
+
+    println!("Start verifying replay; re-verify blocks {from}..{to}:");
+    let txs_verify_cache = Arc::new(RwLock::new(init_cache()));
+    let pb = ProgressBar::new((from..=to).count() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+            )
+            .progress_chars("#>-"),
+    );
+    let (skipped, mismatches) = (from..=to)
+        .into_par_iter()
+        .map(|number| {
+            let verified =
+                verify_block(&store, &consensus, &async_handle, &txs_verify_cache, number);
+            pb.inc(1);
+            match verified {
+                Some(mismatches) => (0, mismatches),
+                None => (1, Vec::new()),
+            }
+        })
+        .reduce(
+            || (0, Vec::new()),
+            |(skipped, mut mismatches), (more_skipped, more_mismatches)| {
+                mismatches.extend(more_mismatches);
+                (skipped + more_skipped, mismatches)
+            },
+        );
+    pb.finish_with_message("finish");
+
+    write_report(&mismatches, args.report).map_err(|err| {
+        eprintln!("Replay error: failed to write the report: {err}");
+        ExitCode::Failure
+    })?;
+    println!(
+        "Verifying replay finished, blocks: {}, skipped: {}, mismatches: {}",
+        (from..=to).count(),
+        skipped,
+        mismatches.len()
+    );
+    if skipped > 0 {
+        println!(
+            "The skipped blocks spend the cells created in the pruned blocks, \
+             which have been consumed since then"
+        );
+    }
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(ExitCode::Failure)
+    }
+}
+
+/// Returns the mismatches of the block, or `None` if the block is skipped because its inputs
+/// depend on the pruned blocks.
+fn verify_block(
+    store: &Arc<ReadOnlyChainDB>,
+    consensus: &Arc<Consensus>,
+    handle: &Handle,
+    txs_verify_cache: &Arc<RwLock<TxVerificationCache>>,
+    number: BlockNumber,
+) -> Option<Vec<ReplayMismatch>> {
+    let block = match store
+        .get_block_hash(number)
+        .and_then(|hash| store.get_block(&hash))
+    {
+        Some(block) => block,
+        None => {
+            return Some(vec![ReplayMismatch {
+                number,
+                hash: H256::default(),
+                kind: MismatchKind::Error {
+                    message: "block not found".to_owned(),
+                },
+            }])
+        }
+    };
+    let kinds = match compare_block(store, consensus, handle, txs_verify_cache, &block) {
+        Ok(kinds) => kinds?,
+        Err(err) => vec![MismatchKind::Error {
+            message: err.to_string(),
+        }],
+    };
+    let mismatches = kinds
+        .into_iter()
+        .map(|kind| ReplayMismatch {
+            number,
+            hash: block.hash().unpack(),
+            kind,
+        })
+        .collect();
+    Some(mismatches)
+}
+
+/// Re-verifies the block and compares the results with the stored block ext and header.
+///
+/// Returns `None` if the inputs of the block depend on the pruned blocks.
+fn compare_block(
+    store: &Arc<ReadOnlyChainDB>,
+    consensus: &Arc<Consensus>,
+    handle: &Handle,
+    txs_verify_cache: &Arc<RwLock<TxVerificationCache>>,
+    block: &BlockView,
+) -> Result<Option<Vec<MismatchKind>>, Error> {
+    let ext = store
+        .get_block_ext(&block.hash())
+        .ok_or_else(|| InternalErrorKind::Database.other("block ext not found"))?;
+    let parent = store
+        .get_block_header(&block.parent_hash())
+        .ok_or_else(|| InternalErrorKind::Database.other("parent header not found"))?;
+
+    let context = VerifyContext::new(Arc::clone(store), Arc::clone(consensus));
+    let resolved = match resolve_block_transactions(store, block, &context)? {
+        Some(resolved) => resolved,
+        None => return Ok(None),
+    };
+    let mmr = ChainRootMMR::new(leaf_index_to_mmr_size(block.number() - 1), store.as_ref());
+    // The DAO field is compared below instead of failing the verification.
+    let verifier = ContextualBlockVerifier::new(
+        context,
+        handle,
+        Switch::DISABLE_DAOHEADER,
+        Arc::clone(txs_verify_cache),
+        &mmr,
+    );
+    let (_, completed) = verifier.verify(&resolved, block)?;
+
+    let mut mismatches = Vec::new();
+    // The cellbase is excluded from both the completed entries and the block ext.
+    for (index, entry) in completed.iter().enumerate() {
+        let tx_index = index + 1;
+        if let Some(&expected) = ext.cycles.as_ref().and_then(|cycles| cycles.get(index)) {
+            if expected != entry.cycles {
+                mismatches.push(MismatchKind::Cycles {
+                    tx_index,
+                    expected,
+                    actual: entry.cycles,
+                });
+            }
+        }
+        if let Some(expected) = ext.txs_fees.get(index) {
+            if *expected != entry.fee {
+                mismatches.push(MismatchKind::Fee {
+                    tx_index,
+                    expected: expected.as_u64(),
+                    actual: entry.fee.as_u64(),
+                });
+            }
+        }
+    }
+
+    let data_loader = store.borrow_as_data_loader();
+    let dao = DaoCalculator::new(consensus, &data_loader)
+        .dao_field(resolved.iter().map(AsRef::as_ref), &parent)?;
+    if dao != block.header().dao() {
+        mismatches.push(MismatchKind::Dao {
+            expected: block.header().dao().unpack(),
+            actual: dao.unpack(),
+        });
+    }
+    Ok(Some(mismatches))
+}
+
+/// Resolves the transactions of the block, returns `None` if some inputs are unknown because
+/// they may be created in the pruned blocks.
+fn resolve_block_transactions(
+    store: &ReadOnlyChainDB,
+    block: &BlockView,
+    context: &VerifyContext<ReadOnlyChainDB>,
+) -> Result<Option<Vec<Arc<ResolvedTransaction>>>, Error> {
+    let mut seen_inputs = HashSet::new();
+    let block_cp = BlockCellProvider::new(block)?;
+    let history_cp = HistoryCellProvider {
+        store,
+        number: block.number(),
+        pruned: store.get_pruned_block_number().unwrap_or_default() > 1,
+        unknown: Cell::new(false),
+    };
+    let cell_provider = OverlayCellProvider::new(&block_cp, &history_cp);
+    let resolved: Result<Vec<_>, Error> = block
+        .transactions()
+        .into_iter()
+        .map(|tx| {
+            resolve_transaction(tx, &mut seen_inputs, &cell_provider, context)
+                .map(Arc::new)
+                .map_err(Into::into)
+        })
+        .collect();
+    match resolved {
+        Err(_) if history_cp.pruned && history_cp.unknown.get() => Ok(None),
+        resolved => resolved.map(Some),
+    }
+}
+
+/// Provides the cells created before the block.
+///
+/// The consumed cells are not tracked, so a cell is provided even if it was consumed before the
+/// block, and the replay doesn't detect double spending. The stored blocks have been checked
+/// against double spending when they were attached to the chain.
+///
+/// The infos of the transactions in the pruned blocks are removed, so their cells are provided
+/// only if they are still live.
+struct HistoryCellProvider<'a> {
+    store: &'a ReadOnlyChainDB,
+    number: BlockNumber,
+    // Whether the bodies of the blocks below some number have been pruned.
+    pruned: bool,
+    // Whether some cells are unknown.
+    unknown: Cell<bool>,
+}
+
+impl<'a> CellProvider for HistoryCellProvider<'a> {
+    fn cell(&self, out_point: &OutPoint, _eager_load: bool) -> CellStatus {
+        let created_before = |info: &TransactionInfo| info.block_number < self.number;
+        let cell = self
+            .store
+            .get_transaction_with_info(&out_point.tx_hash())
+            .filter(|(_, info)| created_before(info))
+            .and_then(|(tx, info)| {
+                let index: usize = out_point.index().unpack();
+                tx.output_with_data(index).map(|(output, data)| {
+                    CellMetaBuilder::from_cell_output(output, data)
+                        .out_point(out_point.clone())
+                        .transaction_info(info)
+                        .build()
+                })
+            })
+            .or_else(|| {
+                self.store
+                    .get_cell(out_point)
+                    .filter(|cell| cell.transaction_info.as_ref().map_or(false, created_before))
+            });
+        match cell {
+            Some(cell) => CellStatus::live_cell(cell),
+            None => {
+                self.unknown.set(true);
+                CellStatus::Unknown
+            }
+        }
+    }
+}
+
+fn write_report(mismatches: &[ReplayMismatch], report: Option<PathBuf>) -> io::Result<()> {
+    let mut writer: Box<dyn Write> = match report {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout()),
+    };
+    for mismatch in mismatches {
+        serde_json::to_writer(&mut writer, mismatch)?;
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_app_config::DBConfig;
+    use ckb_async_runtime::new_background_runtime;
+    use ckb_test_chain_utils::{MockChain, MockStore};
+
+    #[test]
+    fn verify_block_detects_mismatches() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let db_config = DBConfig {
+            path: tmp_dir.path().join("db"),
+            ..Default::default()
+        };
+        let handle = new_background_runtime();
+        let consensus = Consensus::default();
+        let (shared, mut pack) = SharedBuilder::new(
+            "ckb",
+            tmp_dir.path(),
+            &db_config,
+            None,
+            handle.clone(),
+            consensus.clone(),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let chain =
+            ChainService::new(shared.clone(), pack.take_proposal_table()).start::<&str>(None);
+
+        let genesis = consensus.genesis_block().header();
+        let mock_store = MockStore::new(&genesis, shared.store());
+        let mut mock_chain = MockChain::new(genesis, &consensus);
+        for _ in 0..3 {
+            mock_chain.gen_empty_block(&mock_store);
+        }
+        // the dao field of the tip is stored without being verified
+        let (tip, blocks) = mock_chain.blocks().split_last().unwrap();
+        let tip = tip.as_advanced_builder().dao([1u8; 32].pack()).build();
+        for block in blocks.iter().chain([&tip]) {
+            chain
+                .internal_process_block(Arc::new(block.clone()), Switch::DISABLE_ALL)
+                .unwrap();
+        }
+
+        // opened while the database is still opened by the chain
+        let store = ReadOnlyChainDB::open(&db_config.path, None, Default::default())
+            .unwrap()
+            .unwrap();
+        let store = Arc::new(store);
+        let consensus = Arc::new(consensus);
+        let txs_verify_cache = Arc::new(RwLock::new(init_cache()));
+        let verify =
+            |number| verify_block(&store, &consensus, &handle, &txs_verify_cache, number).unwrap();
+
+        assert!(verify(2).is_empty());
+        let mismatches = verify(3);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].number, 3);
+        assert_eq!(mismatches[0].hash, tip.hash().unpack());
+        assert!(matches!(
+            &mismatches[0].kind,
+            MismatchKind::Dao { expected, actual }
+                if *expected == H256([1u8; 32]) && actual != expected
+        ));
+
+        // the missing block is reported as an error
+        let mismatches = verify(4);
+        assert!(matches!(mismatches[0].kind, MismatchKind::Error { .. }));
+
+        // the mismatches are written as JSON lines
+        let report = tmp_dir.path().join("report.json");
+        write_report(&verify(3), Some(report.clone())).unwrap();
+        let lines = std::fs::read_to_string(report).unwrap();
+        let line: serde_json::Value = serde_json::from_str(lines.trim_end()).unwrap();
+        assert_eq!(line["kind"], "dao");
+        assert_eq!(line["number"], 3);
+    }
+}
@@ -24,6 +24,14 @@ pub fn run(args: RunArgs, version: Version, async_handle: Handle) -> Result<(),
     // spawn freezer background process
     let _freezer = shared.spawn_freeze();
 
+    // spawn pruner background process
+    if let Some(prune_depth) = launcher.args.config.store.prune_depth {
+        shared.spawn_prune(prune_depth);
+    }
+
+    // spawn database statistics background process
+    shared.spawn_db_stats();
+
     setup_system_cell_cache(
         shared.consensus().genesis_block(),
         shared.snapshot().as_ref(),
@@ -2,7 +2,7 @@
 use ckb_channel::select;
 use ckb_channel::unbounded;
 use ckb_channel::Receiver;
-use ckb_db::{ReadOnlyDB, RocksDB};
+use ckb_db::{KeyValueRead, KeyValueStore, ReadOnlyDB, RocksDB};
 use ckb_db_schema::{COLUMN_META, META_TIP_HEADER_KEY, MIGRATION_VERSION_KEY};
 use ckb_error::{Error, InternalErrorKind};
 use ckb_logger::{debug, error, info};
@@ -29,9 +29,14 @@ fn internal_error(reason: String) -> Error {
 }
 
 /// TODO(doc): @quake
-#[derive(Default)]
-pub struct Migrations {
-    migrations: BTreeMap<String, Arc<dyn Migration>>,
+pub struct Migrations<DB: KeyValueStore = RocksDB> {
+    migrations: BTreeMap<String, Arc<dyn Migration<DB>>>,
+}
+
+impl<DB: KeyValueStore> Default for Migrations<DB> {
+    fn default() -> Self {
+        Migrations::new()
+    }
 }
 
 /// Commands
@@ -41,15 +46,15 @@ enum Command {
     Stop,
 }
 
-type MigrationTasks = VecDeque<(String, Arc<dyn Migration>)>;
-struct MigrationWorker {
-    tasks: Arc<Mutex<MigrationTasks>>,
-    db: RocksDB,
+type MigrationTasks<DB> = VecDeque<(String, Arc<dyn Migration<DB>>)>;
+struct MigrationWorker<DB: KeyValueStore> {
+    tasks: Arc<Mutex<MigrationTasks<DB>>>,
+    db: DB,
     inbox: Receiver<Command>,
 }
 
-impl MigrationWorker {
-    pub fn new(tasks: Arc<Mutex<MigrationTasks>>, db: RocksDB, inbox: Receiver<Command>) -> Self {
+impl<DB: KeyValueStore> MigrationWorker<DB> {
+    pub fn new(tasks: Arc<Mutex<MigrationTasks<DB>>>, db: DB, inbox: Receiver<Command>) -> Self {
         Self { tasks, db, inbox }
     }
 
@@ -79,7 +84,7 @@ impl MigrationWorker {
                                 pb
                             };
                             if let Ok(db) = task.migrate(self.db.clone(), Arc::new(pb)) {
-                                db.put_default(MIGRATION_VERSION_KEY, task.version())
+                                db.put_default(MIGRATION_VERSION_KEY, task.version().as_bytes())
                                 .map_err(|err| {
                                     internal_error(format!("failed to migrate the database: {err}"))
                                 })
@@ -93,7 +98,7 @@ impl MigrationWorker {
     }
 }
 
-impl Migrations {
+impl<DB: KeyValueStore> Migrations<DB> {
     /// TODO(doc): @quake
     pub fn new() -> Self {
         Migrations {
@@ -102,7 +107,7 @@ impl Migrations {
     }
 
     /// TODO(doc): @quake
-    pub fn add_migration(&mut self, migration: Arc<dyn Migration>) {
+    pub fn add_migration(&mut self, migration: Arc<dyn Migration<DB>>) {
         self.migrations
             .insert(migration.version().to_string(), migration);
     }
@@ -206,8 +211,8 @@ impl Migrations {
         false
     }
 
-    fn is_non_empty_db(&self, db: &RocksDB) -> bool {
-        if let Ok(v) = db.get_pinned(COLUMN_META, META_TIP_HEADER_KEY) {
+    fn is_non_empty_db(&self, db: &DB) -> bool {
+        if let Ok(v) = KeyValueRead::get(db, COLUMN_META, META_TIP_HEADER_KEY) {
             if v.is_some() {
                 return true;
             }
@@ -215,7 +220,7 @@ impl Migrations {
         false
     }
 
-    fn run_migrate(&self, mut db: RocksDB, v: &str) -> Result<RocksDB, Error> {
+    fn run_migrate(&self, mut db: DB, v: &str) -> Result<DB, Error> {
         let mpb = Arc::new(MultiProgress::new());
         let migrations: BTreeMap<_, _> = self
             .migrations
@@ -232,15 +237,15 @@ impl Migrations {
                 pb
             };
             db = m.migrate(db, Arc::new(pb))?;
-            db.put_default(MIGRATION_VERSION_KEY, m.version())
+            db.put_default(MIGRATION_VERSION_KEY, m.version().as_bytes())
                 .map_err(|err| internal_error(format!("failed to migrate the database: {err}")))?;
         }
         mpb.join_and_clear().expect("MultiProgress join");
         Ok(db)
     }
 
-    fn run_migrate_async(&self, db: RocksDB, v: &str) {
-        let migrations: VecDeque<(String, Arc<dyn Migration>)> = self
+    fn run_migrate_async(&self, db: DB, v: &str) {
+        let migrations: MigrationTasks<DB> = self
             .migrations
             .iter()
             .filter(|(mv, _)| mv.as_str() > v)
@@ -271,12 +276,10 @@ impl Migrations {
         tx.send(Command::Start).expect("send start command");
     }
 
-    fn get_migration_version(&self, db: &RocksDB) -> Result<Option<String>, Error> {
-        let raw = db
-            .get_pinned_default(MIGRATION_VERSION_KEY)
-            .map_err(|err| {
-                internal_error(format!("failed to get the version of database: {err}"))
-            })?;
+    fn get_migration_version(&self, db: &DB) -> Result<Option<String>, Error> {
+        let raw = db.get_default(MIGRATION_VERSION_KEY).map_err(|err| {
+            internal_error(format!("failed to get the version of database: {err}"))
+        })?;
 
         Ok(raw.map(|version_bytes| {
             String::from_utf8(version_bytes.to_vec()).expect("version bytes to utf8")
@@ -284,12 +287,12 @@ impl Migrations {
     }
 
     /// Initial db version
-    pub fn init_db_version(&self, db: &RocksDB) -> Result<(), Error> {
+    pub fn init_db_version(&self, db: &DB) -> Result<(), Error> {
         let db_version = self.get_migration_version(db)?;
         if db_version.is_none() {
             if let Some(m) = self.migrations.values().last() {
                 info!("Init database version {}", m.version());
-                db.put_default(MIGRATION_VERSION_KEY, m.version())
+                db.put_default(MIGRATION_VERSION_KEY, m.version().as_bytes())
                     .map_err(|err| {
                         internal_error(format!("failed to migrate the database: {err}"))
                     })?;
@@ -299,7 +302,7 @@ impl Migrations {
     }
 
     /// TODO(doc): @quake
-    pub fn migrate(&self, db: RocksDB, run_in_background: bool) -> Result<RocksDB, Error> {
+    pub fn migrate(&self, db: DB, run_in_background: bool) -> Result<DB, Error> {
         let db_version = self.get_migration_version(&db)?;
         match db_version {
             Some(ref v) => {
@@ -324,7 +327,7 @@ impl Migrations {
         }
     }
 
-    fn patch_220464f(&self, db: RocksDB) -> Result<RocksDB, Error> {
+    fn patch_220464f(&self, db: DB) -> Result<DB, Error> {
         const V: &str = "20210609195048"; // AddExtraDataHash - 1
         self.run_migrate(db, V)
     }
@@ -347,13 +350,16 @@ impl Migrations {
 }
 
 /// TODO(doc): @quake
-pub trait Migration: Send + Sync {
+///
+/// A migration runs against the RocksDB backend by default, a migration only relying on the
+/// `KeyValueStore` traits could implement `Migration<DB>` for all the backends.
+pub trait Migration<DB: KeyValueStore = RocksDB>: Send + Sync {
     /// TODO(doc): @quake
     fn migrate(
         &self,
-        _db: RocksDB,
+        _db: DB,
         _pb: Arc<dyn Fn(u64) -> ProgressBar + Send + Sync>,
-    ) -> Result<RocksDB, Error>;
+    ) -> Result<DB, Error>;
 
     /// returns migration version, use `date +'%Y%m%d%H%M%S'` timestamp format
     fn version(&self) -> &str;
@@ -404,12 +410,12 @@ impl DefaultMigration {
     }
 }
 
-impl Migration for DefaultMigration {
+impl<DB: KeyValueStore> Migration<DB> for DefaultMigration {
     fn migrate(
         &self,
-        db: RocksDB,
+        db: DB,
         _pb: Arc<dyn Fn(u64) -> ProgressBar + Send + Sync>,
-    ) -> Result<RocksDB, Error> {
+    ) -> Result<DB, Error> {
         Ok(db)
     }
 
@@ -1,6 +1,7 @@
 use ckb_app_config::DBConfig;
 use ckb_db::ReadOnlyDB;
 use ckb_db::RocksDB;
+use ckb_db::{IteratorMode, KeyValueRead, KeyValueStore, KeyValueTransaction, MemoryDB};
 use ckb_db_schema::MIGRATION_VERSION_KEY;
 use ckb_error::Error;
 use indicatif::ProgressBar;
@@ -126,6 +127,60 @@ fn test_customized_migration() {
     }
 }
 
+#[test]
+fn test_memory_db_migration() {
+    struct AppendMigration;
+    const COLUMN: &str = "0";
+    const VERSION: &str = "20191127101121";
+
+    impl<DB: KeyValueStore> Migration<DB> for AppendMigration {
+        fn migrate(
+            &self,
+            db: DB,
+            _pb: Arc<dyn Fn(u64) -> ProgressBar + Send + Sync>,
+        ) -> Result<DB, Error> {
+            let txn = db.transaction();
+            for (key, value) in db.iterate(COLUMN, IteratorMode::Start)? {
+                let mut new_value = value.to_vec();
+                new_value.push(1);
+                txn.put(COLUMN, &key, &new_value)?;
+            }
+            txn.commit()?;
+            Ok(db)
+        }
+
+        fn version(&self) -> &str {
+            VERSION
+        }
+    }
+
+    let db = MemoryDB::open(1);
+    let mut migrations = Migrations::default();
+    migrations.add_migration(Arc::new(DefaultMigration::new("20191116225943")));
+    migrations.init_db_version(&db).unwrap();
+    let db = migrations.migrate(db, false).unwrap();
+
+    let txn = db.transaction();
+    txn.put(COLUMN, &[1, 1], &[1, 1, 1]).unwrap();
+    txn.commit().unwrap();
+
+    let mut migrations = Migrations::default();
+    migrations.add_migration(Arc::new(DefaultMigration::new("20191116225943")));
+    migrations.add_migration(Arc::new(AppendMigration));
+    let db = migrations.migrate(db, false).unwrap();
+    assert_eq!(
+        db.get(COLUMN, &[1, 1]).unwrap().unwrap().as_ref(),
+        &[1, 1, 1, 1]
+    );
+    assert_eq!(
+        db.get_default(MIGRATION_VERSION_KEY)
+            .unwrap()
+            .unwrap()
+            .as_ref(),
+        VERSION.as_bytes()
+    );
+}
+
 #[test]
 fn test_background_migration() {
     use ckb_stop_handler::broadcast_exit_signals;
@@ -51,8 +51,91 @@ pub const META_TIP_HEADER_KEY: &[u8] = b"TIP_HEADER";
 pub const META_CURRENT_EPOCH_KEY: &[u8] = b"CURRENT_EPOCH";
 /// META_FILTER_DATA_KEY tracks the latest built filter data block hash
 pub const META_LATEST_BUILT_FILTER_DATA_KEY: &[u8] = b"LATEST_BUILT_FILTER_DATA";
+/// META_PRUNED_BLOCK_NUMBER_KEY tracks the number below which the main chain block bodies are pruned
+pub const META_PRUNED_BLOCK_NUMBER_KEY: &[u8] = b"PRUNED_BLOCK_NUMBER";
 
 /// CHAIN_SPEC_HASH_KEY tracks the hash of chain spec which created current database
 pub const CHAIN_SPEC_HASH_KEY: &[u8] = b"chain-spec-hash";
 /// MIGRATION_VERSION_KEY tracks the current database version.
 pub const MIGRATION_VERSION_KEY: &[u8] = b"db-version";
+
+/// All the columns, in the order of the indices
+pub const ALL_COLUMNS: [Col; COLUMNS as usize] = [
+    COLUMN_INDEX,
+    COLUMN_BLOCK_HEADER,
+    COLUMN_BLOCK_BODY,
+    COLUMN_BLOCK_UNCLE,
+    COLUMN_META,
+    COLUMN_TRANSACTION_INFO,
+    COLUMN_BLOCK_EXT,
+    COLUMN_BLOCK_PROPOSAL_IDS,
+    COLUMN_BLOCK_EPOCH,
+    COLUMN_EPOCH,
+    COLUMN_CELL,
+    COLUMN_UNCLES,
+    COLUMN_CELL_DATA,
+    COLUMN_NUMBER_HASH,
+    COLUMN_CELL_DATA_HASH,
+    COLUMN_BLOCK_EXTENSION,
+    COLUMN_CHAIN_ROOT_MMR,
+    COLUMN_BLOCK_FILTER,
+    COLUMN_BLOCK_FILTER_HASH,
+];
+
+/// Returns the name of the column for the reports, e.g. "block_header" for `COLUMN_BLOCK_HEADER`.
+pub fn column_name(col: Col) -> &'static str {
+    match col {
+        COLUMN_INDEX => "index",
+        COLUMN_BLOCK_HEADER => "block_header",
+        COLUMN_BLOCK_BODY => "block_body",
+        COLUMN_BLOCK_UNCLE => "block_uncle",
+        COLUMN_META => "meta",
+        COLUMN_TRANSACTION_INFO => "transaction_info",
+        COLUMN_BLOCK_EXT => "block_ext",
+        COLUMN_BLOCK_PROPOSAL_IDS => "block_proposal_ids",
+        COLUMN_BLOCK_EPOCH => "block_epoch",
+        COLUMN_EPOCH => "epoch",
+        COLUMN_CELL => "cell",
+        COLUMN_UNCLES => "uncles",
+        COLUMN_CELL_DATA => "cell_data",
+        COLUMN_NUMBER_HASH => "number_hash",
+        COLUMN_CELL_DATA_HASH => "cell_data_hash",
+        COLUMN_BLOCK_EXTENSION => "block_extension",
+        COLUMN_CHAIN_ROOT_MMR => "chain_root_mmr",
+        COLUMN_BLOCK_FILTER => "block_filter",
+        COLUMN_BLOCK_FILTER_HASH => "block_filter_hash",
+        _ => "unknown",
+    }
+}
+
+/// Returns the column of the name returned by [`column_name`], or of the column id, e.g. "1".
+pub fn column_by_name(name: &str) -> Option<Col> {
+    ALL_COLUMNS
+        .into_iter()
+        .find(|col| column_name(col) == name || *col == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_columns() {
+        // every column from "0" to `COLUMNS - 1` in order
+        for (index, col) in ALL_COLUMNS.into_iter().enumerate() {
+            assert_eq!(col, index.to_string());
+        }
+
+        let mut names: Vec<_> = ALL_COLUMNS.into_iter().map(column_name).collect();
+        assert!(!names.contains(&"unknown"));
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), COLUMNS as usize);
+
+        for col in ALL_COLUMNS {
+            assert_eq!(column_by_name(column_name(col)), Some(col));
+            assert_eq!(column_by_name(col), Some(col));
+        }
+        assert_eq!(column_by_name("unknown"), None);
+    }
+}
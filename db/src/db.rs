@@ -1,5 +1,9 @@
 //! RocksDB wrapper base on OptimisticTransactionDB
 use crate::snapshot::RocksDBSnapshot;
+use crate::stats::{
+    ColumnFamilyStats, PROPERTY_AGGREGATED_TABLE_PROPERTIES, PROPERTY_ESTIMATE_NUM_KEYS,
+    PROPERTY_ESTIMATE_PENDING_COMPACTION_BYTES, PROPERTY_LIVE_SST_FILES_SIZE,
+};
 use crate::transaction::RocksDBTransaction;
 use crate::write_batch::RocksDBWriteBatch;
 use crate::{internal_error, Result};
@@ -7,12 +11,12 @@ use ckb_app_config::DBConfig;
 use ckb_db_schema::Col;
 use ckb_logger::info;
 use rocksdb::ops::{
-    CompactRangeCF, CreateCF, DropCF, GetColumnFamilys, GetPinned, GetPinnedCF, IterateCF, OpenCF,
-    Put, SetOptions, WriteOps,
+    CompactRangeCF, CreateCF, DropCF, GetColumnFamilys, GetPinned, GetPinnedCF, GetPropertyCF,
+    IterateCF, OpenCF, Put, SetOptions, WriteOps,
 };
 use rocksdb::{
-    ffi, BlockBasedIndexType, BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor,
-    DBPinnableSlice, FullOptions, IteratorMode, OptimisticTransactionDB,
+    checkpoint::Checkpoint, ffi, BlockBasedIndexType, BlockBasedOptions, Cache, ColumnFamily,
+    ColumnFamilyDescriptor, DBPinnableSlice, FullOptions, IteratorMode, OptimisticTransactionDB,
     OptimisticTransactionOptions, Options, SliceTransform, WriteBatch, WriteOptions,
 };
 use std::path::Path;
@@ -302,6 +306,45 @@ impl RocksDB {
         Ok(())
     }
 
+    /// Creates a consistent checkpoint of the database in the directory, which must not exist.
+    ///
+    /// The SST files are hard linked when the directory is on the same filesystem as the
+    /// database, and copied otherwise.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let checkpoint = Checkpoint::new(&*self.inner).map_err(internal_error)?;
+        checkpoint
+            .create_checkpoint(path)
+            .map_err(|err| internal_error(format!("failed to create checkpoint: {err}")))
+    }
+
+    /// Returns the disk usage statistics of the column family.
+    pub fn column_family_stats(&self, col: Col) -> Result<ColumnFamilyStats> {
+        let cf = cf_handle(&self.inner, col)?;
+        let int_value = |name: &str| -> Result<u64> {
+            self.inner
+                .property_int_value_cf(cf, name)
+                .map(|value| value.unwrap_or_default())
+                .map_err(internal_error)
+        };
+        let mut stats = ColumnFamilyStats {
+            col,
+            estimate_num_keys: int_value(PROPERTY_ESTIMATE_NUM_KEYS)?,
+            live_sst_files_size: int_value(PROPERTY_LIVE_SST_FILES_SIZE)?,
+            estimate_pending_compaction_bytes: int_value(
+                PROPERTY_ESTIMATE_PENDING_COMPACTION_BYTES,
+            )?,
+            ..Default::default()
+        };
+        if let Some(properties) = self
+            .inner
+            .property_value_cf(cf, PROPERTY_AGGREGATED_TABLE_PROPERTIES)
+            .map_err(internal_error)?
+        {
+            stats.set_table_properties(&properties);
+        }
+        Ok(stats)
+    }
+
     /// Return `RocksDBSnapshot`.
     pub fn get_snapshot(&self) -> RocksDBSnapshot {
         unsafe {
@@ -1,7 +1,7 @@
 //! RocksDB iterator wrapper base on DBIter
 use crate::db::cf_handle;
 use crate::{
-    internal_error, Result, RocksDB, RocksDBSnapshot, RocksDBTransaction,
+    internal_error, ReadOnlyDB, Result, RocksDB, RocksDBSnapshot, RocksDBTransaction,
     RocksDBTransactionSnapshot,
 };
 use ckb_db_schema::Col;
@@ -56,3 +56,12 @@ impl DBIterator for RocksDBSnapshot {
             .map_err(internal_error)
     }
 }
+
+impl DBIterator for ReadOnlyDB {
+    fn iter_opt(&self, col: Col, mode: IteratorMode, readopts: &ReadOptions) -> Result<DBIter> {
+        let cf = self.cf_handle(col)?;
+        self.inner
+            .iterator_cf_opt(cf, mode, readopts)
+            .map_err(internal_error)
+    }
+}
@@ -9,8 +9,11 @@ use std::{fmt, result};
 pub mod db;
 pub mod db_with_ttl;
 pub mod iter;
+pub mod memory;
 pub mod read_only_db;
 pub mod snapshot;
+pub mod stats;
+pub mod traits;
 pub mod transaction;
 pub mod write_batch;
 
@@ -20,8 +23,13 @@ mod tests;
 pub use crate::db::RocksDB;
 pub use crate::db_with_ttl::DBWithTTL;
 pub use crate::iter::DBIterator;
+pub use crate::memory::{MemoryDB, MemorySnapshot, MemoryTransaction, MemoryWriteBatch};
 pub use crate::read_only_db::ReadOnlyDB;
 pub use crate::snapshot::RocksDBSnapshot;
+pub use crate::stats::ColumnFamilyStats;
+pub use crate::traits::{
+    DBValue, KeyValueIter, KeyValueRead, KeyValueStore, KeyValueTransaction, KeyValueWriteBatch,
+};
 pub use crate::transaction::{RocksDBTransaction, RocksDBTransactionSnapshot};
 pub use crate::write_batch::RocksDBWriteBatch;
 pub use rocksdb::{
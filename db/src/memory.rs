@@ -0,0 +1,544 @@
+//! In-memory key-value store, for tests and embedded deployments which don't need persistence.
+//!
+//! Every key keeps the versions of its values tagged with the sequence number of the write, so
+//! the snapshots, the iterators and the transactions read a consistent view without copying the
+//! store. The versions no longer visible to any live snapshot are dropped when the key is written
+//! again or the key range is compacted.
+use crate::traits::{
+    DBValue, KeyValueIter, KeyValueRead, KeyValueStore, KeyValueTransaction, KeyValueWriteBatch,
+};
+use crate::{internal_error, Direction, IteratorMode, Result};
+use ckb_db_schema::Col;
+use ckb_util::{Mutex, RwLock};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::sync::Arc;
+
+type Key = Box<[u8]>;
+type Value = Box<[u8]>;
+type Versions = Vec<(u64, Option<Value>)>;
+// The pending writes of a transaction, `None` for a deletion.
+type Writes = Vec<BTreeMap<Key, Option<Value>>>;
+
+#[derive(Default)]
+struct MemoryState {
+    // The versions of every key in ascending order of the sequence number, `None` for a deletion.
+    columns: Vec<BTreeMap<Key, Versions>>,
+    default: BTreeMap<Key, Value>,
+    seq: u64,
+    // The sequence numbers of the live snapshots and their reference counts.
+    snapshots: BTreeMap<u64, usize>,
+}
+
+impl MemoryState {
+    fn column(&self, col: usize) -> &BTreeMap<Key, Versions> {
+        &self.columns[col]
+    }
+
+    fn get(&self, col: usize, key: &[u8], seq: u64) -> Option<Value> {
+        self.column(col)
+            .get(key)
+            .and_then(|versions| visible(versions, seq))
+            .cloned()
+    }
+
+    fn latest_seq(&self, col: usize, key: &[u8]) -> u64 {
+        self.column(col)
+            .get(key)
+            .and_then(|versions| versions.last())
+            .map(|(seq, _)| *seq)
+            .unwrap_or_default()
+    }
+
+    // Returns the first live key-value pair after the bound in the direction.
+    fn seek(
+        &self,
+        col: usize,
+        bound: Bound<&[u8]>,
+        direction: Direction,
+        seq: u64,
+    ) -> Option<(Key, Value)> {
+        range(self.column(col), bound, direction)
+            .filter_map(|(key, versions)| {
+                visible(versions, seq).map(|value| (key.clone(), value.clone()))
+            })
+            .next()
+    }
+
+    fn acquire(&mut self, seq: u64) {
+        *self.snapshots.entry(seq).or_default() += 1;
+    }
+
+    fn release(&mut self, seq: u64) {
+        if let Some(count) = self.snapshots.get_mut(&seq) {
+            *count -= 1;
+            if *count == 0 {
+                self.snapshots.remove(&seq);
+            }
+        }
+    }
+
+    fn apply<I>(&mut self, writes: I)
+    where
+        I: IntoIterator<Item = (usize, Key, Option<Value>)>,
+    {
+        self.seq += 1;
+        let seq = self.seq;
+        for (col, key, value) in writes {
+            self.columns[col]
+                .entry(key.clone())
+                .or_default()
+                .push((seq, value));
+            self.prune(col, &key);
+        }
+    }
+
+    // Drops the versions of the key which are invisible to all the live snapshots.
+    fn prune(&mut self, col: usize, key: &[u8]) {
+        let oldest = self.snapshots.keys().next().copied().unwrap_or(self.seq);
+        let column = &mut self.columns[col];
+        let remove = match column.get_mut(key) {
+            Some(versions) => {
+                let newer = versions.partition_point(|(seq, _)| *seq <= oldest);
+                if newer > 1 {
+                    versions.drain(..newer - 1);
+                }
+                matches!(versions.as_slice(), [(seq, None)] if *seq <= oldest)
+            }
+            None => false,
+        };
+        if remove {
+            column.remove(key);
+        }
+    }
+}
+
+// Returns the entries after the bound in the direction.
+fn range<'a, V>(
+    map: &'a BTreeMap<Key, V>,
+    bound: Bound<&[u8]>,
+    direction: Direction,
+) -> Box<dyn Iterator<Item = (&'a Key, &'a V)> + 'a> {
+    match direction {
+        Direction::Forward => Box::new(map.range::<[u8], _>((bound, Bound::Unbounded))),
+        Direction::Reverse => Box::new(map.range::<[u8], _>((Bound::Unbounded, bound)).rev()),
+    }
+}
+
+fn visible(versions: &[(u64, Option<Value>)], seq: u64) -> Option<&Value> {
+    versions
+        .iter()
+        .rev()
+        .find(|(version, _)| *version <= seq)
+        .and_then(|(_, value)| value.as_ref())
+}
+
+/// An in-memory key-value store.
+#[derive(Clone)]
+pub struct MemoryDB {
+    state: Arc<RwLock<MemoryState>>,
+}
+
+impl MemoryDB {
+    /// Creates an empty store with the given columns count.
+    pub fn open(columns: u32) -> Self {
+        let state = MemoryState {
+            columns: vec![BTreeMap::new(); columns as usize],
+            ..Default::default()
+        };
+        MemoryDB {
+            state: Arc::new(RwLock::new(state)),
+        }
+    }
+
+    fn column(&self, col: Col) -> Result<usize> {
+        column_index(col, self.state.read().columns.len())
+    }
+
+    fn acquire_snapshot(&self) -> SnapshotGuard {
+        let mut state = self.state.write();
+        let seq = state.seq;
+        state.acquire(seq);
+        SnapshotGuard {
+            state: Arc::clone(&self.state),
+            seq,
+        }
+    }
+
+    fn iter_with(
+        &self,
+        col: Col,
+        mode: IteratorMode,
+        writes: Option<&Mutex<Writes>>,
+    ) -> Result<KeyValueIter<'_>> {
+        let col = self.column(col)?;
+        Ok(Box::new(MemoryIter::new(
+            col,
+            mode,
+            self.acquire_snapshot(),
+            writes,
+        )))
+    }
+}
+
+fn column_index(col: Col, columns: usize) -> Result<usize> {
+    col.parse::<usize>()
+        .ok()
+        .filter(|index| *index < columns)
+        .ok_or_else(|| internal_error(format!("column {col} not found")))
+}
+
+// Keeps the versions visible at `seq` alive.
+struct SnapshotGuard {
+    state: Arc<RwLock<MemoryState>>,
+    seq: u64,
+}
+
+impl SnapshotGuard {
+    fn clone_guard(&self) -> SnapshotGuard {
+        self.state.write().acquire(self.seq);
+        SnapshotGuard {
+            state: Arc::clone(&self.state),
+            seq: self.seq,
+        }
+    }
+}
+
+impl Drop for SnapshotGuard {
+    fn drop(&mut self) {
+        self.state.write().release(self.seq);
+    }
+}
+
+// Iterates the store as of the guarded snapshot, overlaid with the pending writes of a
+// transaction. Every step seeks from the last returned key, so no lock is held between the steps.
+struct MemoryIter<'a> {
+    col: usize,
+    direction: Direction,
+    bound: Option<Bound<Key>>,
+    guard: SnapshotGuard,
+    writes: Option<&'a Mutex<Writes>>,
+}
+
+impl<'a> MemoryIter<'a> {
+    fn new(
+        col: usize,
+        mode: IteratorMode,
+        guard: SnapshotGuard,
+        writes: Option<&'a Mutex<Writes>>,
+    ) -> Self {
+        let (bound, direction) = match mode {
+            IteratorMode::Start => (Bound::Unbounded, Direction::Forward),
+            IteratorMode::End => (Bound::Unbounded, Direction::Reverse),
+            IteratorMode::From(key, direction) => (Bound::Included(key.into()), direction),
+        };
+        MemoryIter {
+            col,
+            direction,
+            bound: Some(bound),
+            guard,
+            writes,
+        }
+    }
+
+    // Returns true if `a` comes before `b` in the iteration direction.
+    fn before(&self, a: &[u8], b: &[u8]) -> bool {
+        match self.direction {
+            Direction::Forward => a < b,
+            Direction::Reverse => a > b,
+        }
+    }
+}
+
+impl<'a> Iterator for MemoryIter<'a> {
+    type Item = (Box<[u8]>, Box<[u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let bound = self.bound.take()?;
+            let bound = match &bound {
+                Bound::Included(key) => Bound::Included(key.as_ref()),
+                Bound::Excluded(key) => Bound::Excluded(key.as_ref()),
+                Bound::Unbounded => Bound::Unbounded,
+            };
+            let base =
+                self.guard
+                    .state
+                    .read()
+                    .seek(self.col, bound, self.direction, self.guard.seq);
+            let pending = self.writes.and_then(|writes| {
+                range(&writes.lock()[self.col], bound, self.direction)
+                    .next()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+            });
+
+            // the pending writes of the transaction override the store
+            let from_base = match (&base, &pending) {
+                (None, None) => return None,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some((key, _)), Some((pending_key, _))) => self.before(key, pending_key),
+            };
+            if from_base {
+                let (key, value) = base.expect("checked above");
+                self.bound = Some(Bound::Excluded(key.clone()));
+                return Some((key, value));
+            }
+            let (key, value) = pending.expect("checked above");
+            self.bound = Some(Bound::Excluded(key.clone()));
+            if let Some(value) = value {
+                return Some((key, value));
+            }
+        }
+    }
+}
+
+impl KeyValueRead for MemoryDB {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        let col = self.column(col)?;
+        let state = self.state.read();
+        Ok(state.get(col, key, state.seq).map(DBValue::Owned))
+    }
+
+    fn iterate(&self, col: Col, mode: IteratorMode) -> Result<KeyValueIter<'_>> {
+        self.iter_with(col, mode, None)
+    }
+}
+
+impl KeyValueStore for MemoryDB {
+    type Transaction = MemoryTransaction;
+    type Snapshot = MemorySnapshot;
+    type WriteBatch = MemoryWriteBatch;
+
+    fn get_default(&self, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        Ok(self
+            .state
+            .read()
+            .default
+            .get(key)
+            .map(|value| DBValue::Owned(value.clone())))
+    }
+
+    fn put_default(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.state.write().default.insert(key.into(), value.into());
+        Ok(())
+    }
+
+    fn transaction(&self) -> MemoryTransaction {
+        let columns = self.state.read().columns.len();
+        MemoryTransaction {
+            db: self.clone(),
+            snapshot: self.acquire_snapshot(),
+            writes: Mutex::new(vec![BTreeMap::new(); columns]),
+            state: Mutex::new(TransactionState::default()),
+        }
+    }
+
+    fn get_snapshot(&self) -> MemorySnapshot {
+        MemorySnapshot {
+            guard: self.acquire_snapshot(),
+        }
+    }
+
+    fn new_write_batch(&self) -> MemoryWriteBatch {
+        MemoryWriteBatch {
+            columns: self.state.read().columns.len(),
+            ops: Vec::new(),
+            size: 0,
+        }
+    }
+
+    fn write(&self, batch: &MemoryWriteBatch) -> Result<()> {
+        self.state.write().apply(batch.ops.iter().cloned());
+        Ok(())
+    }
+
+    fn write_sync(&self, batch: &MemoryWriteBatch) -> Result<()> {
+        self.write(batch)
+    }
+
+    fn compact_range(&self, col: Col, start: Option<&[u8]>, end: Option<&[u8]>) -> Result<()> {
+        let col = self.column(col)?;
+        let mut state = self.state.write();
+        let start = start.map_or(Bound::Unbounded, Bound::Included);
+        let end = end.map_or(Bound::Unbounded, Bound::Excluded);
+        let keys: Vec<Key> = state.columns[col]
+            .range::<[u8], _>((start, end))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in keys {
+            state.prune(col, &key);
+        }
+        Ok(())
+    }
+}
+
+/// A point-in-time view of a `MemoryDB`.
+pub struct MemorySnapshot {
+    guard: SnapshotGuard,
+}
+
+impl KeyValueRead for MemorySnapshot {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        let state = self.guard.state.read();
+        let col = column_index(col, state.columns.len())?;
+        Ok(state.get(col, key, self.guard.seq).map(DBValue::Owned))
+    }
+
+    fn iterate(&self, col: Col, mode: IteratorMode) -> Result<KeyValueIter<'_>> {
+        let col = column_index(col, self.guard.state.read().columns.len())?;
+        Ok(Box::new(MemoryIter::new(
+            col,
+            mode,
+            self.guard.clone_guard(),
+            None,
+        )))
+    }
+}
+
+#[derive(Default)]
+struct TransactionState {
+    // The keys read for update.
+    reads: BTreeSet<(usize, Key)>,
+    savepoints: Vec<Writes>,
+}
+
+/// An optimistic transaction of a `MemoryDB`.
+pub struct MemoryTransaction {
+    db: MemoryDB,
+    snapshot: SnapshotGuard,
+    writes: Mutex<Writes>,
+    state: Mutex<TransactionState>,
+}
+
+impl KeyValueRead for MemoryTransaction {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        let col = self.db.column(col)?;
+        if let Some(value) = self.writes.lock()[col].get(key) {
+            return Ok(value.clone().map(DBValue::Owned));
+        }
+        let state = self.db.state.read();
+        Ok(state.get(col, key, state.seq).map(DBValue::Owned))
+    }
+
+    fn iterate(&self, col: Col, mode: IteratorMode) -> Result<KeyValueIter<'_>> {
+        self.db.iter_with(col, mode, Some(&self.writes))
+    }
+}
+
+impl KeyValueTransaction for MemoryTransaction {
+    fn put(&self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        let col = self.db.column(col)?;
+        self.writes.lock()[col].insert(key.into(), Some(value.into()));
+        Ok(())
+    }
+
+    fn delete(&self, col: Col, key: &[u8]) -> Result<()> {
+        let col = self.db.column(col)?;
+        self.writes.lock()[col].insert(key.into(), None);
+        Ok(())
+    }
+
+    fn get_for_update(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        let col = self.db.column(col)?;
+        self.state.lock().reads.insert((col, key.into()));
+        if let Some(value) = self.writes.lock()[col].get(key) {
+            return Ok(value.clone().map(DBValue::Owned));
+        }
+        Ok(self
+            .db
+            .state
+            .read()
+            .get(col, key, self.snapshot.seq)
+            .map(DBValue::Owned))
+    }
+
+    fn commit(&self) -> Result<()> {
+        let mut writes = self.writes.lock();
+        let mut txn_state = self.state.lock();
+        let mut state = self.db.state.write();
+        let written = writes
+            .iter()
+            .enumerate()
+            .flat_map(|(col, column)| column.keys().map(move |key| (col, key.as_ref())));
+        let read = txn_state
+            .reads
+            .iter()
+            .map(|(col, key)| (*col, key.as_ref()));
+        if let Some((col, key)) = written
+            .chain(read)
+            .find(|(col, key)| state.latest_seq(*col, key) > self.snapshot.seq)
+        {
+            return Err(internal_error(format!(
+                "Resource busy: the key {key:?} in column {col} is modified by others"
+            )));
+        }
+
+        state.apply(writes.iter_mut().enumerate().flat_map(|(col, column)| {
+            std::mem::take(column)
+                .into_iter()
+                .map(move |(key, value)| (col, key, value))
+        }));
+        *txn_state = TransactionState::default();
+        Ok(())
+    }
+
+    fn rollback(&self) -> Result<()> {
+        self.writes.lock().iter_mut().for_each(BTreeMap::clear);
+        *self.state.lock() = TransactionState::default();
+        Ok(())
+    }
+
+    fn set_savepoint(&self) {
+        let writes = self.writes.lock().clone();
+        self.state.lock().savepoints.push(writes);
+    }
+
+    fn rollback_to_savepoint(&self) -> Result<()> {
+        let writes = self
+            .state
+            .lock()
+            .savepoints
+            .pop()
+            .ok_or_else(|| internal_error("NotFound: no savepoint is set"))?;
+        *self.writes.lock() = writes;
+        Ok(())
+    }
+}
+
+/// An atomic batch of write operations of a `MemoryDB`.
+pub struct MemoryWriteBatch {
+    columns: usize,
+    ops: Vec<(usize, Key, Option<Value>)>,
+    size: usize,
+}
+
+impl KeyValueWriteBatch for MemoryWriteBatch {
+    fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        let col = column_index(col, self.columns)?;
+        self.size += key.len() + value.len();
+        self.ops.push((col, key.into(), Some(value.into())));
+        Ok(())
+    }
+
+    fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
+        let col = column_index(col, self.columns)?;
+        self.size += key.len();
+        self.ops.push((col, key.into(), None));
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        self.size
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.ops.clear();
+        self.size = 0;
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use crate::{internal_error, Result};
 use ckb_db_schema::Col;
 use ckb_logger::info;
 use rocksdb::ops::{GetColumnFamilys, GetPinned, GetPinnedCF, OpenCF};
-use rocksdb::{DBPinnableSlice, Options, ReadOnlyDB as RawReadOnlyDB};
+use rocksdb::{ColumnFamily, DBPinnableSlice, Options, ReadOnlyDB as RawReadOnlyDB};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -57,10 +57,13 @@ impl ReadOnlyDB {
     /// Return the value associated with a key using RocksDB's PinnableSlice from the given column
     /// so as to avoid unnecessary memory copy.
     pub fn get_pinned(&self, col: Col, key: &[u8]) -> Result<Option<DBPinnableSlice>> {
-        let cf = self
-            .inner
-            .cf_handle(col)
-            .ok_or_else(|| internal_error(format!("column {col} not found")))?;
+        let cf = self.cf_handle(col)?;
         self.inner.get_pinned_cf(cf, key).map_err(internal_error)
     }
+
+    pub(crate) fn cf_handle(&self, col: Col) -> Result<&ColumnFamily> {
+        self.inner
+            .cf_handle(col)
+            .ok_or_else(|| internal_error(format!("column {col} not found")))
+    }
 }
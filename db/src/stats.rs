@@ -0,0 +1,70 @@
+//! Disk usage statistics of the column families.
+use ckb_db_schema::{column_name, Col};
+
+/// "rocksdb.estimate-num-keys" - estimated number of total keys in the memtables and storage.
+pub(crate) const PROPERTY_ESTIMATE_NUM_KEYS: &str = "rocksdb.estimate-num-keys";
+/// "rocksdb.live-sst-files-size" - total size of all the SST files of the latest version.
+pub(crate) const PROPERTY_LIVE_SST_FILES_SIZE: &str = "rocksdb.live-sst-files-size";
+/// "rocksdb.estimate-pending-compaction-bytes" - estimated total number of bytes compaction
+/// needs to rewrite to get all levels down to under target size.
+pub(crate) const PROPERTY_ESTIMATE_PENDING_COMPACTION_BYTES: &str =
+    "rocksdb.estimate-pending-compaction-bytes";
+/// "rocksdb.aggregated-table-properties" - the aggregated table properties of all the SST files.
+pub(crate) const PROPERTY_AGGREGATED_TABLE_PROPERTIES: &str = "rocksdb.aggregated-table-properties";
+
+/// The statistics of a column family, the missing properties are reported as zero.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnFamilyStats {
+    /// The column family.
+    pub col: Col,
+    /// The estimated number of keys.
+    pub estimate_num_keys: u64,
+    /// The total size of the live SST files, in bytes.
+    pub live_sst_files_size: u64,
+    /// The estimated bytes which compactions need to rewrite.
+    pub estimate_pending_compaction_bytes: u64,
+    /// The uncompressed size of the keys and the values in the SST files, in bytes.
+    pub raw_data_size: u64,
+    /// The size of the data blocks in the SST files after compression, in bytes.
+    pub data_blocks_size: u64,
+}
+
+impl ColumnFamilyStats {
+    /// Returns the name of the column family, such as "block_header".
+    pub fn name(&self) -> &'static str {
+        column_name(self.col)
+    }
+
+    /// Returns the ratio of the raw data size to the compressed size, `None` if the column
+    /// family has no SST files.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        if self.data_blocks_size == 0 {
+            None
+        } else {
+            Some(self.raw_data_size as f64 / self.data_blocks_size as f64)
+        }
+    }
+
+    // Fills the sizes from the aggregated table properties, which look like
+    // "# data blocks=1; # entries=2; raw key size=64; ...; raw value size=128; data block size=96; ..."
+    pub(crate) fn set_table_properties(&mut self, properties: &str) {
+        let mut raw_key_size = 0;
+        let mut raw_value_size = 0;
+        for (key, value) in properties
+            .split(';')
+            .filter_map(|property| property.split_once('='))
+        {
+            let value = match value.trim().parse::<u64>() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            match key.trim() {
+                "raw key size" => raw_key_size = value,
+                "raw value size" => raw_value_size = value,
+                "data block size" => self.data_blocks_size = value,
+                _ => {}
+            }
+        }
+        self.raw_data_size = raw_key_size + raw_value_size;
+    }
+}
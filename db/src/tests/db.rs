@@ -1,7 +1,7 @@
 use ckb_app_config::DBConfig;
 use std::collections::HashMap;
 
-use crate::{Result, RocksDB};
+use crate::{ColumnFamilyStats, Result, RocksDB};
 
 fn setup_db(prefix: &str, columns: u32) -> RocksDB {
     setup_db_with_check(prefix, columns).unwrap()
@@ -137,3 +137,73 @@ fn write_and_partial_read() {
 
     assert!(vec![4u8, 3, 2].as_slice() == &ret.as_ref()[1..4]);
 }
+
+#[test]
+fn create_checkpoint() {
+    let db = setup_db("create_checkpoint", 2);
+    db.put_default([0], [0, 0]).unwrap();
+    let txn = db.transaction();
+    txn.put("1", &[1], &[1, 1]).unwrap();
+    txn.commit().unwrap();
+
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("create_checkpoint_target")
+        .tempdir()
+        .unwrap();
+    let path = tmp_dir.path().join("checkpoint");
+    db.create_checkpoint(&path).unwrap();
+    // the target must not exist
+    assert!(db.create_checkpoint(&path).is_err());
+
+    // the later writes are not in the checkpoint
+    db.put_default([2], [2, 2]).unwrap();
+
+    let checkpoint = RocksDB::open_in(&path, 2);
+    assert!(
+        vec![0u8, 0].as_slice()
+            == checkpoint
+                .get_pinned_default(&[0])
+                .unwrap()
+                .unwrap()
+                .as_ref()
+    );
+    assert!(vec![1u8, 1].as_slice() == checkpoint.get_pinned("1", &[1]).unwrap().unwrap().as_ref());
+    assert!(checkpoint.get_pinned_default(&[2]).unwrap().is_none());
+}
+
+#[test]
+fn column_family_stats() {
+    let db = setup_db("column_family_stats", 2);
+    let txn = db.transaction();
+    for i in 0..100u32 {
+        txn.put("1", &i.to_be_bytes(), &[0; 100]).unwrap();
+    }
+    txn.commit().unwrap();
+    // flushes the memtable into the SST files
+    db.compact_range("1", None, None).unwrap();
+
+    let stats = db.column_family_stats("1").unwrap();
+    assert_eq!(stats.col, "1");
+    assert_eq!(stats.estimate_num_keys, 100);
+    assert!(stats.live_sst_files_size > 0);
+    assert_eq!(stats.raw_data_size, 100 * (4 + 100));
+    assert!(stats.compression_ratio().is_some());
+
+    let stats = db.column_family_stats("0").unwrap();
+    assert_eq!(stats.estimate_num_keys, 0);
+    assert_eq!(stats.compression_ratio(), None);
+    assert!(db.column_family_stats("2").is_err());
+}
+
+#[test]
+fn parse_table_properties() {
+    let mut stats = ColumnFamilyStats::default();
+    stats.set_table_properties(
+        "# data blocks=2; # entries=10; raw key size=100; raw average key size=10; \
+         raw value size=300; raw average value size=30; data block size=200; \
+         index block size (user-key? 0, delta-value? 0)=30; filter block size=0; ",
+    );
+    assert_eq!(stats.raw_data_size, 400);
+    assert_eq!(stats.data_blocks_size, 200);
+    assert_eq!(stats.compression_ratio(), Some(2.0));
+}
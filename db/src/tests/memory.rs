@@ -0,0 +1,181 @@
+use crate::{
+    Direction, IteratorMode, KeyValueIter, KeyValueRead, KeyValueStore, KeyValueTransaction,
+    KeyValueWriteBatch, MemoryDB,
+};
+
+fn collect(iter: KeyValueIter) -> Vec<(Vec<u8>, Vec<u8>)> {
+    iter.map(|(key, value)| (key.to_vec(), value.to_vec()))
+        .collect()
+}
+
+#[test]
+fn write_and_read() {
+    let db = MemoryDB::open(2);
+
+    let txn = db.transaction();
+    txn.put("0", &[0, 0], &[0, 0, 0]).unwrap();
+    txn.put("1", &[1, 1], &[1, 1, 1]).unwrap();
+    txn.put("1", &[2], &[1, 1, 1]).unwrap();
+    txn.delete("1", &[2]).unwrap();
+    // the transaction reads its own writes
+    assert_eq!(txn.get("1", &[1, 1]).unwrap().unwrap().as_ref(), &[1, 1, 1]);
+    assert!(db.get("1", &[1, 1]).unwrap().is_none());
+    txn.commit().unwrap();
+
+    assert_eq!(db.get("0", &[0, 0]).unwrap().unwrap().as_ref(), &[0, 0, 0]);
+    assert!(db.get("0", &[1, 1]).unwrap().is_none());
+    assert!(db.get("1", &[0, 0]).unwrap().is_none());
+    assert_eq!(db.get("1", &[1, 1]).unwrap().unwrap().as_ref(), &[1, 1, 1]);
+    assert!(db.get("1", &[2]).unwrap().is_none());
+
+    db.put_default(&[0], &[0, 0]).unwrap();
+    assert_eq!(db.get_default(&[0]).unwrap().unwrap().as_ref(), &[0, 0]);
+    assert!(db.get("2", &[0]).is_err());
+}
+
+#[test]
+fn iterate() {
+    let db = MemoryDB::open(1);
+    let txn = db.transaction();
+    for i in 1..=4u8 {
+        txn.put("0", &[i], &[i, i]).unwrap();
+    }
+    txn.commit().unwrap();
+
+    let keys = |mode: IteratorMode| {
+        db.iterate("0", mode)
+            .unwrap()
+            .map(|(key, _)| key[0])
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(IteratorMode::Start), vec![1, 2, 3, 4]);
+    assert_eq!(keys(IteratorMode::End), vec![4, 3, 2, 1]);
+    assert_eq!(
+        keys(IteratorMode::From(&[2], Direction::Forward)),
+        vec![2, 3, 4]
+    );
+    assert_eq!(
+        keys(IteratorMode::From(&[3], Direction::Reverse)),
+        vec![3, 2, 1]
+    );
+    assert_eq!(
+        keys(IteratorMode::From(&[5], Direction::Forward)),
+        Vec::<u8>::new()
+    );
+
+    // the pending writes of a transaction overlay the store
+    let txn = db.transaction();
+    txn.delete("0", &[2]).unwrap();
+    txn.put("0", &[3], &[0]).unwrap();
+    txn.put("0", &[5], &[5, 5]).unwrap();
+    assert_eq!(
+        collect(txn.iterate("0", IteratorMode::Start).unwrap()),
+        vec![
+            (vec![1], vec![1, 1]),
+            (vec![3], vec![0]),
+            (vec![4], vec![4, 4]),
+            (vec![5], vec![5, 5]),
+        ]
+    );
+    assert_eq!(keys(IteratorMode::Start), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn snapshot_isolation() {
+    let db = MemoryDB::open(2);
+    let snapshot = db.get_snapshot();
+    let txn = db.transaction();
+    txn.put("0", &[0, 0], &[5, 4, 3, 2]).unwrap();
+    txn.put("1", &[1, 1], &[1, 2, 3, 4, 5]).unwrap();
+    txn.commit().unwrap();
+
+    assert!(snapshot.get("0", &[0, 0]).unwrap().is_none());
+    assert!(snapshot.get("1", &[1, 1]).unwrap().is_none());
+    assert_eq!(
+        snapshot.iterate("0", IteratorMode::Start).unwrap().count(),
+        0
+    );
+
+    let snapshot = db.get_snapshot();
+    let txn = db.transaction();
+    txn.delete("0", &[0, 0]).unwrap();
+    txn.commit().unwrap();
+    // compaction keeps the versions visible to the live snapshots
+    db.compact_range("0", None, None).unwrap();
+
+    assert!(db.get("0", &[0, 0]).unwrap().is_none());
+    assert_eq!(
+        snapshot.get("0", &[0, 0]).unwrap().unwrap().as_ref(),
+        &[5, 4, 3, 2]
+    );
+    assert_eq!(
+        snapshot.get("1", &[1, 1]).unwrap().unwrap().as_ref(),
+        &[1, 2, 3, 4, 5]
+    );
+}
+
+#[test]
+fn transaction_conflict() {
+    let db = MemoryDB::open(1);
+    let txn1 = db.transaction();
+    let txn2 = db.transaction();
+    assert!(txn1.get_for_update("0", &[0]).unwrap().is_none());
+    txn2.put("0", &[0], &[2]).unwrap();
+    txn2.commit().unwrap();
+
+    // the key read for update is modified since the transaction began
+    txn1.put("0", &[1], &[1]).unwrap();
+    assert!(txn1.commit().is_err());
+    assert!(db.get("0", &[1]).unwrap().is_none());
+
+    let txn3 = db.transaction();
+    let txn4 = db.transaction();
+    txn3.put("0", &[0], &[3]).unwrap();
+    txn4.put("0", &[0], &[4]).unwrap();
+    txn3.commit().unwrap();
+    assert!(txn4.commit().is_err());
+    assert_eq!(db.get("0", &[0]).unwrap().unwrap().as_ref(), &[3]);
+}
+
+#[test]
+fn savepoint_and_rollback() {
+    let db = MemoryDB::open(1);
+    let txn = db.transaction();
+    txn.put("0", &[0], &[0]).unwrap();
+    txn.set_savepoint();
+    txn.put("0", &[1], &[1]).unwrap();
+    txn.rollback_to_savepoint().unwrap();
+    assert!(txn.rollback_to_savepoint().is_err());
+    txn.commit().unwrap();
+    assert!(db.get("0", &[0]).unwrap().is_some());
+    assert!(db.get("0", &[1]).unwrap().is_none());
+
+    let txn = db.transaction();
+    txn.put("0", &[2], &[2]).unwrap();
+    txn.rollback().unwrap();
+    txn.commit().unwrap();
+    assert!(db.get("0", &[2]).unwrap().is_none());
+}
+
+#[test]
+fn write_batch() {
+    let db = MemoryDB::open(2);
+    let mut batch = db.new_write_batch();
+    assert!(batch.is_empty());
+    batch.put("0", &[0], &[0, 0]).unwrap();
+    batch.put("1", &[1], &[1, 1]).unwrap();
+    batch.put("1", &[2], &[2, 2]).unwrap();
+    batch.delete_range("1", [[2u8]].iter()).unwrap();
+    assert_eq!(batch.len(), 4);
+    assert_eq!(batch.size_in_bytes(), 10);
+    assert!(batch.put("2", &[0], &[0]).is_err());
+    db.write(&batch).unwrap();
+
+    assert_eq!(db.get("0", &[0]).unwrap().unwrap().as_ref(), &[0, 0]);
+    assert_eq!(db.get("1", &[1]).unwrap().unwrap().as_ref(), &[1, 1]);
+    assert!(db.get("1", &[2]).unwrap().is_none());
+
+    batch.clear().unwrap();
+    assert!(batch.is_empty());
+    assert_eq!(batch.size_in_bytes(), 0);
+}
@@ -1,3 +1,4 @@
 mod db;
 mod db_with_ttl;
+mod memory;
 mod read_only_db;
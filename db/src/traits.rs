@@ -0,0 +1,286 @@
+//! The key-value store traits, which abstract the storage backends of the chain store.
+//!
+//! The chain store only relies on these traits, so it runs against `RocksDB` as well as the
+//! in-memory backend `MemoryDB`.
+use crate::iter::DBIterator;
+use crate::{
+    DBPinnableSlice, IteratorMode, ReadOnlyDB, Result, RocksDB, RocksDBSnapshot,
+    RocksDBTransaction, RocksDBWriteBatch,
+};
+use ckb_db_schema::Col;
+use std::ops::Deref;
+
+/// An iterator over the key-value pairs of a column family.
+pub type KeyValueIter<'a> = Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
+
+/// A value read from a key-value store.
+pub enum DBValue<'a> {
+    /// A value pinned by RocksDB, which avoids the memory copy.
+    Pinned(DBPinnableSlice<'a>),
+    /// A value copied out of the store.
+    Owned(Box<[u8]>),
+}
+
+impl<'a> Deref for DBValue<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            DBValue::Pinned(slice) => slice,
+            DBValue::Owned(value) => value,
+        }
+    }
+}
+
+impl<'a> AsRef<[u8]> for DBValue<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+/// The read operations of a key-value store, a transaction or a snapshot.
+pub trait KeyValueRead {
+    /// Returns the value associated with the key in the column.
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>>;
+
+    /// Returns an iterator over the column, starting from the position specified by `mode`.
+    fn iterate(&self, col: Col, mode: IteratorMode) -> Result<KeyValueIter<'_>>;
+}
+
+/// A key-value store with column families, optimistic transactions, snapshots and write batches.
+pub trait KeyValueStore: KeyValueRead + Clone + Send + Sync + 'static {
+    /// The optimistic transaction of the store.
+    type Transaction: KeyValueTransaction;
+    /// The point-in-time view of the store.
+    type Snapshot: KeyValueRead + Send + Sync;
+    /// The atomic batch of write operations.
+    type WriteBatch: KeyValueWriteBatch;
+
+    /// Returns the value associated with the key in the default column.
+    fn get_default(&self, key: &[u8]) -> Result<Option<DBValue<'_>>>;
+
+    /// Inserts the value into the default column under the key.
+    fn put_default(&self, key: &[u8], value: &[u8]) -> Result<()>;
+
+    /// Begins a transaction, which conflicts with the writes committed after it begins.
+    fn transaction(&self) -> Self::Transaction;
+
+    /// Returns a snapshot of the current state of the store.
+    fn get_snapshot(&self) -> Self::Snapshot;
+
+    /// Constructs an empty write batch.
+    fn new_write_batch(&self) -> Self::WriteBatch;
+
+    /// Writes the batch into the store atomically.
+    fn write(&self, batch: &Self::WriteBatch) -> Result<()>;
+
+    /// Writes the batch into the store atomically and flushes it to the disk.
+    fn write_sync(&self, batch: &Self::WriteBatch) -> Result<()>;
+
+    /// Compacts the key range of the column, the unbounded ends are `None`.
+    fn compact_range(&self, col: Col, start: Option<&[u8]>, end: Option<&[u8]>) -> Result<()>;
+}
+
+/// An optimistic transaction of a key-value store.
+///
+/// The reads see the writes of the transaction itself, and the commit fails if a key written or
+/// read for update by the transaction has been modified since the transaction began.
+pub trait KeyValueTransaction: KeyValueRead + Send + Sync {
+    /// Writes the value into the column under the key.
+    fn put(&self, col: Col, key: &[u8], value: &[u8]) -> Result<()>;
+
+    /// Deletes the key from the column.
+    fn delete(&self, col: Col, key: &[u8]) -> Result<()>;
+
+    /// Reads the key as of the beginning of the transaction, and makes the value a precondition
+    /// of the commit.
+    fn get_for_update(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>>;
+
+    /// Commits the transaction.
+    fn commit(&self) -> Result<()>;
+
+    /// Discards all the writes of the transaction.
+    fn rollback(&self) -> Result<()>;
+
+    /// Sets a savepoint of the transaction.
+    fn set_savepoint(&self);
+
+    /// Discards the writes after the last savepoint.
+    fn rollback_to_savepoint(&self) -> Result<()>;
+}
+
+/// An atomic batch of write operations.
+pub trait KeyValueWriteBatch {
+    /// Writes the value into the column under the key.
+    fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()>;
+
+    /// Deletes the key from the column.
+    fn delete(&mut self, col: Col, key: &[u8]) -> Result<()>;
+
+    /// Deletes all the keys yielded by `range` from the column.
+    fn delete_range<K: AsRef<[u8]>>(
+        &mut self,
+        col: Col,
+        range: impl Iterator<Item = K>,
+    ) -> Result<()> {
+        for key in range {
+            self.delete(col, key.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the count of the operations.
+    fn len(&self) -> usize;
+
+    /// Returns true if the batch contains no operations.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the approximate size of the batch in bytes.
+    fn size_in_bytes(&self) -> usize;
+
+    /// Clears all the operations.
+    fn clear(&mut self) -> Result<()>;
+}
+
+impl KeyValueRead for RocksDB {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        self.get_pinned(col, key)
+            .map(|value| value.map(DBValue::Pinned))
+    }
+
+    fn iterate(&self, col: Col, mode: IteratorMode) -> Result<KeyValueIter<'_>> {
+        self.iter(col, mode)
+            .map(|iter| Box::new(iter) as KeyValueIter<'_>)
+    }
+}
+
+impl KeyValueStore for RocksDB {
+    type Transaction = RocksDBTransaction;
+    type Snapshot = RocksDBSnapshot;
+    type WriteBatch = RocksDBWriteBatch;
+
+    fn get_default(&self, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        self.get_pinned_default(key)
+            .map(|value| value.map(DBValue::Pinned))
+    }
+
+    fn put_default(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        RocksDB::put_default(self, key, value)
+    }
+
+    fn transaction(&self) -> RocksDBTransaction {
+        RocksDB::transaction(self)
+    }
+
+    fn get_snapshot(&self) -> RocksDBSnapshot {
+        RocksDB::get_snapshot(self)
+    }
+
+    fn new_write_batch(&self) -> RocksDBWriteBatch {
+        RocksDB::new_write_batch(self)
+    }
+
+    fn write(&self, batch: &RocksDBWriteBatch) -> Result<()> {
+        RocksDB::write(self, batch)
+    }
+
+    fn write_sync(&self, batch: &RocksDBWriteBatch) -> Result<()> {
+        RocksDB::write_sync(self, batch)
+    }
+
+    fn compact_range(&self, col: Col, start: Option<&[u8]>, end: Option<&[u8]>) -> Result<()> {
+        RocksDB::compact_range(self, col, start, end)
+    }
+}
+
+impl KeyValueRead for RocksDBTransaction {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        self.get_pinned(col, key)
+            .map(|value| value.map(DBValue::Pinned))
+    }
+
+    fn iterate(&self, col: Col, mode: IteratorMode) -> Result<KeyValueIter<'_>> {
+        self.iter(col, mode)
+            .map(|iter| Box::new(iter) as KeyValueIter<'_>)
+    }
+}
+
+impl KeyValueTransaction for RocksDBTransaction {
+    fn put(&self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        RocksDBTransaction::put(self, col, key, value)
+    }
+
+    fn delete(&self, col: Col, key: &[u8]) -> Result<()> {
+        RocksDBTransaction::delete(self, col, key)
+    }
+
+    fn get_for_update(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        let snapshot = self.get_snapshot();
+        RocksDBTransaction::get_for_update(self, col, key, &snapshot)
+            .map(|value| value.map(|value| DBValue::Owned(value.as_ref().into())))
+    }
+
+    fn commit(&self) -> Result<()> {
+        RocksDBTransaction::commit(self)
+    }
+
+    fn rollback(&self) -> Result<()> {
+        RocksDBTransaction::rollback(self)
+    }
+
+    fn set_savepoint(&self) {
+        RocksDBTransaction::set_savepoint(self)
+    }
+
+    fn rollback_to_savepoint(&self) -> Result<()> {
+        RocksDBTransaction::rollback_to_savepoint(self)
+    }
+}
+
+impl KeyValueRead for RocksDBSnapshot {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        self.get_pinned(col, key)
+            .map(|value| value.map(DBValue::Pinned))
+    }
+
+    fn iterate(&self, col: Col, mode: IteratorMode) -> Result<KeyValueIter<'_>> {
+        self.iter(col, mode)
+            .map(|iter| Box::new(iter) as KeyValueIter<'_>)
+    }
+}
+
+impl KeyValueRead for ReadOnlyDB {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<DBValue<'_>>> {
+        self.get_pinned(col, key)
+            .map(|value| value.map(DBValue::Pinned))
+    }
+
+    fn iterate(&self, col: Col, mode: IteratorMode) -> Result<KeyValueIter<'_>> {
+        self.iter(col, mode)
+            .map(|iter| Box::new(iter) as KeyValueIter<'_>)
+    }
+}
+
+impl KeyValueWriteBatch for RocksDBWriteBatch {
+    fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        RocksDBWriteBatch::put(self, col, key, value)
+    }
+
+    fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
+        RocksDBWriteBatch::delete(self, col, key)
+    }
+
+    fn len(&self) -> usize {
+        RocksDBWriteBatch::len(self)
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        RocksDBWriteBatch::size_in_bytes(self)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        RocksDBWriteBatch::clear(self)
+    }
+}
@@ -18,6 +18,7 @@ macro_rules! generate_docs {
 
 pub(crate) fn all_rpc_docs() -> Vec<(String, Value)> {
     generate_docs!(
+        admin_rpc_doc,
         alert_rpc_doc,
         net_rpc_doc,
         subscription_rpc_doc,
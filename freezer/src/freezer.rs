@@ -1,4 +1,4 @@
-use crate::freezer_files::FreezerFiles;
+use crate::freezer_files::{FreezerFiles, IndexEntry};
 use crate::internal_error;
 use ckb_error::Error;
 use ckb_types::{
@@ -20,33 +20,177 @@ const LOCKNAME: &str = "FLOCK";
 /// sorted blkhash for making ranges for compaction
 type FreezeResult = BTreeMap<packed::Byte32, (BlockNumber, u32)>;
 
+/// freeze meta result represent blkhash -> (blknum, tx hashes) btree-map
+type FreezeMetaResult = BTreeMap<packed::Byte32, (BlockNumber, Vec<packed::Byte32>)>;
+
+/// The tables of the data frozen after the blocks, each table is stored in separate freezer
+/// files under the sub-directory of the same name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FreezerTable {
+    /// The `packed::HeaderView` of the blocks.
+    Headers = 0,
+    /// The serialized `BlockExt` of the blocks, in the same layout as in the kv-db.
+    BlockExts = 1,
+    /// The `packed::Byte32Vec` of the transaction hashes of the blocks.
+    TxHashes = 2,
+    /// The `packed::Byte32` of the epoch indexes of the blocks.
+    BlockEpochs = 3,
+}
+
+impl FreezerTable {
+    const ALL: [FreezerTable; 4] = [
+        FreezerTable::Headers,
+        FreezerTable::BlockExts,
+        FreezerTable::TxHashes,
+        FreezerTable::BlockEpochs,
+    ];
+
+    fn dir_name(self) -> &'static str {
+        match self {
+            FreezerTable::Headers => "headers",
+            FreezerTable::BlockExts => "block_exts",
+            FreezerTable::TxHashes => "tx_hashes",
+            FreezerTable::BlockEpochs => "block_epochs",
+        }
+    }
+
+    // Verifies the item of the block, returns the reason if it is corrupted.
+    fn verify(self, number: BlockNumber, data: &[u8]) -> Result<(), String> {
+        match self {
+            FreezerTable::Headers => {
+                let header =
+                    packed::HeaderViewReader::from_slice(data).map_err(|e| e.to_string())?;
+                let header_number: BlockNumber = header.data().raw().number().unpack();
+                if header_number != number {
+                    return Err(format!("unexpected header number {header_number}"));
+                }
+                if header.hash().as_slice() != header.data().calc_header_hash().as_slice() {
+                    return Err("header hash mismatch".to_owned());
+                }
+            }
+            FreezerTable::BlockExts => {
+                let ext = packed::BlockExtReader::from_compatible_slice(data)
+                    .map_err(|e| e.to_string())?;
+                match ext.count_extra_fields() {
+                    0 => {}
+                    2 => {
+                        packed::BlockExtV1Reader::from_slice(data).map_err(|e| e.to_string())?;
+                    }
+                    n => return Err(format!("unexpected {n} extra fields")),
+                }
+            }
+            FreezerTable::TxHashes => {
+                packed::Byte32VecReader::verify(data, false).map_err(|e| e.to_string())?;
+            }
+            FreezerTable::BlockEpochs => {
+                packed::Byte32Reader::verify(data, false).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+}
+
 struct Inner {
     pub(crate) files: FreezerFiles,
+    // indexed by `FreezerTable`
+    pub(crate) tables: Vec<FreezerFiles>,
     pub(crate) tip: Option<HeaderView>,
 }
 
+/// The result of the freezer integrity check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreezerCheck {
+    /// The number of the items in the index, including the corrupted ones.
+    pub number: BlockNumber,
+    /// The number of the last good block, all the blocks above it are dropped by the repair.
+    /// Zero means no good block is found.
+    pub last_good: BlockNumber,
+    /// The description of the first corrupted block, `None` if the blocks are intact.
+    pub corruption: Option<String>,
+    /// The number of the items in the headers, the block exts, the transaction hashes and the
+    /// block epochs tables, the smallest one if they differ.
+    pub meta_number: BlockNumber,
+    /// The number of the last item which is good in all the meta tables, the items above it are
+    /// dropped by the repair. Zero means no good item is found.
+    pub meta_last_good: BlockNumber,
+    /// The description of the first corrupted item in the meta tables, `None` if they are intact.
+    pub meta_corruption: Option<String>,
+}
+
+impl FreezerCheck {
+    /// Returns true if no corruption is found.
+    pub fn is_intact(&self) -> bool {
+        self.corruption.is_none() && self.meta_corruption.is_none()
+    }
+}
+
+/// The disk usage statistics of a freezer table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreezerTableStats {
+    /// The name of the table, "blocks" for the frozen blocks.
+    pub name: &'static str,
+    /// The total item number in the table.
+    pub number: u64,
+    /// The number of the data files.
+    pub files: u64,
+    /// The total size of the data files, in bytes.
+    pub data_size: u64,
+    /// The size of the index file, in bytes.
+    pub index_size: u64,
+}
+
 /// Freezer is an memory mapped append-only database to store immutable chain data into flat files
 #[derive(Clone)]
 pub struct Freezer {
     inner: Arc<Mutex<Inner>>,
     number: Arc<AtomicU64>,
+    meta_number: Arc<AtomicU64>,
     /// stop flag
     pub stopped: Arc<AtomicBool>,
-    /// file lock to prevent double opens
-    pub(crate) _lock: Arc<File>,
+    /// file lock to prevent double opens, `None` if opened in read-only mode
+    pub(crate) _lock: Option<Arc<File>>,
 }
 
 impl Freezer {
     /// Creates a freezer at specified path
     pub fn open(path: PathBuf) -> Result<Freezer, Error> {
-        let lock_path = path.join(LOCKNAME);
-        let lock = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(lock_path)
+        let lock = lock(&path)?;
+        let files = FreezerFiles::open(path.clone()).map_err(internal_error)?;
+        let mut tables = FreezerTable::ALL
+            .iter()
+            .map(|table| FreezerFiles::open(path.join(table.dir_name())))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(internal_error)?;
+        // the tables are appended one by one, drop the items which are not in all the tables
+        // after a potential crash
+        let meta_number = meta_number(&tables);
+        for table in tables.iter_mut() {
+            table.truncate_to(meta_number - 1).map_err(internal_error)?;
+        }
+        Self::new(files, tables, meta_number, Some(lock))
+    }
+
+    /// Opens the freezer at specified path in read-only mode.
+    ///
+    /// The freezer is not locked, so it can be opened while another process is using it. Nothing
+    /// is repaired or truncated, the items which are not in all the tables are ignored.
+    pub fn open_read_only(path: PathBuf) -> Result<Freezer, Error> {
+        let files = FreezerFiles::open_read_only(path.clone()).map_err(internal_error)?;
+        let tables = FreezerTable::ALL
+            .iter()
+            .map(|table| FreezerFiles::open_read_only(path.join(table.dir_name())))
+            .collect::<Result<Vec<_>, _>>()
             .map_err(internal_error)?;
-        lock.try_lock_exclusive().map_err(internal_error)?;
-        let mut files = FreezerFiles::open(path).map_err(internal_error)?;
+        let meta_number = meta_number(&tables);
+        Self::new(files, tables, meta_number, None)
+    }
+
+    fn new(
+        mut files: FreezerFiles,
+        tables: Vec<FreezerFiles>,
+        meta_number: BlockNumber,
+        lock: Option<File>,
+    ) -> Result<Freezer, Error> {
         let freezer_number = files.number();
 
         let mut tip = None;
@@ -64,12 +208,13 @@ impl Freezer {
             tip = Some(block.header().into_view());
         }
 
-        let inner = Inner { files, tip };
+        let inner = Inner { files, tables, tip };
         Ok(Freezer {
             number: Arc::clone(&inner.files.number),
+            meta_number: Arc::new(AtomicU64::new(meta_number)),
             inner: Arc::new(Mutex::new(inner)),
             stopped: Arc::new(AtomicBool::new(false)),
-            _lock: Arc::new(lock),
+            _lock: lock.map(Arc::new),
         })
     }
 
@@ -78,6 +223,50 @@ impl Freezer {
         Self::open(path.as_ref().to_path_buf())
     }
 
+    /// Verifies the freezer at specified path item by item.
+    ///
+    /// The index entries are validated against the data files, then every block is decompressed,
+    /// parsed and checked to link to its predecessor, and every item in the meta tables is parsed.
+    /// Unlike `open`, it works on a freezer with a corrupted tip, and the files are opened in
+    /// read-only mode, so nothing is repaired or truncated by the check.
+    pub fn check(path: PathBuf) -> Result<FreezerCheck, Error> {
+        let _lock = lock(&path)?;
+        let mut files = FreezerFiles::open_read_only(path.clone()).map_err(internal_error)?;
+        let mut tables = open_existing_tables(&path, FreezerFiles::open_read_only)?;
+        check_files(&mut files, &mut tables)
+    }
+
+    /// Verifies the freezer at specified path and drops the blocks from the first corrupted one.
+    /// The headers, the block exts, the transaction hashes and the block epochs are dropped from
+    /// the first corrupted item in any of the meta tables, the intact ones are kept since they have been wiped out
+    /// from the kv-db.
+    ///
+    /// Returns the check result before the repair.
+    pub fn repair(path: PathBuf) -> Result<FreezerCheck, Error> {
+        let _lock = lock(&path)?;
+        let mut files = FreezerFiles::open(path.clone()).map_err(internal_error)?;
+        let mut tables = open_existing_tables(&path, FreezerFiles::open)?;
+        let check = check_files(&mut files, &mut tables)?;
+        if check.corruption.is_some() {
+            ckb_logger::warn!("Freezer truncates blocks above {}", check.last_good);
+            files.truncate_to(check.last_good).map_err(internal_error)?;
+            files.sync_all().map_err(internal_error)?;
+        }
+        if check.meta_corruption.is_some() {
+            ckb_logger::warn!(
+                "Freezer truncates block metas above {}",
+                check.meta_last_good
+            );
+            for (_, table_files) in tables.iter_mut() {
+                table_files
+                    .truncate_to(check.meta_last_good)
+                    .map_err(internal_error)?;
+                table_files.sync_all().map_err(internal_error)?;
+            }
+        }
+        Ok(check)
+    }
+
     /// Freeze background process that periodically checks the chain data for any
     /// import progress and moves ancient data from the kv-db into the freezer.
     pub fn freeze<F>(
@@ -134,6 +323,112 @@ impl Freezer {
         Ok(ret)
     }
 
+    /// Freezes the headers, the block exts, the transaction hashes and the epoch indexes of the
+    /// frozen blocks below `threshold`, after that they can be wiped out from the kv-db.
+    ///
+    /// The headers and the transaction hashes are taken from the frozen blocks, the block exts
+    /// and the epoch indexes are loaded by `get_block_meta` with the block hash.
+    pub fn freeze_meta<F>(
+        &self,
+        threshold: BlockNumber,
+        get_block_meta: F,
+    ) -> Result<FreezeMetaResult, Error>
+    where
+        F: Fn(&packed::Byte32) -> Option<(Vec<u8>, packed::Byte32)>,
+    {
+        let number = self.meta_number();
+        let threshold = std::cmp::min(threshold, self.number());
+        let mut guard = self.inner.lock();
+        let mut ret = BTreeMap::new();
+        ckb_logger::trace!(
+            "Freezer meta process initiated, starting from {}, threshold {}",
+            number,
+            threshold
+        );
+
+        for number in number..threshold {
+            if self.stopped.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let raw_block = guard
+                .files
+                .retrieve(number)
+                .map_err(internal_error)?
+                .ok_or_else(|| internal_error("freezer inconsistent"))?;
+            let block = packed::BlockReader::from_compatible_slice(&raw_block)
+                .map_err(internal_error)?
+                .to_entity()
+                .into_view();
+            let hash = block.hash();
+            let (ext, epoch_index) = match get_block_meta(&hash) {
+                Some(meta) => meta,
+                None => {
+                    ckb_logger::error!("Freezer block ext or epoch index missing {}", number);
+                    break;
+                }
+            };
+            let header: packed::HeaderView = block.header().pack();
+            let tx_hashes = block.tx_hashes().to_vec();
+            let packed_tx_hashes = packed::Byte32Vec::new_builder()
+                .set(tx_hashes.clone())
+                .build();
+
+            for (table, data) in FreezerTable::ALL.iter().zip([
+                header.as_slice(),
+                ext.as_slice(),
+                packed_tx_hashes.as_slice(),
+                epoch_index.as_slice(),
+            ]) {
+                guard.tables[*table as usize]
+                    .append(number, data)
+                    .map_err(internal_error)?;
+            }
+            self.meta_number.fetch_add(1, Ordering::SeqCst);
+
+            ret.insert(hash, (number, tx_hashes));
+            ckb_logger::trace!("Freezer block meta append {}", number);
+        }
+        for table in guard.tables.iter() {
+            table.sync_all().map_err(internal_error)?;
+        }
+        Ok(ret)
+    }
+
+    /// Retrieve an item with the given number from the table
+    ///
+    /// The item is verified before returned, returns an error if it is corrupted.
+    pub fn retrieve_meta(
+        &self,
+        table: FreezerTable,
+        number: BlockNumber,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let data = self.inner.lock().tables[table as usize]
+            .retrieve(number)
+            .map_err(internal_error)?;
+        if let Some(ref data) = data {
+            if let Err(reason) = table.verify(number, data) {
+                ckb_logger::error!(
+                    "Freezer {} {} is corrupted: {}, run `ckb freezer check`",
+                    table.dir_name(),
+                    number,
+                    reason
+                );
+                return Err(internal_error(format!(
+                    "{} {number}: {reason}",
+                    table.dir_name()
+                )));
+            }
+        }
+        Ok(data)
+    }
+
+    /// Return total item number in the tables, the headers, the block exts, the transaction
+    /// hashes and the epoch indexes of the blocks below it are frozen
+    pub fn meta_number(&self) -> BlockNumber {
+        self.meta_number.load(Ordering::SeqCst)
+    }
+
     /// Retrieve an item with the given number
     pub fn retrieve(&self, number: BlockNumber) -> Result<Option<Vec<u8>>, Error> {
         self.inner
@@ -148,6 +443,52 @@ impl Freezer {
         self.number.load(Ordering::SeqCst)
     }
 
+    /// Copies the freezer into the directory, the sealed data files are hard linked when possible.
+    ///
+    /// The items to back up are captured while freezing is blocked, so the tables are consistent
+    /// with each other, then they are copied without blocking freezing. Returns the total item
+    /// number in the backup.
+    pub fn backup(&self, path: &Path) -> Result<BlockNumber, Error> {
+        let (number, backups) = {
+            let inner = self.inner.lock();
+            let mut backups = vec![(
+                path.to_path_buf(),
+                inner.files.backup_files().map_err(internal_error)?,
+            )];
+            for (table, files) in FreezerTable::ALL.iter().zip(inner.tables.iter()) {
+                backups.push((
+                    path.join(table.dir_name()),
+                    files.backup_files().map_err(internal_error)?,
+                ));
+            }
+            (inner.files.number(), backups)
+        };
+        for (dir, files) in backups {
+            files.copy_to(&dir).map_err(internal_error)?;
+        }
+        Ok(number)
+    }
+
+    /// Returns the disk usage statistics of the blocks and the meta tables.
+    pub fn stats(&self) -> Result<Vec<FreezerTableStats>, Error> {
+        let inner = self.inner.lock();
+        let table_stats = |name, files: &FreezerFiles| -> Result<FreezerTableStats, Error> {
+            let (count, data_size, index_size) = files.disk_usage().map_err(internal_error)?;
+            Ok(FreezerTableStats {
+                name,
+                number: files.number(),
+                files: count,
+                data_size,
+                index_size,
+            })
+        };
+        let mut stats = vec![table_stats("blocks", &inner.files)?];
+        for (table, files) in FreezerTable::ALL.iter().zip(inner.tables.iter()) {
+            stats.push(table_stats(table.dir_name(), files)?);
+        }
+        Ok(stats)
+    }
+
     /// Truncate discards any recent data above the provided threshold number.
     pub fn truncate(&self, item: u64) -> Result<(), Error> {
         if item > 0 && ((item + 1) < self.number()) {
@@ -170,3 +511,193 @@ impl Freezer {
         Ok(())
     }
 }
+
+// The tables are in sync with each other.
+fn meta_number(tables: &[FreezerFiles]) -> BlockNumber {
+    tables
+        .iter()
+        .map(FreezerFiles::number)
+        .min()
+        .expect("tables are not empty")
+}
+
+// The freezer created before the meta tables has none of them.
+fn open_existing_tables<F>(path: &Path, open: F) -> Result<Vec<(FreezerTable, FreezerFiles)>, Error>
+where
+    F: Fn(PathBuf) -> Result<FreezerFiles, std::io::Error>,
+{
+    FreezerTable::ALL
+        .iter()
+        .map(|table| (*table, path.join(table.dir_name())))
+        .filter(|(_, table_path)| table_path.exists())
+        .map(|(table, table_path)| Ok((table, open(table_path).map_err(internal_error)?)))
+        .collect()
+}
+
+fn lock(path: &Path) -> Result<File, Error> {
+    let lock = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .open(path.join(LOCKNAME))
+        .map_err(internal_error)?;
+    lock.try_lock_exclusive().map_err(internal_error)?;
+    Ok(lock)
+}
+
+fn check_files(
+    files: &mut FreezerFiles,
+    tables: &mut [(FreezerTable, FreezerFiles)],
+) -> Result<FreezerCheck, Error> {
+    let number = files.number();
+    let mut prev = files.index_entry(0).map_err(internal_error)?;
+    let mut parent_hash = None;
+    let mut last_good = number.saturating_sub(1);
+    let mut corruption = None;
+    for item in 1..number {
+        match check_item(files, item, &prev, parent_hash.as_ref()) {
+            Ok((entry, hash)) => {
+                prev = entry;
+                parent_hash = Some(hash);
+            }
+            Err(reason) => {
+                last_good = item - 1;
+                corruption = Some(format!("block {item}: {reason}"));
+                break;
+            }
+        }
+    }
+
+    let mut meta_number = tables
+        .iter()
+        .map(|(_, table_files)| table_files.number())
+        .min()
+        .unwrap_or(0);
+    if tables.len() < FreezerTable::ALL.len() {
+        meta_number = 0;
+    }
+    let mut meta_last_good = meta_number.saturating_sub(1);
+    let mut meta_corruption = None;
+    for (table, table_files) in tables.iter_mut() {
+        let mut prev = table_files.index_entry(0).map_err(internal_error)?;
+        // the items beyond the smallest table are dropped on open, they are not corruptions
+        for item in 1..std::cmp::min(table_files.number(), meta_last_good + 1) {
+            match check_meta_item(table_files, *table, item, &prev) {
+                Ok(entry) => prev = entry,
+                Err(reason) => {
+                    meta_last_good = item - 1;
+                    meta_corruption = Some(format!("{} {item}: {reason}", table.dir_name()));
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(FreezerCheck {
+        number,
+        last_good,
+        corruption,
+        meta_number,
+        meta_last_good,
+        meta_corruption,
+    })
+}
+
+// Returns the index entry and the hash of the item, or the reason why it is corrupted.
+fn check_item(
+    files: &mut FreezerFiles,
+    item: BlockNumber,
+    prev: &IndexEntry,
+    parent_hash: Option<&packed::Byte32>,
+) -> Result<(IndexEntry, packed::Byte32), String> {
+    let entry = check_entry(files, item, prev)?;
+    let raw_block = files
+        .retrieve(item)
+        .map_err(|e| format!("retrieve error {e}"))?
+        .ok_or_else(|| "retrieve nothing".to_owned())?;
+    let block = packed::BlockReader::from_compatible_slice(&raw_block)
+        .map_err(|e| format!("parse error {e}"))?;
+    if block.count_extra_fields() > 1 {
+        return Err("block has more than one extra fields".to_owned());
+    }
+    let header = block.header();
+    let block_number: BlockNumber = header.raw().number().unpack();
+    if block_number != item {
+        return Err(format!("unexpected block number {block_number}"));
+    }
+    if let Some(parent_hash) = parent_hash {
+        if header.raw().parent_hash().as_slice() != parent_hash.as_slice() {
+            return Err(format!(
+                "parent hash {} does not match the previous block hash {}",
+                header.raw().parent_hash(),
+                parent_hash
+            ));
+        }
+    }
+    Ok((entry, header.calc_header_hash()))
+}
+
+// Returns the index entry of the item in the meta table, or the reason why it is corrupted.
+fn check_meta_item(
+    files: &mut FreezerFiles,
+    table: FreezerTable,
+    item: BlockNumber,
+    prev: &IndexEntry,
+) -> Result<IndexEntry, String> {
+    let entry = check_entry(files, item, prev)?;
+    let data = files
+        .retrieve(item)
+        .map_err(|e| format!("retrieve error {e}"))?
+        .ok_or_else(|| "retrieve nothing".to_owned())?;
+    table
+        .verify(item, &data)
+        .map_err(|e| format!("parse error {e}"))?;
+    Ok(entry)
+}
+
+// Validates the index entry of the item against the previous one and the data files.
+fn check_entry(
+    files: &mut FreezerFiles,
+    item: BlockNumber,
+    prev: &IndexEntry,
+) -> Result<IndexEntry, String> {
+    let entry = files
+        .index_entry(item)
+        .map_err(|e| format!("read index entry error {e}"))?;
+    if entry.file_id < files.tail_id || entry.file_id > files.head_id {
+        return Err(format!(
+            "file id {} out of range [{}, {}]",
+            entry.file_id, files.tail_id, files.head_id
+        ));
+    }
+    // a new data file is opened only when the current one is full
+    let start = if entry.file_id == prev.file_id {
+        prev.offset
+    } else if entry.file_id == prev.file_id + 1 {
+        0
+    } else {
+        return Err(format!(
+            "file id {} does not follow the previous file id {}",
+            entry.file_id, prev.file_id
+        ));
+    };
+    if entry.offset <= start {
+        return Err(format!(
+            "end offset {} is not greater than start offset {start}",
+            entry.offset
+        ));
+    }
+    match files
+        .file_size(entry.file_id)
+        .map_err(|e| format!("read data file error {e}"))?
+    {
+        Some(size) if size >= entry.offset => {}
+        Some(size) => {
+            return Err(format!(
+                "end offset {} exceeds the size {size} of file {}",
+                entry.offset, entry.file_id
+            ));
+        }
+        None => return Err(format!("data file {} is missing", entry.file_id)),
+    }
+    Ok(entry)
+}
@@ -59,6 +59,61 @@ pub struct FreezerFiles {
     pub(crate) enable_compression: bool,
 }
 
+/// The files captured by `FreezerFiles::backup_files`
+pub(crate) struct BackupFiles {
+    file_path: PathBuf,
+    tail_id: FileId,
+    head_id: FileId,
+    // number of bytes of the head file to back up
+    head_bytes: u64,
+    // number of bytes of the index to back up
+    index_bytes: u64,
+}
+
+impl BackupFiles {
+    /// Copies the captured files into the directory, which is created if missing.
+    ///
+    /// The sealed data files are immutable, they are hard linked when possible. The head file and
+    /// the index are only appended, their captured prefixes are copied, so the freezer needn't be
+    /// locked while copying.
+    pub(crate) fn copy_to(&self, dir: &Path) -> Result<(), IoError> {
+        fs::create_dir_all(dir)?;
+        for id in self.tail_id..self.head_id {
+            let name = helper::file_name(id);
+            let src = self.file_path.join(&name);
+            if !src.exists() {
+                continue;
+            }
+            if fs::hard_link(&src, dir.join(&name)).is_err() {
+                fs::copy(&src, dir.join(&name))?;
+            }
+        }
+        let name = helper::file_name(self.head_id);
+        copy_prefix(
+            &self.file_path.join(&name),
+            &dir.join(&name),
+            self.head_bytes,
+        )?;
+        copy_prefix(
+            &self.file_path.join(INDEX_FILE_NAME),
+            &dir.join(INDEX_FILE_NAME),
+            self.index_bytes,
+        )
+    }
+}
+
+fn copy_prefix(src: &Path, dst: &Path, len: u64) -> Result<(), IoError> {
+    let mut dst_file = File::create(dst)?;
+    let copied = std::io::copy(&mut File::open(src)?.take(len), &mut dst_file)?;
+    if copied != len {
+        return Err(IoError::new(
+            IoErrorKind::UnexpectedEof,
+            format!("{} is truncated while backing up", src.display()),
+        ));
+    }
+    dst_file.sync_all()
+}
+
 /// An instance of IndexEntry represents an entry inside of a index files
 #[derive(Default)]
 pub struct IndexEntry {
@@ -103,6 +158,11 @@ impl FreezerFiles {
         Ok(files)
     }
 
+    /// Opens freezer files at path in read-only mode, nothing is created, repaired or truncated.
+    pub fn open_read_only(file_path: PathBuf) -> Result<FreezerFiles, IoError> {
+        FreezerFilesBuilder::new(file_path).read_only(true).build()
+    }
+
     /// Return frozen item number
     #[inline]
     pub fn number(&self) -> u64 {
@@ -140,7 +200,7 @@ impl FreezerFiles {
 
             // release old head, reopen with read only
             self.release(head_id);
-            self.open_read_only(head_id)?;
+            self.open_read_only_file(head_id)?;
 
             self.head_id = next_id;
             self.head = Head::new(new_head_file, 0);
@@ -181,7 +241,7 @@ impl FreezerFiles {
             let mut file = if let Some(file) = self.files.get(&file_id) {
                 file
             } else {
-                open_read_only = self.open_read_only(file_id)?;
+                open_read_only = self.open_read_only_file(file_id)?;
                 &open_read_only
             };
 
@@ -253,12 +313,51 @@ impl FreezerFiles {
         )))
     }
 
+    /// Reads the index entry of the item.
+    pub(crate) fn index_entry(&self, item: u64) -> Result<IndexEntry, IoError> {
+        let mut buffer = [0; INDEX_ENTRY_SIZE as usize];
+        let mut index = &self.index;
+        index.seek(SeekFrom::Start(item * INDEX_ENTRY_SIZE))?;
+        index.read_exact(&mut buffer)?;
+        IndexEntry::decode(&buffer)
+    }
+
+    /// Returns the size of the data file, or `None` if the file doesn't exist.
+    pub(crate) fn file_size(&self, id: FileId) -> Result<Option<u64>, IoError> {
+        match fs::metadata(self.file_path.join(helper::file_name(id))) {
+            Ok(metadata) => Ok(Some(metadata.len())),
+            Err(e) if e.kind() == IoErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the number of the data files, the total size of them and the size of the index.
+    pub(crate) fn disk_usage(&self) -> Result<(u64, u64, u64), IoError> {
+        let mut files = 0;
+        let mut data_size = 0;
+        for id in self.tail_id..=self.head_id {
+            if let Some(size) = self.file_size(id)? {
+                files += 1;
+                data_size += size;
+            }
+        }
+        Ok((files, data_size, self.index.metadata()?.len()))
+    }
+
     /// keeping the the provided threshold number item and dropping the rest.
     pub fn truncate(&mut self, item: u64) -> Result<(), IoError> {
         // out of bound, this has no effect.
         if item < 1 || ((item + 1) >= self.number()) {
             return Ok(());
         }
+        self.truncate_to(item)
+    }
+
+    /// Same as `truncate`, but item 0 is accepted to drop all the items.
+    pub(crate) fn truncate_to(&mut self, item: u64) -> Result<(), IoError> {
+        if (item + 1) >= self.number() {
+            return Ok(());
+        }
         ckb_logger::trace!("Freezer truncate items {}", item);
 
         let mut buffer = [0; INDEX_ENTRY_SIZE as usize];
@@ -271,6 +370,8 @@ impl FreezerFiles {
         // truncate files
         if new_index.file_id != self.head_id {
             self.release(new_index.file_id);
+            // the sealed data files may be hard linked by backups, never modify them in place
+            self.unshare_file(new_index.file_id)?;
             let (new_head_file, offset) = self.open_append(new_index.file_id)?;
 
             self.delete_after(new_index.file_id)?;
@@ -284,12 +385,25 @@ impl FreezerFiles {
         Ok(())
     }
 
+    /// Syncs the files and captures the items to back up, the items appended later are not
+    /// included in the backup.
+    pub(crate) fn backup_files(&self) -> Result<BackupFiles, IoError> {
+        self.sync_all()?;
+        Ok(BackupFiles {
+            file_path: self.file_path.clone(),
+            tail_id: self.tail_id,
+            head_id: self.head_id,
+            head_bytes: self.head.bytes,
+            index_bytes: self.number() * INDEX_ENTRY_SIZE,
+        })
+    }
+
     /// Attempts to open files, initialize fd map
     pub fn preopen(&mut self) -> Result<(), IoError> {
         self.release_all();
 
         for id in self.tail_id..self.head_id {
-            self.open_read_only(id)?;
+            self.open_read_only_file(id)?;
         }
         self.files.put(self.head_id, self.head.file.try_clone()?);
         Ok(())
@@ -332,7 +446,7 @@ impl FreezerFiles {
         Ok(())
     }
 
-    fn open_read_only(&mut self, id: FileId) -> Result<File, IoError> {
+    fn open_read_only_file(&mut self, id: FileId) -> Result<File, IoError> {
         fail_point!("open_read_only");
         let mut opt = fs::OpenOptions::new();
         opt.read(true);
@@ -355,6 +469,14 @@ impl FreezerFiles {
         Ok((file, offset))
     }
 
+    // Replaces the data file with a copy of it, so the hard links to it are left untouched.
+    fn unshare_file(&self, id: FileId) -> Result<(), IoError> {
+        let path = self.file_path.join(helper::file_name(id));
+        let tmp = path.with_extension("tmp");
+        fs::copy(&path, &tmp)?;
+        fs::rename(&tmp, &path)
+    }
+
     fn open_file(&mut self, id: FileId, opt: fs::OpenOptions) -> Result<File, IoError> {
         let name = helper::file_name(id);
         let file = opt.open(self.file_path.join(name))?;
@@ -369,6 +491,7 @@ pub struct FreezerFilesBuilder {
     max_file_size: u64,
     enable_compression: bool,
     open_files_limit: usize,
+    read_only: bool,
 }
 
 impl FreezerFilesBuilder {
@@ -379,6 +502,7 @@ impl FreezerFilesBuilder {
             max_file_size: MAX_FILE_SIZE,
             enable_compression: true,
             open_files_limit: OPEN_FILES_LIMIT,
+            read_only: false,
         }
     }
 
@@ -408,9 +532,18 @@ impl FreezerFilesBuilder {
         self
     }
 
+    /// Sets the read-only mode for the new freezer, the files are neither created nor repaired,
+    /// and appending to the freezer fails.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
     /// Creates the freezer with the options configured in this builder.
     pub fn build(self) -> Result<FreezerFiles, IoError> {
-        fs::create_dir_all(&self.file_path)?;
+        if !self.read_only {
+            fs::create_dir_all(&self.file_path)?;
+        }
         let (mut index, mut index_size) = self.open_index()?;
 
         let mut buffer = [0; INDEX_ENTRY_SIZE as usize];
@@ -431,7 +564,7 @@ impl FreezerFilesBuilder {
 
         // try repair cross checks the head and the index file and truncates them to
         // be in sync with each other after a potential crash/data loss.
-        while expect_head_size != head_size {
+        while !self.read_only && expect_head_size != head_size {
             // truncate the head file to the last offset
             if expect_head_size < head_size {
                 ckb_logger::warn!(
@@ -470,8 +603,10 @@ impl FreezerFilesBuilder {
         }
 
         // ensure flush to disk
-        head.sync_all()?;
-        index.sync_all()?;
+        if !self.read_only {
+            head.sync_all()?;
+            index.sync_all()?;
+        }
 
         let number = index_size / INDEX_ENTRY_SIZE;
 
@@ -494,17 +629,32 @@ impl FreezerFilesBuilder {
     // the position for reading may be set at the end of the file.
     // it has differing behaviour on different OS
     fn open_append<P: AsRef<Path>>(&self, path: P) -> Result<(File, u64), IoError> {
-        let mut file = fs::OpenOptions::new()
-            .create(true)
-            .read(true)
-            .write(true)
-            .open(path)?;
+        let mut file = if self.read_only {
+            fs::OpenOptions::new().read(true).open(path)?
+        } else {
+            fs::OpenOptions::new()
+                .create(true)
+                .read(true)
+                .write(true)
+                .open(path)?
+        };
         let offset = file.seek(SeekFrom::End(0))?;
         Ok((file, offset))
     }
 
     fn open_index(&self) -> Result<(File, u64), IoError> {
         let (mut index, mut size) = self.open_append(self.file_path.join(INDEX_FILE_NAME))?;
+        if self.read_only {
+            if size < INDEX_ENTRY_SIZE {
+                return Err(IoError::new(
+                    IoErrorKind::InvalidData,
+                    "empty freezer index",
+                ));
+            }
+            // ignore the partial entry at the end of the index
+            return Ok((index, size - size % INDEX_ENTRY_SIZE));
+        }
+
         // fill a default entry within empty index
         if size == 0 {
             index.write_all(&IndexEntry::default().encode())?;
@@ -285,3 +285,367 @@ fn open_files_limit_retrieve() {
         assert_eq!(Some(expect), actual);
     }
 }
+
+#[test]
+fn check_and_repair() {
+    use crate::Freezer;
+    use ckb_types::{core::BlockView, packed, prelude::*};
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+
+    let tempdir = tempfile::Builder::new().tempdir().unwrap();
+    let mut blocks: Vec<BlockView> = Vec::new();
+    for number in 0..20u64 {
+        let parent_hash = blocks.last().map(|block| block.hash()).unwrap_or_default();
+        let raw = packed::RawHeader::new_builder()
+            .number(number.pack())
+            .parent_hash(parent_hash)
+            .build();
+        let block = packed::Block::new_builder()
+            .header(packed::Header::new_builder().raw(raw).build())
+            .build()
+            .into_view();
+        blocks.push(block);
+    }
+
+    {
+        let freezer = Freezer::open_in(&tempdir).unwrap();
+        freezer
+            .freeze(20, |number| blocks.get(number as usize).cloned())
+            .unwrap();
+        assert_eq!(freezer.number(), 20);
+    }
+
+    let check = Freezer::check(tempdir.path().to_path_buf()).unwrap();
+    assert!(check.is_intact());
+    assert_eq!(check.last_good, 19);
+
+    // corrupt the 10th block
+    let start = FreezerFilesBuilder::new(tempdir.path().to_path_buf())
+        .build()
+        .unwrap()
+        .index_entry(9)
+        .unwrap()
+        .offset;
+    let mut data = OpenOptions::new()
+        .write(true)
+        .open(tempdir.path().join("blk000000"))
+        .unwrap();
+    data.seek(SeekFrom::Start(start)).unwrap();
+    data.write_all(&[0xff; 4]).unwrap();
+    drop(data);
+
+    let check = Freezer::check(tempdir.path().to_path_buf()).unwrap();
+    assert!(!check.is_intact());
+    assert_eq!(check.number, 20);
+    assert_eq!(check.last_good, 9);
+
+    let repaired = Freezer::repair(tempdir.path().to_path_buf()).unwrap();
+    assert_eq!(repaired, check);
+
+    let check = Freezer::check(tempdir.path().to_path_buf()).unwrap();
+    assert!(check.is_intact());
+    assert_eq!(check.number, 10);
+
+    {
+        let freezer = Freezer::open_in(&tempdir).unwrap();
+        assert_eq!(freezer.number(), 10);
+        freezer
+            .freeze(20, |number| blocks.get(number as usize).cloned())
+            .unwrap();
+        assert_eq!(freezer.number(), 20);
+    }
+
+    // the check doesn't repair the dangling index entry
+    {
+        let mut files = FreezerFilesBuilder::new(tempdir.path().to_path_buf())
+            .build()
+            .unwrap();
+        truncate_file(
+            &mut files.index,
+            INDEX_ENTRY_SIZE * 19 + INDEX_ENTRY_SIZE / 2,
+        )
+        .unwrap();
+    }
+    let index_len = || {
+        std::fs::metadata(tempdir.path().join("INDEX"))
+            .unwrap()
+            .len()
+    };
+    let len = index_len();
+    let check = Freezer::check(tempdir.path().to_path_buf()).unwrap();
+    assert_eq!(check.number, 19);
+    assert_eq!(index_len(), len);
+}
+
+#[test]
+fn check_and_repair_meta() {
+    use crate::{Freezer, FreezerTable};
+    use ckb_types::{core::BlockView, packed, prelude::*};
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+
+    let tempdir = tempfile::Builder::new().tempdir().unwrap();
+    let mut blocks: Vec<BlockView> = Vec::new();
+    for number in 0..20u64 {
+        let parent_hash = blocks.last().map(|block| block.hash()).unwrap_or_default();
+        let raw = packed::RawHeader::new_builder()
+            .number(number.pack())
+            .parent_hash(parent_hash)
+            .build();
+        let block = packed::Block::new_builder()
+            .header(packed::Header::new_builder().raw(raw).build())
+            .build()
+            .into_view();
+        blocks.push(block);
+    }
+
+    {
+        let freezer = Freezer::open_in(&tempdir).unwrap();
+        freezer
+            .freeze(20, |number| blocks.get(number as usize).cloned())
+            .unwrap();
+        freezer
+            .freeze_meta(20, |_| {
+                Some((
+                    packed::BlockExt::default().as_slice().to_vec(),
+                    packed::Byte32::default(),
+                ))
+            })
+            .unwrap();
+        assert_eq!(freezer.meta_number(), 20);
+    }
+
+    let check = Freezer::check(tempdir.path().to_path_buf()).unwrap();
+    assert!(check.is_intact());
+    assert_eq!(check.meta_number, 20);
+    assert_eq!(check.meta_last_good, 19);
+
+    // corrupt the 10th block, the metas are kept since they have been wiped out from the kv-db
+    let start = FreezerFilesBuilder::new(tempdir.path().to_path_buf())
+        .build()
+        .unwrap()
+        .index_entry(9)
+        .unwrap()
+        .offset;
+    let mut data = OpenOptions::new()
+        .write(true)
+        .open(tempdir.path().join("blk000000"))
+        .unwrap();
+    data.seek(SeekFrom::Start(start)).unwrap();
+    data.write_all(&[0xff; 4]).unwrap();
+    drop(data);
+
+    let check = Freezer::check(tempdir.path().to_path_buf()).unwrap();
+    assert_eq!(check.last_good, 9);
+    assert!(check.meta_corruption.is_none());
+    Freezer::repair(tempdir.path().to_path_buf()).unwrap();
+    {
+        let freezer = Freezer::open_in(&tempdir).unwrap();
+        assert_eq!(freezer.number(), 10);
+        assert_eq!(freezer.meta_number(), 20);
+        assert!(freezer
+            .retrieve_meta(FreezerTable::Headers, 15)
+            .unwrap()
+            .is_some());
+    }
+
+    // corrupt the 15th header
+    let headers_path = tempdir.path().join("headers");
+    let start = FreezerFilesBuilder::new(headers_path.clone())
+        .build()
+        .unwrap()
+        .index_entry(14)
+        .unwrap()
+        .offset;
+    let mut data = OpenOptions::new()
+        .write(true)
+        .open(headers_path.join("blk000000"))
+        .unwrap();
+    data.seek(SeekFrom::Start(start)).unwrap();
+    data.write_all(&[0xff; 4]).unwrap();
+    drop(data);
+
+    // the corrupted item is not returned
+    {
+        let freezer = Freezer::open_in(&tempdir).unwrap();
+        assert!(freezer.retrieve_meta(FreezerTable::Headers, 15).is_err());
+        assert!(freezer
+            .retrieve_meta(FreezerTable::Headers, 14)
+            .unwrap()
+            .is_some());
+    }
+
+    let check = Freezer::check(tempdir.path().to_path_buf()).unwrap();
+    assert!(!check.is_intact());
+    assert!(check.corruption.is_none());
+    assert_eq!(check.meta_last_good, 14);
+    let repaired = Freezer::repair(tempdir.path().to_path_buf()).unwrap();
+    assert_eq!(repaired, check);
+
+    let check = Freezer::check(tempdir.path().to_path_buf()).unwrap();
+    assert!(check.is_intact());
+    assert_eq!(check.number, 10);
+    assert_eq!(check.meta_number, 15);
+    let freezer = Freezer::open_in(&tempdir).unwrap();
+    assert_eq!(freezer.meta_number(), 15);
+}
+
+#[test]
+fn open_read_only() {
+    use crate::Freezer;
+    use ckb_types::{core::BlockView, packed, prelude::*};
+
+    let tempdir = tempfile::Builder::new().tempdir().unwrap();
+    let mut blocks: Vec<BlockView> = Vec::new();
+    for number in 0..10u64 {
+        let parent_hash = blocks.last().map(|block| block.hash()).unwrap_or_default();
+        let raw = packed::RawHeader::new_builder()
+            .number(number.pack())
+            .parent_hash(parent_hash)
+            .build();
+        let block = packed::Block::new_builder()
+            .header(packed::Header::new_builder().raw(raw).build())
+            .build()
+            .into_view();
+        blocks.push(block);
+    }
+
+    // the freezer locked by another instance can still be opened in read-only mode
+    let freezer = Freezer::open_in(&tempdir).unwrap();
+    freezer
+        .freeze(10, |number| blocks.get(number as usize).cloned())
+        .unwrap();
+    let read_only = Freezer::open_read_only(tempdir.path().to_path_buf()).unwrap();
+    assert_eq!(read_only.number(), 10);
+    assert_eq!(read_only.retrieve(5).unwrap(), freezer.retrieve(5).unwrap());
+    assert_eq!(read_only.retrieve(10).unwrap(), None);
+    drop(freezer);
+
+    // the dangling index entry is ignored but not repaired
+    {
+        let mut files = FreezerFilesBuilder::new(tempdir.path().to_path_buf())
+            .build()
+            .unwrap();
+        truncate_file(
+            &mut files.index,
+            INDEX_ENTRY_SIZE * 9 + INDEX_ENTRY_SIZE / 2,
+        )
+        .unwrap();
+    }
+    let index_len = || {
+        std::fs::metadata(tempdir.path().join("INDEX"))
+            .unwrap()
+            .len()
+    };
+    let len = index_len();
+    let read_only = Freezer::open_read_only(tempdir.path().to_path_buf()).unwrap();
+    assert_eq!(read_only.number(), 9);
+    assert!(read_only.retrieve(8).unwrap().is_some());
+    assert_eq!(read_only.retrieve(9).unwrap(), None);
+    assert_eq!(index_len(), len);
+
+    // nothing is created in read-only mode
+    assert!(Freezer::open_read_only(tempdir.path().join("missing")).is_err());
+    assert!(!tempdir.path().join("missing").exists());
+}
+
+#[test]
+fn backup() {
+    let tempdir = tempfile::Builder::new().tempdir().unwrap();
+    let backup_dir = tempdir.path().join("backup");
+    {
+        let mut freezer = FreezerFilesBuilder::new(tempdir.path().join("freezer"))
+            .enable_compression(false)
+            .max_file_size(50)
+            .build()
+            .unwrap();
+        freezer.preopen().unwrap();
+        for i in 1..30 {
+            let data = make_bytes(15, i);
+            freezer.append(i.into(), &data).unwrap();
+        }
+        freezer
+            .backup_files()
+            .unwrap()
+            .copy_to(&backup_dir)
+            .unwrap();
+
+        // the truncation must not touch the files linked by the backup
+        freezer.truncate(5).unwrap();
+        assert_eq!(freezer.number(), 6);
+    }
+
+    let mut freezer = FreezerFilesBuilder::new(backup_dir)
+        .enable_compression(false)
+        .max_file_size(50)
+        .build()
+        .unwrap();
+    freezer.preopen().unwrap();
+    assert_eq!(freezer.number(), 30);
+    for i in 1..30 {
+        let expect = make_bytes(15, i);
+        let actual = freezer.retrieve(i.into()).unwrap();
+        assert_eq!(Some(expect), actual);
+    }
+}
+
+#[test]
+fn backup_while_appending() {
+    let tempdir = tempfile::Builder::new().tempdir().unwrap();
+    let backup_dir = tempdir.path().join("backup");
+    {
+        let mut freezer = FreezerFilesBuilder::new(tempdir.path().join("freezer"))
+            .enable_compression(false)
+            .max_file_size(50)
+            .build()
+            .unwrap();
+        freezer.preopen().unwrap();
+        for i in 1..20 {
+            let data = make_bytes(15, i);
+            freezer.append(i.into(), &data).unwrap();
+        }
+        let backup = freezer.backup_files().unwrap();
+
+        // the items appended after the capture are not backed up
+        for i in 20..30 {
+            let data = make_bytes(15, i);
+            freezer.append(i.into(), &data).unwrap();
+        }
+        backup.copy_to(&backup_dir).unwrap();
+    }
+
+    let mut freezer = FreezerFilesBuilder::new(backup_dir)
+        .enable_compression(false)
+        .max_file_size(50)
+        .build()
+        .unwrap();
+    freezer.preopen().unwrap();
+    assert_eq!(freezer.number(), 20);
+    for i in 1..20 {
+        let expect = make_bytes(15, i);
+        let actual = freezer.retrieve(i.into()).unwrap();
+        assert_eq!(Some(expect), actual);
+    }
+}
+
+#[test]
+fn disk_usage() {
+    let tempdir = tempfile::Builder::new().tempdir().unwrap();
+    let mut freezer = FreezerFilesBuilder::new(tempdir.path().to_path_buf())
+        .enable_compression(false)
+        .max_file_size(50)
+        .build()
+        .unwrap();
+    freezer.preopen().unwrap();
+    for i in 1..30 {
+        let data = make_bytes(15, i);
+        freezer.append(i.into(), &data).unwrap();
+    }
+
+    // 3 items per file
+    let (files, data_size, index_size) = freezer.disk_usage().unwrap();
+    assert_eq!(files, 10);
+    assert_eq!(data_size, 29 * 15);
+    assert_eq!(index_size, 30 * INDEX_ENTRY_SIZE);
+}
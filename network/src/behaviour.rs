@@ -4,24 +4,35 @@ use crate::Score;
 /// we maintain a score to each peer
 /// report peer behaviour will affects peer's score
 ///
-/// Currently this feature is disabled, maybe someday we will add it back or totally remove it.
+/// The score decays towards the default score over time, and the peer is banned once its score
+/// falls below the ban score, see [`PeerScoreConfig`](crate::peer_store::PeerScoreConfig).
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Behaviour {
-    #[cfg(test)]
-    TestGood,
-    #[cfg(test)]
-    TestBad,
+    /// The peer sent a block which failed the verification
+    InvalidBlock,
+    /// The peer sent a malformed or unexpected protocol message
+    InvalidMessage,
+    /// The peer sent a message which was not requested or had been received
+    UnrequestedMessage,
+    /// The peer did not respond in time
+    Timeout,
+    /// The peer delivered a block which extended the chain
+    UsefulBlock,
+    /// The peer relayed transactions which were accepted by the tx-pool
+    FreshTransactions,
 }
 
 impl Behaviour {
     /// Behaviour score
     pub fn score(self) -> Score {
-        #[cfg(test)]
         match self {
-            Behaviour::TestGood => 10,
-            Behaviour::TestBad => -10,
+            // bans the peer regardless of its score
+            Behaviour::InvalidBlock => -200,
+            Behaviour::InvalidMessage => -50,
+            Behaviour::UnrequestedMessage => -5,
+            Behaviour::Timeout => -10,
+            Behaviour::UsefulBlock => 2,
+            Behaviour::FreshTransactions => 1,
         }
-        #[cfg(not(test))]
-        0
     }
 }
@@ -16,6 +16,7 @@ mod peer;
 pub mod peer_registry;
 pub mod peer_store;
 mod protocols;
+mod proxy;
 mod services;
 
 #[cfg(test)]
@@ -28,6 +29,7 @@ pub use crate::{
         DefaultExitHandler, EventHandler, ExitHandler, NetworkController, NetworkService,
         NetworkState,
     },
+    network_group::{AsMap, Group},
     peer::{Peer, PeerIdentifyInfo},
     peer_registry::PeerRegistry,
     peer_store::Score,
@@ -35,6 +37,7 @@ pub use crate::{
         identify::Flags, support_protocols::SupportProtocols, CKBProtocol, CKBProtocolContext,
         CKBProtocolHandler, PeerIndex,
     },
+    proxy::is_onion_addr,
 };
 pub use p2p::{
     async_trait,
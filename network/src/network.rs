@@ -3,7 +3,7 @@ use crate::errors::{Error, P2PError};
 use crate::peer_registry::{ConnectionStatus, PeerRegistry};
 use crate::peer_store::{
     types::{AddrInfo, BannedAddr},
-    PeerStore,
+    PeerStore, ReportResult,
 };
 use crate::protocols::{
     disconnect_message::DisconnectMessageProtocol,
@@ -13,12 +13,15 @@ use crate::protocols::{
     ping::PingHandler,
     support_protocols::SupportProtocols,
 };
+use crate::proxy::{is_onion_addr, Proxy};
 use crate::services::{
     dump_peer_store::DumpPeerStoreService, outbound_peer::OutboundPeerService,
     protocol_type_checker::ProtocolTypeCheckerService,
 };
-use crate::{Behaviour, CKBProtocol, Peer, PeerIndex, ProtocolId, ServiceControl};
-use ckb_app_config::{default_support_all_protocols, NetworkConfig, SupportProtocol};
+use crate::{AsMap, Behaviour, CKBProtocol, Peer, PeerIndex, ProtocolId, ServiceControl};
+use ckb_app_config::{
+    default_support_all_protocols, format_multiaddr, NetworkConfig, SupportProtocol,
+};
 use ckb_logger::{debug, error, info, trace, warn};
 use ckb_spawn::Spawn;
 use ckb_stop_handler::{broadcast_exit_signals, new_tokio_exit_rx, CancellationToken};
@@ -65,6 +68,30 @@ const P2P_SEND_TIMEOUT: Duration = Duration::from_secs(6);
 const P2P_TRY_SEND_INTERVAL: Duration = Duration::from_millis(100);
 // After 5 minutes we consider this dial hang
 const DIAL_HANG_TIMEOUT: Duration = Duration::from_secs(300);
+// The handshake through the proxy takes longer, especially for the onion services
+const PROXY_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The transport of an address
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum TransportType {
+    /// WebSocket
+    Ws,
+    /// Plain TCP
+    Tcp,
+}
+
+/// Returns the transport of the address
+pub(crate) fn find_type(addr: &Multiaddr) -> TransportType {
+    addr.iter()
+        .find_map(|proto| {
+            if let Protocol::Ws = proto {
+                Some(TransportType::Ws)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(TransportType::Tcp)
+}
 
 /// The global shared state of the network module
 pub struct NetworkState {
@@ -86,6 +113,8 @@ pub struct NetworkState {
     /// fields: ProtocolId, Protocol Name, Supported Versions
     pub(crate) protocols: RwLock<Vec<(ProtocolId, String, Vec<String>)>>,
     pub(crate) required_flags: Flags,
+    /// The SOCKS5 proxy dialer
+    pub(crate) proxy: Option<Proxy>,
 
     pub(crate) ckb2023: AtomicBool,
 }
@@ -101,30 +130,39 @@ impl NetworkState {
             .listen_addresses
             .iter()
             .chain(config.public_addresses.iter())
+            .filter(|addr| {
+                is_onion_addr(addr)
+                    || multiaddr_to_socketaddr(addr)
+                        .map(|socket_addr| is_reachable(socket_addr.ip()))
+                        .unwrap_or(false)
+            })
             .cloned()
-            .filter_map(|mut addr| {
-                multiaddr_to_socketaddr(&addr)
-                    .filter(|addr| is_reachable(addr.ip()))
-                    .and({
-                        if extract_peer_id(&addr).is_none() {
-                            addr.push(Protocol::P2P(Cow::Borrowed(local_peer_id.as_bytes())));
-                        }
-                        Some(addr)
-                    })
+            .map(|mut addr| {
+                if extract_peer_id(&addr).is_none() {
+                    addr.push(Protocol::P2P(Cow::Borrowed(local_peer_id.as_bytes())));
+                }
+                addr
             })
             .collect();
         info!("Loading the peer store. This process may take a few seconds to complete.");
-        let peer_store = Mutex::new(PeerStore::load_from_dir_or_default(
-            config.peer_store_path(),
-        ));
+        let mut peer_store = PeerStore::load_from_dir_or_default(config.peer_store_path());
+        peer_store.set_onion_reachable(config.proxy.is_some());
         let bootnodes = config.bootnodes();
 
-        let peer_registry = PeerRegistry::new(
+        let mut peer_registry = PeerRegistry::new(
             config.max_inbound_peers(),
             config.max_outbound_peers(),
             config.whitelist_only,
             config.whitelist_peers(),
         );
+        if let Some(ref path) = config.asmap {
+            info!("Loading the asmap from {}", path.display());
+            let asmap = Arc::new(AsMap::load(path)?);
+            peer_store.set_asmap(Arc::clone(&asmap));
+            peer_registry.set_asmap(asmap);
+        }
+        let peer_store = Mutex::new(peer_store);
+        let proxy = config.proxy.clone().map(Proxy::new);
 
         Ok(NetworkState {
             peer_store,
@@ -140,6 +178,7 @@ impl NetworkState {
             active: AtomicBool::new(true),
             protocols: RwLock::new(Vec::new()),
             required_flags: Flags::SYNC | Flags::DISCOVERY | Flags::RELAY,
+            proxy,
             ckb2023: AtomicBool::new(false),
         })
     }
@@ -163,23 +202,45 @@ impl NetworkState {
         session_id: SessionId,
         behaviour: Behaviour,
     ) {
-        if let Some(addr) = self.with_peer_registry(|reg| {
-            reg.get_peer(session_id)
+        if self
+            .report_session_behaviour(session_id, behaviour)
+            .map_or(false, ReportResult::is_banned)
+        {
+            if let Err(err) = disconnect_with_message(p2p_control, session_id, "banned") {
+                debug!("Disconnect failed {:?}, error: {:?}", session_id, err);
+            }
+        }
+    }
+
+    /// Updates the score of the session by its behaviour without disconnecting it, returns `None`
+    /// if the session is not found or it is on the whitelist.
+    pub(crate) fn report_session_behaviour(
+        &self,
+        session_id: SessionId,
+        behaviour: Behaviour,
+    ) -> Option<ReportResult> {
+        if let Some(addr) = self.with_peer_registry_mut(|reg| {
+            reg.get_peer_mut(session_id)
                 .filter(|peer| !peer.is_whitelist)
-                .map(|peer| peer.connected_addr.clone())
+                .map(|peer| {
+                    match behaviour {
+                        Behaviour::UsefulBlock => peer.last_block_relayed_at = Some(Instant::now()),
+                        Behaviour::FreshTransactions => {
+                            peer.last_tx_relayed_at = Some(Instant::now())
+                        }
+                        _ => {}
+                    }
+                    peer.connected_addr.clone()
+                })
         }) {
             trace!("Report {:?} because {:?}", addr, behaviour);
-            let report_result = self.peer_store.lock().report(&addr, behaviour);
-            if report_result.is_banned() {
-                if let Err(err) = disconnect_with_message(p2p_control, session_id, "banned") {
-                    debug!("Disconnect failed {:?}, error: {:?}", session_id, err);
-                }
-            }
+            Some(self.peer_store.lock().report(&addr, behaviour))
         } else {
             debug!(
                 "Report {} failure: not found in peer registry or it is on the whitelist",
                 session_id
             );
+            None
         }
     }
 
@@ -234,7 +295,7 @@ impl NetworkState {
         let mut peer_store = self.peer_store.lock();
         let accept_peer_result = {
             self.peer_registry.write().accept_peer(
-                session_context.address.clone(),
+                self.remote_addr(session_context),
                 session_context.id,
                 session_context.ty,
                 &mut peer_store,
@@ -243,6 +304,31 @@ impl NetworkState {
         accept_peer_result.map_err(Into::into)
     }
 
+    /// Returns the dialed address if the address is a bridge of the proxy
+    pub(crate) fn dialed_addr(&self, addr: &Multiaddr) -> Multiaddr {
+        self.proxy
+            .as_ref()
+            .and_then(|proxy| proxy.dialed_addr(addr))
+            .unwrap_or_else(|| addr.to_owned())
+    }
+
+    /// Returns the address of the remote peer, which is the dialed address rather than the bridge
+    /// if the session is dialed through the proxy
+    pub(crate) fn remote_addr(&self, session_context: &SessionContext) -> Multiaddr {
+        if session_context.ty.is_outbound() {
+            self.dialed_addr(&session_context.address)
+        } else {
+            session_context.address.clone()
+        }
+    }
+
+    /// Forgets the bridge of the proxy once the session is closed or the dialing failed
+    pub(crate) fn close_bridge(&self, addr: &Multiaddr) {
+        if let Some(ref proxy) = self.proxy {
+            proxy.close_bridge(addr);
+        }
+    }
+
     /// For restrict lock in inner scope
     pub fn with_peer_registry<F, T>(&self, callback: F) -> T
     where
@@ -308,7 +394,7 @@ impl NetworkState {
                 }
             })
             .chain(listened_addrs.iter().map(|addr| (addr.to_owned(), 1)))
-            .map(|(addr, score)| (addr.to_string(), score))
+            .map(|(addr, score)| (format_multiaddr(&addr), score))
             .collect()
     }
 
@@ -413,7 +499,19 @@ impl NetworkState {
         }
 
         debug!("Dialing {addr}");
-        p2p_control.dial(addr.clone(), target)?;
+        let dial_addr = match self.proxy {
+            Some(ref proxy) if proxy.should_proxy(&addr) => proxy.open_bridge(&addr)?,
+            _ if is_onion_addr(&addr) => {
+                return Err(Error::Dial(format!(
+                    "dialing onion address {addr} requires a proxy"
+                )));
+            }
+            _ => addr.clone(),
+        };
+        if let Err(err) = p2p_control.dial(dial_addr.clone(), target) {
+            self.close_bridge(&dial_addr);
+            return Err(err.into());
+        }
         self.dialing_addrs.write().insert(
             extract_peer_id(&addr).expect("verified addr"),
             Instant::now(),
@@ -449,31 +547,43 @@ impl NetworkState {
 
     /// this method is intent to check observed addr by dial to self
     pub(crate) fn try_dial_observed_addrs(&self, p2p_control: &ServiceControl) {
+        for addr in self.observed_addrs_to_dial() {
+            trace!("try dial observed addr: {:?}", addr);
+            if let Err(err) = p2p_control.dial(
+                addr,
+                TargetProtocol::Single(SupportProtocols::Identify.protocol_id()),
+            ) {
+                trace!("try_dial_observed_addrs {err} failed")
+            }
+        }
+    }
+
+    /// Returns the addresses to dial to self, a random public address if there are no pending
+    /// observed addresses.
+    ///
+    /// The addresses which should be dialed through the proxy are skipped, dialing them directly
+    /// would reveal the real IP of the node.
+    pub(crate) fn observed_addrs_to_dial(&self) -> Vec<Multiaddr> {
+        let can_dial_directly = |addr: &Multiaddr| match self.proxy {
+            Some(ref proxy) => !proxy.should_proxy(addr),
+            None => !is_onion_addr(addr),
+        };
         let mut pending_observed_addrs = self.pending_observed_addrs.write();
         if pending_observed_addrs.is_empty() {
-            let addrs = self.public_addrs.read();
-            if addrs.is_empty() {
-                return;
-            }
-            // random get addr
-            if let Some(addr) = addrs.iter().choose(&mut rand::thread_rng()) {
-                if let Err(err) = p2p_control.dial(
-                    addr.clone(),
-                    TargetProtocol::Single(SupportProtocols::Identify.protocol_id()),
-                ) {
-                    trace!("try_dial_observed_addrs {err} failed in public address")
-                }
-            }
+            // random get addr, the onion services can't be dialed directly
+            self.public_addrs
+                .read()
+                .iter()
+                .filter(|addr| can_dial_directly(addr))
+                .choose(&mut rand::thread_rng())
+                .cloned()
+                .into_iter()
+                .collect()
         } else {
-            for addr in pending_observed_addrs.drain() {
-                trace!("try dial observed addr: {:?}", addr);
-                if let Err(err) = p2p_control.dial(
-                    addr,
-                    TargetProtocol::Single(SupportProtocols::Identify.protocol_id()),
-                ) {
-                    trace!("try_dial_observed_addrs {err} failed in pending observed addresses")
-                }
-            }
+            pending_observed_addrs
+                .drain()
+                .filter(can_dial_directly)
+                .collect()
         }
     }
 
@@ -584,7 +694,9 @@ impl ServiceHandle for EventHandler {
                     }
                 }
                 public_addrs.remove(&address);
-                self.network_state.dial_failed(&address);
+                let dialed_addr = self.network_state.dialed_addr(&address);
+                self.network_state.close_bridge(&address);
+                self.network_state.dial_failed(&dialed_addr);
             }
             ServiceError::ProtocolError {
                 id,
@@ -670,7 +782,8 @@ impl ServiceHandle for EventHandler {
                     "SessionOpen({}, {})",
                     session_context.id, session_context.address,
                 );
-                self.network_state.dial_success(&session_context.address);
+                let remote_addr = self.network_state.remote_addr(&session_context);
+                self.network_state.dial_success(&remote_addr);
 
                 let iter = self.inbound_eviction();
 
@@ -686,12 +799,9 @@ impl ServiceHandle for EventHandler {
 
                 if self
                     .network_state
-                    .with_peer_registry(|reg| reg.is_feeler(&session_context.address))
+                    .with_peer_registry(|reg| reg.is_feeler(&remote_addr))
                 {
-                    debug!(
-                        "Feeler connected {} => {}",
-                        session_context.id, session_context.address,
-                    );
+                    debug!("Feeler connected {} => {}", session_context.id, remote_addr,);
                 } else {
                     match self.network_state.accept_peer(&session_context) {
                         Ok(Some(evicted_peer)) => {
@@ -738,20 +848,24 @@ impl ServiceHandle for EventHandler {
                     "SessionClose({}, {})",
                     session_context.id, session_context.address,
                 );
+                let remote_addr = self.network_state.remote_addr(&session_context);
                 let peer_exists = self.network_state.with_peer_registry_mut(|reg| {
                     // should make sure feelers is clean
-                    reg.remove_feeler(&session_context.address);
+                    reg.remove_feeler(&remote_addr);
                     reg.remove_peer(session_context.id).is_some()
                 });
                 if peer_exists {
                     debug!(
                         "{} closed. Remove {} from peer_registry",
-                        session_context.id, session_context.address,
+                        session_context.id, remote_addr,
                     );
                     self.network_state.with_peer_store_mut(|peer_store| {
-                        peer_store.remove_disconnected_peer(&session_context.address);
+                        peer_store.remove_disconnected_peer(&remote_addr);
                     });
                 }
+                if session_context.ty.is_outbound() {
+                    self.network_state.close_bridge(&session_context.address);
+                }
             }
             _ => {
                 info!("p2p service event: {:?}", event);
@@ -897,32 +1011,17 @@ impl NetworkService {
             .max_connection_number(1024)
             .set_send_buffer_size(config.max_send_buffer())
             .set_channel_size(config.channel_size())
-            .timeout(Duration::from_secs(5));
+            .timeout(if config.proxy.is_some() {
+                PROXY_HANDSHAKE_TIMEOUT
+            } else {
+                Duration::from_secs(5)
+            });
 
         #[cfg(target_os = "linux")]
         let p2p_service = {
             if config.reuse_port_on_linux {
                 let iter = config.listen_addresses.iter();
 
-                #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-                enum TransportType {
-                    Ws,
-                    Tcp,
-                }
-
-                fn find_type(addr: &Multiaddr) -> TransportType {
-                    let mut iter = addr.iter();
-
-                    iter.find_map(|proto| {
-                        if let p2p::multiaddr::Protocol::Ws = proto {
-                            Some(TransportType::Ws)
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(TransportType::Tcp)
-                }
-
                 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
                 enum BindType {
                     None,
@@ -942,8 +1041,7 @@ impl NetworkService {
                     }
 
                     fn is_ready(&self) -> bool {
-                        // should change to Both if ckb enable ws
-                        matches!(self, BindType::Tcp)
+                        matches!(self, BindType::Both)
                     }
                 }
 
@@ -953,8 +1051,33 @@ impl NetworkService {
                         break;
                     }
                     match find_type(addr) {
-                        // wait ckb enable ws support
-                        TransportType::Ws => (),
+                        TransportType::Ws => {
+                            // only bind once
+                            if matches!(init, BindType::Ws) {
+                                continue;
+                            }
+                            if let Some(addr) = multiaddr_to_socketaddr(addr) {
+                                use p2p::service::TcpSocket;
+                                let domain = socket2::Domain::for_address(addr);
+                                service_builder =
+                                    service_builder.tcp_config_on_ws(move |socket: TcpSocket| {
+                                        let socket_ref = socket2::SockRef::from(&socket);
+                                        #[cfg(all(
+                                            unix,
+                                            not(target_os = "solaris"),
+                                            not(target_os = "illumos")
+                                        ))]
+                                        socket_ref.set_reuse_port(true)?;
+
+                                        socket_ref.set_reuse_address(true)?;
+                                        if socket_ref.domain()? == domain {
+                                            socket_ref.bind(&addr.into())?;
+                                        }
+                                        Ok(socket)
+                                    });
+                                init.transform(TransportType::Ws)
+                            }
+                        }
                         TransportType::Tcp => {
                             // only bind once
                             if matches!(init, BindType::Tcp) {
@@ -1017,6 +1140,13 @@ impl NetworkService {
             );
             bg_services.push(Box::pin(outbound_peer_service) as Pin<Box<_>>);
         };
+        if let Some(proxy_service) = network_state
+            .proxy
+            .as_ref()
+            .and_then(|proxy| proxy.take_service())
+        {
+            bg_services.push(Box::pin(proxy_service.start()) as Pin<Box<_>>);
+        }
 
         #[cfg(feature = "with_dns_seeding")]
         if config.dns_seeding_service_enabled() {
@@ -1,12 +1,142 @@
-use crate::{multiaddr::Multiaddr, multiaddr_to_socketaddr};
+use crate::{multiaddr::Multiaddr, multiaddr_to_socketaddr, proxy::onion_service};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Error, ErrorKind};
 use std::net::IpAddr;
+use std::path::Path;
 
-#[derive(Hash, Eq, PartialEq, Debug)]
+/// The network group of an address, the peers in the same group are likely controlled by the
+/// same operator
+#[derive(Hash, Eq, PartialEq, Debug, Clone)]
 pub enum Group {
+    /// The address has no IP or onion service
     None,
+    /// The loopback addresses
     LocalNetwork,
+    /// The IPv4 addresses, grouped by the /16 prefixes
     IP4([u8; 2]),
+    /// The IPv6 addresses, grouped by the /32 prefixes
     IP6([u8; 4]),
+    /// The autonomous system which announces the address, see [`AsMap`]
+    Asn(u32),
+    /// The onion services, all in one group since their names are free to choose
+    Onion,
+}
+
+impl Group {
+    /// Whether the group is a routable network, which makes sense for the diversity of peers
+    pub fn is_routable(&self) -> bool {
+        !matches!(self, Group::None | Group::LocalNetwork)
+    }
+}
+
+/// The map from IP prefixes to the autonomous system numbers.
+///
+/// Grouping peers by the autonomous systems rather than the /16 prefixes makes it harder for an
+/// attacker, who controls many addresses in a few networks, to occupy all the connections.
+///
+/// It's loaded from a text file, each line of which is an IP prefix and the ASN, e.g.,
+/// `1.0.0.0/24 AS13335`. Empty lines and lines starting with `#` are ignored.
+#[derive(Default, Debug)]
+pub struct AsMap {
+    // IPv4 addresses are mapped into IPv6, indexed by the prefix length
+    prefixes: BTreeMap<u8, HashMap<u128, u32>>,
+}
+
+impl AsMap {
+    /// Loads the map from the file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        fs::read_to_string(path).and_then(|content| content.parse())
+    }
+
+    /// Inserts an IP prefix announced by the ASN, the prefix length must not exceed the bits of
+    /// the IP
+    pub fn insert(&mut self, ip: IpAddr, prefix_len: u8, asn: u32) -> Result<(), Error> {
+        let (bits, max_len, offset) = match ip {
+            IpAddr::V4(ipv4) => (ipv4.to_ipv6_mapped().into(), 32, 96),
+            IpAddr::V6(ipv6) => (u128::from(ipv6), 128, 0),
+        };
+        if prefix_len > max_len {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid prefix length {prefix_len} of {ip}"),
+            ));
+        }
+        let prefix_len = prefix_len + offset;
+        self.prefixes
+            .entry(prefix_len)
+            .or_default()
+            .insert(mask(bits, prefix_len), asn);
+        Ok(())
+    }
+
+    /// Returns the ASN which announces the longest prefix matching the IP
+    pub fn lookup(&self, ip: IpAddr) -> Option<u32> {
+        let bits: u128 = match ip {
+            IpAddr::V4(ipv4) => ipv4.to_ipv6_mapped().into(),
+            IpAddr::V6(ipv6) => ipv6.into(),
+        };
+        self.prefixes
+            .iter()
+            .rev()
+            .find_map(|(prefix_len, asns)| asns.get(&mask(bits, *prefix_len)).copied())
+    }
+
+    /// Returns the network group of the address, falls back to the prefix group if the address
+    /// is not in the map
+    pub fn group(&self, multiaddr: &Multiaddr) -> Group {
+        let group = Group::from(multiaddr);
+        if !group.is_routable() {
+            return group;
+        }
+        multiaddr_to_socketaddr(multiaddr)
+            .and_then(|socket_addr| self.lookup(socket_addr.ip()))
+            .map(Group::Asn)
+            .unwrap_or(group)
+    }
+}
+
+impl std::str::FromStr for AsMap {
+    type Err = Error;
+
+    fn from_str(content: &str) -> Result<Self, Error> {
+        let mut asmap = AsMap::default();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid asmap entry at line {}: {}", index + 1, line),
+                )
+            };
+            let mut parts = line.split_whitespace();
+            let (prefix, asn) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(prefix), Some(asn), None) => (prefix, asn),
+                _ => return Err(invalid()),
+            };
+            let (ip, prefix_len) = prefix.split_once('/').ok_or_else(invalid)?;
+            let ip: IpAddr = ip.parse().map_err(|_| invalid())?;
+            let prefix_len: u8 = prefix_len.parse().map_err(|_| invalid())?;
+            let asn = asn
+                .strip_prefix("AS")
+                .unwrap_or(asn)
+                .parse()
+                .map_err(|_| invalid())?;
+            asmap.insert(ip, prefix_len, asn).map_err(|_| invalid())?;
+        }
+        Ok(asmap)
+    }
+}
+
+fn mask(bits: u128, prefix_len: u8) -> u128 {
+    match prefix_len {
+        0 => 0,
+        len if len >= 128 => bits,
+        len => bits & !(u128::MAX >> len),
+    }
 }
 
 impl From<&Multiaddr> for Group {
@@ -37,6 +167,9 @@ impl From<&Multiaddr> for Group {
                 return Group::IP6([bits[0], bits[1], bits[2], bits[3]]);
             }
         }
+        if onion_service(multiaddr).is_some() {
+            return Group::Onion;
+        }
         // Can't group addr
         Group::None
     }
@@ -42,6 +42,10 @@ pub struct Peer {
     pub is_whitelist: bool,
     /// Whether the remote peer is a light client, and it subscribes the chain state.
     pub if_lightclient_subscribed: bool,
+    /// Last time the peer delivered a block which extended the chain
+    pub last_block_relayed_at: Option<Instant>,
+    /// Last time the peer relayed fresh transactions
+    pub last_tx_relayed_at: Option<Instant>,
 }
 
 impl Peer {
@@ -65,6 +69,8 @@ impl Peer {
             protocols: HashMap::with_capacity_and_hasher(1, Default::default()),
             is_whitelist,
             if_lightclient_subscribed: false,
+            last_block_relayed_at: None,
+            last_tx_relayed_at: None,
         }
     }
 
@@ -1,5 +1,5 @@
 //! Peer registry
-use crate::network_group::Group;
+use crate::network_group::{AsMap, Group};
 use crate::peer_store::PeerStore;
 use crate::{
     errors::{Error, PeerError},
@@ -7,11 +7,15 @@ use crate::{
 };
 use ckb_logger::debug;
 use p2p::{multiaddr::Multiaddr, SessionId};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use std::collections::hash_map::RandomState;
 use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::sync::Arc;
+use std::time::Instant;
 
 pub(crate) const EVICTION_PROTECT_PEERS: usize = 8;
+pub(crate) const EVICTION_PROTECT_GROUPS: usize = 4;
+pub(crate) const EVICTION_PROTECT_RELAY_PEERS: usize = 4;
 
 /// Memory records of opened session information
 pub struct PeerRegistry {
@@ -24,6 +28,9 @@ pub struct PeerRegistry {
     whitelist_only: bool,
     whitelist_peers: HashSet<PeerId>,
     feeler_peers: HashSet<PeerId>,
+    asmap: Option<Arc<AsMap>>,
+    // the key to choose the protected network groups on eviction
+    group_key: RandomState,
 }
 
 /// Global network connection status
@@ -51,6 +58,15 @@ where
     }
 }
 
+// drop at most n peers which did something most recently, the peers never did it are kept
+fn protect_recent<F>(list: &mut Vec<&Peer>, n: usize, last_at: F)
+where
+    F: Fn(&Peer) -> Option<Instant>,
+{
+    let n = n.min(list.iter().filter(|peer| last_at(peer).is_some()).count());
+    sort_then_drop(list, n, |peer1, peer2| last_at(peer1).cmp(&last_at(peer2)));
+}
+
 impl PeerRegistry {
     /// Init registry from config
     pub fn new(
@@ -66,6 +82,8 @@ impl PeerRegistry {
             max_inbound,
             max_outbound,
             whitelist_only,
+            asmap: None,
+            group_key: RandomState::new(),
         }
     }
 
@@ -116,7 +134,7 @@ impl PeerRegistry {
     }
 
     // try to evict an inbound peer
-    fn try_evict_inbound_peer(&self, _peer_store: &PeerStore) -> Option<SessionId> {
+    fn try_evict_inbound_peer(&self, peer_store: &PeerStore) -> Option<SessionId> {
         let mut candidate_peers = {
             self.peers
                 .values()
@@ -124,6 +142,36 @@ impl PeerRegistry {
                 .collect::<Vec<_>>()
         };
         // Protect peers based on characteristics that an attacker hard to simulate or manipulate
+        // Protect peers from distinct network groups, the groups are chosen by a keyed hash which
+        // is unpredictable to the attacker
+        let mut group_peers: HashMap<Group, &Peer> = HashMap::default();
+        for peer in &candidate_peers {
+            let group = self.network_group(peer);
+            if !group.is_routable() {
+                continue;
+            }
+            group_peers
+                .entry(group)
+                .and_modify(|oldest| {
+                    if peer.connected_time < oldest.connected_time {
+                        *oldest = *peer;
+                    }
+                })
+                .or_insert(*peer);
+        }
+        let mut keyed_groups: Vec<_> = group_peers
+            .into_iter()
+            .map(|(group, peer)| (self.group_key.hash_one(group), peer.session_id))
+            .collect();
+        keyed_groups.sort_unstable();
+        let protected_sessions: HashSet<SessionId> = keyed_groups
+            .into_iter()
+            .rev()
+            .take(EVICTION_PROTECT_GROUPS)
+            .map(|(_, session_id)| session_id)
+            .collect();
+        candidate_peers.retain(|peer| !protected_sessions.contains(&peer.session_id));
+
         // Protect peers which has the lowest ping
         sort_then_drop(
             &mut candidate_peers,
@@ -158,33 +206,80 @@ impl PeerRegistry {
                 peer2_last_message.cmp(&peer1_last_message)
             },
         );
+
+        // Protect peers which most recently relayed useful blocks and fresh transactions
+        protect_recent(&mut candidate_peers, EVICTION_PROTECT_RELAY_PEERS, |peer| {
+            peer.last_block_relayed_at
+        });
+        protect_recent(&mut candidate_peers, EVICTION_PROTECT_RELAY_PEERS, |peer| {
+            peer.last_tx_relayed_at
+        });
+
         // Protect half peers which have the longest connection time
         let protect_peers = candidate_peers.len() >> 1;
         sort_then_drop(&mut candidate_peers, protect_peers, |peer1, peer2| {
             peer2.connected_time.cmp(&peer1.connected_time)
         });
 
-        // Group peers by network group
-        let evict_group = candidate_peers
+        // Evict the unprotected peer with the lowest score if it has misbehaved, the score is only
+        // used after the protections, since a single penalty of an honest peer decays slowly
+        let default_score = peer_store.score_config().default_score;
+        if let Some(peer) = candidate_peers
+            .iter()
+            .filter_map(|peer| {
+                extract_peer_id(&peer.connected_addr)
+                    .and_then(|peer_id| peer_store.peer_score(&peer_id))
+                    .filter(|score| *score < default_score)
+                    .map(|score| (score, peer))
+            })
+            .min_by_key(|(score, _)| *score)
+            .map(|(_, peer)| peer)
+        {
+            debug!(
+                "Disconnect misbehaving inbound peer {:?}",
+                peer.connected_addr
+            );
+            return Some(peer.session_id);
+        }
+
+        // Group peers by network group, and evict the youngest peer of the most represented group
+        candidate_peers
             .into_iter()
             .fold(
                 HashMap::new(),
                 |mut groups: HashMap<Group, Vec<&Peer>>, peer| {
-                    groups.entry(peer.network_group()).or_default().push(peer);
+                    groups
+                        .entry(self.network_group(peer))
+                        .or_default()
+                        .push(peer);
                     groups
                 },
             )
-            .values()
-            .max_by_key(|group| group.len())
-            .cloned()
-            .unwrap_or_default();
+            .into_values()
+            .max_by_key(|group| {
+                (
+                    group.len(),
+                    group.iter().map(|peer| peer.connected_time).max(),
+                )
+            })
+            .and_then(|group| group.into_iter().max_by_key(|peer| peer.connected_time))
+            .map(|peer| {
+                debug!("Disconnect inbound peer {:?}", peer.connected_addr);
+                peer.session_id
+            })
+    }
 
-        // randomly evict a peer
-        let mut rng = thread_rng();
-        evict_group.choose(&mut rng).map(|peer| {
-            debug!("Disconnect inbound peer {:?}", peer.connected_addr);
-            peer.session_id
-        })
+    /// Group peers by the autonomous systems in the asmap instead of the /16 prefixes
+    pub fn set_asmap(&mut self, asmap: Arc<AsMap>) {
+        self.asmap = Some(asmap);
+    }
+
+    /// Get the network group of the peer
+    pub fn network_group(&self, peer: &Peer) -> Group {
+        match self.asmap {
+            Some(ref asmap) => asmap.group(&peer.connected_addr),
+            None => peer.network_group(),
+        }
     }
 
     /// Add feeler dail task
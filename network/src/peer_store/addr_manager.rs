@@ -1,15 +1,31 @@
 //! Address manager
 use crate::peer_store::types::AddrInfo;
+use crate::proxy::{is_onion_addr, onion_service};
 use p2p::{multiaddr::Multiaddr, utils::multiaddr_to_socketaddr};
 use rand::Rng;
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 
+/// The key of an address, the onion services are identified by their names
+#[derive(Hash, Eq, PartialEq, Debug, Clone)]
+enum AddrKey {
+    Socket(SocketAddr),
+    Onion(String, u16),
+}
+
+impl AddrKey {
+    fn from_multiaddr(addr: &Multiaddr) -> Option<AddrKey> {
+        multiaddr_to_socketaddr(addr)
+            .map(AddrKey::Socket)
+            .or_else(|| onion_service(addr).map(|(name, port)| AddrKey::Onion(name, port)))
+    }
+}
+
 /// Address manager
 #[derive(Default)]
 pub struct AddrManager {
     next_id: u64,
-    addr_to_id: HashMap<SocketAddr, u64>,
+    addr_to_id: HashMap<AddrKey, u64>,
     id_to_info: HashMap<u64, AddrInfo>,
     random_ids: Vec<u64>,
 }
@@ -17,15 +33,17 @@ pub struct AddrManager {
 impl AddrManager {
     /// Add an address information to address manager
     pub fn add(&mut self, mut addr_info: AddrInfo) {
-        if let Some(key) = multiaddr_to_socketaddr(&addr_info.addr) {
+        if let Some(key) = AddrKey::from_multiaddr(&addr_info.addr) {
             if let Some(&id) = self.addr_to_id.get(&key) {
-                let (exist_last_connected_at_ms, random_id_pos) = {
-                    let info = self.id_to_info.get(&id).expect("must exists");
-                    (info.last_connected_at_ms, info.random_id_pos)
-                };
+                let info = self.id_to_info.get(&id).expect("must exists");
                 // Get time earlier than record time, return directly
-                if addr_info.last_connected_at_ms >= exist_last_connected_at_ms {
-                    addr_info.random_id_pos = random_id_pos;
+                if addr_info.last_connected_at_ms >= info.last_connected_at_ms {
+                    addr_info.random_id_pos = info.random_id_pos;
+                    // keep the score earned by the address
+                    if info.score_updated_at_ms > addr_info.score_updated_at_ms {
+                        addr_info.score = info.score;
+                        addr_info.score_updated_at_ms = info.score_updated_at_ms;
+                    }
                     self.id_to_info.insert(id, addr_info);
                 }
                 return;
@@ -55,22 +73,26 @@ impl AddrManager {
             let j = rng.gen_range(i, self.random_ids.len());
             self.swap_random_id(j, i);
             let addr_info: AddrInfo = self.id_to_info[&self.random_ids[i]].to_owned();
-            if let Some(socket_addr) = multiaddr_to_socketaddr(&addr_info.addr) {
-                let ip = socket_addr.ip();
-                let is_unique_ip = !duplicate_ips.contains(&ip);
-                // A trick to make our tests work
-                // TODO remove this after fix the network tests.
-                let is_test_ip = ip.is_unspecified() || ip.is_loopback();
-                if (is_test_ip || is_unique_ip)
-                    && addr_info.is_connectable(now_ms)
-                    && filter(&addr_info)
-                {
+            let ip = multiaddr_to_socketaddr(&addr_info.addr).map(|socket_addr| socket_addr.ip());
+            if ip.is_none() && !is_onion_addr(&addr_info.addr) {
+                continue;
+            }
+            // the onion services have no IPs, they are unique by their names
+            let is_unique_ip = ip.map_or(true, |ip| !duplicate_ips.contains(&ip));
+            // A trick to make our tests work
+            // TODO remove this after fix the network tests.
+            let is_test_ip = ip.map_or(false, |ip| ip.is_unspecified() || ip.is_loopback());
+            if (is_test_ip || is_unique_ip)
+                && addr_info.is_connectable(now_ms)
+                && filter(&addr_info)
+            {
+                if let Some(ip) = ip {
                     duplicate_ips.insert(ip);
-                    addr_infos.push(addr_info);
-                }
-                if addr_infos.len() == count {
-                    break;
                 }
+                addr_infos.push(addr_info);
+            }
+            if addr_infos.len() == count {
+                break;
             }
         }
         addr_infos
@@ -88,8 +110,8 @@ impl AddrManager {
 
     /// Remove an address by ip and port
     pub fn remove(&mut self, addr: &Multiaddr) -> Option<AddrInfo> {
-        multiaddr_to_socketaddr(addr).and_then(|addr| {
-            self.addr_to_id.remove(&addr).and_then(|id| {
+        AddrKey::from_multiaddr(addr).and_then(|key| {
+            self.addr_to_id.remove(&key).and_then(|id| {
                 let random_id_pos = self.id_to_info.get(&id).expect("exists").random_id_pos;
                 // swap with last index, then remove the last index
                 self.swap_random_id(random_id_pos, self.random_ids.len() - 1);
@@ -99,19 +121,35 @@ impl AddrManager {
         })
     }
 
+    /// Retains only the addresses specified by the predicate, returns the removed addresses
+    pub fn retain<F>(&mut self, mut f: F) -> Vec<AddrInfo>
+    where
+        F: FnMut(&AddrInfo) -> bool,
+    {
+        let removed_addrs: Vec<Multiaddr> = self
+            .addrs_iter()
+            .filter(|addr_info| !f(addr_info))
+            .map(|addr_info| addr_info.addr.clone())
+            .collect();
+        removed_addrs
+            .iter()
+            .filter_map(|addr| self.remove(addr))
+            .collect()
+    }
+
     /// Get an address information by ip and port
     pub fn get(&self, addr: &Multiaddr) -> Option<&AddrInfo> {
-        multiaddr_to_socketaddr(addr).and_then(|addr| {
+        AddrKey::from_multiaddr(addr).and_then(|key| {
             self.addr_to_id
-                .get(&addr)
+                .get(&key)
                 .and_then(|id| self.id_to_info.get(id))
         })
     }
 
     /// Get a mutable address information by ip and port
     pub fn get_mut(&mut self, addr: &Multiaddr) -> Option<&mut AddrInfo> {
-        if let Some(addr) = multiaddr_to_socketaddr(addr) {
-            if let Some(id) = self.addr_to_id.get(&addr) {
+        if let Some(key) = AddrKey::from_multiaddr(addr) {
+            if let Some(id) = self.addr_to_id.get(&key) {
                 self.id_to_info.get_mut(id)
             } else {
                 None
@@ -41,8 +41,12 @@ pub struct PeerScoreConfig {
     pub default_score: Score,
     /// Ban score
     pub ban_score: Score,
+    /// Max score, the good behaviours can't raise the score above it
+    pub max_score: Score,
     /// Ban time
     pub ban_timeout_ms: u64,
+    /// The time it takes for the distance between a score and the default score to halve
+    pub score_half_life_ms: u64,
 }
 
 impl Default for PeerScoreConfig {
@@ -50,11 +54,40 @@ impl Default for PeerScoreConfig {
         PeerScoreConfig {
             default_score: 100,
             ban_score: 40,
-            ban_timeout_ms: 24 * 3600 * 1000, // 1 day
+            max_score: 200,
+            ban_timeout_ms: 24 * 3600 * 1000,    // 1 day
+            score_half_life_ms: 6 * 3600 * 1000, // 6 hours
         }
     }
 }
 
+impl PeerScoreConfig {
+    /// Returns the score updated at `updated_at_ms` after decaying towards the default score
+    pub fn decay(&self, score: Score, updated_at_ms: u64, now_ms: u64) -> Score {
+        if self.score_half_life_ms == 0 {
+            return score;
+        }
+        let half_lives =
+            now_ms.saturating_sub(updated_at_ms) as f64 / self.score_half_life_ms as f64;
+        let distance =
+            f64::from(score.saturating_sub(self.default_score)) * 0.5f64.powf(half_lives);
+        self.default_score.saturating_add(distance.round() as Score)
+    }
+
+    /// Returns the decayed score after applying the behaviour
+    pub fn apply(
+        &self,
+        score: Score,
+        updated_at_ms: u64,
+        now_ms: u64,
+        behaviour: Behaviour,
+    ) -> Score {
+        self.decay(score, updated_at_ms, now_ms)
+            .saturating_add(behaviour.score())
+            .min(self.max_score)
+    }
+}
+
 /// Peer Status
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Status {
@@ -1,7 +1,7 @@
 use crate::{
     errors::{PeerStoreError, Result},
-    extract_peer_id, multiaddr_to_socketaddr,
-    network_group::Group,
+    extract_peer_id, is_onion_addr, multiaddr_to_socketaddr,
+    network_group::{AsMap, Group},
     peer_store::{
         addr_manager::AddrManager,
         ban_list::BanList,
@@ -9,11 +9,12 @@ use crate::{
         Behaviour, Multiaddr, PeerScoreConfig, ReportResult, Status, ADDR_COUNT_LIMIT,
         ADDR_TIMEOUT_MS, ADDR_TRY_TIMEOUT_MS, DIAL_INTERVAL,
     },
-    Flags, PeerId, SessionType,
+    Flags, PeerId, Score, SessionType,
 };
 use ipnetwork::IpNetwork;
 use rand::prelude::IteratorRandom;
 use std::collections::{hash_map::Entry, HashMap};
+use std::sync::Arc;
 
 /// Peer store
 ///
@@ -25,6 +26,9 @@ pub struct PeerStore {
     ban_list: BanList,
     connected_peers: HashMap<PeerId, PeerInfo>,
     score_config: PeerScoreConfig,
+    // the onion services are only reachable through a proxy
+    onion_reachable: bool,
+    asmap: Option<Arc<AsMap>>,
 }
 
 impl PeerStore {
@@ -35,9 +39,23 @@ impl PeerStore {
             ban_list,
             connected_peers: Default::default(),
             score_config: Default::default(),
+            onion_reachable: false,
+            asmap: None,
         }
     }
 
+    /// Groups the addresses by the autonomous systems in the asmap on eviction, instead of the
+    /// /16 prefixes
+    pub fn set_asmap(&mut self, asmap: Arc<AsMap>) {
+        self.asmap = Some(asmap);
+    }
+
+    /// Sets whether the onion services are reachable, the unreachable ones are kept for the
+    /// discovery but never attempted
+    pub fn set_onion_reachable(&mut self, onion_reachable: bool) {
+        self.onion_reachable = onion_reachable;
+    }
+
     /// this method will assume peer is connected, which implies address is "verified".
     pub fn add_connected_peer(&mut self, addr: Multiaddr, session_type: SessionType) {
         let now_ms = ckb_systemtime::unix_time_as_millis();
@@ -52,7 +70,16 @@ impl PeerStore {
                 peer.session_type = session_type;
             }
             Entry::Vacant(entry) => {
-                let peer = PeerInfo::new(addr, session_type, now_ms);
+                // inherit the score of the known address
+                let score = self
+                    .addr_manager
+                    .get(&addr)
+                    .map(|info| {
+                        self.score_config
+                            .decay(info.score, info.score_updated_at_ms, now_ms)
+                    })
+                    .unwrap_or(self.score_config.default_score);
+                let peer = PeerInfo::new(addr, session_type, now_ms, score);
                 entry.insert(peer);
             }
         }
@@ -105,23 +132,58 @@ impl PeerStore {
         &mut self.addr_manager
     }
 
+    /// Get scoring configuration
+    pub fn score_config(&self) -> &PeerScoreConfig {
+        &self.score_config
+    }
+
     /// Report peer behaviours
+    ///
+    /// The score of the connected peer is updated, and it's saved to the known address, so that
+    /// the score is persisted with the peer store.
     pub fn report(&mut self, addr: &Multiaddr, behaviour: Behaviour) -> ReportResult {
+        let now_ms = ckb_systemtime::unix_time_as_millis();
+        let config = self.score_config;
+        let mut score = None;
+        if let Some(peer) =
+            extract_peer_id(addr).and_then(|peer_id| self.connected_peers.get_mut(&peer_id))
+        {
+            peer.score = config.apply(peer.score, peer.score_updated_at_ms, now_ms, behaviour);
+            peer.score_updated_at_ms = now_ms;
+            score = Some(peer.score);
+        }
         if let Some(peer_addr) = self.addr_manager.get_mut(addr) {
-            let score = peer_addr.score.saturating_add(behaviour.score());
-            peer_addr.score = score;
-            if score < self.score_config.ban_score {
-                self.ban_addr(
-                    addr,
-                    self.score_config.ban_timeout_ms,
-                    format!("report behaviour {behaviour:?}"),
-                );
-                return ReportResult::Banned;
-            }
+            peer_addr.score = score.unwrap_or_else(|| {
+                config.apply(
+                    peer_addr.score,
+                    peer_addr.score_updated_at_ms,
+                    now_ms,
+                    behaviour,
+                )
+            });
+            peer_addr.score_updated_at_ms = now_ms;
+            score = Some(peer_addr.score);
+        }
+        if score.map_or(false, |score| score < config.ban_score) {
+            self.ban_addr(
+                addr,
+                config.ban_timeout_ms,
+                format!("report behaviour {behaviour:?}"),
+            );
+            return ReportResult::Banned;
         }
         ReportResult::Ok
     }
 
+    /// Get the current score of a connected peer
+    pub fn peer_score(&self, peer_id: &PeerId) -> Option<Score> {
+        let now_ms = ckb_systemtime::unix_time_as_millis();
+        self.connected_peers.get(peer_id).map(|peer| {
+            self.score_config
+                .decay(peer.score, peer.score_updated_at_ms, now_ms)
+        })
+    }
+
     /// Remove peer id
     pub fn remove_disconnected_peer(&mut self, addr: &Multiaddr) -> Option<PeerInfo> {
         extract_peer_id(addr).and_then(|peer_id| self.connected_peers.remove(&peer_id))
@@ -144,13 +206,15 @@ impl PeerStore {
 
         let now_ms = ckb_systemtime::unix_time_as_millis();
         let peers = &self.connected_peers;
+        let onion_reachable = self.onion_reachable;
         let addr_expired_ms = now_ms.saturating_sub(ADDR_TRY_TIMEOUT_MS);
         // get addrs that can attempt.
         self.addr_manager
             .fetch_random(count, |peer_addr: &AddrInfo| {
-                extract_peer_id(&peer_addr.addr)
-                    .map(|peer_id| !peers.contains_key(&peer_id))
-                    .unwrap_or_default()
+                (onion_reachable || !is_onion_addr(&peer_addr.addr))
+                    && extract_peer_id(&peer_addr.addr)
+                        .map(|peer_id| !peers.contains_key(&peer_id))
+                        .unwrap_or_default()
                     && peer_addr.connected(|t| {
                         t > addr_expired_ms && t <= now_ms.saturating_sub(DIAL_INTERVAL)
                     })
@@ -172,11 +236,13 @@ impl PeerStore {
         let now_ms = ckb_systemtime::unix_time_as_millis();
         let addr_expired_ms = now_ms.saturating_sub(ADDR_TRY_TIMEOUT_MS);
         let peers = &self.connected_peers;
+        let onion_reachable = self.onion_reachable;
         self.addr_manager
             .fetch_random(count, |peer_addr: &AddrInfo| {
-                extract_peer_id(&peer_addr.addr)
-                    .map(|peer_id| !peers.contains_key(&peer_id))
-                    .unwrap_or_default()
+                (onion_reachable || !is_onion_addr(&peer_addr.addr))
+                    && extract_peer_id(&peer_addr.addr)
+                        .map(|peer_id| !peers.contains_key(&peer_id))
+                        .unwrap_or_default()
                     && !peer_addr.tried_in_last_minute(now_ms)
                     && !peer_addr.connected(|t| t > addr_expired_ms)
             })
@@ -274,7 +340,10 @@ impl PeerStore {
                 let mut peers_by_network_group: HashMap<Group, Vec<_>> = HashMap::default();
                 for addr in self.addr_manager.addrs_iter() {
                     peers_by_network_group
-                        .entry((&addr.addr).into())
+                        .entry(match self.asmap {
+                            Some(ref asmap) => asmap.group(&addr.addr),
+                            None => (&addr.addr).into(),
+                        })
                         .or_default()
                         .push(addr);
                 }
@@ -17,6 +17,10 @@ pub struct PeerInfo {
     pub session_type: SessionType,
     /// Connected time
     pub last_connected_at_ms: u64,
+    /// Score of the peer
+    pub score: Score,
+    /// Last time the score changed
+    pub score_updated_at_ms: u64,
 }
 
 impl PeerInfo {
@@ -25,11 +29,14 @@ impl PeerInfo {
         connected_addr: Multiaddr,
         session_type: SessionType,
         last_connected_at_ms: u64,
+        score: Score,
     ) -> Self {
         PeerInfo {
             connected_addr,
             session_type,
             last_connected_at_ms,
+            score,
+            score_updated_at_ms: last_connected_at_ms,
         }
     }
 }
@@ -41,6 +48,9 @@ pub struct AddrInfo {
     pub addr: Multiaddr,
     /// Score about this addr
     pub score: Score,
+    /// Last time the score changed
+    #[serde(default)]
+    pub score_updated_at_ms: u64,
     /// Last connected time
     pub last_connected_at_ms: u64,
     /// Last try time
@@ -64,6 +74,7 @@ impl AddrInfo {
         AddrInfo {
             addr,
             score,
+            score_updated_at_ms: 0,
             last_connected_at_ms,
             last_tried_at_ms: 0,
             attempts_count: 0,
@@ -3,7 +3,7 @@ use std::collections::hash_map::RandomState;
 use bloom_filters::{BloomFilter, DefaultBuildHashKernels, StableBloomFilter};
 use p2p::{context::SessionContext, multiaddr::Multiaddr, ProtocolId, SessionId};
 
-use crate::Flags;
+use crate::{Behaviour, Flags};
 
 pub(crate) const DEFAULT_BUCKETS_NUM: usize = 5000;
 
@@ -21,6 +21,19 @@ pub enum Misbehavior {
     InvalidData,
 }
 
+impl Misbehavior {
+    pub(crate) fn behaviour(&self) -> Behaviour {
+        match self {
+            Misbehavior::DuplicateGetNodes | Misbehavior::DuplicateFirstNodes => {
+                Behaviour::UnrequestedMessage
+            }
+            Misbehavior::TooManyItems { .. }
+            | Misbehavior::TooManyAddresses(_)
+            | Misbehavior::InvalidData => Behaviour::InvalidMessage,
+        }
+    }
+}
+
 /// Misbehavior report result
 pub enum MisbehaveResult {
     /// Disconnect this peer
@@ -41,6 +54,7 @@ pub trait AddressManager {
     fn register(&self, id: SessionId, pid: ProtocolId, version: &str);
     fn unregister(&self, id: SessionId, pid: ProtocolId);
     fn is_valid_addr(&self, addr: &Multiaddr) -> bool;
+    fn remote_addr(&self, session: &SessionContext) -> Multiaddr;
     fn add_new_addr(&mut self, session_id: SessionId, addr: (Multiaddr, Flags));
     fn add_new_addrs(&mut self, session_id: SessionId, addrs: Vec<(Multiaddr, Flags)>);
     fn misbehave(&mut self, session: &SessionContext, kind: &Misbehavior) -> MisbehaveResult;
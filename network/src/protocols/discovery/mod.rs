@@ -24,7 +24,7 @@ use self::{
     protocol::{decode, encode},
     state::RemoteAddress,
 };
-use crate::{Flags, NetworkState, ProtocolId};
+use crate::{is_onion_addr, Flags, NetworkState, ProtocolId};
 
 mod addr;
 pub(crate) mod protocol;
@@ -330,13 +330,17 @@ impl AddressManager for DiscoveryAddressManager {
         if !self.discovery_local_address {
             let local_or_invalid = multiaddr_to_socketaddr(addr)
                 .map(|socket_addr| !is_reachable(socket_addr.ip()))
-                .unwrap_or(true);
+                .unwrap_or_else(|| !is_onion_addr(addr));
             !local_or_invalid
         } else {
             true
         }
     }
 
+    fn remote_addr(&self, session: &SessionContext) -> Multiaddr {
+        self.network_state.remote_addr(session)
+    }
+
     fn add_new_addr(&mut self, session_id: SessionId, addr: (Multiaddr, Flags)) {
         self.add_new_addrs(session_id, vec![addr])
     }
@@ -364,6 +368,8 @@ impl AddressManager for DiscoveryAddressManager {
             "DiscoveryProtocol detects abnormal behavior, session: {:?}, behavior: {:?}",
             session, behavior
         );
+        self.network_state
+            .report_session_behaviour(session.id, behavior.behaviour());
 
         // FIXME:
         MisbehaveResult::Disconnect
@@ -8,7 +8,10 @@ use p2p::{
     SessionId,
 };
 
-use crate::Flags;
+use crate::{
+    network::{find_type, TransportType},
+    Flags,
+};
 
 use super::{
     addr::AddrKnown,
@@ -49,7 +52,10 @@ impl SessionState {
                 .flat_map(|address| {
                     // Verify self is a public node first
                     // if not, try to make public network nodes broadcast hole punching information
-                    if addr_manager.is_valid_addr(address) {
+                    // the WebSocket listen port can't be told in the message
+                    if addr_manager.is_valid_addr(address)
+                        && find_type(address) == TransportType::Tcp
+                    {
                         multiaddr_to_socketaddr(address).map(|socket_addr| socket_addr.port())
                     } else {
                         None
@@ -74,9 +80,10 @@ impl SessionState {
                 )
             }
 
-            addr_known.insert(&context.session.address);
+            let remote_addr = addr_manager.remote_addr(context.session);
+            addr_known.insert(&remote_addr);
 
-            RemoteAddress::Listen(context.session.address.clone())
+            RemoteAddress::Listen(remote_addr)
         } else {
             RemoteAddress::Init(context.session.address.clone())
         };
@@ -152,13 +159,15 @@ impl RemoteAddress {
 
     pub(crate) fn update_port(&mut self, port: u16) {
         if let RemoteAddress::Init(ref addr) = self {
+            // the listen port is always a plain TCP port
             let addr = addr
                 .into_iter()
-                .map(|proto| {
+                .filter_map(|proto| {
                     match proto {
                         // TODO: other transport, UDP for example
-                        Protocol::Tcp(_) => Protocol::Tcp(port),
-                        value => value,
+                        Protocol::Tcp(_) => Some(Protocol::Tcp(port)),
+                        Protocol::Ws => None,
+                        value => Some(value),
                     }
                 })
                 .collect();
@@ -26,12 +26,13 @@ impl ServiceProtocol for Feeler {
 
     async fn connected(&mut self, context: ProtocolContextMutRef<'_>, version: &str) {
         let session = context.session;
+        let remote_addr = self.network_state.remote_addr(session);
         if self.network_state.ckb2023.load(Ordering::SeqCst) && version != "3" {
             self.network_state
                 .peer_store
                 .lock()
                 .mut_addr_manager()
-                .remove(&session.address);
+                .remove(&remote_addr);
         } else if context.session.ty.is_outbound() {
             let flags = self.network_state.with_peer_registry(|reg| {
                 if let Some(p) = reg.get_peer(session.id) {
@@ -44,11 +45,11 @@ impl ServiceProtocol for Feeler {
                 }
             });
             self.network_state.with_peer_store_mut(|peer_store| {
-                peer_store.add_outbound_addr(session.address.clone(), flags);
+                peer_store.add_outbound_addr(remote_addr.clone(), flags);
             });
         }
 
-        debug!("peer={} FeelerProtocol.connected", session.address);
+        debug!("peer={} FeelerProtocol.connected", remote_addr);
         if let Err(err) =
             async_disconnect_with_message(context.control(), session.id, "feeler connection").await
         {
@@ -57,10 +58,10 @@ impl ServiceProtocol for Feeler {
     }
 
     async fn disconnected(&mut self, context: ProtocolContextMutRef<'_>) {
-        let session = context.session;
+        let remote_addr = self.network_state.remote_addr(context.session);
         self.network_state.with_peer_registry_mut(|reg| {
-            reg.remove_feeler(&session.address);
+            reg.remove_feeler(&remote_addr);
         });
-        debug!("peer={} FeelerProtocol.disconnected", session.address);
+        debug!("peer={} FeelerProtocol.disconnected", remote_addr);
     }
 }
@@ -17,7 +17,12 @@ use p2p::{
 
 mod protocol;
 
-use crate::{peer_store::required_flags_filter, NetworkState, PeerIdentifyInfo, SupportProtocols};
+use crate::{
+    is_onion_addr,
+    network::{find_type, TransportType},
+    peer_store::required_flags_filter,
+    Behaviour, NetworkState, PeerIdentifyInfo, SupportProtocols,
+};
 use ckb_types::{packed, prelude::*};
 
 use protocol::IdentifyMessage;
@@ -43,6 +48,18 @@ pub enum Misbehavior {
     TooManyAddresses(usize),
 }
 
+impl Misbehavior {
+    fn behaviour(&self) -> Behaviour {
+        match self {
+            Misbehavior::DuplicateReceived => Behaviour::UnrequestedMessage,
+            Misbehavior::Timeout => Behaviour::Timeout,
+            Misbehavior::InvalidData | Misbehavior::TooManyAddresses(_) => {
+                Behaviour::InvalidMessage
+            }
+        }
+    }
+}
+
 /// Misbehavior report result
 pub enum MisbehaveResult {
     /// Continue to run
@@ -141,7 +158,7 @@ impl<T: Callback> IdentifyProtocol<T> {
                 .filter(|addr| {
                     multiaddr_to_socketaddr(addr)
                         .map(|socket_addr| !global_ip_only || is_reachable(socket_addr.ip()))
-                        .unwrap_or(false)
+                        .unwrap_or_else(|| is_onion_addr(addr))
                 })
                 .collect::<Vec<_>>();
             self.callback
@@ -228,7 +245,7 @@ impl<T: Callback> ServiceProtocol for IdentifyProtocol<T> {
             .filter(|addr| {
                 multiaddr_to_socketaddr(addr)
                     .map(|socket_addr| !self.global_ip_only || is_reachable(socket_addr.ip()))
-                    .unwrap_or(false)
+                    .unwrap_or_else(|| is_onion_addr(addr))
             })
             .take(MAX_ADDRS)
             .cloned()
@@ -386,8 +403,9 @@ impl Callback for IdentifyCallback {
             // disconnected after a long connection is maintained for more than seven days,
             // it is possible that the node will be accidentally evicted, so it is necessary
             // to reset the last_connected_time of the node when disconnected.
+            let remote_addr = self.network_state.remote_addr(context.session);
             self.network_state.with_peer_store_mut(|peer_store| {
-                peer_store.update_outbound_addr_last_connected_ms(context.session.address.clone());
+                peer_store.update_outbound_addr_last_connected_ms(remote_addr);
             });
         }
     }
@@ -438,13 +456,14 @@ impl Callback for IdentifyCallback {
                     .unwrap_or_default();
                 let ckb2023 = self.network_state.ckb2023.load(Ordering::SeqCst);
 
+                let remote_addr = self.network_state.remote_addr(context.session);
                 let renew = if ckb2023 && protocol_version_match {
                     if context.session.ty.is_outbound() {
                         self.network_state
                             .peer_store
                             .lock()
                             .mut_addr_manager()
-                            .remove(&context.session.address);
+                            .remove(&remote_addr);
                     }
                     false
                 } else {
@@ -458,13 +477,13 @@ impl Callback for IdentifyCallback {
                     // but this is an unverified address
                     if renew {
                         self.network_state.with_peer_store_mut(|peer_store| {
-                            peer_store.add_outbound_addr(context.session.address.clone(), flags);
+                            peer_store.add_outbound_addr(remote_addr.clone(), flags);
                         });
                     }
 
                     if self
                         .network_state
-                        .with_peer_registry(|reg| reg.is_feeler(&context.session.address))
+                        .with_peer_registry(|reg| reg.is_feeler(&remote_addr))
                     {
                         let _ = context
                             .open_protocols(
@@ -553,12 +572,19 @@ impl Callback for IdentifyCallback {
         let observed_addrs_iter = self
             .listen_addrs()
             .into_iter()
-            .filter_map(|listen_addr| multiaddr_to_socketaddr(&listen_addr))
-            .map(|socket_addr| {
+            .filter_map(|listen_addr| {
+                multiaddr_to_socketaddr(&listen_addr)
+                    .map(|socket_addr| (socket_addr.port(), find_type(&listen_addr)))
+            })
+            .map(|(port, transport)| {
                 addr.iter()
-                    .map(|proto| match proto {
-                        Protocol::Tcp(_) => Protocol::Tcp(socket_addr.port()),
-                        value => value,
+                    .filter(|proto| !matches!(proto, Protocol::Ws))
+                    .flat_map(|proto| match proto {
+                        Protocol::Tcp(_) if transport == TransportType::Ws => {
+                            vec![Protocol::Tcp(port), Protocol::Ws]
+                        }
+                        Protocol::Tcp(_) => vec![Protocol::Tcp(port)],
+                        value => vec![value],
                     })
                     .collect::<Multiaddr>()
             })
@@ -574,6 +600,8 @@ impl Callback for IdentifyCallback {
             "IdentifyProtocol detects abnormal behavior, session: {:?}, reason: {:?}",
             session, reason
         );
+        self.network_state
+            .report_session_behaviour(session.id, reason.behaviour());
         MisbehaveResult::Disconnect
     }
 }
@@ -639,5 +667,7 @@ bitflags::bitflags! {
         const LIGHT_CLIENT = 0b10000;
         /// Client-side block filter protocol can provide BlockFilter download service
         const BLOCK_FILTER = 0b100000;
+        /// Pruned node, Sync protocol can only provide the blocks in the recent history, the headers are complete
+        const PRUNED = 0b1000000;
     }
 }
@@ -110,6 +110,23 @@ fn net_service_start(
     required_flags: Flags,
     self_flags: Flags,
 ) -> Node {
+    net_service_start_on(
+        "/ip4/127.0.0.1/tcp/0",
+        name,
+        enable_discovery_push,
+        required_flags,
+        self_flags,
+    )
+}
+
+fn net_service_start_on(
+    listen_addr: &str,
+    name: String,
+    enable_discovery_push: bool,
+    required_flags: Flags,
+    self_flags: Flags,
+) -> Node {
+    let listen_addr: Multiaddr = listen_addr.parse().unwrap();
     let tmp_dir = tempdir().expect("create tempdir failed");
     let config = NetworkConfig {
         max_peers: 19,
@@ -242,10 +259,7 @@ fn net_service_start(
             .unwrap()
     });
     rt.spawn(async move {
-        let mut listen_addr = p2p_service
-            .listen("/ip4/127.0.0.1/tcp/0".parse().unwrap())
-            .await
-            .unwrap();
+        let mut listen_addr = p2p_service.listen(listen_addr).await.unwrap();
         listen_addr.push(Protocol::P2P(Cow::Owned(peer_id.into_bytes())));
         addr_sender.send(listen_addr).unwrap();
         p2p_service.run().await
@@ -412,6 +426,49 @@ fn test_identify_behavior() {
     );
 }
 
+#[test]
+fn test_identify_over_ws() {
+    let node1 = net_service_start(
+        "/test/1".to_string(),
+        false,
+        Flags::COMPATIBILITY,
+        Flags::COMPATIBILITY,
+    );
+    let node2 = net_service_start_on(
+        "/ip4/127.0.0.1/tcp/0/ws",
+        "/test/1".to_string(),
+        false,
+        Flags::COMPATIBILITY,
+        Flags::COMPATIBILITY,
+    );
+    assert!(node2
+        .listen_addr
+        .iter()
+        .any(|proto| matches!(proto, Protocol::Ws)));
+
+    node1.dial(
+        &node2,
+        TargetProtocol::Single(SupportProtocols::Identify.protocol_id()),
+    );
+
+    wait_connect_state(&node1, 1);
+    wait_connect_state(&node2, 1);
+
+    // the WebSocket address is stored as it is after identified
+    if !wait_until(10, || {
+        node1
+            .network_state
+            .peer_store
+            .lock()
+            .addr_manager()
+            .get(&node2.listen_addr)
+            .map(|info| info.addr == node2.listen_addr)
+            .unwrap_or(false)
+    }) {
+        panic!("identify can't store the WebSocket address")
+    }
+}
+
 #[test]
 fn test_feeler_behavior() {
     let node1 = net_service_start(
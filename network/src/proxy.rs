@@ -0,0 +1,376 @@
+//! SOCKS5 proxy support
+//!
+//! Tentacle only dials plain TCP, so every dial through the proxy is served by a one-shot bridge
+//! listening on the loopback interface: tentacle dials the bridge, then the bridge connects the
+//! target through the proxy and relays the bytes in both directions.
+//!
+//! A local process could dial the bridge before tentacle, so the bridge only relays the
+//! connection dialed by this process, and the dialed address always has the peer id, which is
+//! verified by the secio handshake through the bridge.
+//!
+//! Tor onion services are addressed by the `onion3` protocol, e.g.,
+//! `/onion3/<name>:8115/p2p/<peer-id>`, which are only reachable through the proxy. They are
+//! carried as `/dns4/<name>.onion/tcp/8115/p2p/<peer-id>` inside the node, see
+//! [`parse_multiaddr`](ckb_app_config::parse_multiaddr).
+use crate::extract_peer_id;
+use crate::multiaddr::{Multiaddr, Protocol};
+use ckb_app_config::{is_onion_name, ProxyConfig};
+use ckb_logger::debug;
+use ckb_util::Mutex;
+use p2p::utils::multiaddr_to_socketaddr;
+#[cfg(target_os = "linux")]
+use std::fs;
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    net::{Ipv4Addr, SocketAddr, TcpListener},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::mpsc,
+};
+
+// The bridge is closed if it's not dialed in time
+const BRIDGE_ACCEPT_TIMEOUT: Duration = Duration::from_secs(30);
+// Building a Tor circuit may take a while
+const PROXY_CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+const SOCKS5_VERSION: u8 = 0x05;
+const SOCKS5_NO_AUTHENTICATION: u8 = 0x00;
+const SOCKS5_CMD_CONNECT: u8 = 0x01;
+const SOCKS5_ATYP_IPV4: u8 = 0x01;
+const SOCKS5_ATYP_DOMAIN: u8 = 0x03;
+const SOCKS5_ATYP_IPV6: u8 = 0x04;
+
+/// Returns the name and the port of the onion service, e.g., `<name>.onion` and `8115` of
+/// `/onion3/<name>:8115`
+pub(crate) fn onion_service(addr: &Multiaddr) -> Option<(String, u16)> {
+    let mut name = None;
+    let mut port = None;
+    for proto in addr.iter() {
+        match proto {
+            Protocol::Dns4(host) | Protocol::Dns6(host) if is_onion_name(&host) => {
+                name = Some(host.into_owned())
+            }
+            Protocol::Tcp(tcp_port) => port = Some(tcp_port),
+            _ => (),
+        }
+    }
+    name.zip(port)
+}
+
+/// Whether the address is a Tor onion service
+pub fn is_onion_addr(addr: &Multiaddr) -> bool {
+    onion_service(addr).is_some()
+}
+
+/// The target address passed to the proxy
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum TargetAddr {
+    Ip(SocketAddr),
+    Domain(String, u16),
+}
+
+impl TargetAddr {
+    fn from_multiaddr(addr: &Multiaddr) -> Option<TargetAddr> {
+        if let Some(socket_addr) = multiaddr_to_socketaddr(addr) {
+            return Some(TargetAddr::Ip(socket_addr));
+        }
+        let mut host = None;
+        let mut port = None;
+        for proto in addr.iter() {
+            match proto {
+                Protocol::Dns4(name) | Protocol::Dns6(name) => host = Some(name.into_owned()),
+                Protocol::Tcp(tcp_port) => port = Some(tcp_port),
+                _ => (),
+            }
+        }
+        host.zip(port)
+            .map(|(host, port)| TargetAddr::Domain(host, port))
+    }
+}
+
+impl std::fmt::Display for TargetAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetAddr::Ip(socket_addr) => write!(f, "{socket_addr}"),
+            TargetAddr::Domain(host, port) => write!(f, "{host}:{port}"),
+        }
+    }
+}
+
+/// The SOCKS5 proxy dialer
+pub(crate) struct Proxy {
+    config: ProxyConfig,
+    // bridge address => the dialed address, kept until the session is closed
+    bridges: Mutex<HashMap<SocketAddr, Multiaddr>>,
+    sender: mpsc::UnboundedSender<Bridge>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<Bridge>>>,
+}
+
+impl Proxy {
+    pub(crate) fn new(config: ProxyConfig) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Proxy {
+            config,
+            bridges: Mutex::new(HashMap::default()),
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+
+    /// Whether the address should be dialed through the proxy
+    pub(crate) fn should_proxy(&self, addr: &Multiaddr) -> bool {
+        self.config.only_proxy || is_onion_addr(addr)
+    }
+
+    /// Opens a bridge to the address, returns the bridge address for tentacle to dial
+    pub(crate) fn open_bridge(&self, addr: &Multiaddr) -> Result<Multiaddr, Error> {
+        // the peer id authenticates the target through the bridge
+        if extract_peer_id(addr).is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("can't dial {addr} through the proxy without the peer id"),
+            ));
+        }
+        let target = TargetAddr::from_multiaddr(addr).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("can't dial {addr} through the proxy"),
+            )
+        })?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        listener.set_nonblocking(true)?;
+        let bridge_socket_addr = listener.local_addr()?;
+        let bridge_addr = addr
+            .iter()
+            .map(|proto| match proto {
+                Protocol::Ip4(_) | Protocol::Ip6(_) | Protocol::Dns4(_) | Protocol::Dns6(_) => {
+                    Protocol::Ip4(Ipv4Addr::LOCALHOST)
+                }
+                Protocol::Tcp(_) => Protocol::Tcp(bridge_socket_addr.port()),
+                value => value,
+            })
+            .collect();
+        self.sender
+            .send(Bridge { listener, target })
+            .map_err(|_| Error::new(ErrorKind::NotConnected, "the proxy service is stopped"))?;
+        self.bridges
+            .lock()
+            .insert(bridge_socket_addr, addr.to_owned());
+        Ok(bridge_addr)
+    }
+
+    /// Returns the dialed address if the address is a bridge
+    pub(crate) fn dialed_addr(&self, bridge_addr: &Multiaddr) -> Option<Multiaddr> {
+        multiaddr_to_socketaddr(bridge_addr)
+            .and_then(|socket_addr| self.bridges.lock().get(&socket_addr).cloned())
+    }
+
+    /// Forgets the bridge once the session is closed or the dialing failed
+    pub(crate) fn close_bridge(&self, bridge_addr: &Multiaddr) {
+        if let Some(socket_addr) = multiaddr_to_socketaddr(bridge_addr) {
+            self.bridges.lock().remove(&socket_addr);
+        }
+    }
+
+    /// Takes the background service which serves the bridges, returns `None` if it has been taken
+    pub(crate) fn take_service(&self) -> Option<ProxyService> {
+        self.receiver.lock().take().map(|receiver| ProxyService {
+            proxy_address: self.config.proxy_address.clone(),
+            receiver,
+        })
+    }
+}
+
+struct Bridge {
+    listener: TcpListener,
+    target: TargetAddr,
+}
+
+/// Serves the bridges opened by the proxy dialer
+pub(crate) struct ProxyService {
+    proxy_address: String,
+    receiver: mpsc::UnboundedReceiver<Bridge>,
+}
+
+impl ProxyService {
+    pub(crate) async fn start(mut self) {
+        while let Some(Bridge { listener, target }) = self.receiver.recv().await {
+            let proxy_address = self.proxy_address.clone();
+            tokio::spawn(async move {
+                if let Err(err) = relay(listener, &proxy_address, &target).await {
+                    debug!("Proxy bridge to {} is closed, error: {}", target, err);
+                }
+            });
+        }
+        // the background services never complete
+        futures::future::pending::<()>().await
+    }
+}
+
+async fn relay(
+    listener: TcpListener,
+    proxy_address: &str,
+    target: &TargetAddr,
+) -> Result<(), Error> {
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    let bridge_addr = listener.local_addr()?;
+    let accept = async {
+        loop {
+            let (inbound, remote) = listener.accept().await?;
+            if is_dialed_by_self(remote, bridge_addr) {
+                return Ok::<_, Error>(inbound);
+            }
+            debug!(
+                "Proxy bridge rejects the connection from another process {}",
+                remote
+            );
+        }
+    };
+    let mut inbound = tokio::time::timeout(BRIDGE_ACCEPT_TIMEOUT, accept)
+        .await
+        .map_err(|_| Error::new(ErrorKind::TimedOut, "the bridge is not dialed in time"))??;
+    drop(listener);
+    let mut outbound =
+        tokio::time::timeout(PROXY_CONNECT_TIMEOUT, socks5_connect(proxy_address, target))
+            .await
+            .map_err(|_| Error::new(ErrorKind::TimedOut, "the proxy does not connect in time"))??;
+    tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await?;
+    Ok(())
+}
+
+/// Whether the loopback connection from `remote` to the bridge is dialed by this process.
+///
+/// The socket of the connection is looked up in `/proc/net/tcp`, it must be one of the open files
+/// of this process.
+#[cfg(target_os = "linux")]
+pub(crate) fn is_dialed_by_self(remote: SocketAddr, bridge_addr: SocketAddr) -> bool {
+    let inode = match fs::read_to_string("/proc/net/tcp").map(|tcp| {
+        tcp.lines().skip(1).find_map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            // the local address, the remote address, ..., the inode
+            match (fields.get(1), fields.get(2), fields.get(9)) {
+                (Some(local), Some(peer), Some(inode))
+                    if parse_proc_socket_addr(local) == Some(remote)
+                        && parse_proc_socket_addr(peer) == Some(bridge_addr) =>
+                {
+                    Some(inode.to_string())
+                }
+                _ => None,
+            }
+        })
+    }) {
+        Ok(Some(inode)) => inode,
+        _ => return false,
+    };
+    let socket = format!("socket:[{inode}]");
+    fs::read_dir("/proc/self/fd").map_or(false, |fds| {
+        fds.filter_map(Result::ok)
+            .filter_map(|fd| fs::read_link(fd.path()).ok())
+            .any(|link| link.as_os_str() == socket.as_str())
+    })
+}
+
+/// The sockets of the other processes can't be looked up, the first connection is relayed.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn is_dialed_by_self(_remote: SocketAddr, _bridge_addr: SocketAddr) -> bool {
+    true
+}
+
+// Parses the IPv4 socket address in `/proc/net/tcp`, e.g., `0100007F:1F90` is `127.0.0.1:8080`,
+// the IP is printed in the native byte order of the network ordered bytes.
+#[cfg(target_os = "linux")]
+fn parse_proc_socket_addr(addr: &str) -> Option<SocketAddr> {
+    let (ip, port) = addr.split_once(':')?;
+    let ip = u32::from_str_radix(ip, 16).ok()?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    Some(SocketAddr::from((Ipv4Addr::from(ip.to_ne_bytes()), port)))
+}
+
+/// Connects the target through the SOCKS5 proxy, see [RFC 1928](https://www.rfc-editor.org/rfc/rfc1928)
+async fn socks5_connect(proxy_address: &str, target: &TargetAddr) -> Result<TcpStream, Error> {
+    let mut stream = TcpStream::connect(proxy_address).await?;
+
+    stream
+        .write_all(&[SOCKS5_VERSION, 1, SOCKS5_NO_AUTHENTICATION])
+        .await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply != [SOCKS5_VERSION, SOCKS5_NO_AUTHENTICATION] {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "the proxy requires an unsupported authentication method",
+        ));
+    }
+
+    let mut request = vec![SOCKS5_VERSION, SOCKS5_CMD_CONNECT, 0];
+    let port = match target {
+        TargetAddr::Ip(SocketAddr::V4(socket_addr)) => {
+            request.push(SOCKS5_ATYP_IPV4);
+            request.extend_from_slice(&socket_addr.ip().octets());
+            socket_addr.port()
+        }
+        TargetAddr::Ip(SocketAddr::V6(socket_addr)) => {
+            request.push(SOCKS5_ATYP_IPV6);
+            request.extend_from_slice(&socket_addr.ip().octets());
+            socket_addr.port()
+        }
+        TargetAddr::Domain(host, port) => {
+            let len = u8::try_from(host.len())
+                .map_err(|_| Error::new(ErrorKind::InvalidInput, "the host name is too long"))?;
+            request.push(SOCKS5_ATYP_DOMAIN);
+            request.push(len);
+            request.extend_from_slice(host.as_bytes());
+            *port
+        }
+    };
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS5_VERSION {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "the proxy is not a SOCKS5 proxy",
+        ));
+    }
+    if reply[1] != 0 {
+        return Err(Error::new(
+            ErrorKind::ConnectionRefused,
+            format!("the proxy failed to connect, {}", reply_error(reply[1])),
+        ));
+    }
+    // skip the bound address
+    let addr_len = match reply[3] {
+        SOCKS5_ATYP_IPV4 => 4,
+        SOCKS5_ATYP_IPV6 => 16,
+        SOCKS5_ATYP_DOMAIN => usize::from(stream.read_u8().await?),
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "the proxy replied an unknown address type",
+            ))
+        }
+    };
+    let mut bound_addr = vec![0u8; addr_len + 2];
+    stream.read_exact(&mut bound_addr).await?;
+    Ok(stream)
+}
+
+fn reply_error(code: u8) -> &'static str {
+    match code {
+        0x01 => "general SOCKS server failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
@@ -40,3 +40,28 @@ proptest! {
         assert_eq!(addrs.len(), count);
     }
 }
+
+#[test]
+fn test_retain_addrs() {
+    let mut addr_manager: AddrManager = Default::default();
+    for i in 1..=10u32 {
+        let addr: Multiaddr = format!(
+            "/ip4/{}/tcp/42/p2p/{}",
+            Ipv4Addr::from((225 << 24) + i),
+            PeerId::random().to_base58()
+        )
+        .parse()
+        .unwrap();
+        addr_manager.add(AddrInfo::new(addr, u64::from(i), 0, 0));
+    }
+
+    let removed_addrs = addr_manager.retain(|addr_info| addr_info.last_connected_at_ms > 4);
+    assert_eq!(removed_addrs.len(), 4);
+    assert_eq!(addr_manager.count(), 6);
+    for addr_info in removed_addrs {
+        assert!(addr_info.last_connected_at_ms <= 4);
+        assert!(addr_manager.get(&addr_info.addr).is_none());
+    }
+    // the random ids are still consistent after the removal
+    assert_eq!(addr_manager.fetch_random(10, |_| true).len(), 6);
+}
@@ -1,8 +1,10 @@
 mod addr_manager;
 mod compress;
+mod network_group;
 mod peer_registry;
 mod peer_store;
 mod peer_store_db;
+mod proxy;
 
 fn random_addr() -> crate::multiaddr::Multiaddr {
     format!(
@@ -20,7 +22,7 @@ fn random_addr_v6() -> crate::multiaddr::Multiaddr {
 
     multi_addr.push(crate::multiaddr::Protocol::Tcp(43));
     multi_addr.push(crate::multiaddr::Protocol::P2P(
-        crate::PeerId::random().to_base58().into_bytes().into(),
+        crate::PeerId::random().as_bytes().to_vec().into(),
     ));
     multi_addr
 }
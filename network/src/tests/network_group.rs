@@ -0,0 +1,61 @@
+use crate::{multiaddr::Multiaddr, AsMap, Group};
+use std::net::IpAddr;
+
+const ASMAP: &str = "
+# prefix asn
+1.2.0.0/16 AS100
+1.2.3.0/24 AS200
+2001:db8::/32 300
+";
+
+#[test]
+fn test_asmap_lookup() {
+    let asmap: AsMap = ASMAP.parse().unwrap();
+    let lookup = |ip: &str| asmap.lookup(ip.parse::<IpAddr>().unwrap());
+
+    // the longest prefix wins
+    assert_eq!(lookup("1.2.3.4"), Some(200));
+    assert_eq!(lookup("1.2.4.4"), Some(100));
+    assert_eq!(lookup("1.3.0.1"), None);
+    assert_eq!(lookup("::ffff:1.2.3.4"), Some(200));
+    assert_eq!(lookup("2001:db8:1::1"), Some(300));
+    assert_eq!(lookup("2001:db9::1"), None);
+}
+
+#[test]
+fn test_asmap_group() {
+    let asmap: AsMap = ASMAP.parse().unwrap();
+    let group = |addr: &str| asmap.group(&addr.parse::<Multiaddr>().unwrap());
+
+    assert_eq!(group("/ip4/1.2.3.4/tcp/8115"), Group::Asn(200));
+    assert_eq!(group("/ip4/1.2.200.1/tcp/8115"), Group::Asn(100));
+    // fall back to the prefix group
+    assert_eq!(group("/ip4/5.6.7.8/tcp/8115"), Group::IP4([5, 6]));
+    assert_eq!(group("/ip4/127.0.0.1/tcp/8115"), Group::LocalNetwork);
+}
+
+#[test]
+fn test_asmap_invalid_entry() {
+    assert!("1.2.0.0 AS100".parse::<AsMap>().is_err());
+    assert!("1.2.0.0/33 AS100".parse::<AsMap>().is_err());
+    assert!("1.2.0.0/16 ASX".parse::<AsMap>().is_err());
+    assert!("1.2.0.0/16 AS100 AS200".parse::<AsMap>().is_err());
+}
+
+#[test]
+fn test_asmap_insert_invalid_prefix_len() {
+    let mut asmap = AsMap::default();
+    assert!(asmap.insert("1.2.0.0".parse().unwrap(), 33, 100).is_err());
+    assert!(asmap
+        .insert("1.2.0.0".parse().unwrap(), u8::MAX, 100)
+        .is_err());
+    assert!(asmap
+        .insert("2001:db8::".parse().unwrap(), 129, 100)
+        .is_err());
+    assert!(asmap.insert("1.2.0.0".parse().unwrap(), 32, 100).is_ok());
+    assert!(asmap
+        .insert("2001:db8::".parse().unwrap(), 128, 200)
+        .is_ok());
+    assert_eq!(asmap.lookup("1.2.0.0".parse().unwrap()), Some(100));
+    assert_eq!(asmap.lookup("2001:db8::".parse().unwrap()), Some(200));
+}
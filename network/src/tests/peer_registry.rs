@@ -7,7 +7,7 @@ use crate::{
     multiaddr::Multiaddr,
     peer_registry::{PeerRegistry, EVICTION_PROTECT_PEERS},
     peer_store::PeerStore,
-    PeerId, SessionType,
+    Behaviour, PeerId, SessionType,
 };
 use std::time::{Duration, Instant};
 
@@ -221,3 +221,82 @@ fn test_accept_inbound_peer_eviction() {
     // should evict from one of evict_targets
     assert_eq!(len_after_eviction, evict_targets.len() - 1);
 }
+
+#[test]
+fn test_accept_inbound_peer_eviction_by_score() {
+    // the misbehaving peer is evicted first among the unprotected peers, instead of the youngest
+    // peer of the largest network group
+    let mut peer_store = PeerStore::default();
+    let mut peers_registry = PeerRegistry::new(3, 3, false, vec![]);
+    let mut addrs = Vec::new();
+    let now = Instant::now();
+    for session_id in 1..=3 {
+        let addr = random_addr();
+        peers_registry
+            .accept_peer(
+                addr.clone(),
+                session_id.into(),
+                SessionType::Inbound,
+                &mut peer_store,
+            )
+            .expect("accept");
+        if let Some(peer) = peers_registry.get_peer_mut(session_id.into()) {
+            peer.connected_time = now - Duration::from_secs(40 - 10 * session_id as u64);
+        }
+        addrs.push(addr);
+    }
+    // the oldest peer is protected though it has the lowest score
+    assert!(peer_store
+        .report(&addrs[0], Behaviour::InvalidMessage)
+        .is_ok());
+    assert!(peer_store.report(&addrs[1], Behaviour::Timeout).is_ok());
+
+    let evicted = peers_registry
+        .accept_peer(
+            random_addr(),
+            4.into(),
+            SessionType::Inbound,
+            &mut peer_store,
+        )
+        .expect("accept")
+        .expect("evicted");
+    assert_eq!(evicted.connected_addr, addrs[1]);
+}
+
+#[test]
+fn test_accept_inbound_peer_eviction_protects_relaying_peers() {
+    let mut peer_store = PeerStore::default();
+    let mut peers_registry = PeerRegistry::new(3, 3, false, vec![]);
+    let mut addrs = Vec::new();
+    let now = Instant::now();
+    for session_id in 1..=3 {
+        let addr = random_addr();
+        peers_registry
+            .accept_peer(
+                addr.clone(),
+                session_id.into(),
+                SessionType::Inbound,
+                &mut peer_store,
+            )
+            .expect("accept");
+        if let Some(peer) = peers_registry.get_peer_mut(session_id.into()) {
+            peer.connected_time = now - Duration::from_secs(40 - 10 * session_id as u64);
+        }
+        addrs.push(addr);
+    }
+    // the youngest peer is protected since it relayed a useful block
+    if let Some(peer) = peers_registry.get_peer_mut(3.into()) {
+        peer.last_block_relayed_at = Some(now);
+    }
+
+    let evicted = peers_registry
+        .accept_peer(
+            random_addr(),
+            4.into(),
+            SessionType::Inbound,
+            &mut peer_store,
+        )
+        .expect("accept")
+        .expect("evicted");
+    assert_eq!(evicted.connected_addr, addrs[1]);
+}
@@ -9,6 +9,7 @@ use crate::{
     Behaviour, Flags, PeerId, SessionType,
 };
 use std::collections::HashSet;
+use std::sync::Arc;
 
 #[test]
 fn test_add_connected_peer() {
@@ -58,19 +59,63 @@ fn test_report() {
     peer_store
         .add_addr(addr.clone(), Flags::COMPATIBILITY)
         .unwrap();
-    assert!(peer_store.report(&addr, Behaviour::TestGood).is_ok());
+    assert!(peer_store.report(&addr, Behaviour::UsefulBlock).is_ok());
 
-    for _ in 0..7 {
-        assert!(peer_store.report(&addr, Behaviour::TestBad).is_ok());
+    for _ in 0..12 {
+        assert!(peer_store
+            .report(&addr, Behaviour::UnrequestedMessage)
+            .is_ok());
     }
 
-    assert!(peer_store.report(&addr, Behaviour::TestBad).is_banned());
+    assert!(peer_store
+        .report(&addr, Behaviour::UnrequestedMessage)
+        .is_banned());
     assert!(peer_store
         .add_addr(addr.clone(), Flags::COMPATIBILITY)
         .is_ok());
     assert!(peer_store.addr_manager().get(&addr).is_none())
 }
 
+#[test]
+fn test_report_connected_peer() {
+    let mut peer_store: PeerStore = Default::default();
+    let addr = random_addr();
+    let peer_id = extract_peer_id(&addr).unwrap();
+    let default_score = peer_store.score_config().default_score;
+    peer_store.add_connected_peer(addr.clone(), SessionType::Inbound);
+    assert_eq!(peer_store.peer_score(&peer_id), Some(default_score));
+
+    // the inbound address is unknown to the address manager, but the peer is still scored
+    assert!(peer_store.report(&addr, Behaviour::Timeout).is_ok());
+    assert!(peer_store.peer_score(&peer_id).unwrap() < default_score);
+    assert!(peer_store
+        .report(&addr, Behaviour::InvalidBlock)
+        .is_banned());
+    assert!(peer_store.is_addr_banned(&addr));
+}
+
+#[test]
+fn test_score_decay() {
+    let peer_store: PeerStore = Default::default();
+    let config = *peer_store.score_config();
+    let half_life = config.score_half_life_ms;
+    let good = config.default_score + 40;
+    let bad = config.default_score - 40;
+
+    assert_eq!(config.decay(good, 0, 0), good);
+    assert_eq!(config.decay(good, 0, half_life), config.default_score + 20);
+    assert_eq!(config.decay(bad, 0, half_life), config.default_score - 20);
+    assert_eq!(config.decay(bad, 0, 64 * half_life), config.default_score);
+
+    // good behaviours can't raise the score above the max score
+    assert_eq!(
+        config.apply(config.max_score, 0, 0, Behaviour::UsefulBlock),
+        config.max_score
+    );
+    // an invalid block bans a peer with any score
+    assert!(config.apply(config.max_score, 0, 0, Behaviour::InvalidBlock) < config.ban_score);
+}
+
 #[test]
 fn test_update_status() {
     let mut peer_store: PeerStore = Default::default();
@@ -570,6 +615,53 @@ fn test_eviction() {
     assert!(peer_store.mut_addr_manager().get(&new_peer_addr).is_some());
 }
 
+#[test]
+fn test_eviction_by_asmap() {
+    let fill = |peer_store: &mut PeerStore| {
+        // 4 addrs in each /16 prefix, all the prefixes are announced by the same AS
+        for i in 0..(ADDR_COUNT_LIMIT - 1) {
+            let group = i / 4;
+            let addr: Multiaddr = format!(
+                "/ip4/{}.{}.0.{}/tcp/43/p2p/{}",
+                1 + (group >> 8),
+                group & 0xff,
+                i % 4,
+                PeerId::random().to_base58()
+            )
+            .parse()
+            .unwrap();
+            peer_store.add_addr(addr, Flags::COMPATIBILITY).unwrap();
+        }
+        let addr: Multiaddr = format!("/ip4/100.0.0.1/tcp/43/p2p/{}", PeerId::random().to_base58())
+            .parse()
+            .unwrap();
+        peer_store.add_addr(addr, Flags::COMPATIBILITY).unwrap();
+        assert_eq!(peer_store.addr_manager().count(), ADDR_COUNT_LIMIT);
+    };
+
+    // no /16 prefix has enough addrs to evict
+    let mut peer_store = PeerStore::default();
+    fill(&mut peer_store);
+    assert!(peer_store
+        .add_addr(random_addr(), Flags::COMPATIBILITY)
+        .is_err());
+
+    // the addrs are in one group by the asmap
+    let mut peer_store = PeerStore::default();
+    let asmap = (1..=16)
+        .map(|a| format!("{a}.0.0.0/8 AS100"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    peer_store.set_asmap(Arc::new(asmap.parse().unwrap()));
+    fill(&mut peer_store);
+    let new_addr = random_addr();
+    peer_store
+        .add_addr(new_addr.clone(), Flags::COMPATIBILITY)
+        .unwrap();
+    assert!(peer_store.addr_manager().get(&new_addr).is_some());
+    assert_eq!(peer_store.addr_manager().count(), ADDR_COUNT_LIMIT - 1);
+}
+
 #[test]
 fn test_addr_unique() {
     let mut peer_store = PeerStore::default();
@@ -0,0 +1,241 @@
+use crate::{
+    is_onion_addr, multiaddr::Multiaddr, peer_store::PeerStore, proxy::Proxy, Flags, Group,
+    NetworkState, PeerId,
+};
+use ckb_app_config::{format_multiaddr, parse_multiaddr, NetworkConfig, ProxyConfig};
+use std::net::SocketAddr;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+fn onion_addr(c: char) -> Multiaddr {
+    format!(
+        "/dns4/{}.onion/tcp/8115/p2p/{}",
+        c.to_string().repeat(56),
+        PeerId::random().to_base58()
+    )
+    .parse()
+    .unwrap()
+}
+
+#[test]
+fn test_onion_addr() {
+    assert!(is_onion_addr(&onion_addr('a')));
+    assert!(is_onion_addr(
+        &format!("/dns6/{}.onion/tcp/8115", "7".repeat(56))
+            .parse()
+            .unwrap()
+    ));
+    // version 2 onion services are not supported
+    assert!(!is_onion_addr(
+        &format!("/dns4/{}.onion/tcp/8115", "a".repeat(16))
+            .parse()
+            .unwrap()
+    ));
+    assert!(!is_onion_addr(
+        &"/dns4/example.com/tcp/8115".parse().unwrap()
+    ));
+    assert!(!is_onion_addr(&"/ip4/1.2.3.4/tcp/8115".parse().unwrap()));
+}
+
+#[test]
+fn test_onion3_multiaddr() {
+    let name = "a".repeat(56);
+    let peer_id = PeerId::random().to_base58();
+    let onion3 = format!("/onion3/{name}:8115/p2p/{peer_id}");
+    let addr = parse_multiaddr(&onion3).unwrap();
+    assert!(is_onion_addr(&addr));
+    assert_eq!(
+        addr,
+        format!("/dns4/{name}.onion/tcp/8115/p2p/{peer_id}")
+            .parse()
+            .unwrap()
+    );
+    assert_eq!(format_multiaddr(&addr), onion3);
+
+    // the other addresses are not changed
+    let ip4 = format!("/ip4/1.2.3.4/tcp/8115/p2p/{peer_id}");
+    assert_eq!(parse_multiaddr(&ip4).unwrap(), ip4.parse().unwrap());
+    assert_eq!(format_multiaddr(&ip4.parse().unwrap()), ip4);
+
+    for invalid in [
+        format!("/onion3/{name}"),
+        format!("/onion3/{name}:port"),
+        format!("/onion3/{}:8115", "a".repeat(16)),
+        format!("/onion3/{}:8115", "A".repeat(56)),
+    ] {
+        assert!(parse_multiaddr(&invalid).is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn test_onion_group() {
+    // the onion services are in one group, since an attacker can create them at no cost
+    assert_eq!(Group::from(&onion_addr('a')), Group::Onion);
+    assert_eq!(Group::from(&onion_addr('a')), Group::from(&onion_addr('b')));
+}
+
+#[test]
+fn test_peer_store_onion_addrs() {
+    let mut peer_store = PeerStore::default();
+    let addr1 = onion_addr('a');
+    let addr2 = onion_addr('b');
+    peer_store.add_addr(addr1.clone(), Flags::all()).unwrap();
+    peer_store.add_addr(addr2.clone(), Flags::all()).unwrap();
+    assert_eq!(peer_store.addr_manager().count(), 2);
+    assert!(peer_store.addr_manager().get(&addr1).is_some());
+
+    // the onion services are never attempted without a proxy
+    assert!(peer_store.fetch_addrs_to_feeler(2).is_empty());
+    peer_store.set_onion_reachable(true);
+
+    // the onion services are unique by their names
+    let addrs: Vec<_> = peer_store
+        .fetch_addrs_to_feeler(2)
+        .into_iter()
+        .map(|paddr| paddr.addr)
+        .collect();
+    assert_eq!(addrs.len(), 2);
+    assert!(addrs.contains(&addr1));
+    assert!(addrs.contains(&addr2));
+
+    // the connected onion services are attempted
+    peer_store
+        .mut_addr_manager()
+        .get_mut(&addr1)
+        .unwrap()
+        .last_connected_at_ms = ckb_systemtime::unix_time_as_millis() - 60_000;
+    let addrs: Vec<_> = peer_store
+        .fetch_addrs_to_attempt(2, Flags::all())
+        .into_iter()
+        .map(|paddr| paddr.addr)
+        .collect();
+    assert_eq!(addrs, vec![addr1.clone()]);
+    peer_store.set_onion_reachable(false);
+    assert!(peer_store
+        .fetch_addrs_to_attempt(2, Flags::all())
+        .is_empty());
+
+    peer_store.mut_addr_manager().remove(&addr1);
+    assert!(peer_store.addr_manager().get(&addr1).is_none());
+}
+
+fn network_state_with_proxy(proxy: Option<ProxyConfig>) -> (NetworkState, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let config = NetworkConfig {
+        path: dir.path().to_path_buf(),
+        public_addresses: vec!["/ip4/1.2.3.4/tcp/8115".parse().unwrap()],
+        proxy,
+        ..Default::default()
+    };
+    (NetworkState::from_config(config).unwrap(), dir)
+}
+
+#[test]
+fn test_observed_addrs_not_dialed_directly_with_only_proxy() {
+    let observed_addrs =
+        || vec!["/ip4/5.6.7.8/tcp/8115".parse().unwrap(), onion_addr('a')].into_iter();
+
+    let (network_state, _dir) = network_state_with_proxy(None);
+    assert_eq!(network_state.observed_addrs_to_dial().len(), 1);
+    network_state.add_observed_addrs(observed_addrs());
+    assert_eq!(
+        network_state.observed_addrs_to_dial(),
+        vec!["/ip4/5.6.7.8/tcp/8115".parse().unwrap()]
+    );
+
+    let (network_state, _dir) = network_state_with_proxy(Some(ProxyConfig {
+        proxy_address: "127.0.0.1:9050".to_string(),
+        only_proxy: true,
+    }));
+    // neither the public addresses nor the observed addresses are dialed directly
+    assert!(network_state.observed_addrs_to_dial().is_empty());
+    network_state.add_observed_addrs(observed_addrs());
+    assert!(network_state.observed_addrs_to_dial().is_empty());
+}
+
+// A SOCKS5 proxy which echoes the bytes, returns the requested target
+async fn echo_proxy(listener: TcpListener) -> (String, u16) {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut greeting = [0u8; 3];
+    stream.read_exact(&mut greeting).await.unwrap();
+    assert_eq!(greeting, [5, 1, 0]);
+    stream.write_all(&[5, 0]).await.unwrap();
+
+    let mut request = [0u8; 5];
+    stream.read_exact(&mut request).await.unwrap();
+    assert_eq!(request[..4], [5, 1, 0, 3]);
+    let mut host = vec![0u8; usize::from(request[4])];
+    stream.read_exact(&mut host).await.unwrap();
+    let port = stream.read_u16().await.unwrap();
+    stream
+        .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0])
+        .await
+        .unwrap();
+
+    let mut buf = [0u8; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    stream.write_all(&buf).await.unwrap();
+    (String::from_utf8(host).unwrap(), port)
+}
+
+#[test]
+fn test_proxy_bridge() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = Proxy::new(ProxyConfig {
+            proxy_address: listener.local_addr().unwrap().to_string(),
+            only_proxy: false,
+        });
+        let proxy_server = tokio::spawn(echo_proxy(listener));
+        tokio::spawn(proxy.take_service().unwrap().start());
+        assert!(proxy.take_service().is_none());
+
+        let addr = onion_addr('a');
+        assert!(proxy.should_proxy(&addr));
+        assert!(!proxy.should_proxy(&"/ip4/1.2.3.4/tcp/8115".parse().unwrap()));
+        // the target is authenticated by the peer id
+        let addr_without_peer_id = format!("/dns4/{}.onion/tcp/8115", "a".repeat(56));
+        assert!(proxy
+            .open_bridge(&addr_without_peer_id.parse().unwrap())
+            .is_err());
+        let bridge_addr = proxy.open_bridge(&addr).unwrap();
+        assert_eq!(proxy.dialed_addr(&bridge_addr), Some(addr.clone()));
+
+        let bridge: SocketAddr = crate::multiaddr_to_socketaddr(&bridge_addr).unwrap();
+        assert!(bridge.ip().is_loopback());
+        let mut stream = TcpStream::connect(bridge).await.unwrap();
+        stream.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        let (host, port) = proxy_server.await.unwrap();
+        assert_eq!(host, format!("{}.onion", "a".repeat(56)));
+        assert_eq!(port, 8115);
+
+        proxy.close_bridge(&bridge_addr);
+        assert_eq!(proxy.dialed_addr(&bridge_addr), None);
+    });
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_bridge_dialed_by_self() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let bridge_addr = listener.local_addr().unwrap();
+    let stream = std::net::TcpStream::connect(bridge_addr).unwrap();
+    let (_inbound, remote) = listener.accept().unwrap();
+    assert_eq!(stream.local_addr().unwrap(), remote);
+    assert!(crate::proxy::is_dialed_by_self(remote, bridge_addr));
+
+    // no socket of this process is connected from the address
+    drop(stream);
+    let other: SocketAddr = "127.0.0.1:1".parse().unwrap();
+    assert!(!crate::proxy::is_dialed_by_self(other, bridge_addr));
+}
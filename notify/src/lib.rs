@@ -5,19 +5,24 @@ use ckb_logger::{debug, error, info, trace};
 use ckb_stop_handler::{new_tokio_exit_rx, CancellationToken};
 use ckb_types::packed::Byte32;
 use ckb_types::{
-    core::{tx_pool::Reject, BlockView},
+    core::{service::ChainReorg, tx_pool::Reject, BlockView},
     packed::Alert,
 };
 use std::{collections::HashMap, time::Duration};
 use tokio::process::Command;
 use tokio::sync::watch;
 use tokio::sync::{
-    mpsc::{self, Receiver, Sender},
+    mpsc::{self, error::TrySendError, Receiver, Sender, UnboundedSender},
     oneshot,
 };
 use tokio::time::timeout;
 
-pub use ckb_types::core::service::PoolTransactionEntry;
+#[cfg(test)]
+mod tests;
+
+pub use ckb_types::core::service::{
+    PoolTransactionChange, PoolTransactionDiff, PoolTransactionEntry,
+};
 
 /// Asynchronous request sent to the service.
 pub struct Request<A, R> {
@@ -91,12 +96,17 @@ pub struct NotifyController {
     new_block_register: NotifyRegister<BlockView>,
     new_block_watcher: NotifyWatcher<Byte32>,
     new_block_notifier: Sender<BlockView>,
+    chain_reorg_register: NotifyRegister<ChainReorg>,
+    chain_reorg_notifier: Sender<ChainReorg>,
     new_transaction_register: NotifyRegister<PoolTransactionEntry>,
     new_transaction_notifier: Sender<PoolTransactionEntry>,
     proposed_transaction_register: NotifyRegister<PoolTransactionEntry>,
     proposed_transaction_notifier: Sender<PoolTransactionEntry>,
     reject_transaction_register: NotifyRegister<(PoolTransactionEntry, Reject)>,
     reject_transaction_notifier: Sender<(PoolTransactionEntry, Reject)>,
+    pool_diff_register: NotifyRegister<PoolTransactionDiff>,
+    // unbounded to send the diffs in order without blocking the tx-pool
+    pool_diff_notifier: UnboundedSender<PoolTransactionDiff>,
     network_alert_register: NotifyRegister<Alert>,
     network_alert_notifier: Sender<Alert>,
     handle: Handle,
@@ -107,9 +117,11 @@ pub struct NotifyService {
     config: NotifyConfig,
     new_block_subscribers: HashMap<String, Sender<BlockView>>,
     new_block_watchers: HashMap<String, watch::Sender<Byte32>>,
+    chain_reorg_subscribers: HashMap<String, Sender<ChainReorg>>,
     new_transaction_subscribers: HashMap<String, Sender<PoolTransactionEntry>>,
     proposed_transaction_subscribers: HashMap<String, Sender<PoolTransactionEntry>>,
     reject_transaction_subscribers: HashMap<String, Sender<(PoolTransactionEntry, Reject)>>,
+    pool_diff_subscribers: HashMap<String, Sender<PoolTransactionDiff>>,
     network_alert_subscribers: HashMap<String, Sender<Alert>>,
     timeout: NotifyTimeout,
     handle: Handle,
@@ -124,9 +136,11 @@ impl NotifyService {
             config,
             new_block_subscribers: HashMap::default(),
             new_block_watchers: HashMap::default(),
+            chain_reorg_subscribers: HashMap::default(),
             new_transaction_subscribers: HashMap::default(),
             proposed_transaction_subscribers: HashMap::default(),
             reject_transaction_subscribers: HashMap::default(),
+            pool_diff_subscribers: HashMap::default(),
             network_alert_subscribers: HashMap::default(),
             timeout,
             handle,
@@ -144,6 +158,10 @@ impl NotifyService {
             mpsc::channel(REGISTER_CHANNEL_SIZE);
         let (new_block_sender, mut new_block_receiver) = mpsc::channel(NOTIFY_CHANNEL_SIZE);
 
+        let (chain_reorg_register, mut chain_reorg_register_receiver) =
+            mpsc::channel(REGISTER_CHANNEL_SIZE);
+        let (chain_reorg_sender, mut chain_reorg_receiver) = mpsc::channel(NOTIFY_CHANNEL_SIZE);
+
         let (new_transaction_register, mut new_transaction_register_receiver) =
             mpsc::channel(REGISTER_CHANNEL_SIZE);
         let (new_transaction_sender, mut new_transaction_receiver) =
@@ -159,6 +177,10 @@ impl NotifyService {
         let (reject_transaction_sender, mut reject_transaction_receiver) =
             mpsc::channel(NOTIFY_CHANNEL_SIZE);
 
+        let (pool_diff_register, mut pool_diff_register_receiver) =
+            mpsc::channel(REGISTER_CHANNEL_SIZE);
+        let (pool_diff_sender, mut pool_diff_receiver) = mpsc::unbounded_channel();
+
         let (network_alert_register, mut network_alert_register_receiver) =
             mpsc::channel(REGISTER_CHANNEL_SIZE);
         let (network_alert_sender, mut network_alert_receiver) = mpsc::channel(NOTIFY_CHANNEL_SIZE);
@@ -169,12 +191,16 @@ impl NotifyService {
                     Some(msg) = new_block_register_receiver.recv() => { self.handle_register_new_block(msg) },
                     Some(msg) = new_block_watcher_receiver.recv() => { self.handle_watch_new_block(msg) },
                     Some(msg) = new_block_receiver.recv() => { self.handle_notify_new_block(msg) },
+                    Some(msg) = chain_reorg_register_receiver.recv() => { self.handle_register_chain_reorg(msg) },
+                    Some(msg) = chain_reorg_receiver.recv() => { self.handle_notify_chain_reorg(msg) },
                     Some(msg) = new_transaction_register_receiver.recv() => { self.handle_register_new_transaction(msg) },
                     Some(msg) = new_transaction_receiver.recv() => { self.handle_notify_new_transaction(msg) },
                     Some(msg) = proposed_transaction_register_receiver.recv() => { self.handle_register_proposed_transaction(msg) },
                     Some(msg) = proposed_transaction_receiver.recv() => { self.handle_notify_proposed_transaction(msg) },
                     Some(msg) = reject_transaction_register_receiver.recv() => { self.handle_register_reject_transaction(msg) },
                     Some(msg) = reject_transaction_receiver.recv() => { self.handle_notify_reject_transaction(msg) },
+                    Some(msg) = pool_diff_register_receiver.recv() => { self.handle_register_pool_diff(msg) },
+                    Some(msg) = pool_diff_receiver.recv() => { self.handle_notify_pool_diff(msg) },
                     Some(msg) = network_alert_register_receiver.recv() => { self.handle_register_network_alert(msg) },
                     Some(msg) = network_alert_receiver.recv() => { self.handle_notify_network_alert(msg) },
                     _ = signal_receiver.cancelled() => {
@@ -190,12 +216,16 @@ impl NotifyService {
             new_block_register,
             new_block_watcher,
             new_block_notifier: new_block_sender,
+            chain_reorg_register,
+            chain_reorg_notifier: chain_reorg_sender,
             new_transaction_register,
             new_transaction_notifier: new_transaction_sender,
             proposed_transaction_register,
             proposed_transaction_notifier: proposed_transaction_sender,
             reject_transaction_register,
             reject_transaction_notifier: reject_transaction_sender,
+            pool_diff_register,
+            pool_diff_notifier: pool_diff_sender,
             network_alert_register,
             network_alert_notifier: network_alert_sender,
             handle,
@@ -264,6 +294,31 @@ impl NotifyService {
         }
     }
 
+    fn handle_register_chain_reorg(&mut self, msg: Request<String, Receiver<ChainReorg>>) {
+        let Request {
+            responder,
+            arguments: name,
+        } = msg;
+        debug!("Register chain_reorg {:?}", name);
+        let (sender, receiver) = mpsc::channel(NOTIFY_CHANNEL_SIZE);
+        self.chain_reorg_subscribers.insert(name, sender);
+        let _ = responder.send(receiver);
+    }
+
+    fn handle_notify_chain_reorg(&self, reorg: ChainReorg) {
+        trace!("Chain reorg event {:?}", reorg);
+        // notify all subscribers
+        for subscriber in self.chain_reorg_subscribers.values() {
+            let reorg = reorg.clone();
+            let subscriber = subscriber.clone();
+            self.handle.spawn(async move {
+                if let Err(e) = subscriber.send(reorg).await {
+                    error!("Failed to notify chain reorg, error: {}", e);
+                }
+            });
+        }
+    }
+
     fn handle_register_new_transaction(
         &mut self,
         msg: Request<String, Receiver<PoolTransactionEntry>>,
@@ -354,6 +409,39 @@ impl NotifyService {
         }
     }
 
+    fn handle_register_pool_diff(&mut self, msg: Request<String, Receiver<PoolTransactionDiff>>) {
+        let Request {
+            responder,
+            arguments: name,
+        } = msg;
+        debug!("Register pool_diff {:?}", name);
+        let (sender, receiver) = mpsc::channel(NOTIFY_CHANNEL_SIZE);
+        self.pool_diff_subscribers.insert(name, sender);
+        let _ = responder.send(receiver);
+    }
+
+    fn handle_notify_pool_diff(&mut self, diff: PoolTransactionDiff) {
+        trace!("Tx-pool diff event {:?}", diff);
+        // notify all subscribers in order, the subscriber which falls behind misses the diff and
+        // detects it by the sequence
+        self.pool_diff_subscribers.retain(|name, subscriber| {
+            match subscriber.try_send(diff.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    error!(
+                        "Failed to notify tx-pool diff {} to {}, the channel is full",
+                        diff.sequence, name
+                    );
+                    true
+                }
+                Err(TrySendError::Closed(_)) => {
+                    debug!("Unregister pool_diff {:?}", name);
+                    false
+                }
+            }
+        });
+    }
+
     fn handle_register_network_alert(&mut self, msg: Request<String, Receiver<Alert>>) {
         let Request {
             responder,
@@ -433,6 +521,23 @@ impl NotifyController {
         });
     }
 
+    /// Subscribe chain reorganizations.
+    pub async fn subscribe_chain_reorg<S: ToString>(&self, name: S) -> Receiver<ChainReorg> {
+        Request::call(&self.chain_reorg_register, name.to_string())
+            .await
+            .expect("Subscribe chain reorg should be OK")
+    }
+
+    /// Notify a chain reorganization, i.e. a new tip which detaches blocks from the main chain.
+    pub fn notify_chain_reorg(&self, reorg: ChainReorg) {
+        let chain_reorg_notifier = self.chain_reorg_notifier.clone();
+        self.handle.spawn(async move {
+            if let Err(e) = chain_reorg_notifier.send(reorg).await {
+                error!("notify_chain_reorg channel is closed: {}", e);
+            }
+        });
+    }
+
     /// TODO(doc): @quake
     pub async fn subscribe_new_transaction<S: ToString>(
         &self,
@@ -493,6 +598,22 @@ impl NotifyController {
         });
     }
 
+    /// Subscribe tx-pool changes.
+    pub async fn subscribe_pool_diff<S: ToString>(&self, name: S) -> Receiver<PoolTransactionDiff> {
+        Request::call(&self.pool_diff_register, name.to_string())
+            .await
+            .expect("Subscribe pool diff should be OK")
+    }
+
+    /// Notify a tx-pool change.
+    ///
+    /// The diffs are delivered to the subscribers in the order of the calls.
+    pub fn notify_pool_diff(&self, diff: PoolTransactionDiff) {
+        if let Err(e) = self.pool_diff_notifier.send(diff) {
+            error!("notify_pool_diff channel is closed: {}", e);
+        }
+    }
+
     /// TODO(doc): @quake
     pub async fn subscribe_network_alert<S: ToString>(&self, name: S) -> Receiver<Alert> {
         Request::call(&self.network_alert_register, name.to_string())
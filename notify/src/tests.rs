@@ -0,0 +1,38 @@
+use crate::{NotifyService, PoolTransactionChange, PoolTransactionDiff, PoolTransactionEntry};
+use ckb_app_config::NotifyConfig;
+use ckb_async_runtime::new_background_runtime;
+use ckb_types::core::{Capacity, TransactionBuilder};
+
+fn pool_diff(sequence: u64) -> PoolTransactionDiff {
+    PoolTransactionDiff {
+        sequence,
+        entry: PoolTransactionEntry {
+            transaction: TransactionBuilder::default().build(),
+            cycles: 0,
+            size: 0,
+            fee: Capacity::zero(),
+            timestamp: 0,
+        },
+        change: PoolTransactionChange::Pending,
+    }
+}
+
+#[test]
+fn test_pool_diffs_are_delivered_in_order() {
+    let handle = new_background_runtime();
+    let notify_controller = NotifyService::new(NotifyConfig::default(), handle.clone()).start();
+    let mut receiver = handle.block_on(notify_controller.subscribe_pool_diff("test"));
+
+    // fewer than the subscriber channel size, so none of them is dropped
+    let count = 100;
+    for sequence in 0..count {
+        notify_controller.notify_pool_diff(pool_diff(sequence));
+    }
+
+    for sequence in 0..count {
+        let diff = handle
+            .block_on(receiver.recv())
+            .expect("pool diff should be delivered");
+        assert_eq!(diff.sequence, sequence);
+    }
+}
@@ -0,0 +1,145 @@
+//! Authentication and per-module access control of the RPC server.
+use crate::rate_limit::{self, ClientKey};
+use crate::{IoHandler, RPCError};
+use base64::Engine;
+use ckb_app_config::{RpcConfig, RpcCredential, RpcModule};
+use ckb_logger::warn;
+use jsonrpc_core::{RemoteProcedure, Value};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// The RPC methods handlers, one for each credential.
+///
+/// Each handler serves the methods of the modules the credential is granted to access, and
+/// responds the error `RPCModuleIsUnauthorized` to the methods of the other modules. The calls
+/// of each client are limited further if `rpc.rate_limit` is configured, they must be processed in
+/// the `ClientKey::scope` of the client returned by `AccessControl::authorize`.
+#[doc(hidden)]
+pub struct AccessControl {
+    // Whether `rpc.auth` is configured, the `Authorization` header is ignored otherwise.
+    enabled: bool,
+    // The handler for the requests without credentials.
+    public: Arc<IoHandler>,
+    // The value of the `Authorization` header => the handler.
+    credentials: HashMap<String, Arc<IoHandler>>,
+}
+
+impl AccessControl {
+    pub(crate) fn new(
+        config: &RpcConfig,
+        io_handler: IoHandler,
+        method_modules: &HashMap<String, RpcModule>,
+    ) -> Self {
+        let io_handler = match &config.rate_limit {
+            Some(rate_limit) => rate_limit::limit(rate_limit.clone(), io_handler),
+            None => io_handler,
+        };
+        let config = match &config.auth {
+            Some(config) => config,
+            None => {
+                return AccessControl {
+                    enabled: false,
+                    public: Arc::new(io_handler),
+                    credentials: HashMap::new(),
+                }
+            }
+        };
+
+        let public = Arc::new(restrict(
+            &io_handler,
+            method_modules,
+            &config.public_modules,
+        ));
+        let credentials = config
+            .credentials
+            .iter()
+            .filter_map(|credential| match authorization(credential) {
+                Some(authorization) => {
+                    let handler = restrict(&io_handler, method_modules, &credential.modules);
+                    Some((authorization, Arc::new(handler)))
+                }
+                None => {
+                    warn!(
+                        "Ignore the rpc credential which sets neither token nor username and password"
+                    );
+                    None
+                }
+            })
+            .collect();
+        AccessControl {
+            enabled: true,
+            public,
+            credentials,
+        }
+    }
+
+    /// Returns the names of the methods, every handler has all the methods.
+    pub(crate) fn methods(&self) -> HashSet<String> {
+        self.public
+            .iter()
+            .map(|(name, _)| name.to_owned())
+            .collect()
+    }
+
+    /// Returns the client and the handler for the client with the value of the `Authorization`
+    /// header.
+    ///
+    /// Returns `None` if the credential is unknown.
+    pub(crate) fn authorize(
+        &self,
+        authorization: Option<&str>,
+        remote: IpAddr,
+    ) -> Option<(ClientKey, Arc<IoHandler>)> {
+        match authorization.map(str::trim) {
+            Some(authorization) if self.enabled => {
+                let rpc = self.credentials.get(authorization)?;
+                Some((
+                    ClientKey::Credential(authorization.to_owned()),
+                    Arc::clone(rpc),
+                ))
+            }
+            _ => Some((ClientKey::Ip(remote), Arc::clone(&self.public))),
+        }
+    }
+}
+
+/// Returns the expected value of the `Authorization` header of the credential.
+fn authorization(credential: &RpcCredential) -> Option<String> {
+    match (
+        &credential.token,
+        &credential.username,
+        &credential.password,
+    ) {
+        (Some(token), _, _) => Some(format!("Bearer {token}")),
+        (None, Some(username), Some(password)) => {
+            let encoded = base64::prelude::BASE64_STANDARD.encode(format!("{username}:{password}"));
+            Some(format!("Basic {encoded}"))
+        }
+        _ => None,
+    }
+}
+
+/// Clones the handler, replacing the methods of the modules not in `modules` by errors.
+fn restrict(
+    io_handler: &IoHandler,
+    method_modules: &HashMap<String, RpcModule>,
+    modules: &[RpcModule],
+) -> IoHandler {
+    let mut restricted = IoHandler::with_compatibility(jsonrpc_core::Compatibility::V2);
+    restricted.extend_with(io_handler.iter().map(|(name, method)| {
+        let method = match method_modules.get(name) {
+            Some(module) if !modules.contains(module) => {
+                let error: jsonrpc_core::Result<Value> =
+                    Err(RPCError::rpc_module_is_unauthorized(&format!("{module:?}")));
+                RemoteProcedure::Method(Arc::new(move |_param, _meta| {
+                    let error = error.clone();
+                    async move { error }
+                }))
+            }
+            _ => method.clone(),
+        };
+        (name.to_owned(), method)
+    }));
+    restricted
+}
@@ -57,6 +57,14 @@ pub enum RPCError {
     ///
     /// Users have to edit the config file to fix the error.
     ConfigError = -7,
+    /// (-8): The RPC method is in a module which the credential is not granted to access.
+    ///
+    /// The accessible modules of each credential are configured in `rpc.auth`.
+    RPCModuleIsUnauthorized = -8,
+    /// (-9): The client has exceeded the rate limits.
+    ///
+    /// The rate limits are configured in `rpc.rate_limit`. The client should retry later.
+    RateLimitExceeded = -9,
     /// (-101): The CKB local node failed to broadcast a message to its peers.
     P2PFailedToBroadcast = -101,
     /// (-200): Internal database error.
@@ -249,6 +257,31 @@ impl RPCError {
         )
     }
 
+    /// RPC error which indicates that the credential is not granted to access the method.
+    ///
+    /// RPC methods belong to modules and each credential in `rpc.auth` is only granted to access
+    /// the listed modules.
+    pub fn rpc_module_is_unauthorized(module: &str) -> Error {
+        Self::custom(
+            RPCError::RPCModuleIsUnauthorized,
+            format!(
+                "This RPC method is in the module `{module}`, which is not accessible with the credential. \
+                 Please send the credential granted to access it via the HTTP header `Authorization`."
+            ),
+        )
+    }
+
+    /// RPC error which indicates that the client has exceeded the rate limits.
+    pub fn rate_limit_exceeded(reason: &str) -> Error {
+        Self::custom(
+            RPCError::RateLimitExceeded,
+            format!(
+                "The rate limit is exceeded: {reason}. \
+                 Please retry later, the limits are configured by `rpc.rate_limit` in ckb.toml."
+            ),
+        )
+    }
+
     /// RPC error which indicates that the method is deprecated.
     ///
     /// Deprecated methods are disabled by default unless they are enabled via the config options
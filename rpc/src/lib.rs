@@ -1,6 +1,9 @@
 //! See [module](module/index.html) for the RPC methods documentation.
 
+pub(crate) mod auth;
 pub(crate) mod error;
+pub(crate) mod metrics;
+pub(crate) mod rate_limit;
 pub(crate) mod server;
 pub(crate) mod service_builder;
 pub(crate) mod util;
@@ -13,6 +16,7 @@ mod tests;
 use jsonrpc_core::MetaIoHandler;
 use jsonrpc_utils::pub_sub::Session;
 
+pub use crate::auth::AccessControl;
 pub use crate::error::RPCError;
 pub use crate::server::RpcServer;
 pub use crate::service_builder::ServiceBuilder;
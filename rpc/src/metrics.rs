@@ -0,0 +1,262 @@
+//! Metrics and slow-call logging of the RPC calls.
+use crate::IoHandler;
+use ckb_logger::warn;
+use ckb_util::Mutex;
+use jsonrpc_core::RemoteProcedure;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Wraps the methods to record the duration and the result code of each call.
+pub(crate) fn instrument(io_handler: IoHandler) -> IoHandler {
+    let mut instrumented = IoHandler::with_compatibility(jsonrpc_core::Compatibility::V2);
+    instrumented.extend_with(io_handler.iter().map(|(name, method)| {
+        let method = match method {
+            RemoteProcedure::Method(method) => {
+                let method = Arc::clone(method);
+                let name = name.to_owned();
+                RemoteProcedure::Method(Arc::new(move |params, meta| {
+                    let name = name.clone();
+                    let start = Instant::now();
+                    let call = method.call(params, meta);
+                    async move {
+                        let result = call.await;
+                        if let Some(metrics) = ckb_metrics::handle() {
+                            metrics
+                                .ckb_rpc_call_duration
+                                .with_label_values(&[name.as_str()])
+                                .observe(start.elapsed().as_secs_f64());
+                            let code = match &result {
+                                Ok(_) => 0,
+                                Err(err) => err.code.code(),
+                            };
+                            metrics
+                                .ckb_rpc_calls
+                                .with_label_values(&[name.as_str(), code.to_string().as_str()])
+                                .inc();
+                        }
+                        result
+                    }
+                }))
+            }
+            other => other.clone(),
+        };
+        (name.to_owned(), method)
+    }));
+    instrumented
+}
+
+/// The method label of the calls to the methods which are not registered.
+const UNKNOWN_METHOD: &str = "unknown";
+/// The max number of the calls waiting for the responses in a connection.
+const MAX_PENDING_CALLS: usize = 1024;
+/// The calls waiting longer are dropped when the pending calls are full, e.g., the calls whose
+/// responses have different ids, such as the invalid requests responded with the `null` id.
+const PENDING_CALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The config of the request metrics, shared by the transports.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestMetricsConfig {
+    /// The threshold of logging the slow calls.
+    pub(crate) slow_call_threshold: Option<Duration>,
+    /// The registered methods, the calls to the other methods are labelled as `unknown`.
+    pub(crate) methods: Arc<HashSet<String>>,
+}
+
+/// Records the metrics of the encoded requests and responses of a connection, and logs the slow
+/// calls.
+///
+/// The responses are matched to the calls by their ids, since the stream transports may respond
+/// out of order.
+pub(crate) struct RequestMetrics {
+    transport: &'static str,
+    config: RequestMetricsConfig,
+    pending: Mutex<PendingCalls>,
+}
+
+struct PendingCall {
+    method: String,
+    params_size: usize,
+    received_at: Instant,
+}
+
+#[derive(Default)]
+struct PendingCalls {
+    // the raw id => the calls waiting for the responses
+    calls: HashMap<String, VecDeque<PendingCall>>,
+    len: usize,
+}
+
+impl PendingCalls {
+    /// Returns false if the call is dropped since there are too many pending calls.
+    fn push(&mut self, id: String, call: PendingCall) -> bool {
+        if self.len >= MAX_PENDING_CALLS {
+            self.expire(call.received_at);
+            if self.len >= MAX_PENDING_CALLS {
+                return false;
+            }
+        }
+        self.calls.entry(id).or_default().push_back(call);
+        self.len += 1;
+        true
+    }
+
+    fn pop(&mut self, id: &str) -> Option<PendingCall> {
+        let calls = self.calls.get_mut(id)?;
+        let call = calls.pop_front();
+        if calls.is_empty() {
+            self.calls.remove(id);
+        }
+        if call.is_some() {
+            self.len -= 1;
+        }
+        call
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let mut len = 0;
+        self.calls.retain(|_, calls| {
+            calls.retain(|call| {
+                now.saturating_duration_since(call.received_at) < PENDING_CALL_TIMEOUT
+            });
+            len += calls.len();
+            !calls.is_empty()
+        });
+        self.len = len;
+    }
+}
+
+#[derive(Deserialize)]
+struct RawCall<'a> {
+    #[serde(borrow, default)]
+    id: Option<&'a RawValue>,
+    #[serde(default)]
+    method: String,
+    #[serde(borrow, default)]
+    params: Option<&'a RawValue>,
+}
+
+#[derive(Deserialize)]
+struct RawOutput<'a> {
+    #[serde(borrow)]
+    id: &'a RawValue,
+}
+
+impl RequestMetrics {
+    pub(crate) fn new(transport: &'static str, config: RequestMetricsConfig) -> Self {
+        RequestMetrics {
+            transport,
+            config,
+            pending: Default::default(),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.config.slow_call_threshold.is_some() || ckb_metrics::handle().is_some()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn pending_calls(&self) -> usize {
+        self.pending.lock().len
+    }
+
+    /// Records the number of calls in the request, and the calls expecting responses.
+    pub(crate) fn observe_request(&self, request: &str) {
+        if !self.enabled() {
+            return;
+        }
+        let calls: Vec<RawCall> = if is_batch(request) {
+            match serde_json::from_str(request) {
+                Ok(calls) => calls,
+                Err(_) => return,
+            }
+        } else {
+            match serde_json::from_str(request) {
+                Ok(call) => vec![call],
+                Err(_) => return,
+            }
+        };
+        if let Some(metrics) = ckb_metrics::handle() {
+            metrics
+                .ckb_rpc_batch_size
+                .with_label_values(&[self.transport])
+                .observe(calls.len() as f64);
+        }
+
+        let received_at = Instant::now();
+        let mut pending = self.pending.lock();
+        for call in calls {
+            // the notifications have no responses
+            let id = match call.id {
+                Some(id) => id.get().to_owned(),
+                None => continue,
+            };
+            // the method names come from the clients, only the registered ones are labelled
+            let method = if self.config.methods.contains(&call.method) {
+                call.method
+            } else {
+                UNKNOWN_METHOD.to_owned()
+            };
+            let call = PendingCall {
+                method,
+                params_size: call.params.map(|params| params.get().len()).unwrap_or(0),
+                received_at,
+            };
+            if !pending.push(id, call) {
+                break;
+            }
+        }
+    }
+
+    /// Records the size of the response of each call, and logs the slow calls.
+    pub(crate) fn observe_response(&self, response: &str) {
+        if !self.enabled() {
+            return;
+        }
+        let outputs: Vec<&RawValue> = if is_batch(response) {
+            match serde_json::from_str(response) {
+                Ok(outputs) => outputs,
+                Err(_) => return,
+            }
+        } else {
+            match serde_json::from_str(response) {
+                Ok(output) => vec![output],
+                Err(_) => return,
+            }
+        };
+
+        for output in outputs {
+            // the subscription notifications have no ids
+            let id = match serde_json::from_str::<RawOutput>(output.get()) {
+                Ok(RawOutput { id }) => id.get(),
+                Err(_) => continue,
+            };
+            let call = self.pending.lock().pop(id);
+            let call = match call {
+                Some(call) => call,
+                None => continue,
+            };
+            if let Some(metrics) = ckb_metrics::handle() {
+                metrics
+                    .ckb_rpc_response_bytes
+                    .with_label_values(&[call.method.as_str()])
+                    .observe(output.get().len() as f64);
+            }
+            if let Some(threshold) = self.config.slow_call_threshold {
+                let duration = call.received_at.elapsed();
+                if duration >= threshold {
+                    warn!(
+                        "slow rpc call {}, params size: {} bytes, duration: {:?}",
+                        call.method, call.params_size, duration
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn is_batch(message: &str) -> bool {
+    message.trim_start().starts_with('[')
+}
@@ -0,0 +1,320 @@
+use crate::error::RPCError;
+use async_trait::async_trait;
+use ckb_jsonrpc_types::{BackupInfo, CompactionParams, CompactionState, CompactionStatus};
+use ckb_logger::{error, info};
+use ckb_shared::shared::Shared;
+use ckb_stop_handler::{new_crossbeam_exit_rx, register_thread};
+use ckb_store::{backup_chain_db, CompactionProgress, CompactionTask};
+use ckb_util::RwLock;
+use jsonrpc_core::Result;
+use jsonrpc_utils::rpc;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// RPC Module Admin for node maintenance.
+///
+/// The methods here operate on the files in the server running the CKB node, it is recommended to
+/// restrict the access to this module via `rpc.auth`.
+#[rpc(openrpc)]
+#[async_trait]
+pub trait AdminRpc {
+    /// Creates a consistent backup of the chain database while the node keeps running.
+    ///
+    /// The backup directory contains a RocksDB checkpoint of the database in `db`, a copy of the
+    /// freezer in `ancient` when the freezer is enabled, and the manifest `manifest.json`, which
+    /// is written last. The immutable files are hard linked when the backup directory is on the
+    /// same filesystem as the node data, so a backup is cheap to create.
+    ///
+    /// A node is restored from the backup by pointing `db.path` and `ancient` in `ckb.toml` to
+    /// the two sub-directories.
+    ///
+    /// ## Params
+    ///
+    /// * `path` - The absolute path of the backup directory in the server, which must not exist.
+    ///
+    /// ## Returns
+    ///
+    /// The summary of the backup, which is the same as the manifest.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "backup_database",
+    ///   "params": [
+    ///     "/var/lib/ckb/backups/20240101"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "created_at": "0x18cc2e5b800",
+    ///     "frozen_number": "0x0",
+    ///     "path": "/var/lib/ckb/backups/20240101",
+    ///     "tip_hash": "0xa5f5c85987a15de25661e5a214f2c1449cd803f071acc7999820f25246471f40",
+    ///     "tip_number": "0x400"
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "backup_database")]
+    async fn backup_database(&self, path: String) -> Result<BackupInfo>;
+
+    /// Compacts the column families of the chain database to reclaim the disk space of the
+    /// deleted keys, e.g., after large reorganizations or migrations.
+    ///
+    /// The compaction runs in steps, one step per column family, or up to 256 steps per column
+    /// family when it is throttled. Only one compaction can run at a time.
+    ///
+    /// ## Params
+    ///
+    /// * `params` - The column families, the key range, the throttling and whether to run in the
+    ///   background.
+    ///
+    /// ## Returns
+    ///
+    /// The final status of the compaction, or the initial status if it runs in the background.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "compact_database",
+    ///   "params": [
+    ///     {
+    ///       "columns": ["block_body", "cell_data"],
+    ///       "throttle": "0x64",
+    ///       "background": true
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "columns": ["block_body", "cell_data"],
+    ///     "completed_steps": "0x0",
+    ///     "current_column": null,
+    ///     "error": null,
+    ///     "finished_at": null,
+    ///     "started_at": "0x18cc2e5b800",
+    ///     "state": "running",
+    ///     "total_steps": "0x200"
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "compact_database")]
+    async fn compact_database(&self, params: CompactionParams) -> Result<CompactionStatus>;
+
+    /// Returns the status of the running or the last manual compaction.
+    ///
+    /// ## Returns
+    ///
+    /// The status of the compaction, or null if no compaction has been started since the node
+    /// started.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "get_compaction_status",
+    ///   "params": []
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "columns": ["block_body", "cell_data"],
+    ///     "completed_steps": "0x8c",
+    ///     "current_column": "block_body",
+    ///     "error": null,
+    ///     "finished_at": null,
+    ///     "started_at": "0x18cc2e5b800",
+    ///     "state": "running",
+    ///     "total_steps": "0x200"
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "get_compaction_status")]
+    fn get_compaction_status(&self) -> Result<Option<CompactionStatus>>;
+}
+
+#[derive(Clone)]
+pub(crate) struct AdminRpcImpl {
+    pub shared: Shared,
+    pub compaction: Arc<RwLock<Option<CompactionStatus>>>,
+}
+
+impl AdminRpcImpl {
+    pub fn new(shared: Shared) -> Self {
+        AdminRpcImpl {
+            shared,
+            compaction: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl AdminRpc for AdminRpcImpl {
+    async fn backup_database(&self, path: String) -> Result<BackupInfo> {
+        let target = PathBuf::from(&path);
+        if !target.is_absolute() {
+            return Err(RPCError::invalid_params(format!(
+                "the backup path {path} is not absolute"
+            )));
+        }
+        // Copying the files takes a while, keep it off the RPC workers
+        let store = self.shared.store().clone();
+        let manifest = self
+            .shared
+            .async_handle()
+            .spawn_blocking(move || backup_chain_db(&store, &target))
+            .await
+            .map_err(|err| RPCError::custom_with_error(RPCError::CKBInternalError, err))?
+            .map_err(RPCError::from_ckb_error)?;
+        Ok(BackupInfo {
+            path,
+            tip_number: manifest.tip_number.into(),
+            tip_hash: manifest.tip_hash,
+            frozen_number: manifest.frozen_number.into(),
+            created_at: manifest.created_at.into(),
+        })
+    }
+    async fn compact_database(&self, params: CompactionParams) -> Result<CompactionStatus> {
+        let columns = params.columns.unwrap_or_default();
+        let mut task = CompactionTask::new(&columns)
+            .map_err(|err| RPCError::invalid_params(err.to_string()))?
+            .key_range(
+                params.start_key.map(|key| key.as_bytes().to_vec()),
+                params.end_key.map(|key| key.as_bytes().to_vec()),
+            );
+        if let Some(throttle) = params.throttle {
+            task = task.throttle(Duration::from_millis(throttle.into()));
+        }
+
+        {
+            let mut status = self.compaction.write();
+            if let Some(CompactionState::Running) = status.as_ref().map(|status| &status.state) {
+                return Err(RPCError::custom(
+                    RPCError::Invalid,
+                    "another compaction is running",
+                ));
+            }
+            *status = Some(compaction_status(&task.progress()));
+        }
+
+        let shared = self.shared.clone();
+        let compaction = Arc::clone(&self.compaction);
+        let signal_receiver = new_crossbeam_exit_rx();
+        if !params.background.unwrap_or(false) {
+            // Wait for the compaction without blocking the RPC workers
+            self.shared
+                .async_handle()
+                .spawn_blocking(move || {
+                    run_compaction(&shared, &task, &compaction, || {
+                        signal_receiver.try_recv().is_err()
+                    });
+                })
+                .await
+                .map_err(|err| RPCError::custom_with_error(RPCError::CKBInternalError, err))?;
+            return Ok(self.compaction.read().clone().expect("compaction started"));
+        }
+
+        let compaction_jh = thread::Builder::new()
+            .name("compaction".to_string())
+            .spawn(move || {
+                run_compaction(&shared, &task, &compaction, || {
+                    signal_receiver.try_recv().is_err()
+                });
+            })
+            .map_err(|err| RPCError::custom_with_error(RPCError::CKBInternalError, err))?;
+        register_thread("compaction", compaction_jh);
+        Ok(self.compaction.read().clone().expect("compaction started"))
+    }
+
+    fn get_compaction_status(&self) -> Result<Option<CompactionStatus>> {
+        Ok(self.compaction.read().clone())
+    }
+}
+
+// Runs the compaction and keeps the status up to date, the compaction is cancelled when
+// `running` returns false.
+fn run_compaction<F: Fn() -> bool>(
+    shared: &Shared,
+    task: &CompactionTask,
+    status: &RwLock<Option<CompactionStatus>>,
+    running: F,
+) {
+    info!("Compaction started, columns {:?}", task.progress().columns);
+    let result = task.run(shared.store(), |progress| {
+        *status.write() = Some(compaction_status(progress));
+        running()
+    });
+    match result {
+        Ok(progress) => {
+            info!(
+                "Compaction finished, {}/{} steps",
+                progress.completed_steps, progress.total_steps
+            );
+        }
+        Err(err) => {
+            error!("Compaction error {}", err);
+            let mut status = status.write();
+            if let Some(status) = status.as_mut() {
+                status.state = CompactionState::Failed;
+                status.current_column = None;
+                status.finished_at = Some(ckb_systemtime::unix_time_as_millis().into());
+                status.error = Some(err.to_string());
+            }
+        }
+    }
+}
+
+fn compaction_status(progress: &CompactionProgress) -> CompactionStatus {
+    let state = if progress.finished_at.is_none() {
+        CompactionState::Running
+    } else if progress.cancelled {
+        CompactionState::Cancelled
+    } else {
+        CompactionState::Done
+    };
+    CompactionStatus {
+        state,
+        columns: progress.columns.iter().map(ToString::to_string).collect(),
+        total_steps: (progress.total_steps as u64).into(),
+        completed_steps: (progress.completed_steps as u64).into(),
+        current_column: progress.current_column.map(ToString::to_string),
+        started_at: progress.started_at.into(),
+        finished_at: progress.finished_at.map(Into::into),
+        error: None,
+    }
+}
@@ -4,8 +4,9 @@ use async_trait::async_trait;
 use ckb_jsonrpc_types::{
     BlockEconomicState, BlockFilter, BlockNumber, BlockResponse, BlockView, CellWithStatus,
     Consensus, EpochNumber, EpochView, EstimateCycles, FeeRateStatistics, HeaderView, OutPoint,
-    ResponseFormat, ResponseFormatInnerType, Timestamp, Transaction, TransactionAndWitnessProof,
-    TransactionProof, TransactionWithStatusResponse, Uint32, Uint64,
+    ResponseFormat, ResponseFormatInnerType, ScriptGroupSimulation, Timestamp, Transaction,
+    TransactionAndWitnessProof, TransactionProof, TransactionSimulation,
+    TransactionWithStatusResponse, Uint32, Uint64,
 };
 use ckb_logger::error;
 use ckb_reward_calculator::RewardCalculator;
@@ -24,8 +25,9 @@ use ckb_types::{
     utilities::{merkle_root, MerkleProof, CBMT},
     H256,
 };
+use ckb_util::Mutex;
 use ckb_verification::ScriptVerifier;
-use ckb_verification::TxVerifyEnv;
+use ckb_verification::{ScriptError, ScriptGroupType, TxVerifyEnv};
 use jsonrpc_core::Result;
 use jsonrpc_utils::rpc;
 use std::collections::HashSet;
@@ -555,6 +557,10 @@ pub trait ChainRpc {
     /// When verbosity is 2: if tx_status.status is pending, proposed, or committed,
     /// the RPC returns the transaction content as field transaction, otherwise the field is null.
     ///
+    /// If tx_status.status is rejected and the node still holds the structured record of the
+    /// removal from the tx-pool, the record is returned as field `removal`, see
+    /// [`RemovedTransaction`](../../ckb_jsonrpc_types/struct.RemovedTransaction.html).
+    ///
     /// ## Examples
     ///
     /// Request
@@ -2388,4 +2394,83 @@ impl<'a> CyclesEstimator<'a> {
             )),
         }
     }
+
+    /// Runs the script groups one by one, and collects the result of each group.
+    pub(crate) fn simulate(&self, tx: packed::Transaction) -> Result<TransactionSimulation> {
+        let snapshot = self.shared.cloned_snapshot();
+        let consensus = snapshot.cloned_consensus();
+        let resolved = resolve_transaction(tx.into_view(), &mut HashSet::new(), self, self)
+            .map_err(|err| {
+                RPCError::custom_with_error(RPCError::TransactionFailedToResolve, err)
+            })?;
+        let max_cycles = consensus.max_block_cycles;
+        let tx_env = TxVerifyEnv::new_submit(snapshot.tip_header());
+        let mut verifier = ScriptVerifier::new(
+            Arc::new(resolved),
+            snapshot.as_data_loader(),
+            consensus,
+            Arc::new(tx_env),
+        );
+        let debug_output = Arc::new(Mutex::new(Vec::new()));
+        let printer_output = Arc::clone(&debug_output);
+        verifier.set_debug_printer(move |_script_hash, message| {
+            printer_output.lock().push(message.to_owned());
+        });
+
+        let mut total_cycles: core::Cycle = 0;
+        let mut script_groups = Vec::new();
+        for (group_type, script_hash, group) in verifier.groups_with_type() {
+            // the script groups share the max block cycles, stop once they are exhausted
+            let remaining_cycles = max_cycles.saturating_sub(total_cycles);
+            if remaining_cycles == 0 {
+                break;
+            }
+            let (cycles, exit_code, error) =
+                match verifier.detailed_verify_single(group_type, script_hash, remaining_cycles) {
+                    Ok((0, cycles)) => (Some(cycles), Some(0), None),
+                    Ok((code, cycles)) => (
+                        Some(cycles),
+                        Some(code),
+                        Some(ScriptError::validation_failure(&group.script, code).to_string()),
+                    ),
+                    Err(err) => {
+                        let exit_code = match err {
+                            ScriptError::ValidationFailure(_, code) => Some(code),
+                            _ => None,
+                        };
+                        if let ScriptError::ExceededMaximumCycles(_) = err {
+                            total_cycles = max_cycles;
+                        }
+                        (None, exit_code, Some(err.to_string()))
+                    }
+                };
+            total_cycles = total_cycles.saturating_add(cycles.unwrap_or(0));
+            script_groups.push(ScriptGroupSimulation {
+                group_type: match group_type {
+                    ScriptGroupType::Lock => ckb_jsonrpc_types::ScriptGroupType::Lock,
+                    ScriptGroupType::Type => ckb_jsonrpc_types::ScriptGroupType::Type,
+                },
+                script_hash: script_hash.unpack(),
+                input_indices: group
+                    .input_indices
+                    .iter()
+                    .map(|index| (*index as u32).into())
+                    .collect(),
+                output_indices: group
+                    .output_indices
+                    .iter()
+                    .map(|index| (*index as u32).into())
+                    .collect(),
+                cycles: cycles.map(Into::into),
+                exit_code,
+                error,
+                debug_output: std::mem::take(&mut *debug_output.lock()),
+            });
+        }
+
+        Ok(TransactionSimulation {
+            cycles: total_cycles.into(),
+            script_groups,
+        })
+    }
 }
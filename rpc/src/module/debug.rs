@@ -1,6 +1,11 @@
+use crate::error::RPCError;
 use async_trait::async_trait;
-use ckb_jsonrpc_types::{ExtraLoggerConfig, MainLoggerConfig};
+use ckb_jsonrpc_types::{
+    ColumnFamilyStats, DBStats, ExtraLoggerConfig, FreezerTableStats, MainLoggerConfig,
+};
 use ckb_logger_service::Logger;
+use ckb_shared::shared::Shared;
+use ckb_store::DBStats as StoreDBStats;
 use jsonrpc_core::{Error, ErrorCode::InternalError, Result};
 use jsonrpc_utils::rpc;
 use std::time;
@@ -33,10 +38,20 @@ pub trait DebugRpc {
     /// Removes the logger when this is null.
     #[rpc(name = "set_extra_logger")]
     fn set_extra_logger(&self, name: String, config_opt: Option<ExtraLoggerConfig>) -> Result<()>;
+
+    /// Returns the disk usage statistics of the RocksDB column families and the freezer tables.
+    ///
+    /// The same numbers are exposed as the metrics `ckb_db_cf_stats`,
+    /// `ckb_db_cf_compression_ratio` and `ckb_freezer_table_stats` when the metrics service is
+    /// enabled.
+    #[rpc(name = "get_db_stats")]
+    fn get_db_stats(&self) -> Result<DBStats>;
 }
 
 #[derive(Clone)]
-pub(crate) struct DebugRpcImpl {}
+pub(crate) struct DebugRpcImpl {
+    pub shared: Shared,
+}
 
 #[async_trait]
 impl DebugRpc for DebugRpcImpl {
@@ -92,4 +107,36 @@ impl DebugRpc for DebugRpcImpl {
             data: None,
         })
     }
+
+    fn get_db_stats(&self) -> Result<DBStats> {
+        let stats = StoreDBStats::collect(self.shared.store()).map_err(RPCError::from_ckb_error)?;
+        Ok(DBStats {
+            columns: stats
+                .columns
+                .iter()
+                .map(|stats| ColumnFamilyStats {
+                    column: stats.col.to_owned(),
+                    name: stats.name().to_owned(),
+                    estimate_num_keys: stats.estimate_num_keys.into(),
+                    live_sst_files_size: stats.live_sst_files_size.into(),
+                    estimate_pending_compaction_bytes: stats
+                        .estimate_pending_compaction_bytes
+                        .into(),
+                    raw_data_size: stats.raw_data_size.into(),
+                    data_blocks_size: stats.data_blocks_size.into(),
+                })
+                .collect(),
+            freezer: stats
+                .freezer
+                .iter()
+                .map(|stats| FreezerTableStats {
+                    name: stats.name.to_owned(),
+                    number: stats.number.into(),
+                    files: stats.files.into(),
+                    data_size: stats.data_size.into(),
+                    index_size: stats.index_size.into(),
+                })
+                .collect(),
+        })
+    }
 }
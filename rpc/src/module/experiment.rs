@@ -4,6 +4,7 @@ use async_trait::async_trait;
 use ckb_dao::DaoCalculator;
 use ckb_jsonrpc_types::{
     Capacity, DaoWithdrawingCalculationKind, EstimateCycles, OutPoint, Transaction,
+    TransactionSimulation,
 };
 use ckb_shared::{shared::Shared, Snapshot};
 use ckb_store::ChainStore;
@@ -102,6 +103,108 @@ pub trait ExperimentRpc {
     #[rpc(name = "dry_run_transaction")]
     fn dry_run_transaction(&self, tx: Transaction) -> Result<EstimateCycles>;
 
+    /// Simulates a transaction and returns the result of each script group.
+    ///
+    /// Like [`estimate_cycles`](trait.ChainRpc.html#tymethod.estimate_cycles), this method does not
+    /// check the transaction validity, but only runs the lock scripts and type scripts. It runs the
+    /// script groups one by one even if some of them fail, and returns the consumed cycles, the exit
+    /// code, the error and the messages printed via the debug syscall of each script group.
+    ///
+    /// It is used to find out which script fails or consumes too many cycles.
+    ///
+    /// ## Params
+    ///
+    /// * `tx` - The transaction to simulate.
+    ///
+    /// ## Returns
+    ///
+    /// The total cycles and the results of the script groups, the lock script groups first. The script
+    /// groups share the max block cycles, the groups after the cycles are exhausted are not run and
+    /// not returned.
+    ///
+    /// ## Errors
+    ///
+    /// * [`TransactionFailedToResolve (-301)`](../enum.RPCError.html#variant.TransactionFailedToResolve) - Failed to resolve the referenced cells and headers used in the transaction, as inputs or dependencies.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "simulate_transaction",
+    ///   "params": [
+    ///     {
+    ///       "cell_deps": [
+    ///         {
+    ///           "dep_type": "code",
+    ///           "out_point": {
+    ///             "index": "0x0",
+    ///             "tx_hash": "0xa4037a893eb48e18ed4ef61034ce26eba9c585f15c9cee102ae58505565eccc3"
+    ///           }
+    ///         }
+    ///       ],
+    ///       "header_deps": [
+    ///         "0x7978ec7ce5b507cfb52e149e36b1a23f6062ed150503c85bbf825da3599095ed"
+    ///       ],
+    ///       "inputs": [
+    ///         {
+    ///           "previous_output": {
+    ///             "index": "0x0",
+    ///             "tx_hash": "0x365698b50ca0da75dca2c87f9e7b563811d3b5813736b8cc62cc3b106faceb17"
+    ///           },
+    ///           "since": "0x0"
+    ///         }
+    ///       ],
+    ///       "outputs": [
+    ///         {
+    ///           "capacity": "0x2540be400",
+    ///           "lock": {
+    ///             "code_hash": "0x28e83a1277d48add8e72fadaa9248559e1b632bab2bd60b27955ebc4c03800a5",
+    ///             "hash_type": "data",
+    ///             "args": "0x"
+    ///           },
+    ///           "type": null
+    ///         }
+    ///       ],
+    ///       "outputs_data": [
+    ///         "0x"
+    ///       ],
+    ///       "version": "0x0",
+    ///       "witnesses": []
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "cycles": "0x219",
+    ///     "script_groups": [
+    ///       {
+    ///         "cycles": "0x219",
+    ///         "debug_output": [],
+    ///         "error": null,
+    ///         "exit_code": 0,
+    ///         "group_type": "lock",
+    ///         "input_indices": ["0x0"],
+    ///         "output_indices": [],
+    ///         "script_hash": "0x4ceaa32f692948413e213ce6f3a83337145bde6e11fd8cb94377ce2637dcc412"
+    ///       }
+    ///     ]
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "simulate_transaction")]
+    fn simulate_transaction(&self, tx: Transaction) -> Result<TransactionSimulation>;
+
     /// Calculates the maximum withdrawal one can get, given a referenced DAO cell, and
     /// a withdrawing block hash.
     ///
@@ -176,6 +279,11 @@ impl ExperimentRpc for ExperimentRpcImpl {
         CyclesEstimator::new(&self.shared).run(tx)
     }
 
+    fn simulate_transaction(&self, tx: Transaction) -> Result<TransactionSimulation> {
+        let tx: packed::Transaction = tx.into();
+        CyclesEstimator::new(&self.shared).simulate(tx)
+    }
+
     fn calculate_dao_maximum_withdraw(
         &self,
         out_point: OutPoint,
@@ -110,6 +110,7 @@
 //! JSON, `Status` can be one of "pending", "proposed" or "committed".
 #![allow(deprecated)]
 
+mod admin;
 mod alert;
 pub(crate) mod chain;
 mod debug;
@@ -120,9 +121,10 @@ mod net;
 pub(crate) mod pool;
 mod rich_indexer;
 mod stats;
-mod subscription;
+pub(crate) mod subscription;
 mod test;
 
+pub(crate) use self::admin::AdminRpcImpl;
 pub(crate) use self::alert::AlertRpcImpl;
 pub(crate) use self::chain::ChainRpcImpl;
 pub(crate) use self::debug::DebugRpcImpl;
@@ -136,6 +138,7 @@ pub(crate) use self::stats::StatsRpcImpl;
 pub(crate) use self::subscription::SubscriptionRpcImpl;
 pub(crate) use self::test::IntegrationTestRpcImpl;
 
+pub use self::admin::{add_admin_rpc_methods, admin_rpc_doc, AdminRpc};
 pub use self::alert::{add_alert_rpc_methods, alert_rpc_doc, AlertRpc};
 pub use self::chain::{add_chain_rpc_methods, chain_rpc_doc, ChainRpc};
 pub use self::debug::{add_debug_rpc_methods, debug_rpc_doc, DebugRpc};
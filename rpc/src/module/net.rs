@@ -1,10 +1,11 @@
 use crate::error::RPCError;
 use async_trait::async_trait;
+use ckb_app_config::{format_multiaddr, parse_multiaddr};
 use ckb_jsonrpc_types::{
     BannedAddr, LocalNode, LocalNodeProtocol, NodeAddress, PeerSyncState, RemoteNode,
     RemoteNodeProtocol, SyncState, Timestamp,
 };
-use ckb_network::{extract_peer_id, multiaddr::Multiaddr, NetworkController};
+use ckb_network::{extract_peer_id, NetworkController};
 use ckb_sync::SyncShared;
 use ckb_systemtime::unix_time_as_millis;
 use jsonrpc_core::Result;
@@ -416,6 +417,9 @@ pub trait NetRpc {
     /// * `peer_id` - The node id of the node.
     /// * `address` - The address of the node.
     ///
+    /// The onion services are addressed as `/onion3/<name>:<port>`, which are only reachable through
+    /// the proxy `network.proxy`.
+    ///
     /// The full P2P address is usually displayed as `address/peer_id`, for example in the log
     ///
     /// ```text
@@ -589,7 +593,7 @@ impl NetRpc for NetRpcImpl {
                             .unwrap_or(1);
                         let non_negative_score = if score > 0 { score as u64 } else { 0 };
                         NodeAddress {
-                            address: addr.to_string(),
+                            address: format_multiaddr(addr),
                             score: non_negative_score.into(),
                         }
                     })
@@ -741,10 +745,10 @@ impl NetRpc for NetRpcImpl {
     }
 
     fn add_node(&self, peer_id: String, address: String) -> Result<()> {
-        if let Ok(multiaddr) = address.parse::<Multiaddr>() {
+        if let Ok(multiaddr) = parse_multiaddr(&address) {
             if extract_peer_id(&multiaddr).is_some() {
                 self.network_controller.add_node(multiaddr)
-            } else if let Ok(addr) = format!("{address}/p2p/{peer_id}").parse() {
+            } else if let Ok(addr) = parse_multiaddr(&format!("{address}/p2p/{peer_id}")) {
                 self.network_controller.add_node(addr)
             }
         }
@@ -3,7 +3,8 @@ use async_trait::async_trait;
 use ckb_chain_spec::consensus::Consensus;
 use ckb_constant::hardfork::{mainnet, testnet};
 use ckb_jsonrpc_types::{
-    OutputsValidator, PoolTxDetailInfo, RawTxPool, Script, Transaction, TxPoolInfo,
+    BlockNumber, OutputsValidator, PoolTxDetailInfo, RawTxPool, RawTxPoolWithSequence,
+    RemovedTransaction, Script, Transaction, TxPoolInfo, Uint64,
 };
 use ckb_logger::error;
 use ckb_shared::shared::Shared;
@@ -109,6 +110,148 @@ pub trait PoolRpc {
         outputs_validator: Option<OutputsValidator>,
     ) -> Result<H256>;
 
+    /// Submits a package of transactions into the transaction pool atomically, the transactions
+    /// are either all accepted or all rejected. The accepted package is relayed to peers as a unit.
+    ///
+    /// A transaction whose fee rate is below the config option `tx_pool.min_fee_rate` is accepted
+    /// if a child in the package pays for it, i.e., the fee rate of the child together with all
+    /// its ancestors is greater than or equal to `tx_pool.min_fee_rate`.
+    ///
+    /// ## Params
+    ///
+    /// * `transactions` - The transactions, sorted so that parents come before children. Every
+    /// transaction must have a parent or a child in the package. The package
+    /// can contain at most 25 transactions. None of them may conflict with the transactions in the
+    /// pool, and the transactions already in the pool are skipped.
+    /// * `outputs_validator` - Validates the transactions outputs before entering the tx-pool. (**Optional**, default is "passthrough").
+    ///
+    /// ## Returns
+    ///
+    /// The hashes of the transactions in the package.
+    ///
+    /// ## Errors
+    ///
+    /// * [`PoolRejectedTransactionByOutputsValidator (-1102)`](../enum.RPCError.html#variant.PoolRejectedTransactionByOutputsValidator) - A transaction is rejected by the validator specified by `outputs_validator`.
+    /// * [`PoolRejectedTransactionByMinFeeRate (-1104)`](../enum.RPCError.html#variant.PoolRejectedTransactionByMinFeeRate) - A transaction fee rate is lower than `tx_pool.min_fee_rate`, and no child in the package pays for it.
+    /// * [`PoolRejectedTransactionByMaxAncestorsCountLimit (-1105)`](../enum.RPCError.html#variant.PoolRejectedTransactionByMaxAncestorsCountLimit) - The ancestors count must be greater than or equal to the config option `tx_pool.max_ancestors_count`.
+    /// * [`PoolIsFull (-1106)`](../enum.RPCError.html#variant.PoolIsFull) - Pool is full, and a transaction of the package is evicted right away.
+    /// * [`PoolRejectedDuplicatedTransaction (-1107)`](../enum.RPCError.html#variant.PoolRejectedDuplicatedTransaction) - A transaction is already in the pool.
+    /// * [`PoolRejectedMalformedTransaction (-1108)`](../enum.RPCError.html#variant.PoolRejectedMalformedTransaction) - The package is malformed, e.g., the transactions are not sorted or not connected.
+    /// * [`TransactionFailedToResolve (-301)`](../enum.RPCError.html#variant.TransactionFailedToResolve) - Failed to resolve the referenced cells and headers used in a transaction, as inputs or dependencies.
+    /// * [`TransactionFailedToVerify (-302)`](../enum.RPCError.html#variant.TransactionFailedToVerify) - Failed to verify a transaction.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "send_transaction_package",
+    ///   "params": [
+    ///     [
+    ///       {
+    ///         "cell_deps": [
+    ///           {
+    ///             "dep_type": "code",
+    ///             "out_point": {
+    ///               "index": "0x0",
+    ///               "tx_hash": "0xa4037a893eb48e18ed4ef61034ce26eba9c585f15c9cee102ae58505565eccc3"
+    ///             }
+    ///           }
+    ///         ],
+    ///         "header_deps": [
+    ///           "0x7978ec7ce5b507cfb52e149e36b1a23f6062ed150503c85bbf825da3599095ed"
+    ///         ],
+    ///         "inputs": [
+    ///           {
+    ///             "previous_output": {
+    ///               "index": "0x0",
+    ///               "tx_hash": "0x365698b50ca0da75dca2c87f9e7b563811d3b5813736b8cc62cc3b106faceb17"
+    ///             },
+    ///             "since": "0x0"
+    ///           }
+    ///         ],
+    ///         "outputs": [
+    ///           {
+    ///             "capacity": "0x2540be400",
+    ///             "lock": {
+    ///               "code_hash": "0x28e83a1277d48add8e72fadaa9248559e1b632bab2bd60b27955ebc4c03800a5",
+    ///               "hash_type": "data",
+    ///               "args": "0x"
+    ///             },
+    ///             "type": null
+    ///           }
+    ///         ],
+    ///         "outputs_data": [
+    ///           "0x"
+    ///         ],
+    ///         "version": "0x0",
+    ///         "witnesses": []
+    ///       },
+    ///       {
+    ///         "cell_deps": [
+    ///           {
+    ///             "dep_type": "code",
+    ///             "out_point": {
+    ///               "index": "0x0",
+    ///               "tx_hash": "0xa4037a893eb48e18ed4ef61034ce26eba9c585f15c9cee102ae58505565eccc3"
+    ///             }
+    ///           }
+    ///         ],
+    ///         "header_deps": [],
+    ///         "inputs": [
+    ///           {
+    ///             "previous_output": {
+    ///               "index": "0x0",
+    ///               "tx_hash": "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3"
+    ///             },
+    ///             "since": "0x0"
+    ///           }
+    ///         ],
+    ///         "outputs": [
+    ///           {
+    ///             "capacity": "0x2540a9b80",
+    ///             "lock": {
+    ///               "code_hash": "0x28e83a1277d48add8e72fadaa9248559e1b632bab2bd60b27955ebc4c03800a5",
+    ///               "hash_type": "data",
+    ///               "args": "0x"
+    ///             },
+    ///             "type": null
+    ///           }
+    ///         ],
+    ///         "outputs_data": [
+    ///           "0x"
+    ///         ],
+    ///         "version": "0x0",
+    ///         "witnesses": []
+    ///       }
+    ///     ],
+    ///     "passthrough"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": [
+    ///     "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3",
+    ///     "0x0e081e2bb2717be90798e553574a37f6ef03c217a7ffdbc3edf5823cb694f702"
+    ///   ]
+    /// }
+    /// ```
+    #[rpc(name = "send_transaction_package")]
+    fn send_transaction_package(
+        &self,
+        transactions: Vec<Transaction>,
+        outputs_validator: Option<OutputsValidator>,
+    ) -> Result<Vec<H256>>;
+
     /// Removes a transaction and all transactions which depends on it from tx pool if it exists.
     ///
     /// ## Params
@@ -258,6 +401,51 @@ pub trait PoolRpc {
     #[rpc(name = "get_raw_tx_pool")]
     fn get_raw_tx_pool(&self, verbose: Option<bool>) -> Result<RawTxPool>;
 
+    /// Returns all transaction ids in tx pool together with the sequence of the last tx-pool change.
+    ///
+    /// The sequence increases by one for each change pushed by the subscription topic `pool_diff`.
+    /// Subscribe `pool_diff` first, then apply the changes whose sequence is greater than the
+    /// returned one to the returned pool in order to keep an up-to-date copy of the tx-pool. Changes
+    /// may be dropped when the subscriber is slow, call this RPC again if a sequence is missing.
+    ///
+    /// ## Params
+    ///
+    /// * `verbose` - True for a json object, false for array of transaction ids, default=false
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "get_raw_tx_pool_with_sequence",
+    ///   "params": [false]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "sequence": "0x1",
+    ///     "pool": {
+    ///       "pending": [
+    ///         "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3"
+    ///       ],
+    ///       "proposed": []
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "get_raw_tx_pool_with_sequence")]
+    fn get_raw_tx_pool_with_sequence(&self, verbose: Option<bool>)
+        -> Result<RawTxPoolWithSequence>;
+
     /// Query and returns the details of a transaction in the pool, only for trouble shooting
     /// ## Params
     ///
@@ -330,6 +518,105 @@ pub trait PoolRpc {
     /// ```
     #[rpc(name = "tx_pool_ready")]
     fn tx_pool_ready(&self) -> Result<bool>;
+
+    /// Estimates the fee rate for a transaction to get committed within the target blocks.
+    ///
+    /// The estimation combines the transactions in the pool sorted by their ancestors scores,
+    /// which shows the fee rate required to be packaged ahead of the transactions that can't fit
+    /// into the target blocks, with the historical inclusion delays of the pool transactions
+    /// grouped by fee rates. The result is never lower than the `min_fee_rate` of the pool.
+    ///
+    /// ## Params
+    ///
+    /// * `target_blocks` - The number of blocks within which the transaction is expected to get
+    /// committed. It must be greater than 0, and it is capped at 128.
+    ///
+    /// ## Returns
+    ///
+    /// The estimated fee rate in shannons per kilo-weight.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "estimate_fee_rate",
+    ///   "params": ["0xa"]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": "0x3e8"
+    /// }
+    /// ```
+    #[rpc(name = "estimate_fee_rate")]
+    fn estimate_fee_rate(&self, target_blocks: Uint64) -> Result<Uint64>;
+
+    /// Returns the records of the transactions recently removed from the tx-pool.
+    ///
+    /// The records are kept in memory, and only the most recent 10000 ones are kept. They
+    /// are lost when the node restarts.
+    ///
+    /// ## Params
+    ///
+    /// * `start_number` - The tip block number when the transactions were removed, inclusive.
+    /// * `end_number` - The tip block number when the transactions were removed, exclusive.
+    ///
+    /// The range is `[start_number, end_number)`, e.g., `["0x400", "0x401"]` only queries the records
+    /// of the transactions removed when the tip block number is `0x400`.
+    ///
+    /// ## Returns
+    ///
+    /// The records ordered by the block number and then the removal order. A transaction which
+    /// has been removed more than once only keeps the latest record.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "get_removed_transactions",
+    ///   "params": ["0x400", "0x401"]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": [
+    ///     {
+    ///       "tx_hash": "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3",
+    ///       "reason": "replaced",
+    ///       "description": "RBF rejected: replaced by tx Byte32(0x0e081e2bb2717be90798e553574a37f6ef03c217a7ffdbc3edf5823cb694f702)",
+    ///       "conflicting_tx": "0x0e081e2bb2717be90798e553574a37f6ef03c217a7ffdbc3edf5823cb694f702",
+    ///       "removed_descendants": [],
+    ///       "block_number": "0x400",
+    ///       "fee_rate": "0x52607f1408",
+    ///       "timestamp": "0x17c983e6e44"
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    #[rpc(name = "get_removed_transactions")]
+    fn get_removed_transactions(
+        &self,
+        start_number: BlockNumber,
+        end_number: BlockNumber,
+    ) -> Result<Vec<RemovedTransaction>>;
 }
 
 #[derive(Clone)]
@@ -359,6 +646,35 @@ impl PoolRpcImpl {
             well_known_type_scripts,
         }
     }
+
+    /// Checks the outputs of the transaction by the validator set in the RPC params.
+    fn validate_outputs(
+        &self,
+        tx: &core::TransactionView,
+        outputs_validator: Option<OutputsValidator>,
+    ) -> Result<()> {
+        if let Err(e) = match outputs_validator {
+            None | Some(OutputsValidator::Passthrough) => Ok(()),
+            Some(OutputsValidator::WellKnownScriptsOnly) => WellKnownScriptsOnlyValidator::new(
+                self.shared.consensus(),
+                &self.well_known_lock_scripts,
+                &self.well_known_type_scripts,
+            )
+            .validate(tx),
+        } {
+            return Err(RPCError::custom_with_data(
+                RPCError::PoolRejectedTransactionByOutputsValidator,
+                format!(
+                    "The transaction {} is rejected by OutputsValidator set in params[1]: {}. \
+                    Please check the related information in https://github.com/nervosnetwork/ckb/wiki/Transaction-%C2%BB-Default-Outputs-Validator",
+                    tx.hash(),
+                    outputs_validator.unwrap_or(OutputsValidator::WellKnownScriptsOnly).json_display()
+                ),
+                e,
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Build well known lock scripts
@@ -452,25 +768,7 @@ impl PoolRpc for PoolRpcImpl {
         let tx: packed::Transaction = tx.into();
         let tx: core::TransactionView = tx.into_view();
 
-        if let Err(e) = match outputs_validator {
-            None | Some(OutputsValidator::Passthrough) => Ok(()),
-            Some(OutputsValidator::WellKnownScriptsOnly) => WellKnownScriptsOnlyValidator::new(
-                self.shared.consensus(),
-                &self.well_known_lock_scripts,
-                &self.well_known_type_scripts,
-            )
-            .validate(&tx),
-        } {
-            return Err(RPCError::custom_with_data(
-                RPCError::PoolRejectedTransactionByOutputsValidator,
-                format!(
-                    "The transaction is rejected by OutputsValidator set in params[1]: {}. \
-                    Please check the related information in https://github.com/nervosnetwork/ckb/wiki/Transaction-%C2%BB-Default-Outputs-Validator",
-                    outputs_validator.unwrap_or(OutputsValidator::WellKnownScriptsOnly).json_display()
-                ),
-                e,
-            ));
-        }
+        self.validate_outputs(&tx, outputs_validator)?;
 
         let tx_pool = self.shared.tx_pool_controller();
         let submit_tx = tx_pool.submit_local_tx(tx.clone());
@@ -487,6 +785,32 @@ impl PoolRpc for PoolRpcImpl {
         }
     }
 
+    fn send_transaction_package(
+        &self,
+        transactions: Vec<Transaction>,
+        outputs_validator: Option<OutputsValidator>,
+    ) -> Result<Vec<H256>> {
+        let txs: Vec<core::TransactionView> = transactions
+            .into_iter()
+            .map(|tx| packed::Transaction::from(tx).into_view())
+            .collect();
+
+        for tx in &txs {
+            self.validate_outputs(tx, outputs_validator)?;
+        }
+
+        let tx_hashes = txs.iter().map(|tx| tx.hash().unpack()).collect();
+        let tx_pool = self.shared.tx_pool_controller();
+        match tx_pool.submit_local_package(txs) {
+            Err(e) => {
+                error!("Send submit_package request error {}", e);
+                Err(RPCError::ckb_internal_error(e))
+            }
+            Ok(Ok(_)) => Ok(tx_hashes),
+            Ok(Err(reject)) => Err(RPCError::from_submit_transaction_reject(&reject)),
+        }
+    }
+
     fn remove_transaction(&self, tx_hash: H256) -> Result<bool> {
         let tx_pool = self.shared.tx_pool_controller();
 
@@ -536,6 +860,29 @@ impl PoolRpc for PoolRpcImpl {
         Ok(raw)
     }
 
+    fn get_raw_tx_pool_with_sequence(
+        &self,
+        verbose: Option<bool>,
+    ) -> Result<RawTxPoolWithSequence> {
+        let tx_pool = self.shared.tx_pool_controller();
+
+        let (sequence, pool) = if verbose.unwrap_or(false) {
+            let (sequence, info) = tx_pool
+                .get_all_entry_info_with_sequence()
+                .map_err(|err| RPCError::custom(RPCError::CKBInternalError, err.to_string()))?;
+            (sequence, RawTxPool::Verbose(info.into()))
+        } else {
+            let (sequence, ids) = tx_pool
+                .get_all_ids_with_sequence()
+                .map_err(|err| RPCError::custom(RPCError::CKBInternalError, err.to_string()))?;
+            (sequence, RawTxPool::Ids(ids.into()))
+        };
+        Ok(RawTxPoolWithSequence {
+            sequence: sequence.into(),
+            pool,
+        })
+    }
+
     fn get_pool_tx_detail_info(&self, tx_hash: H256) -> Result<PoolTxDetailInfo> {
         let tx_pool = self.shared.tx_pool_controller();
         let tx_detail = tx_pool
@@ -543,6 +890,32 @@ impl PoolRpc for PoolRpcImpl {
             .map_err(|err| RPCError::custom(RPCError::CKBInternalError, err.to_string()))?;
         Ok(tx_detail.into())
     }
+
+    fn estimate_fee_rate(&self, target_blocks: Uint64) -> Result<Uint64> {
+        let target_blocks: u64 = target_blocks.into();
+        if target_blocks == 0 {
+            return Err(RPCError::invalid_params(
+                "target_blocks must be greater than 0",
+            ));
+        }
+        let tx_pool = self.shared.tx_pool_controller();
+        let fee_rate = tx_pool
+            .estimate_fee_rate(target_blocks)
+            .map_err(|err| RPCError::custom(RPCError::CKBInternalError, err.to_string()))?;
+        Ok(fee_rate.as_u64().into())
+    }
+
+    fn get_removed_transactions(
+        &self,
+        start_number: BlockNumber,
+        end_number: BlockNumber,
+    ) -> Result<Vec<RemovedTransaction>> {
+        let tx_pool = self.shared.tx_pool_controller();
+        let removed = tx_pool
+            .get_removed_transactions(start_number.into(), end_number.into())
+            .map_err(|err| RPCError::custom(RPCError::CKBInternalError, err.to_string()))?;
+        Ok(removed.into_iter().map(Into::into).collect())
+    }
 }
 
 pub(crate) struct WellKnownScriptsOnlyValidator<'a> {
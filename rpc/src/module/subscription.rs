@@ -1,11 +1,17 @@
+use crate::error::RPCError;
 use async_trait::async_trait;
 use ckb_async_runtime::Handle;
-use ckb_jsonrpc_types::Topic;
+use ckb_indexer::SearchKeyMatcher;
+use ckb_jsonrpc_types::{CommittedTransaction, IndexerCellType, IndexerSearchKey, Topic};
 use ckb_notify::NotifyController;
+use ckb_shared::Shared;
 use ckb_stop_handler::new_tokio_exit_rx;
+use ckb_store::ChainStore;
+use ckb_types::{bytes::Bytes, core::BlockView, packed, prelude::*};
 use futures_util::{stream::BoxStream, Stream};
 use jsonrpc_core::Result;
 use jsonrpc_utils::{pub_sub::PublishMsg, rpc};
+use std::sync::Arc;
 use tokio::sync::broadcast;
 
 /// RPC Module Subscription that CKB node will push new messages to subscribers, support with WebSocket or TCP.
@@ -57,7 +63,9 @@ pub trait SubscriptionRpc {
     ///
     /// ###### Params
     ///
-    /// * `topic` - Subscription topic (enum: new_tip_header | new_tip_block | new_transaction | proposed_transaction | rejected_transaction)
+    /// * `topic` - Subscription topic (enum: new_tip_header | new_tip_block | new_transaction | proposed_transaction | rejected_transaction | committed_transaction | chain_reorg | pool_diff)
+    /// * `search_key` - Search key of the cells to watch, only required and accepted by the topic `committed_transaction`. See
+    /// [`IndexerSearchKey`](../../ckb_jsonrpc_types/struct.IndexerSearchKey.html).
     ///
     /// ###### Returns
     ///
@@ -117,6 +125,62 @@ pub trait SubscriptionRpc {
     /// -   the first item type is [`PoolTransactionEntry`](../../ckb_jsonrpc_types/struct.PoolTransactionEntry.html), and
     /// -   the second item type is [`PoolTransactionReject`](../../ckb_jsonrpc_types/struct.PoolTransactionReject.html).
     ///
+    /// ###### `committed_transaction`
+    ///
+    /// Whenever there's a block that is appended to the canonical chain, subscribers will get notified of
+    /// each committed transaction in the block which has an input or an output cell matching `search_key`.
+    /// The search key follows the same rules as the indexer RPC `get_cells`, with the following differences:
+    ///
+    /// -   only the `prefix` and `exact` script search modes are supported,
+    /// -   `filter.block_range` applies to the number of the block which commits the transaction, and
+    /// -   `with_data` and `group_by_transaction` are ignored.
+    ///
+    /// The previous output of an input is unknown if the transaction creating it has been pruned. Such
+    /// inputs are listed in `unresolved_inputs` and are not matched against the search key. A transaction
+    /// is pushed if one of its outputs or resolved inputs matches, or if none of its inputs is resolved,
+    /// since it may spend the matching cells.
+    ///
+    /// The type of the `params.result` in the push message is [`CommittedTransaction`](../../ckb_jsonrpc_types/struct.CommittedTransaction.html).
+    ///
+    /// Subscribe Request
+    ///
+    /// ```json+skip
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "subscribe",
+    ///   "params": [
+    ///     "committed_transaction",
+    ///     {
+    ///       "script": {
+    ///         "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+    ///         "hash_type": "type",
+    ///         "args": "0x8211f1b938a107cd53b6302cc752a6fc3965638d"
+    ///       },
+    ///       "script_type": "lock"
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// ###### `chain_reorg`
+    ///
+    /// Whenever a new tip block switches the canonical chain to another fork, the CKB node will publish
+    /// the common ancestor together with the detached and attached block hashes to subscribers. Blocks
+    /// which only extend the canonical chain are not published.
+    ///
+    /// The type of the `params.result` in the push message is [`ChainReorg`](../../ckb_jsonrpc_types/struct.ChainReorg.html).
+    ///
+    /// ###### `pool_diff`
+    ///
+    /// Subscribers will get notified of every change of the tx-pool: a transaction enters the pending
+    /// pool, moves into the gap or the proposed pool, or is removed because it has been committed,
+    /// rejected or removed manually. Each change carries a sequence which increases by one, use it
+    /// together with the RPC `get_raw_tx_pool_with_sequence` to keep a copy of the tx-pool. The
+    /// changes are pushed in order, and may be dropped for slow subscribers.
+    ///
+    /// The type of the `params.result` in the push message is [`PoolTransactionDiff`](../../ckb_jsonrpc_types/struct.PoolTransactionDiff.html).
+    ///
     /// ###### Examples
     ///
     /// Subscribe Request
@@ -178,7 +242,7 @@ pub trait SubscriptionRpc {
     /// ```
     ///
     #[rpc(pub_sub(notify = "subscribe", unsubscribe = "unsubscribe"))]
-    fn subscribe(&self, topic: Topic) -> Result<Self::S>;
+    fn subscribe(&self, topic: Topic, search_key: Option<IndexerSearchKey>) -> Result<Self::S>;
 }
 
 #[derive(Clone)]
@@ -188,6 +252,90 @@ pub struct SubscriptionRpcImpl {
     pub new_transaction_sender: broadcast::Sender<PublishMsg<String>>,
     pub proposed_transaction_sender: broadcast::Sender<PublishMsg<String>>,
     pub new_reject_transaction_sender: broadcast::Sender<PublishMsg<String>>,
+    pub chain_reorg_sender: broadcast::Sender<PublishMsg<String>>,
+    pub pool_diff_sender: broadcast::Sender<PublishMsg<String>>,
+    committed_block_sender: broadcast::Sender<Arc<CommittedBlock>>,
+}
+
+// A new tip block whose input cells are resolved, shared by all `committed_transaction` subscribers.
+pub(crate) struct CommittedBlock {
+    block: BlockView,
+    // The previous output and data of each input, grouped by transaction. Empty for cellbase.
+    // `None` if the transaction creating the input cell has been pruned.
+    inputs: Vec<Vec<Option<(packed::CellOutput, Bytes)>>>,
+}
+
+impl CommittedBlock {
+    pub(crate) fn resolve<S: ChainStore>(block: BlockView, store: &S) -> Self {
+        let inputs = block
+            .transactions()
+            .iter()
+            .map(|tx| {
+                if tx.is_cellbase() {
+                    return Vec::new();
+                }
+                tx.input_pts_iter()
+                    .map(|out_point| {
+                        store
+                            .get_transaction(&out_point.tx_hash())
+                            .and_then(|(tx, _)| tx.output_with_data(out_point.index().unpack()))
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { block, inputs }
+    }
+
+    pub(crate) fn matched_transactions(
+        &self,
+        matcher: &SearchKeyMatcher,
+    ) -> Vec<CommittedTransaction> {
+        let block_number = self.block.number();
+        if !matcher.match_block_number(block_number) {
+            return Vec::new();
+        }
+        let block_hash = self.block.hash();
+        self.block
+            .transactions()
+            .into_iter()
+            .zip(self.inputs.iter())
+            .enumerate()
+            .filter_map(|(tx_index, (tx, inputs))| {
+                let mut cells = Vec::new();
+                let mut unresolved_inputs = Vec::new();
+                for (index, input) in inputs.iter().enumerate() {
+                    match input {
+                        Some((output, data)) => {
+                            if matcher.match_cell(output, data) {
+                                cells.push((IndexerCellType::Input, (index as u32).into()));
+                            }
+                        }
+                        None => unresolved_inputs.push((index as u32).into()),
+                    }
+                }
+                for (index, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+                    if matcher.match_cell(&output, &data) {
+                        cells.push((IndexerCellType::Output, (index as u32).into()));
+                    }
+                }
+                // The unresolved inputs may spend the matching cells, they push the transaction
+                // only if no input is resolved to decide it.
+                let undecided =
+                    !unresolved_inputs.is_empty() && unresolved_inputs.len() == inputs.len();
+                if cells.is_empty() && !undecided {
+                    return None;
+                }
+                Some(CommittedTransaction {
+                    transaction: tx.into(),
+                    block_hash: block_hash.unpack(),
+                    block_number: block_number.into(),
+                    tx_index: (tx_index as u32).into(),
+                    cells,
+                    unresolved_inputs,
+                })
+            })
+            .collect()
+    }
 }
 
 macro_rules! publiser_send {
@@ -201,13 +349,23 @@ macro_rules! publiser_send {
 #[async_trait]
 impl SubscriptionRpc for SubscriptionRpcImpl {
     type S = BoxStream<'static, PublishMsg<String>>;
-    fn subscribe(&self, topic: Topic) -> Result<Self::S> {
+    fn subscribe(&self, topic: Topic, search_key: Option<IndexerSearchKey>) -> Result<Self::S> {
+        if topic != Topic::CommittedTransaction && search_key.is_some() {
+            return Err(RPCError::invalid_params(
+                "search_key is only accepted by topic committed_transaction",
+            ));
+        }
         let tx = match topic {
             Topic::NewTipHeader => self.new_tip_header_sender.clone(),
             Topic::NewTipBlock => self.new_tip_block_sender.clone(),
             Topic::NewTransaction => self.new_transaction_sender.clone(),
             Topic::ProposedTransaction => self.proposed_transaction_sender.clone(),
             Topic::RejectedTransaction => self.new_reject_transaction_sender.clone(),
+            Topic::ChainReorg => self.chain_reorg_sender.clone(),
+            Topic::PoolDiff => self.pool_diff_sender.clone(),
+            Topic::CommittedTransaction => {
+                return self.subscribe_committed_transaction(search_key);
+            }
         };
         let mut rx = tx.subscribe();
         Ok(Box::pin(async_stream::stream! {
@@ -219,11 +377,32 @@ impl SubscriptionRpc for SubscriptionRpcImpl {
 }
 
 impl SubscriptionRpcImpl {
-    pub fn new(notify_controller: NotifyController, handle: Handle) -> Self {
+    fn subscribe_committed_transaction(
+        &self,
+        search_key: Option<IndexerSearchKey>,
+    ) -> Result<BoxStream<'static, PublishMsg<String>>> {
+        let search_key = search_key.ok_or_else(|| {
+            RPCError::invalid_params("search_key is required by topic committed_transaction")
+        })?;
+        let matcher = SearchKeyMatcher::new(search_key).map_err(RPCError::invalid_params)?;
+        let mut rx = self.committed_block_sender.subscribe();
+        Ok(Box::pin(async_stream::stream! {
+               while let Ok(committed_block) = rx.recv().await {
+                    for tx in committed_block.matched_transactions(&matcher) {
+                        let json_string = serde_json::to_string(&tx).expect("serialization should be ok");
+                        yield PublishMsg::result(&json_string);
+                    }
+               }
+        }))
+    }
+
+    pub fn new(shared: Shared, notify_controller: NotifyController, handle: Handle) -> Self {
         const SUBSCRIBER_NAME: &str = "TcpSubscription";
 
         let mut new_block_receiver =
             handle.block_on(notify_controller.subscribe_new_block(SUBSCRIBER_NAME.to_string()));
+        let mut chain_reorg_receiver =
+            handle.block_on(notify_controller.subscribe_chain_reorg(SUBSCRIBER_NAME.to_string()));
         let mut new_transaction_receiver = handle
             .block_on(notify_controller.subscribe_new_transaction(SUBSCRIBER_NAME.to_string()));
         let mut proposed_transaction_receiver = handle.block_on(
@@ -231,12 +410,17 @@ impl SubscriptionRpcImpl {
         );
         let mut reject_transaction_receiver = handle
             .block_on(notify_controller.subscribe_reject_transaction(SUBSCRIBER_NAME.to_string()));
+        let mut pool_diff_receiver =
+            handle.block_on(notify_controller.subscribe_pool_diff(SUBSCRIBER_NAME.to_string()));
 
         let (new_tip_header_sender, _) = broadcast::channel(10);
         let (new_tip_block_sender, _) = broadcast::channel(10);
         let (proposed_transaction_sender, _) = broadcast::channel(10);
         let (new_transaction_sender, _) = broadcast::channel(10);
         let (new_reject_transaction_sender, _) = broadcast::channel(10);
+        let (chain_reorg_sender, _) = broadcast::channel(10);
+        let (pool_diff_sender, _) = broadcast::channel(10);
+        let (committed_block_sender, _) = broadcast::channel(10);
 
         let stop_rx = new_tokio_exit_rx();
         handle.spawn({
@@ -245,12 +429,27 @@ impl SubscriptionRpcImpl {
             let new_transaction_sender = new_transaction_sender.clone();
             let proposed_transaction_sender = proposed_transaction_sender.clone();
             let new_reject_transaction_sender = new_reject_transaction_sender.clone();
+            let chain_reorg_sender = chain_reorg_sender.clone();
+            let pool_diff_sender = pool_diff_sender.clone();
+            let committed_block_sender = committed_block_sender.clone();
+            let handle = handle.clone();
             async move {
                 loop {
                     tokio::select! {
+                        Some(reorg) = chain_reorg_receiver.recv() => {
+                            publiser_send!(ckb_jsonrpc_types::ChainReorg, reorg, chain_reorg_sender);
+                        },
                         Some(block) = new_block_receiver.recv() => {
                             publiser_send!(ckb_jsonrpc_types::HeaderView, block.header(), new_tip_header_sender);
-                            publiser_send!(ckb_jsonrpc_types::BlockView, block, new_tip_block_sender);
+                            publiser_send!(ckb_jsonrpc_types::BlockView, block.clone(), new_tip_block_sender);
+                            if committed_block_sender.receiver_count() > 0 {
+                                // Resolving reads the store, keep it off the async workers.
+                                let snapshot = shared.cloned_snapshot();
+                                let resolve = handle.spawn_blocking(move || CommittedBlock::resolve(block, &*snapshot));
+                                if let Ok(committed_block) = resolve.await {
+                                    drop(committed_block_sender.send(Arc::new(committed_block)));
+                                }
+                            }
                         },
                         Some(tx_entry) = new_transaction_receiver.recv() => {
                             publiser_send!(ckb_jsonrpc_types::PoolTransactionEntry, tx_entry, new_transaction_sender);
@@ -263,6 +462,9 @@ impl SubscriptionRpcImpl {
                                             (tx_entry.into(), reject.into()),
                                             new_reject_transaction_sender);
                         }
+                        Some(diff) = pool_diff_receiver.recv() => {
+                            publiser_send!(ckb_jsonrpc_types::PoolTransactionDiff, diff, pool_diff_sender);
+                        }
                         _ = stop_rx.cancelled() => {
                             break;
                         },
@@ -278,6 +480,9 @@ impl SubscriptionRpcImpl {
             new_transaction_sender,
             proposed_transaction_sender,
             new_reject_transaction_sender,
+            chain_reorg_sender,
+            pool_diff_sender,
+            committed_block_sender,
         }
     }
 }
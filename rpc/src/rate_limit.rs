@@ -0,0 +1,151 @@
+//! Per-client rate limiting of the RPC server.
+use crate::{IoHandler, RPCError};
+use ckb_app_config::RpcRateLimitConfig;
+use ckb_util::Mutex;
+use governor::{clock::DefaultClock, state::InMemoryState, state::NotKeyed, Quota};
+use jsonrpc_core::RemoteProcedure;
+use lru::LruCache;
+use std::future::Future;
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+// The max number of clients whose limiting states are kept.
+const MAX_CLIENTS: usize = 4096;
+
+type RateLimiter = governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
+/// Identifies a client.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ClientKey {
+    /// The value of the `Authorization` header of an authorized client.
+    Credential(String),
+    /// The IP address of the other clients.
+    Ip(IpAddr),
+}
+
+tokio::task_local! {
+    // The client whose calls are being processed.
+    static CLIENT: ClientKey;
+}
+
+impl ClientKey {
+    /// Processes the calls of the client in `f`.
+    pub(crate) async fn scope<F: Future>(self, f: F) -> F::Output {
+        CLIENT.scope(self, f).await
+    }
+
+    /// Processes the calls of the client in the blocking `f`.
+    #[cfg(test)]
+    pub(crate) fn sync_scope<R>(self, f: impl FnOnce() -> R) -> R {
+        CLIENT.sync_scope(self, f)
+    }
+}
+
+/// Wraps the methods to limit the calls of each client.
+///
+/// The client is looked up when the method is called, so the calls must be processed in the
+/// [`ClientKey::scope`] of the client. The calls outside of any scope are not limited.
+pub(crate) fn limit(config: RpcRateLimitConfig, rpc: IoHandler) -> IoHandler {
+    let rate_limit = Arc::new(RateLimit::new(config));
+    let mut limited = IoHandler::with_compatibility(jsonrpc_core::Compatibility::V2);
+    limited.extend_with(rpc.iter().map(|(name, method)| {
+        let method = match method {
+            RemoteProcedure::Method(method) => {
+                let method = Arc::clone(method);
+                let rate_limit = Arc::clone(&rate_limit);
+                let cost = rate_limit
+                    .config
+                    .method_costs
+                    .get(name)
+                    .copied()
+                    .unwrap_or(1);
+                RemoteProcedure::Method(Arc::new(move |params, meta| {
+                    let call = rate_limit
+                        .acquire(cost)
+                        .map(|permit| (permit, method.call(params, meta)));
+                    async move {
+                        let (_permit, call) = call?;
+                        call.await
+                    }
+                }))
+            }
+            other => other.clone(),
+        };
+        (name.to_owned(), method)
+    }));
+    limited
+}
+
+/// The limiting states of the recent clients.
+struct RateLimit {
+    config: RpcRateLimitConfig,
+    // client => the limiting state of the client
+    clients: Mutex<LruCache<ClientKey, Arc<ClientLimiter>>>,
+}
+
+impl RateLimit {
+    fn new(config: RpcRateLimitConfig) -> Self {
+        RateLimit {
+            config,
+            clients: Mutex::new(LruCache::new(MAX_CLIENTS)),
+        }
+    }
+
+    /// Consumes the quota of a call of the current client.
+    fn acquire(&self, cost: u32) -> jsonrpc_core::Result<Option<OwnedSemaphorePermit>> {
+        match CLIENT.try_with(|client| self.client_limiter(client)) {
+            Ok(limiter) => limiter.acquire(cost),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn client_limiter(&self, client: &ClientKey) -> Arc<ClientLimiter> {
+        let mut clients = self.clients.lock();
+        if let Some(limiter) = clients.get(client) {
+            return Arc::clone(limiter);
+        }
+        let limiter = Arc::new(ClientLimiter::new(&self.config));
+        clients.put(client.clone(), Arc::clone(&limiter));
+        limiter
+    }
+}
+
+struct ClientLimiter {
+    rate: Option<RateLimiter>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+impl ClientLimiter {
+    fn new(config: &RpcRateLimitConfig) -> Self {
+        let rate = NonZeroU32::new(config.requests_per_second).map(|requests_per_second| {
+            let burst = config
+                .burst
+                .and_then(NonZeroU32::new)
+                .unwrap_or(requests_per_second);
+            RateLimiter::direct(Quota::per_second(requests_per_second).allow_burst(burst))
+        });
+        let in_flight = config
+            .max_concurrent_requests
+            .map(|max| Arc::new(Semaphore::new(max)));
+        ClientLimiter { rate, in_flight }
+    }
+
+    /// Consumes the quota of a call, the returned permit must be held until the call finishes.
+    fn acquire(&self, cost: u32) -> jsonrpc_core::Result<Option<OwnedSemaphorePermit>> {
+        let permit = match &self.in_flight {
+            Some(in_flight) => {
+                Some(Arc::clone(in_flight).try_acquire_owned().map_err(|_| {
+                    RPCError::rate_limit_exceeded("too many requests in processing")
+                })?)
+            }
+            None => None,
+        };
+        if let (Some(rate), Some(cost)) = (&self.rate, NonZeroU32::new(cost)) {
+            rate.check_n(cost)
+                .map_err(|_| RPCError::rate_limit_exceeded("too many requests per second"))?;
+        }
+        Ok(permit)
+    }
+}
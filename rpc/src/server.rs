@@ -1,7 +1,18 @@
+use crate::auth::AccessControl;
+use crate::metrics::{RequestMetrics, RequestMetricsConfig};
+use crate::rate_limit::ClientKey;
 use crate::IoHandler;
-use axum::response::IntoResponse;
+use axum::body::Bytes;
+use axum::extract::ws::{Message, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, State};
+use axum::http::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    Request,
+};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
-use axum::{Extension, Router};
+use axum::{Extension, Json, Router};
 use ckb_app_config::RpcConfig;
 use ckb_async_runtime::Handle;
 use ckb_error::AnyError;
@@ -9,10 +20,7 @@ use ckb_logger::info;
 
 use axum::http::StatusCode;
 use ckb_stop_handler::{new_tokio_exit_rx, CancellationToken};
-use futures_util::{SinkExt, TryStreamExt};
-use jsonrpc_core::MetaIoHandler;
-use jsonrpc_utils::axum_utils::{handle_jsonrpc, handle_jsonrpc_ws};
-use jsonrpc_utils::pub_sub::Session;
+use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use jsonrpc_utils::stream::{serve_stream_sink, StreamMsg, StreamServerConfig};
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::sync::Arc;
@@ -22,6 +30,9 @@ use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec, LinesCodecError};
 use tower_http::cors::CorsLayer;
 use tower_http::timeout::TimeoutLayer;
 
+/// TCP clients send the credential in this line before any requests.
+const TCP_AUTHORIZATION_PREFIX: &str = "Authorization:";
+
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct RpcServer {
@@ -36,16 +47,21 @@ impl RpcServer {
     /// ## Parameters
     ///
     /// * `config` - RPC config options.
-    /// * `io_handler` - RPC methods handler. See [ServiceBuilder](../service_builder/struct.ServiceBuilder.html).
+    /// * `access_control` - RPC methods handlers. See [ServiceBuilder](../service_builder/struct.ServiceBuilder.html).
     /// * `handler` - Tokio runtime handle.
-    pub fn new(config: RpcConfig, io_handler: IoHandler, handler: Handle) -> Self {
-        let rpc = Arc::new(io_handler);
+    pub fn new(config: RpcConfig, access_control: AccessControl, handler: Handle) -> Self {
+        let rpc = Arc::new(access_control);
+        let metrics_config = RequestMetricsConfig {
+            slow_call_threshold: config.slow_call_threshold_ms.map(Duration::from_millis),
+            methods: Arc::new(rpc.methods()),
+        };
 
         let http_address = Self::start_server(
             &rpc,
             config.listen_address.to_owned(),
             handler.clone(),
             false,
+            metrics_config.clone(),
         )
         .map(|local_addr| {
             info!("Listen HTTP RPCServer on address: {}", local_addr);
@@ -54,17 +70,20 @@ impl RpcServer {
         .unwrap();
 
         let ws_address = if let Some(addr) = config.ws_listen_address {
-            let local_addr = Self::start_server(&rpc, addr, handler.clone(), true).map(|addr| {
-                info!("Listen WebSocket RPCServer on address: {}", addr);
-                addr
-            });
+            let local_addr =
+                Self::start_server(&rpc, addr, handler.clone(), true, metrics_config.clone()).map(
+                    |addr| {
+                        info!("Listen WebSocket RPCServer on address: {}", addr);
+                        addr
+                    },
+                );
             local_addr.ok()
         } else {
             None
         };
 
         let tcp_address = if let Some(addr) = config.tcp_listen_address {
-            let local_addr = handler.block_on(Self::start_tcp_server(rpc, addr));
+            let local_addr = handler.block_on(Self::start_tcp_server(rpc, addr, metrics_config));
             if let Ok(addr) = &local_addr {
                 info!("Listen TCP RPCServer on address: {}", addr);
             };
@@ -81,10 +100,11 @@ impl RpcServer {
     }
 
     fn start_server(
-        rpc: &Arc<MetaIoHandler<Option<Session>>>,
+        rpc: &Arc<AccessControl>,
         address: String,
         handler: Handle,
         enable_websocket: bool,
+        metrics_config: RequestMetricsConfig,
     ) -> Result<SocketAddr, AnyError> {
         let stream_config = StreamServerConfig::default()
             .with_keep_alive(true)
@@ -94,9 +114,9 @@ impl RpcServer {
             });
 
         // HTTP and WS server.
-        let post_router = post(handle_jsonrpc::<Option<Session>>);
+        let post_router = post(handle_http_jsonrpc);
         let get_router = if enable_websocket {
-            get(handle_jsonrpc_ws::<Option<Session>>)
+            get(handle_jsonrpc_ws)
         } else {
             get(get_error_handler)
         };
@@ -106,10 +126,14 @@ impl RpcServer {
             .route("/", method_router.clone())
             .route("/*path", method_router)
             .route("/ping", get(ping_handler))
-            .layer(Extension(Arc::clone(rpc)))
+            .layer(middleware::from_fn_with_state(
+                Arc::clone(rpc),
+                authorize_handler,
+            ))
             .layer(CorsLayer::permissive())
             .layer(TimeoutLayer::new(Duration::from_secs(30)))
-            .layer(Extension(stream_config));
+            .layer(Extension(stream_config))
+            .layer(Extension(metrics_config));
 
         let (tx_addr, rx_addr) = tokio::sync::oneshot::channel::<SocketAddr>();
 
@@ -121,7 +145,10 @@ impl RpcServer {
                     .next()
                     .expect("config listen_address parsed"),
             )
-            .serve(app.clone().into_make_service());
+            .serve(
+                app.clone()
+                    .into_make_service_with_connect_info::<SocketAddr>(),
+            );
 
             let _ = tx_addr.send(server.local_addr());
             let graceful = server.with_graceful_shutdown(async move {
@@ -135,8 +162,9 @@ impl RpcServer {
     }
 
     async fn start_tcp_server(
-        rpc: Arc<MetaIoHandler<Option<Session>>>,
+        rpc: Arc<AccessControl>,
         tcp_listen_address: String,
+        metrics_config: RequestMetricsConfig,
     ) -> Result<SocketAddr, AnyError> {
         // TCP server with line delimited json codec.
         let listener = TcpListener::bind(tcp_listen_address).await?;
@@ -153,21 +181,47 @@ impl RpcServer {
             let exit_signal: CancellationToken = new_tokio_exit_rx();
             tokio::select! {
                 _ = async {
-                        while let Ok((stream, _)) = listener.accept().await {
+                        while let Ok((stream, remote)) = listener.accept().await {
                             let rpc = Arc::clone(&rpc);
                             let stream_config = stream_config.clone();
+                            let metrics_config = metrics_config.clone();
                             let codec = codec.clone();
                             tokio::spawn(async move {
                                 let (r, w) = stream.into_split();
-                                let r = FramedRead::new(r, codec.clone()).map_ok(StreamMsg::Str);
-                                let w = FramedWrite::new(w, codec).with(|msg| async move {
-                                    Ok::<_, LinesCodecError>(match msg {
-                                        StreamMsg::Str(msg) => msg,
+                                let mut r = FramedRead::new(r, codec.clone());
+                                // The first line is either the credential or the first request.
+                                let first_line = match r.try_next().await {
+                                    Ok(Some(line)) => line,
+                                    _ => return,
+                                };
+                                let authorization = first_line.strip_prefix(TCP_AUTHORIZATION_PREFIX);
+                                let (client, rpc) = match rpc.authorize(authorization, remote.ip()) {
+                                    Some(authorized) => authorized,
+                                    None => {
+                                        info!("TCP RPCServer rejected the unknown credential");
+                                        return;
+                                    }
+                                };
+                                let first_line = authorization.is_none().then_some(first_line);
+                                let metrics = Arc::new(RequestMetrics::new("tcp", metrics_config));
+                                let read_metrics = Arc::clone(&metrics);
+                                let r = futures_util::stream::iter(first_line.map(Ok))
+                                    .chain(r)
+                                    .inspect_ok(move |line| read_metrics.observe_request(line))
+                                    .map_ok(StreamMsg::Str);
+                                let w = FramedWrite::new(w, codec).with(move |msg: StreamMsg| {
+                                    let line = match msg {
+                                        StreamMsg::Str(msg) => {
+                                            metrics.observe_response(&msg);
+                                            msg
+                                        }
                                         _ => "".into(),
-                                    })
+                                    };
+                                    async move { Ok::<_, LinesCodecError>(line) }
                                 });
                                 tokio::pin!(w);
-                                if let Err(err) = serve_stream_sink(&rpc, w, r, stream_config).await {
+                                let serve = serve_stream_sink(&rpc, w, r, stream_config);
+                                if let Err(err) = client.scope(serve).await {
                                     info!("TCP RPCServer error: {:?}", err);
                                 }
                             });
@@ -182,6 +236,109 @@ impl RpcServer {
     }
 }
 
+/// Picks the RPC methods handler and identifies the client according to the credential in the
+/// `Authorization` header
+async fn authorize_handler<B>(
+    State(access_control): State<Arc<AccessControl>>,
+    ConnectInfo(remote): ConnectInfo<SocketAddr>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let authorization = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    match access_control.authorize(authorization, remote.ip()) {
+        Some((client, rpc)) => {
+            request.extensions_mut().insert(client);
+            request.extensions_mut().insert(rpc);
+            next.run(request).await
+        }
+        None => (
+            StatusCode::UNAUTHORIZED,
+            "Unknown credential in the Authorization header",
+        )
+            .into_response(),
+    }
+}
+
+/// Handles the HTTP JSON-RPC requests and records the metrics of the request and the response
+async fn handle_http_jsonrpc(
+    Extension(client): Extension<ClientKey>,
+    Extension(rpc): Extension<Arc<IoHandler>>,
+    Extension(metrics_config): Extension<RequestMetricsConfig>,
+    body: Bytes,
+) -> Response {
+    let request = match std::str::from_utf8(body.as_ref()) {
+        Ok(request) => request,
+        Err(_) => {
+            return Json(jsonrpc_core::Failure {
+                jsonrpc: Some(jsonrpc_core::Version::V2),
+                error: jsonrpc_core::Error::parse_error(),
+                id: jsonrpc_core::Id::Null,
+            })
+            .into_response();
+        }
+    };
+
+    let metrics = RequestMetrics::new("http", metrics_config);
+    metrics.observe_request(request);
+    // the methods are called in `handle_request`, which must run in the scope of the client
+    let response = client
+        .scope(async { rpc.handle_request(request, None).await })
+        .await;
+    match response {
+        Some(response) => {
+            metrics.observe_response(&response);
+            ([(CONTENT_TYPE, "application/json")], response).into_response()
+        }
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// Handles the JSON-RPC requests over WebSocket and records the metrics of the requests and the
+/// responses
+async fn handle_jsonrpc_ws(
+    Extension(client): Extension<ClientKey>,
+    Extension(rpc): Extension<Arc<IoHandler>>,
+    Extension(stream_config): Extension<StreamServerConfig>,
+    Extension(metrics_config): Extension<RequestMetricsConfig>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        let metrics = Arc::new(RequestMetrics::new("ws", metrics_config));
+        let read_metrics = Arc::clone(&metrics);
+        let (socket_write, socket_read) = socket.split();
+        let write = socket_write.with(move |msg: StreamMsg| {
+            let msg = match msg {
+                StreamMsg::Str(msg) => {
+                    metrics.observe_response(&msg);
+                    Message::Text(msg)
+                }
+                StreamMsg::Ping => Message::Ping(b"ping".to_vec()),
+                StreamMsg::Pong => Message::Pong(vec![]),
+            };
+            async move { Ok::<_, axum::Error>(msg) }
+        });
+        let read = socket_read.filter_map(move |msg| {
+            let msg = match msg {
+                Ok(Message::Text(text)) => {
+                    read_metrics.observe_request(&text);
+                    Some(Ok(StreamMsg::Str(text)))
+                }
+                Ok(Message::Pong(_)) => Some(Ok(StreamMsg::Pong)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            };
+            async move { msg }
+        });
+        tokio::pin!(write);
+        tokio::pin!(read);
+        let serve = serve_stream_sink(&rpc, write, read, stream_config);
+        drop(client.scope(serve).await);
+    })
+}
+
 /// used for compatible with old health endpoint
 async fn ping_handler() -> impl IntoResponse {
     "pong"
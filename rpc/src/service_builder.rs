@@ -1,14 +1,16 @@
 #![allow(deprecated)]
+use crate::auth::AccessControl;
+use crate::metrics::instrument;
 use crate::module::{
-    add_alert_rpc_methods, add_chain_rpc_methods, add_debug_rpc_methods,
+    add_admin_rpc_methods, add_alert_rpc_methods, add_chain_rpc_methods, add_debug_rpc_methods,
     add_experiment_rpc_methods, add_indexer_rpc_methods, add_integration_test_rpc_methods,
     add_miner_rpc_methods, add_net_rpc_methods, add_pool_rpc_methods, add_rich_indexer_rpc_methods,
-    add_stats_rpc_methods, add_subscription_rpc_methods, AlertRpcImpl, ChainRpcImpl, DebugRpcImpl,
-    ExperimentRpcImpl, IndexerRpcImpl, IntegrationTestRpcImpl, MinerRpcImpl, NetRpcImpl,
-    PoolRpcImpl, RichIndexerRpcImpl, StatsRpcImpl, SubscriptionRpcImpl,
+    add_stats_rpc_methods, add_subscription_rpc_methods, AdminRpcImpl, AlertRpcImpl, ChainRpcImpl,
+    DebugRpcImpl, ExperimentRpcImpl, IndexerRpcImpl, IntegrationTestRpcImpl, MinerRpcImpl,
+    NetRpcImpl, PoolRpcImpl, RichIndexerRpcImpl, StatsRpcImpl, SubscriptionRpcImpl,
 };
 use crate::{IoHandler, RPCError};
-use ckb_app_config::{DBConfig, IndexerConfig, RpcConfig};
+use ckb_app_config::{DBConfig, IndexerConfig, RpcConfig, RpcModule};
 use ckb_chain::chain::ChainController;
 use ckb_indexer::IndexerService;
 use ckb_indexer_sync::{new_secondary_db, PoolService};
@@ -22,6 +24,7 @@ use ckb_types::packed::Script;
 use ckb_util::Mutex;
 use jsonrpc_core::{MetaIoHandler, RemoteProcedure};
 use jsonrpc_utils::pub_sub::Session;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 const DEPRECATED_RPC_PREFIX: &str = "deprecated.";
@@ -30,16 +33,18 @@ const DEPRECATED_RPC_PREFIX: &str = "deprecated.";
 pub struct ServiceBuilder<'a> {
     config: &'a RpcConfig,
     io_handler: IoHandler,
+    // method name => the module which the method belongs to
+    method_modules: HashMap<String, RpcModule>,
 }
 
 macro_rules! set_rpc_module_methods {
-    ($self:ident, $name:expr, $check:ident, $add_methods:ident, $methods:expr) => {{
+    ($self:ident, $module:expr, $check:ident, $add_methods:ident, $methods:expr) => {{
         let mut meta_io = MetaIoHandler::default();
         $add_methods(&mut meta_io, $methods);
         if $self.config.$check() {
-            $self.add_methods(meta_io);
+            $self.add_methods($module, meta_io);
         } else {
-            $self.update_disabled_methods($name, meta_io);
+            $self.update_disabled_methods($module, meta_io);
         }
         $self
     }};
@@ -51,13 +56,20 @@ impl<'a> ServiceBuilder<'a> {
         Self {
             config,
             io_handler: IoHandler::with_compatibility(jsonrpc_core::Compatibility::V2),
+            method_modules: HashMap::new(),
         }
     }
 
     /// Mounts methods from module Chain if it is enabled in the config.
     pub fn enable_chain(mut self, shared: Shared) -> Self {
         let methods = ChainRpcImpl { shared };
-        set_rpc_module_methods!(self, "Chain", chain_enable, add_chain_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Chain,
+            chain_enable,
+            add_chain_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Pool if it is enabled in the config.
@@ -72,7 +84,13 @@ impl<'a> ServiceBuilder<'a> {
             extra_well_known_lock_scripts,
             extra_well_known_type_scripts,
         );
-        set_rpc_module_methods!(self, "Pool", pool_enable, add_pool_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Pool,
+            pool_enable,
+            add_pool_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Miner if `enable` is `true` and it is enabled in the config.
@@ -91,9 +109,9 @@ impl<'a> ServiceBuilder<'a> {
         };
         add_miner_rpc_methods(&mut meta_io, methods);
         if enable && self.config.miner_enable() {
-            self.add_methods(meta_io);
+            self.add_methods(RpcModule::Miner, meta_io);
         } else {
-            self.update_disabled_methods("Miner", meta_io);
+            self.update_disabled_methods(RpcModule::Miner, meta_io);
         }
         self
     }
@@ -108,7 +126,13 @@ impl<'a> ServiceBuilder<'a> {
             network_controller,
             sync_shared,
         };
-        set_rpc_module_methods!(self, "Net", net_enable, add_net_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Net,
+            net_enable,
+            add_net_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Stats if it is enabled in the config.
@@ -121,7 +145,13 @@ impl<'a> ServiceBuilder<'a> {
             shared,
             alert_notifier,
         };
-        set_rpc_module_methods!(self, "Stats", stats_enable, add_stats_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Stats,
+            stats_enable,
+            add_stats_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Experiment if it is enabled in the config.
@@ -129,7 +159,7 @@ impl<'a> ServiceBuilder<'a> {
         let methods = ExperimentRpcImpl { shared };
         set_rpc_module_methods!(
             self,
-            "Experiment",
+            RpcModule::Experiment,
             experiment_enable,
             add_experiment_rpc_methods,
             methods
@@ -158,7 +188,7 @@ impl<'a> ServiceBuilder<'a> {
         };
         set_rpc_module_methods!(
             self,
-            "IntegrationTest",
+            RpcModule::IntegrationTest,
             integration_test_enable,
             add_integration_test_rpc_methods,
             methods
@@ -173,13 +203,37 @@ impl<'a> ServiceBuilder<'a> {
         network_controller: NetworkController,
     ) -> Self {
         let methods = AlertRpcImpl::new(alert_verifier, alert_notifier, network_controller);
-        set_rpc_module_methods!(self, "Alert", alert_enable, add_alert_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Alert,
+            alert_enable,
+            add_alert_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Debug if it is enabled in the config.
-    pub fn enable_debug(mut self) -> Self {
-        let methods = DebugRpcImpl {};
-        set_rpc_module_methods!(self, "Debug", debug_enable, add_debug_rpc_methods, methods)
+    pub fn enable_debug(mut self, shared: Shared) -> Self {
+        let methods = DebugRpcImpl { shared };
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Debug,
+            debug_enable,
+            add_debug_rpc_methods,
+            methods
+        )
+    }
+
+    /// Mounts methods from module Admin if it is enabled in the config.
+    pub fn enable_admin(mut self, shared: Shared) -> Self {
+        let methods = AdminRpcImpl::new(shared);
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Admin,
+            admin_enable,
+            add_admin_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Indexer if it is enabled in the config.
@@ -211,7 +265,7 @@ impl<'a> ServiceBuilder<'a> {
             let methods = IndexerRpcImpl::new(indexer_handle);
             self = set_rpc_module_methods!(
                 self,
-                "Indexer",
+                RpcModule::Indexer,
                 indexer_enable,
                 add_indexer_rpc_methods,
                 methods
@@ -235,7 +289,7 @@ impl<'a> ServiceBuilder<'a> {
             let rich_indexer_methods = RichIndexerRpcImpl::new(rich_indexer_handle);
             self = set_rpc_module_methods!(
                 self,
-                "RichIndexer",
+                RpcModule::RichIndexer,
                 rich_indexer_enable,
                 add_rich_indexer_rpc_methods,
                 rich_indexer_methods
@@ -247,23 +301,27 @@ impl<'a> ServiceBuilder<'a> {
     pub fn enable_subscription(&mut self, shared: Shared) {
         if self.config.subscription_enable() {
             let methods = SubscriptionRpcImpl::new(
+                shared.clone(),
                 shared.notify_controller().clone(),
                 shared.async_handle().clone(),
             );
             let mut meta_io = MetaIoHandler::default();
             add_subscription_rpc_methods(&mut meta_io, methods);
-            self.add_methods(meta_io);
+            self.add_methods(RpcModule::Subscription, meta_io);
         }
     }
 
-    fn add_methods<I>(&mut self, rpc_methods: I)
+    fn add_methods<I>(&mut self, module: RpcModule, rpc_methods: I)
     where
         I: IntoIterator<Item = (String, RemoteProcedure<Option<Session>>)>,
     {
         let enable_deprecated_rpc = self.config.enable_deprecated_rpc;
+        let method_modules = &mut self.method_modules;
         self.io_handler
             .extend_with(rpc_methods.into_iter().map(|(name, method)| {
-                if let Some(striped_method_name) = name.strip_prefix(DEPRECATED_RPC_PREFIX) {
+                let striped_method_name = name.strip_prefix(DEPRECATED_RPC_PREFIX);
+                method_modules.insert(striped_method_name.unwrap_or(&name).to_owned(), module);
+                if let Some(striped_method_name) = striped_method_name {
                     (
                         striped_method_name.to_owned(),
                         if enable_deprecated_rpc {
@@ -280,26 +338,32 @@ impl<'a> ServiceBuilder<'a> {
             }));
     }
 
-    fn update_disabled_methods<I, M>(&mut self, module: &str, rpc_methods: I)
+    fn update_disabled_methods<I, M>(&mut self, module: RpcModule, rpc_methods: I)
     where
         I: IntoIterator<Item = (String, M)>,
     {
         rpc_methods.into_iter().for_each(|(name, _method)| {
-            let error = Err(RPCError::rpc_module_is_disabled(module));
-            self.io_handler.add_sync_method(
-                name.split(DEPRECATED_RPC_PREFIX)
-                    .collect::<Vec<&str>>()
-                    .last()
-                    .unwrap(),
-                move |_param| error.clone(),
-            )
+            let error = Err(RPCError::rpc_module_is_disabled(&format!("{module:?}")));
+            let name = name
+                .split(DEPRECATED_RPC_PREFIX)
+                .collect::<Vec<&str>>()
+                .last()
+                .unwrap()
+                .to_string();
+            self.io_handler
+                .add_sync_method(&name, move |_param| error.clone());
+            self.method_modules.insert(name, module);
         });
     }
 
-    /// Builds the RPC methods handler used in the RPC server.
-    pub fn build(self) -> IoHandler {
+    /// Builds the RPC methods handlers used in the RPC server.
+    ///
+    /// There is a handler for each credential configured in `rpc.auth`, which only serves the
+    /// methods of the modules the credential is granted to access.
+    pub fn build(self) -> AccessControl {
         let mut io_handler = self.io_handler;
         io_handler.add_method("ping", |_| async { Ok("pong".into()) });
-        io_handler
+        let io_handler = instrument(io_handler);
+        AccessControl::new(self.config, io_handler, &self.method_modules)
     }
 }
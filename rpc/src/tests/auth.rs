@@ -0,0 +1,158 @@
+use crate::auth::AccessControl;
+use crate::{IoHandler, RPCError};
+use ckb_app_config::{RpcAuthConfig, RpcConfig, RpcCredential, RpcModule, RpcRateLimitConfig};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+
+const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+fn new_access_control(
+    auth: Option<RpcAuthConfig>,
+    rate_limit: Option<RpcRateLimitConfig>,
+) -> AccessControl {
+    let config = RpcConfig {
+        listen_address: "127.0.0.1:0".to_owned(),
+        tcp_listen_address: None,
+        ws_listen_address: None,
+        max_request_body_size: 20_000_000,
+        threads: None,
+        modules: vec![RpcModule::Chain, RpcModule::Net],
+        reject_ill_transactions: true,
+        enable_deprecated_rpc: false,
+        extra_well_known_lock_scripts: vec![],
+        extra_well_known_type_scripts: vec![],
+        auth,
+        rate_limit,
+        slow_call_threshold_ms: None,
+    };
+    let mut io_handler = IoHandler::with_compatibility(jsonrpc_core::Compatibility::V2);
+    io_handler.add_sync_method("get_tip_block_number", |_| Ok("0x0".into()));
+    io_handler.add_sync_method("set_ban", |_| Ok(Value::Null));
+    io_handler.add_sync_method("ping", |_| Ok("pong".into()));
+    let method_modules: HashMap<String, RpcModule> = vec![
+        ("get_tip_block_number".to_owned(), RpcModule::Chain),
+        ("set_ban".to_owned(), RpcModule::Net),
+    ]
+    .into_iter()
+    .collect();
+    AccessControl::new(&config, io_handler, &method_modules)
+}
+
+// Returns the error code, or `None` if the call succeeds.
+fn call(access_control: &AccessControl, authorization: Option<&str>, method: &str) -> Option<i64> {
+    call_from(access_control, authorization, LOCALHOST, method)
+}
+
+fn call_from(
+    access_control: &AccessControl,
+    authorization: Option<&str>,
+    remote: IpAddr,
+    method: &str,
+) -> Option<i64> {
+    let (client, handler) = access_control
+        .authorize(authorization, remote)
+        .expect("known credential");
+    let request = format!(r#"{{"id": 42, "jsonrpc": "2.0", "method": "{method}", "params": []}}"#);
+    let response = client.sync_scope(|| handler.handle_request_sync(&request, None).unwrap());
+    let response: Value = serde_json::from_str(&response).unwrap();
+    response["error"]["code"].as_i64()
+}
+
+#[test]
+fn test_access_control_disabled() {
+    let access_control = new_access_control(None, None);
+    for authorization in [None, Some("Bearer unknown")] {
+        assert_eq!(
+            call(&access_control, authorization, "get_tip_block_number"),
+            None
+        );
+        assert_eq!(call(&access_control, authorization, "set_ban"), None);
+    }
+}
+
+#[test]
+fn test_access_control() {
+    let config = RpcAuthConfig {
+        public_modules: vec![RpcModule::Chain],
+        credentials: vec![
+            RpcCredential {
+                token: Some("secret".to_owned()),
+                username: None,
+                password: None,
+                modules: vec![RpcModule::Net],
+            },
+            RpcCredential {
+                token: None,
+                username: Some("admin".to_owned()),
+                password: Some("password".to_owned()),
+                modules: vec![RpcModule::Chain, RpcModule::Net],
+            },
+        ],
+    };
+    let access_control = new_access_control(Some(config), None);
+    let unauthorized = Some(RPCError::RPCModuleIsUnauthorized as i64);
+
+    // public
+    assert_eq!(call(&access_control, None, "get_tip_block_number"), None);
+    assert_eq!(call(&access_control, None, "set_ban"), unauthorized);
+    assert_eq!(call(&access_control, None, "ping"), None);
+
+    // bearer token
+    let token = Some("Bearer secret");
+    assert_eq!(
+        call(&access_control, token, "get_tip_block_number"),
+        unauthorized
+    );
+    assert_eq!(call(&access_control, token, "set_ban"), None);
+    assert_eq!(call(&access_control, token, "ping"), None);
+
+    // basic auth, base64 of "admin:password"
+    let basic = Some("Basic YWRtaW46cGFzc3dvcmQ=");
+    assert_eq!(call(&access_control, basic, "get_tip_block_number"), None);
+    assert_eq!(call(&access_control, basic, "set_ban"), None);
+
+    // unknown credentials
+    assert!(access_control
+        .authorize(Some("Bearer unknown"), LOCALHOST)
+        .is_none());
+    assert!(access_control
+        .authorize(Some("Basic YWRtaW46d3Jvbmc="), LOCALHOST)
+        .is_none());
+}
+
+#[test]
+fn test_rate_limit() {
+    let rate_limit = RpcRateLimitConfig {
+        requests_per_second: 1,
+        burst: Some(3),
+        max_concurrent_requests: None,
+        method_costs: vec![("set_ban".to_owned(), 2)].into_iter().collect(),
+    };
+    let auth = RpcAuthConfig {
+        public_modules: vec![RpcModule::Chain, RpcModule::Net],
+        credentials: vec![RpcCredential {
+            token: Some("secret".to_owned()),
+            username: None,
+            password: None,
+            modules: vec![RpcModule::Chain, RpcModule::Net],
+        }],
+    };
+    let access_control = new_access_control(Some(auth), Some(rate_limit));
+    let exceeded = Some(RPCError::RateLimitExceeded as i64);
+
+    assert_eq!(call(&access_control, None, "set_ban"), None);
+    assert_eq!(call(&access_control, None, "get_tip_block_number"), None);
+    assert_eq!(call(&access_control, None, "set_ban"), exceeded);
+    assert_eq!(
+        call(&access_control, None, "get_tip_block_number"),
+        exceeded
+    );
+
+    // other clients are limited separately
+    let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    assert_eq!(call_from(&access_control, None, other, "set_ban"), None);
+    let token = Some("Bearer secret");
+    assert_eq!(call(&access_control, token, "set_ban"), None);
+    assert_eq!(call(&access_control, token, "set_ban"), exceeded);
+}
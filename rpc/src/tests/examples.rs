@@ -102,10 +102,19 @@ fn collect_code_block(
 }
 
 fn construct_example_transaction() -> TransactionView {
+    construct_example_transaction_with_capacity(capacity_bytes!(100))
+}
+
+// The example tx is replaced by the tx paying a higher fee via RBF
+fn construct_example_replacement_transaction() -> TransactionView {
+    construct_example_transaction_with_capacity(capacity_bytes!(99))
+}
+
+fn construct_example_transaction_with_capacity(capacity: Capacity) -> TransactionView {
     let previous_output = OutPoint::new(EXAMPLE_TX_PARENT.clone().pack(), 0);
     let input = CellInput::new(previous_output, 0);
     let output = CellOutputBuilder::default()
-        .capacity(capacity_bytes!(100).pack())
+        .capacity(capacity.pack())
         .lock(always_success_cell().2.clone())
         .build();
     let cell_dep = CellDep::new_builder()
@@ -290,6 +299,17 @@ impl RpcTestSuite {
         });
     }
 
+    fn send_example_replacement_transaction(&self) {
+        let replacement_tx: ckb_jsonrpc_types::Transaction =
+            construct_example_replacement_transaction().data().into();
+        self.rpc(&RpcTestRequest {
+            id: 42,
+            jsonrpc: "2.0".to_string(),
+            method: "send_transaction".to_string(),
+            params: vec![json!(replacement_tx), json!("passthrough")],
+        });
+    }
+
     fn wait_block_template_update(&self) {
         self.wait_block_template_array_ge("proposals", 1)
     }
@@ -382,10 +402,16 @@ fn mock_rpc_response(example: &RpcTestExample, response: &mut RpcTestResponse) {
             response.result["pending"][example_tx_hash.as_str()]["timestamp"] =
                 example.response.result["pending"][example_tx_hash.as_str()]["timestamp"].clone()
         }
+        "get_raw_tx_pool_with_sequence" => {
+            response.result["sequence"] = example.response.result["sequence"].clone()
+        }
         "generate_block_with_template" => replace_rpc_response::<H256>(example, response),
         "generate_block" => replace_rpc_response::<H256>(example, response),
         "process_block_without_verify" => replace_rpc_response::<H256>(example, response),
         "notify_transaction" => replace_rpc_response::<H256>(example, response),
+        "get_removed_transactions" => {
+            response.result[0]["timestamp"] = example.response.result[0]["timestamp"].clone()
+        }
         "get_pool_tx_detail_info" => {
             response.result["timestamp"] = example.response.result["timestamp"].clone()
         }
@@ -412,7 +438,11 @@ fn before_rpc_example(suite: &RpcTestSuite, example: &mut RpcTestExample) -> boo
         ("generate_block_with_template", 42) => return false,
         ("process_block_without_verify", 42) => return false,
         ("notify_transaction", 42) => return false,
+        ("get_removed_transactions", 42) => suite.send_example_replacement_transaction(),
         ("truncate", 42) => return false,
+        ("backup_database", 42) => return false,
+        ("compact_database", 42) => return false,
+        ("get_compaction_status", 42) => return false,
         ("get_block_template", 42) => suite.wait_block_template_update(),
         _ => return true,
     }
@@ -424,7 +454,7 @@ fn before_rpc_example(suite: &RpcTestSuite, example: &mut RpcTestExample) -> boo
 fn after_rpc_example(suite: &RpcTestSuite, example: &RpcTestExample) {
     match example.request.method.as_str() {
         "clear_tx_pool" => suite.send_example_transaction(),
-        "send_transaction" => {
+        "send_transaction" | "send_transaction_package" | "get_removed_transactions" => {
             suite.rpc(&RpcTestRequest {
                 id: 42,
                 jsonrpc: "2.0".to_string(),
@@ -0,0 +1,139 @@
+use crate::metrics::{RequestMetrics, RequestMetricsConfig};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn request_metrics(transport: &'static str) -> RequestMetrics {
+    let _ = ckb_metrics::METRICS_SERVICE_ENABLED.set(true);
+    let methods = [
+        "test_single_call",
+        "test_batch_a",
+        "test_batch_b",
+        "test_batch_notification",
+        "test_duplicated_call",
+        "test_pending_call",
+    ];
+    RequestMetrics::new(
+        transport,
+        RequestMetricsConfig {
+            slow_call_threshold: Some(Duration::from_secs(60)),
+            methods: Arc::new(methods.iter().map(|method| method.to_string()).collect()),
+        },
+    )
+}
+
+fn batch_sizes(transport: &str) -> (u64, f64) {
+    let histogram = ckb_metrics::handle()
+        .expect("metrics enabled")
+        .ckb_rpc_batch_size
+        .with_label_values(&[transport]);
+    (histogram.get_sample_count(), histogram.get_sample_sum())
+}
+
+fn response_bytes(method: &str) -> (u64, f64) {
+    let histogram = ckb_metrics::handle()
+        .expect("metrics enabled")
+        .ckb_rpc_response_bytes
+        .with_label_values(&[method]);
+    (histogram.get_sample_count(), histogram.get_sample_sum())
+}
+
+#[test]
+fn test_observe_single_call() {
+    let metrics = request_metrics("test_single");
+    metrics.observe_request(
+        r#"{"id": 1, "jsonrpc": "2.0", "method": "test_single_call", "params": [1, 2]}"#,
+    );
+    assert_eq!(batch_sizes("test_single"), (1, 1.0));
+
+    let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+    metrics.observe_response(response);
+    assert_eq!(
+        response_bytes("test_single_call"),
+        (1, response.len() as f64)
+    );
+
+    // the call has been responded
+    metrics.observe_response(response);
+    assert_eq!(
+        response_bytes("test_single_call"),
+        (1, response.len() as f64)
+    );
+}
+
+#[test]
+fn test_observe_batch_out_of_order() {
+    let metrics = request_metrics("test_batch");
+    metrics.observe_request(
+        r#" [
+            {"id": 1, "jsonrpc": "2.0", "method": "test_batch_a"},
+            {"id": "2", "jsonrpc": "2.0", "method": "test_batch_b"},
+            {"jsonrpc": "2.0", "method": "test_batch_notification"}
+        ]"#,
+    );
+    assert_eq!(batch_sizes("test_batch"), (1, 3.0));
+
+    let a = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+    let b = r#"{"jsonrpc":"2.0","error":{"code":-1,"message":"err"},"id":"2"}"#;
+    metrics.observe_response(b);
+    metrics.observe_response(&format!("[{a}]"));
+    assert_eq!(response_bytes("test_batch_a"), (1, a.len() as f64));
+    assert_eq!(response_bytes("test_batch_b"), (1, b.len() as f64));
+
+    // the unknown responses, the subscription notifications and the invalid requests are ignored
+    metrics.observe_response(r#"{"jsonrpc":"2.0","result":"0x1","id":"1"}"#);
+    metrics.observe_response(r#"{"jsonrpc":"2.0","method":"subscribe","params":{}}"#);
+    metrics.observe_request("not json");
+    assert_eq!(batch_sizes("test_batch"), (1, 3.0));
+    assert_eq!(response_bytes("test_batch_a"), (1, a.len() as f64));
+    assert_eq!(response_bytes("test_batch_notification").0, 0);
+}
+
+#[test]
+fn test_observe_duplicated_ids() {
+    let metrics = request_metrics("test_duplicated");
+    let request = r#"{"id": 1, "jsonrpc": "2.0", "method": "test_duplicated_call"}"#;
+    metrics.observe_request(request);
+    metrics.observe_request(request);
+    assert_eq!(batch_sizes("test_duplicated"), (2, 2.0));
+
+    let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+    for _ in 0..3 {
+        metrics.observe_response(response);
+    }
+    assert_eq!(
+        response_bytes("test_duplicated_call"),
+        (2, 2.0 * response.len() as f64)
+    );
+}
+
+#[test]
+fn test_observe_unknown_method() {
+    let metrics = request_metrics("test_unknown");
+    let before = response_bytes("unknown").0;
+    metrics.observe_request(r#"{"id": 1, "jsonrpc": "2.0", "method": "test_not_registered"}"#);
+    metrics.observe_response(
+        r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#,
+    );
+    assert_eq!(response_bytes("test_not_registered").0, 0);
+    assert_eq!(response_bytes("unknown").0, before + 1);
+}
+
+#[test]
+fn test_pending_calls_are_capped() {
+    let metrics = request_metrics("test_pending");
+    // the responses of the invalid requests have the null ids, these calls are never responded
+    for id in 0..2000 {
+        metrics.observe_request(&format!(
+            r#"{{"id": {id}, "jsonrpc": "2.0", "method": "test_pending_call"}}"#
+        ));
+        metrics.observe_response(
+            r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":null}"#,
+        );
+    }
+    assert_eq!(metrics.pending_calls(), 1024);
+
+    // the responded calls are removed
+    metrics.observe_response(r#"{"jsonrpc":"2.0","result":null,"id":0}"#);
+    assert_eq!(metrics.pending_calls(), 1023);
+    assert_eq!(response_bytes("test_pending_call").0, 1);
+}
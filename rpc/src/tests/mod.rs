@@ -23,9 +23,11 @@ use tokio::net::TcpStream;
 
 use self::setup::setup_rpc_test_suite;
 
+mod auth;
 mod error;
 mod examples;
 mod fee_rate;
+mod metrics;
 mod module;
 mod setup;
 
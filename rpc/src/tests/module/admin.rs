@@ -0,0 +1,95 @@
+use ckb_store::BackupManifest;
+use ckb_types::prelude::*;
+use serde_json::json;
+
+use crate::tests::{setup_rpc_test_suite, RpcTestRequest};
+
+#[test]
+fn test_backup_database() {
+    let suite = setup_rpc_test_suite(20, None);
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let target = tmp_dir.path().join("backup");
+
+    let response = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "backup_database".to_string(),
+        params: vec![target.to_str().unwrap().into()],
+    });
+    assert_eq!(response.error, serde_json::Value::Null);
+
+    let tip = suite.shared.snapshot().tip_header().clone();
+    let manifest = BackupManifest::load(&target).unwrap();
+    assert_eq!(manifest.tip_number, tip.number());
+    assert_eq!(manifest.tip_hash, tip.hash().unpack());
+    assert_eq!(
+        response.result["tip_hash"],
+        format!("{:#x}", manifest.tip_hash)
+    );
+
+    // the existing directory is rejected
+    let response = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "backup_database".to_string(),
+        params: vec![target.to_str().unwrap().into()],
+    });
+    assert_ne!(response.error, serde_json::Value::Null);
+
+    // the relative path is rejected
+    let response = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "backup_database".to_string(),
+        params: vec!["backup".into()],
+    });
+    assert_ne!(response.error, serde_json::Value::Null);
+}
+
+#[test]
+fn test_compact_database() {
+    let suite = setup_rpc_test_suite(20, None);
+
+    let response = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "get_compaction_status".to_string(),
+        params: vec![],
+    });
+    assert_eq!(response.result, serde_json::Value::Null);
+
+    let response = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "compact_database".to_string(),
+        params: vec![json!({
+            "columns": ["block_header", "2"],
+            "throttle": "0x0",
+        })],
+    });
+    assert_eq!(response.error, serde_json::Value::Null);
+    assert_eq!(response.result["state"], "done");
+    assert_eq!(
+        response.result["columns"],
+        json!(["block_header", "block_body"])
+    );
+    assert_eq!(response.result["total_steps"], "0x200");
+    assert_eq!(response.result["completed_steps"], "0x200");
+
+    let status = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "get_compaction_status".to_string(),
+        params: vec![],
+    });
+    assert_eq!(status.result, response.result);
+
+    // the unknown column family is rejected
+    let response = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "compact_database".to_string(),
+        params: vec![json!({ "columns": ["no_such_column"] })],
+    });
+    assert_ne!(response.error, serde_json::Value::Null);
+}
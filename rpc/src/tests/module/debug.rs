@@ -0,0 +1,19 @@
+use crate::tests::{setup_rpc_test_suite, RpcTestRequest};
+
+#[test]
+fn test_get_db_stats() {
+    let suite = setup_rpc_test_suite(20, None);
+    let response = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "get_db_stats".to_string(),
+        params: vec![],
+    });
+    assert_eq!(response.error, serde_json::Value::Null);
+
+    let columns = response.result["columns"].as_array().unwrap();
+    assert_eq!(columns[1]["column"], "1");
+    assert_eq!(columns[1]["name"], "block_header");
+    // the freezer is disabled
+    assert_eq!(response.result["freezer"], serde_json::json!([]));
+}
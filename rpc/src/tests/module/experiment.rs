@@ -0,0 +1,103 @@
+use ckb_jsonrpc_types::{ScriptGroupType, TransactionSimulation};
+use ckb_store::ChainStore;
+use ckb_test_chain_utils::{always_success_cell, always_success_consensus};
+use ckb_types::{
+    core::{Capacity, ScriptHashType, TransactionBuilder, TransactionView},
+    h256,
+    packed::{CellDep, CellInput, CellOutputBuilder, OutPoint, Script},
+    prelude::*,
+};
+use serde_json::json;
+
+use crate::tests::{always_success_transaction, setup, RpcTestRequest, RpcTestSuite};
+
+fn build_tx(input: OutPoint, type_script: Option<Script>) -> TransactionView {
+    let output = CellOutputBuilder::default()
+        .capacity(Capacity::bytes(1000).unwrap().pack())
+        .lock(always_success_cell().2.clone())
+        .type_(type_script.pack())
+        .build();
+    let cell_dep = CellDep::new_builder()
+        .out_point(OutPoint::new(always_success_transaction().hash(), 0))
+        .build();
+    TransactionBuilder::default()
+        .input(CellInput::new(input, 0))
+        .output(output)
+        .output_data(Default::default())
+        .cell_dep(cell_dep)
+        .build()
+}
+
+fn simulate_transaction(suite: &RpcTestSuite, tx: &TransactionView) -> serde_json::Value {
+    let tx: ckb_jsonrpc_types::Transaction = tx.data().into();
+    let response = suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "simulate_transaction".to_string(),
+        params: vec![json!(tx)],
+    });
+    if response.error.is_null() {
+        response.result
+    } else {
+        response.error
+    }
+}
+
+#[test]
+fn test_simulate_transaction() {
+    let suite = setup(always_success_consensus());
+    let store = suite.shared.store();
+    let tip = store.get_tip_header().unwrap();
+    let cellbase = store.get_block(&tip.hash()).unwrap().transactions()[0].hash();
+    let always_success_script = always_success_cell().2.clone();
+
+    // the script groups are run one by one even if some of them fail
+    let missing_type_script = Script::new_builder()
+        .code_hash(h256!("0x1").pack())
+        .hash_type(ScriptHashType::Data.into())
+        .build();
+    let tx = build_tx(
+        OutPoint::new(cellbase.clone(), 0),
+        Some(missing_type_script.clone()),
+    );
+    let simulation: TransactionSimulation =
+        serde_json::from_value(simulate_transaction(&suite, &tx)).unwrap();
+    assert_eq!(simulation.script_groups.len(), 2);
+
+    let lock_group = &simulation.script_groups[0];
+    assert_eq!(lock_group.group_type, ScriptGroupType::Lock);
+    assert_eq!(
+        lock_group.script_hash,
+        always_success_script.calc_script_hash().unpack()
+    );
+    assert_eq!(lock_group.input_indices, vec![0u32.into()]);
+    assert!(lock_group.output_indices.is_empty());
+    assert_eq!(lock_group.exit_code, Some(0));
+    assert_eq!(lock_group.error, None);
+    let lock_cycles = lock_group.cycles.expect("lock script is run");
+    assert!(lock_cycles.value() > 0);
+
+    let type_group = &simulation.script_groups[1];
+    assert_eq!(type_group.group_type, ScriptGroupType::Type);
+    assert_eq!(
+        type_group.script_hash,
+        missing_type_script.calc_script_hash().unpack()
+    );
+    assert!(type_group.input_indices.is_empty());
+    assert_eq!(type_group.output_indices, vec![0u32.into()]);
+    assert_eq!(type_group.cycles, None);
+    assert_eq!(type_group.exit_code, None);
+    assert!(type_group
+        .error
+        .as_ref()
+        .unwrap()
+        .contains("ScriptNotFound"));
+
+    // only the cycles of the completed script groups are counted
+    assert_eq!(simulation.cycles, lock_cycles);
+
+    // the transaction with a missing input can't be simulated
+    let tx = build_tx(OutPoint::new(h256!("0x1").pack(), 0), None);
+    let error = simulate_transaction(&suite, &tx);
+    assert_eq!(error["code"], json!(-301));
+}
@@ -1,3 +1,7 @@
+mod admin;
+mod debug;
+mod experiment;
 mod miner;
 mod pool;
+mod subscription;
 mod test;
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use ckb_indexer::SearchKeyMatcher;
+use ckb_jsonrpc_types::{
+    CommittedTransaction, IndexerCellType, IndexerRange, IndexerScriptType, IndexerSearchKey,
+    IndexerSearchKeyFilter, IndexerSearchMode, Topic,
+};
+use ckb_notify::NotifyService;
+use ckb_store::ChainStore;
+use ckb_test_chain_utils::{always_success_cell, always_success_consensus};
+use ckb_types::{
+    core::{BlockBuilder, BlockView, Capacity, TransactionBuilder, TransactionView},
+    h256,
+    packed::{CellInput, CellOutputBuilder, OutPoint, Script},
+    prelude::*,
+};
+use futures_util::StreamExt;
+
+use crate::{
+    module::{subscription::CommittedBlock, SubscriptionRpc, SubscriptionRpcImpl},
+    tests::setup,
+};
+
+fn lock_script(args: &[u8]) -> Script {
+    Script::new_builder()
+        .code_hash(h256!("0x1").pack())
+        .args(args.to_vec().pack())
+        .build()
+}
+
+fn search_key(script: &Script) -> IndexerSearchKey {
+    IndexerSearchKey {
+        script: script.clone().into(),
+        script_search_mode: Some(IndexerSearchMode::Exact),
+        ..Default::default()
+    }
+}
+
+fn build_tx(inputs: Vec<OutPoint>, lock: &Script) -> TransactionView {
+    let output = CellOutputBuilder::default()
+        .capacity(Capacity::bytes(1000).unwrap().pack())
+        .lock(lock.clone())
+        .build();
+    TransactionBuilder::default()
+        .inputs(inputs.into_iter().map(|input| CellInput::new(input, 0)))
+        .output(output)
+        .output_data(Default::default())
+        .build()
+}
+
+fn cell_indices(tx: &CommittedTransaction) -> Vec<(IndexerCellType, u32)> {
+    tx.cells
+        .iter()
+        .map(|(io_type, index)| (io_type.clone(), index.value()))
+        .collect()
+}
+
+fn unresolved_indices(tx: &CommittedTransaction) -> Vec<u32> {
+    tx.unresolved_inputs
+        .iter()
+        .map(|index| index.value())
+        .collect()
+}
+
+// Builds a block on the tip with the transactions:
+//
+// - #0 spends the cellbase of the tip, creates a cell locked by `a`,
+// - #1 spends an unknown cell, creates a cell locked by `a`,
+// - #2 spends the cellbase of the parent of the tip and an unknown cell, creates a cell locked by
+//   `b`.
+fn build_block(store: &impl ChainStore, a: &Script, b: &Script) -> BlockView {
+    let tip = store.get_tip_header().unwrap();
+    let cellbase_of = |hash| store.get_block(&hash).unwrap().transactions()[0].hash();
+    let tip_cellbase = cellbase_of(tip.hash());
+    let parent_cellbase = cellbase_of(tip.parent_hash());
+    let unknown = h256!("0x2").pack();
+    BlockBuilder::default()
+        .transaction(build_tx(vec![OutPoint::new(tip_cellbase, 0)], a))
+        .transaction(build_tx(vec![OutPoint::new(unknown.clone(), 0)], a))
+        .transaction(build_tx(
+            vec![OutPoint::new(parent_cellbase, 0), OutPoint::new(unknown, 1)],
+            b,
+        ))
+        .number((tip.number() + 1).pack())
+        .build()
+}
+
+#[test]
+fn test_committed_block_matched_transactions() {
+    let suite = setup(always_success_consensus());
+    let store = suite.shared.store();
+    let (a, b) = (lock_script(b"a"), lock_script(b"b"));
+    let block = build_block(store, &a, &b);
+    let committed_block = CommittedBlock::resolve(block.clone(), store);
+    let matched = |search_key| {
+        committed_block.matched_transactions(&SearchKeyMatcher::new(search_key).unwrap())
+    };
+
+    // the spent cellbases are locked by the always success script
+    let txs = matched(search_key(&always_success_cell().2));
+    assert_eq!(txs.len(), 3);
+    assert_eq!(txs[0].tx_index.value(), 0);
+    assert_eq!(txs[0].block_hash, block.hash().unpack());
+    assert_eq!(txs[0].block_number.value(), block.number());
+    assert_eq!(cell_indices(&txs[0]), vec![(IndexerCellType::Input, 0)]);
+    assert!(txs[0].unresolved_inputs.is_empty());
+    // no input is resolved to decide the transaction
+    assert_eq!(txs[1].tx_index.value(), 1);
+    assert!(txs[1].cells.is_empty());
+    assert_eq!(unresolved_indices(&txs[1]), vec![0]);
+    assert_eq!(txs[2].tx_index.value(), 2);
+    assert_eq!(cell_indices(&txs[2]), vec![(IndexerCellType::Input, 0)]);
+    assert_eq!(unresolved_indices(&txs[2]), vec![1]);
+
+    // the resolved input of #2 doesn't match
+    let txs = matched(search_key(&a));
+    assert_eq!(txs.len(), 2);
+    assert_eq!(cell_indices(&txs[0]), vec![(IndexerCellType::Output, 0)]);
+    assert_eq!(cell_indices(&txs[1]), vec![(IndexerCellType::Output, 0)]);
+    assert_eq!(unresolved_indices(&txs[1]), vec![0]);
+
+    let txs = matched(search_key(&b));
+    assert_eq!(txs.len(), 2);
+    assert_eq!(txs[0].tx_index.value(), 1);
+    assert!(txs[0].cells.is_empty());
+    assert_eq!(txs[1].tx_index.value(), 2);
+    assert_eq!(cell_indices(&txs[1]), vec![(IndexerCellType::Output, 0)]);
+
+    // the cell filters and the block range apply
+    let txs = matched(IndexerSearchKey {
+        filter: Some(IndexerSearchKeyFilter {
+            output_capacity_range: Some(IndexerRange::new(0u64, 100_000_000_000u64)),
+            ..Default::default()
+        }),
+        ..search_key(&a)
+    });
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].tx_index.value(), 1);
+    assert!(txs[0].cells.is_empty());
+    let txs = matched(IndexerSearchKey {
+        filter: Some(IndexerSearchKeyFilter {
+            block_range: Some(IndexerRange::new(0u64, block.number())),
+            ..Default::default()
+        }),
+        ..search_key(&a)
+    });
+    assert!(txs.is_empty());
+
+    // the type script search doesn't match the cells without type script
+    let txs = matched(IndexerSearchKey {
+        script_type: IndexerScriptType::Type,
+        ..search_key(&a)
+    });
+    assert_eq!(txs.len(), 1);
+    assert!(txs[0].cells.is_empty());
+}
+
+#[test]
+fn test_subscribe_committed_transaction() {
+    let suite = setup(always_success_consensus());
+    let handle = suite.shared.async_handle().clone();
+    let notify_controller = NotifyService::new(Default::default(), handle.clone()).start();
+    let subscription = SubscriptionRpcImpl::new(
+        suite.shared.clone(),
+        notify_controller.clone(),
+        handle.clone(),
+    );
+
+    // the search key is only accepted by topic committed_transaction
+    let (a, b) = (lock_script(b"a"), lock_script(b"b"));
+    assert!(subscription
+        .subscribe(Topic::NewTipBlock, Some(search_key(&a)))
+        .is_err());
+    assert!(subscription
+        .subscribe(Topic::CommittedTransaction, None)
+        .is_err());
+
+    let mut stream = subscription
+        .subscribe(Topic::CommittedTransaction, Some(search_key(&b)))
+        .unwrap();
+    let mut out_of_range_stream = subscription
+        .subscribe(
+            Topic::CommittedTransaction,
+            Some(IndexerSearchKey {
+                filter: Some(IndexerSearchKeyFilter {
+                    block_range: Some(IndexerRange::new(0u64, 1u64)),
+                    ..Default::default()
+                }),
+                ..search_key(&b)
+            }),
+        )
+        .unwrap();
+    notify_controller.notify_new_block(build_block(suite.shared.store(), &a, &b));
+
+    // each matched transaction is pushed in a message
+    let next = |stream: &mut <SubscriptionRpcImpl as SubscriptionRpc>::S| {
+        handle.block_on(tokio::time::timeout(Duration::from_secs(1), stream.next()))
+    };
+    assert!(next(&mut stream).unwrap().is_some());
+    assert!(next(&mut stream).unwrap().is_some());
+    assert!(next(&mut stream).is_err());
+    assert!(next(&mut out_of_range_stream).is_err());
+}
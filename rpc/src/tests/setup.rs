@@ -189,12 +189,16 @@ pub(crate) fn setup_rpc_test_suite(height: u64, consensus: Option<Consensus>) ->
             RpcModule::Alert,
             RpcModule::Subscription,
             RpcModule::Debug,
+            RpcModule::Admin,
         ],
         reject_ill_transactions: true,
         // enable deprecated rpc in unit test
         enable_deprecated_rpc: true,
         extra_well_known_lock_scripts: vec![],
         extra_well_known_type_scripts: vec![],
+        auth: None,
+        rate_limit: None,
+        slow_call_threshold_ms: None,
     };
 
     let builder = ServiceBuilder::new(&rpc_config)
@@ -214,13 +218,14 @@ pub(crate) fn setup_rpc_test_suite(height: u64, consensus: Option<Consensus>) ->
             network_controller.clone(),
             chain_controller.clone(),
         )
-        .enable_debug()
+        .enable_debug(shared.clone())
+        .enable_admin(shared.clone())
         .enable_alert(alert_verifier, alert_notifier, network_controller);
 
-    let io_handler = builder.build();
+    let access_control = builder.build();
     let shared_clone = shared.clone();
     let handler = shared_clone.async_handle().clone();
-    let rpc_server = RpcServer::new(rpc_config, io_handler, handler.clone());
+    let rpc_server = RpcServer::new(rpc_config, access_control, handler.clone());
 
     let rpc_client = reqwest::blocking::Client::new();
     let rpc_uri = format!(
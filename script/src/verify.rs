@@ -909,6 +909,34 @@ impl<DL: CellDataProvider + HeaderProvider + ExtensionProvider + Send + Sync + C
         }
     }
 
+    /// Runs a single script in current transaction, and returns the exit code together with the
+    /// consumed cycles, even if the script exits with a non-zero code.
+    ///
+    /// It is used to inspect the failed scripts, e.g., when simulating a transaction.
+    pub fn detailed_verify_single(
+        &self,
+        script_group_type: ScriptGroupType,
+        script_hash: &Byte32,
+        max_cycles: Cycle,
+    ) -> Result<(i8, Cycle), ScriptError> {
+        let group = self
+            .find_script_group(script_group_type, script_hash)
+            .ok_or_else(|| ScriptError::ScriptNotFound(script_hash.clone()))?;
+        if group.script.code_hash() == TYPE_ID_CODE_HASH.pack()
+            && Into::<u8>::into(group.script.hash_type()) == Into::<u8>::into(ScriptHashType::Type)
+        {
+            let verifier = TypeIdSystemScript {
+                rtx: &self.rtx,
+                script_group: group,
+                max_cycles,
+            };
+            verifier.verify().map(|cycles| (0, cycles))
+        } else {
+            let (code, machine) = self.detailed_run(group, max_cycles)?;
+            Ok((code, machine.machine.cycles()))
+        }
+    }
+
     fn verify_script_group(
         &self,
         group: &ScriptGroup,
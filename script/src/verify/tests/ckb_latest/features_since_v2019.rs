@@ -1215,3 +1215,94 @@ fn _check_typical_secp256k1_blake160_2_in_2_out_resume_load_cycles(step_cycles:
     }
     assert_eq!(cycles, cycles_once, "step_cycles {step_cycles}");
 }
+
+#[test]
+fn check_detailed_verify_single() {
+    let script_version = SCRIPT_VERSION;
+
+    let (always_success_cell, always_success_cell_data, always_success_script) =
+        always_success_cell();
+    let always_success_cell = CellMetaBuilder::from_cell_output(
+        always_success_cell.clone(),
+        always_success_cell_data.to_owned(),
+    )
+    .transaction_info(default_transaction_info())
+    .build();
+    let (always_failure_cell, always_failure_data_hash) =
+        load_cell_from_path("testdata/always_failure");
+    let always_failure_script = Script::new_builder()
+        .code_hash(always_failure_data_hash)
+        .hash_type(script_version.data_hash_type().into())
+        .build();
+
+    let transaction = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::null(), 0))
+        .input(CellInput::new(OutPoint::null(), 0))
+        .build();
+    let dummy_cell = |lock: &Script| {
+        create_dummy_cell(
+            CellOutputBuilder::default()
+                .capacity(capacity_bytes!(100).pack())
+                .lock(lock.clone())
+                .build(),
+        )
+    };
+    let rtx = ResolvedTransaction {
+        transaction,
+        resolved_cell_deps: vec![always_success_cell, always_failure_cell],
+        resolved_inputs: vec![
+            dummy_cell(always_success_script),
+            dummy_cell(&always_failure_script),
+        ],
+        resolved_dep_groups: vec![],
+    };
+
+    let verifier = TransactionScriptsVerifierWithEnv::new();
+    verifier.verify_map(script_version, &rtx, |verifier| {
+        // the consumed cycles are returned as the normal verification
+        let result = verifier.detailed_verify_single(
+            ScriptGroupType::Lock,
+            &always_success_script.calc_script_hash(),
+            u64::MAX,
+        );
+        let cycles = verifier
+            .verify_single(
+                ScriptGroupType::Lock,
+                &always_success_script.calc_script_hash(),
+                u64::MAX,
+            )
+            .unwrap();
+        assert_eq!(result.unwrap(), (0, cycles));
+
+        // the failed script returns its exit code instead of an error
+        let (exit_code, cycles) = verifier
+            .detailed_verify_single(
+                ScriptGroupType::Lock,
+                &always_failure_script.calc_script_hash(),
+                u64::MAX,
+            )
+            .unwrap();
+        assert_eq!(exit_code, -1);
+        assert!(cycles > 0);
+
+        // the scripts which can't run are still errors
+        let result = verifier.detailed_verify_single(
+            ScriptGroupType::Lock,
+            &always_failure_script.calc_script_hash(),
+            1,
+        );
+        assert!(matches!(
+            result.unwrap_err(),
+            ScriptError::ExceededMaximumCycles(_)
+        ));
+        let result = verifier.detailed_verify_single(
+            ScriptGroupType::Type,
+            &always_success_script.calc_script_hash(),
+            u64::MAX,
+        );
+        assert!(matches!(
+            result.unwrap_err(),
+            ScriptError::ScriptNotFound(_)
+        ));
+    });
+}
@@ -6,12 +6,12 @@ use ckb_chain_spec::consensus::Consensus;
 use ckb_constant::store::TX_INDEX_UPPER_BOUND;
 use ckb_constant::sync::MAX_TIP_AGE;
 use ckb_db::{Direction, IteratorMode};
-use ckb_db_schema::{COLUMN_BLOCK_BODY, COLUMN_NUMBER_HASH};
+use ckb_db_schema::{COLUMN_BLOCK_BODY, COLUMN_BLOCK_EXT, COLUMN_NUMBER_HASH};
 use ckb_error::{AnyError, Error};
 use ckb_notify::NotifyController;
 use ckb_proposal_table::ProposalView;
 use ckb_stop_handler::{new_crossbeam_exit_rx, register_thread};
-use ckb_store::{ChainDB, ChainStore};
+use ckb_store::{ChainDB, ChainStore, DBStats};
 use ckb_systemtime::unix_time_as_millis;
 use ckb_tx_pool::{BlockTemplate, TokioRwLock, TxPoolController};
 use ckb_types::{
@@ -31,6 +31,9 @@ use std::time::Duration;
 const FREEZER_INTERVAL: Duration = Duration::from_secs(60);
 const THRESHOLD_EPOCH: EpochNumber = 2;
 const MAX_FREEZE_LIMIT: BlockNumber = 30_000;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_PRUNE_LIMIT: BlockNumber = 10_000;
+const DB_STATS_INTERVAL: Duration = Duration::from_secs(60);
 
 /// An owned permission to close on a freezer thread
 pub struct FreezerClose {
@@ -122,25 +125,17 @@ impl Shared {
             return Ok(());
         }
 
-        if current_epoch <= THRESHOLD_EPOCH {
-            ckb_logger::trace!("Freezer idles");
-            return Ok(());
-        }
-
-        let limit_block_hash = snapshot
-            .get_epoch_index(current_epoch + 1 - THRESHOLD_EPOCH)
-            .and_then(|index| snapshot.get_epoch_ext(&index))
-            .expect("get_epoch_ext")
-            .last_block_hash_in_previous_epoch();
+        let limit_block_number = match ancient_block_number(&snapshot) {
+            Some(number) => number,
+            None => {
+                ckb_logger::trace!("Freezer idles");
+                return Ok(());
+            }
+        };
 
         let frozen_number = freezer.number();
 
-        let threshold = cmp::min(
-            snapshot
-                .get_block_number(&limit_block_hash)
-                .expect("get_block_number"),
-            frozen_number + MAX_FREEZE_LIMIT,
-        );
+        let threshold = cmp::min(limit_block_number, frozen_number + MAX_FREEZE_LIMIT);
 
         ckb_logger::trace!(
             "Freezer current_epoch {} number {} threshold {}",
@@ -163,11 +158,131 @@ impl Shared {
         // Wipe out frozen data
         self.wipe_out_frozen_data(&snapshot, ret, stopped)?;
 
+        // The headers, the block exts, the transaction infos and the epoch indexes are frozen
+        // after the bodies, it also catches up the freezers created before they were frozen.
+        let meta_threshold = cmp::min(limit_block_number, freezer.meta_number() + MAX_FREEZE_LIMIT);
+        let get_block_meta = |hash: &packed::Byte32| {
+            let ext = store
+                .get(COLUMN_BLOCK_EXT, hash.as_slice())
+                .map(|slice| slice.as_ref().to_vec())?;
+            Some((ext, store.get_block_epoch_index(hash)?))
+        };
+        let frozen_meta = freezer.freeze_meta(meta_threshold, get_block_meta)?;
+        self.wipe_out_frozen_meta(frozen_meta)?;
+
         ckb_logger::trace!("Freezer completed");
 
         Ok(())
     }
 
+    /// Spawn prune background thread that periodically removes the bodies and the transaction
+    /// infos of the main chain blocks deeper than `prune_depth`.
+    pub fn spawn_prune(&self, prune_depth: BlockNumber) {
+        ckb_logger::info!("Prune mode enabled, depth {}", prune_depth);
+        let signal_receiver = new_crossbeam_exit_rx();
+        let shared = self.clone();
+        let prune_jh = thread::Builder::new()
+            .spawn(move || loop {
+                match signal_receiver.recv_timeout(PRUNE_INTERVAL) {
+                    Err(_) => {
+                        if let Err(e) = shared.prune(prune_depth) {
+                            ckb_logger::error!("Pruner error {}", e);
+                            break;
+                        }
+                    }
+                    Ok(_) => {
+                        ckb_logger::info!("Pruner closing");
+                        break;
+                    }
+                }
+            })
+            .expect("Start PruneService failed");
+
+        register_thread("prune", prune_jh);
+    }
+
+    /// Spawn a background process that periodically exposes the disk usage statistics of the
+    /// database and the freezer through the metrics, only when the metrics service is enabled.
+    pub fn spawn_db_stats(&self) {
+        if ckb_metrics::handle().is_none() {
+            return;
+        }
+        let signal_receiver = new_crossbeam_exit_rx();
+        let store = self.store.clone();
+        let db_stats_jh = thread::Builder::new()
+            .spawn(move || loop {
+                match DBStats::collect(&store) {
+                    Ok(stats) => stats.update_metrics(),
+                    Err(e) => ckb_logger::error!("Collect database statistics error {}", e),
+                }
+                if signal_receiver.recv_timeout(DB_STATS_INTERVAL).is_ok() {
+                    ckb_logger::info!("Database statistics closing");
+                    break;
+                }
+            })
+            .expect("Start DBStatsService failed");
+
+        register_thread("db_stats", db_stats_jh);
+    }
+
+    fn prune(&self, prune_depth: BlockNumber) -> Result<(), Error> {
+        let snapshot = self.snapshot();
+
+        // The blocks in the latest epochs are never pruned, the same as the freezer, since they
+        // are still required by the reorganization and the versionbits.
+        let limit_block_number = match ancient_block_number(&snapshot) {
+            Some(number) => number,
+            None => {
+                ckb_logger::trace!("Pruner idles");
+                return Ok(());
+            }
+        };
+
+        let start = cmp::max(snapshot.get_pruned_block_number().unwrap_or(1), 1);
+        let end = cmp::min(
+            cmp::min(
+                snapshot.tip_number().saturating_sub(prune_depth),
+                limit_block_number + 1,
+            ),
+            start + MAX_PRUNE_LIMIT,
+        );
+        if start >= end {
+            return Ok(());
+        }
+
+        ckb_logger::trace!("Pruner prunes blocks [{}, {})", start, end);
+
+        let mut batch = self.store.new_write_batch();
+        let mut pruned = Vec::with_capacity((end - start) as usize);
+        for number in start..end {
+            let hash = snapshot
+                .get_block_hash(number)
+                .expect("main chain index must be stored");
+            let tx_hashes = snapshot.get_block_txs_hashes(&hash);
+            batch.prune_block_body(&hash, &tx_hashes)?;
+            pruned.push(hash);
+        }
+        batch.set_pruned_block_number(end)?;
+        self.store.write_sync(&batch).map_err(|e| {
+            ckb_logger::error!("Pruner write_batch delete failed {}", e);
+            e
+        })?;
+
+        let start_hash = pruned.iter().min().expect("pruned empty checked");
+        let end_hash = pruned.iter().max().expect("pruned empty checked");
+        self.compact_block_body(start_hash, end_hash);
+
+        ckb_logger::trace!("Pruner completed");
+
+        Ok(())
+    }
+
+    /// Returns the number of the last block before the latest epochs, the bodies of the blocks up
+    /// to it can be frozen or pruned. Returns `None` if the chain is not long enough.
+    pub fn ancient_block_number(&self) -> Option<BlockNumber> {
+        ancient_block_number(&self.snapshot())
+    }
+
     fn wipe_out_frozen_data(
         &self,
         snapshot: &Snapshot,
@@ -243,6 +358,31 @@ impl Shared {
         Ok(())
     }
 
+    fn wipe_out_frozen_meta(
+        &self,
+        frozen: BTreeMap<packed::Byte32, (BlockNumber, Vec<packed::Byte32>)>,
+    ) -> Result<(), Error> {
+        ckb_logger::trace!("freezer wipe_out_frozen_meta {} ", frozen.len());
+        if frozen.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch = self.store.new_write_batch();
+        for (hash, (number, tx_hashes)) in &frozen {
+            batch
+                .wipe_frozen_block_meta(*number, hash, tx_hashes)
+                .map_err(|e| {
+                    ckb_logger::error!("Freezer wipe_frozen_block_meta failed {}", e);
+                    e
+                })?;
+        }
+        self.store.write_sync(&batch).map_err(|e| {
+            ckb_logger::error!("Freezer write_batch delete failed {}", e);
+            e
+        })?;
+        Ok(())
+    }
+
     fn compact_block_body(&self, start: &packed::Byte32, end: &packed::Byte32) {
         let start_t = packed::TransactionKey::new_builder()
             .block_hash(start.clone())
@@ -371,3 +511,24 @@ impl Shared {
         )
     }
 }
+
+// Returns the number of the last block before the latest `THRESHOLD_EPOCH` epochs, or `None` if the
+// chain is not long enough.
+fn ancient_block_number(snapshot: &Snapshot) -> Option<BlockNumber> {
+    let current_epoch = snapshot.epoch_ext().number();
+    if current_epoch <= THRESHOLD_EPOCH {
+        return None;
+    }
+
+    let limit_block_hash = snapshot
+        .get_epoch_index(current_epoch + 1 - THRESHOLD_EPOCH)
+        .and_then(|index| snapshot.get_epoch_ext(&index))
+        .expect("get_epoch_ext")
+        .last_block_hash_in_previous_epoch();
+
+    Some(
+        snapshot
+            .get_block_number(&limit_block_hash)
+            .expect("get_block_number"),
+    )
+}
@@ -13,7 +13,8 @@ use ckb_proposal_table::ProposalView;
 use ckb_snapshot::{Snapshot, SnapshotMgr};
 
 use ckb_app_config::{
-    BlockAssemblerConfig, DBConfig, ExitCode, NotifyConfig, StoreConfig, TxPoolConfig,
+    BlockAssemblerConfig, CellSnapshotConfig, DBConfig, ExitCode, NotifyConfig, StoreConfig,
+    TxPoolConfig,
 };
 use ckb_async_runtime::{new_background_runtime, Handle};
 use ckb_db::RocksDB;
@@ -22,12 +23,13 @@ use ckb_error::{Error, InternalErrorKind};
 use ckb_logger::{error, info};
 use ckb_migrate::migrate::Migrate;
 use ckb_notify::{NotifyController, NotifyService};
-use ckb_store::{ChainDB, ChainStore, Freezer};
+use ckb_store::{import_cell_snapshot, ChainDB, ChainStore, Freezer};
 use ckb_types::core::hardfork::HardForks;
-use ckb_types::core::service::PoolTransactionEntry;
+use ckb_types::core::service::{PoolTransactionChange, PoolTransactionDiff, PoolTransactionEntry};
 use ckb_types::core::tx_pool::Reject;
 use ckb_types::core::EpochExt;
 use ckb_types::core::HeaderView;
+use ckb_types::prelude::*;
 use ckb_verification::cache::init_cache;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
@@ -44,6 +46,7 @@ pub struct SharedBuilder {
     store_config: Option<StoreConfig>,
     block_assembler_config: Option<BlockAssemblerConfig>,
     notify_config: Option<NotifyConfig>,
+    cell_snapshot: Option<CellSnapshotConfig>,
     async_handle: Handle,
 }
 
@@ -147,6 +150,7 @@ impl SharedBuilder {
             notify_config: None,
             store_config: None,
             block_assembler_config: None,
+            cell_snapshot: None,
             async_handle,
         })
     }
@@ -192,6 +196,7 @@ impl SharedBuilder {
             notify_config: None,
             store_config: None,
             block_assembler_config: None,
+            cell_snapshot: None,
             async_handle: runtime.get_or_init(new_background_runtime).clone(),
         })
     }
@@ -228,6 +233,12 @@ impl SharedBuilder {
         self
     }
 
+    /// Bootstraps an empty database from the live cell snapshot
+    pub fn cell_snapshot(mut self, config: Option<CellSnapshotConfig>) -> Self {
+        self.cell_snapshot = config;
+        self
+    }
+
     /// specifies the async_handle for the shared
     pub fn async_handle(mut self, async_handle: Handle) -> Self {
         self.async_handle = async_handle;
@@ -262,6 +273,23 @@ impl SharedBuilder {
         (proposal_ids, proposals)
     }
 
+    fn bootstrap_from_cell_snapshot(
+        store: &ChainDB,
+        config: &CellSnapshotConfig,
+    ) -> Result<(), Error> {
+        if store.get_tip_header().is_some() {
+            info!("The database is not empty, skip importing the cell snapshot");
+            return Ok(());
+        }
+        info!("Importing the cell snapshot {}", config.path.display());
+        let snapshot = import_cell_snapshot(store, &config.path, &config.hash.pack())?;
+        info!(
+            "Imported the cell snapshot at block {} {}, start syncing from it",
+            snapshot.number, snapshot.block_hash
+        );
+        Ok(())
+    }
+
     fn init_store(store: &ChainDB, consensus: &Consensus) -> Result<(HeaderView, EpochExt), Error> {
         match store
             .get_tip_header()
@@ -327,6 +355,7 @@ impl SharedBuilder {
             store_config,
             block_assembler_config,
             notify_config,
+            cell_snapshot,
             async_handle,
         } = self;
 
@@ -342,6 +371,13 @@ impl SharedBuilder {
             ExitCode::Failure
         })?;
 
+        if let Some(cell_snapshot) = cell_snapshot {
+            Self::bootstrap_from_cell_snapshot(&store, &cell_snapshot).map_err(|e| {
+                eprintln!("bootstrap_from_cell_snapshot {e}");
+                ExitCode::Failure
+            })?;
+        }
+
         let txs_verify_cache = Arc::new(TokioRwLock::new(init_cache()));
 
         let (snapshot, table) =
@@ -411,6 +447,19 @@ fn register_tx_pool_callback(tx_pool_builder: &mut TxPoolServiceBuilder, notify:
         notify_proposed.notify_proposed_transaction(notify_tx_entry);
     }));
 
+    let notify_diff = notify.clone();
+    tx_pool_builder.register_diff(Box::new(
+        move |sequence: u64, entry: &TxEntry, change: PoolTransactionChange| {
+            // notify
+            let notify_tx_entry = create_notify_entry(entry);
+            notify_diff.notify_pool_diff(PoolTransactionDiff {
+                sequence,
+                entry: notify_tx_entry,
+                change,
+            });
+        },
+    ));
+
     let notify_reject = notify;
     tx_pool_builder.register_reject(Box::new(
         move |tx_pool: &mut TxPool, entry: &TxEntry, reject: Reject| {
@@ -449,6 +498,11 @@ fn build_store(
     store_config: StoreConfig,
     ancient_path: Option<PathBuf>,
 ) -> Result<ChainDB, Error> {
+    if store_config.freezer_enable && store_config.prune_depth.is_some() {
+        return Err(InternalErrorKind::Config
+            .other("the freezer and the prune mode cannot be enabled at the same time")
+            .into());
+    }
     let store = if store_config.freezer_enable && ancient_path.is_some() {
         let freezer = Freezer::open(ancient_path.expect("exist checked"))?;
         ChainDB::new_with_freezer(db, freezer, store_config)
@@ -0,0 +1,110 @@
+//! Online backups of the chain database.
+//!
+//! A backup directory contains:
+//!
+//! - `db`: a consistent RocksDB checkpoint of the chain database;
+//! - `ancient`: a copy of the freezer, only when the freezer is enabled;
+//! - `manifest.json`: the `BackupManifest`, which is written last, a backup without it is
+//!   incomplete.
+//!
+//! The checkpoint is taken before copying the freezer. The blocks are moved into the freezer
+//! before they are wiped out from the database, so every block in the checkpoint is either in the
+//! checkpoint itself or in the copied freezer.
+//!
+//! A node is restored by pointing `[db] path` and `ancient` in `ckb.toml` to the two
+//! sub-directories, or by moving them to the configured paths.
+use crate::ChainDB;
+use ckb_db::ReadOnlyDB;
+use ckb_db_schema::{COLUMN_BLOCK_HEADER, COLUMN_META, META_TIP_HEADER_KEY};
+use ckb_error::{Error, InternalErrorKind};
+use ckb_types::{core::BlockNumber, core::HeaderView, packed, prelude::*, H256};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The name of the sub-directory of the database checkpoint.
+pub const BACKUP_DB_DIR: &str = "db";
+/// The name of the sub-directory of the freezer copy.
+pub const BACKUP_ANCIENT_DIR: &str = "ancient";
+/// The name of the manifest file.
+pub const BACKUP_MANIFEST_FILE: &str = "manifest.json";
+
+/// The summary of a backup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// The number of the tip block in the backup.
+    pub tip_number: BlockNumber,
+    /// The hash of the tip block in the backup.
+    pub tip_hash: H256,
+    /// The number of the items in the freezer copy, zero if the freezer is disabled.
+    pub frozen_number: BlockNumber,
+    /// The creation time of the backup, in milliseconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+impl BackupManifest {
+    /// Loads the manifest from the backup directory.
+    pub fn load(target: &Path) -> Result<Self, Error> {
+        let content = fs::read(target.join(BACKUP_MANIFEST_FILE)).map_err(io_error)?;
+        serde_json::from_slice(&content).map_err(|err| {
+            InternalErrorKind::Database
+                .other(format!("invalid backup manifest: {err}"))
+                .into()
+        })
+    }
+}
+
+/// Backs up the chain database and its freezer into the directory `target`, which must not exist.
+///
+/// The node keeps running during the backup, the freezing is paused while copying the freezer.
+pub fn backup_chain_db(db: &ChainDB, target: &Path) -> Result<BackupManifest, Error> {
+    if target.exists() {
+        return Err(InternalErrorKind::Database
+            .other(format!(
+                "the backup target {} already exists",
+                target.display()
+            ))
+            .into());
+    }
+    fs::create_dir_all(target).map_err(io_error)?;
+
+    let db_path = target.join(BACKUP_DB_DIR);
+    db.db().create_checkpoint(&db_path)?;
+    let tip_header = checkpoint_tip_header(&db_path)?;
+
+    let frozen_number = match db.freezer() {
+        Some(freezer) => freezer.backup(&target.join(BACKUP_ANCIENT_DIR))?,
+        None => 0,
+    };
+
+    let manifest = BackupManifest {
+        tip_number: tip_header.number(),
+        tip_hash: tip_header.hash().unpack(),
+        frozen_number,
+        created_at: ckb_systemtime::unix_time_as_millis(),
+    };
+    let content = serde_json::to_vec_pretty(&manifest).map_err(|err| {
+        InternalErrorKind::Database.other(format!("failed to encode the backup manifest: {err}"))
+    })?;
+    fs::write(target.join(BACKUP_MANIFEST_FILE), content).map_err(io_error)?;
+    Ok(manifest)
+}
+
+// The tip header is never frozen, it is always in the checkpoint.
+fn checkpoint_tip_header(path: &Path) -> Result<HeaderView, Error> {
+    let db = ReadOnlyDB::open_cf(path, vec![COLUMN_META, COLUMN_BLOCK_HEADER])?
+        .ok_or_else(|| InternalErrorKind::Database.other("the checkpoint is not found"))?;
+    let tip_hash = db
+        .get_pinned(COLUMN_META, META_TIP_HEADER_KEY)?
+        .ok_or_else(|| InternalErrorKind::Database.other("the tip is not found"))?;
+    let header = db
+        .get_pinned(COLUMN_BLOCK_HEADER, tip_hash.as_ref())?
+        .ok_or_else(|| InternalErrorKind::Database.other("the tip header is not found"))?;
+    let reader = packed::HeaderViewReader::from_slice_should_be_ok(header.as_ref());
+    Ok(Unpack::<HeaderView>::unpack(&reader))
+}
+
+fn io_error(err: io::Error) -> Error {
+    InternalErrorKind::System.because(err).into()
+}
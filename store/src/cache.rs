@@ -6,6 +6,7 @@ use ckb_types::{
 };
 use ckb_util::Mutex;
 use lru::LruCache;
+use std::sync::atomic::AtomicU64;
 
 /// The cache of chain store.
 pub struct StoreCache {
@@ -23,6 +24,8 @@ pub struct StoreCache {
     pub block_uncles: Mutex<LruCache<Byte32, UncleBlockVecView>>,
     /// The cache of block extension sections.
     pub block_extensions: Mutex<LruCache<Byte32, Option<packed::Bytes>>>,
+    /// The number below which the main chain block bodies have been pruned, 0 if none is pruned.
+    pub pruned_block_number: AtomicU64,
 }
 
 impl Default for StoreCache {
@@ -42,6 +45,7 @@ impl StoreCache {
             block_tx_hashes: Mutex::new(LruCache::new(config.block_tx_hashes_cache_size)),
             block_uncles: Mutex::new(LruCache::new(config.block_uncles_cache_size)),
             block_extensions: Mutex::new(LruCache::new(config.block_extensions_cache_size)),
+            pruned_block_number: AtomicU64::new(0),
         }
     }
 }
@@ -1,4 +1,5 @@
 use crate::{ChainStore, StoreTransaction};
+use ckb_db::KeyValueStore;
 use ckb_error::Error;
 use ckb_types::{core::BlockView, packed, prelude::*};
 use std::collections::HashMap;
@@ -23,7 +24,10 @@ use std::collections::HashMap;
  */
 
 // Apply the effects of this block on the live cell set.
-pub fn attach_block_cell(txn: &StoreTransaction, block: &BlockView) -> Result<(), Error> {
+pub fn attach_block_cell<DB: KeyValueStore>(
+    txn: &StoreTransaction<DB>,
+    block: &BlockView,
+) -> Result<(), Error> {
     let transactions = block.transactions();
 
     // add new live cells
@@ -82,7 +86,10 @@ pub fn attach_block_cell(txn: &StoreTransaction, block: &BlockView) -> Result<()
 }
 
 /// Undoes the effects of this block on the live cell set.
-pub fn detach_block_cell(txn: &StoreTransaction, block: &BlockView) -> Result<(), Error> {
+pub fn detach_block_cell<DB: KeyValueStore>(
+    txn: &StoreTransaction<DB>,
+    block: &BlockView,
+) -> Result<(), Error> {
     let transactions = block.transactions();
     let mut input_pts = HashMap::with_capacity(transactions.len());
 
@@ -0,0 +1,421 @@
+//! Live cell set snapshots.
+//!
+//! A snapshot contains the live cell set at the tip block, the headers and the indices of the
+//! whole main chain, and the bodies of the recent blocks. A new node can be bootstrapped from a
+//! trusted snapshot and start syncing from its tip block instead of the genesis block. The bodies
+//! of the older blocks are treated as pruned by the imported node.
+//!
+//! A snapshot can only be exported at the tip block, since the database keeps the live cell set
+//! of the tip block only.
+//!
+//! The snapshot file starts with the magic bytes and the version, followed by the records of
+//! `column id (u8) | key length (u32 LE) | key | value length (u32 LE) | value`. The hash of a
+//! snapshot is the blake2b hash of the whole file.
+use crate::store::load_pruned_block_number;
+use crate::{ChainDB, ChainStore, StoreWriteBatch};
+use ckb_db::{Direction, IteratorMode};
+use ckb_db_schema::{
+    Col, COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_EXTENSION,
+    COLUMN_BLOCK_FILTER, COLUMN_BLOCK_FILTER_HASH, COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS,
+    COLUMN_BLOCK_UNCLE, COLUMN_CELL, COLUMN_CELL_DATA, COLUMN_CELL_DATA_HASH,
+    COLUMN_CHAIN_ROOT_MMR, COLUMN_EPOCH, COLUMN_INDEX, COLUMN_META, COLUMN_NUMBER_HASH,
+    COLUMN_TRANSACTION_INFO, COLUMN_UNCLES, META_CURRENT_EPOCH_KEY,
+    META_LATEST_BUILT_FILTER_DATA_KEY, META_PRUNED_BLOCK_NUMBER_KEY, META_TIP_HEADER_KEY,
+};
+use ckb_error::{Error, InternalErrorKind};
+use ckb_hash::{new_blake2b, Blake2b};
+use ckb_types::{core::BlockNumber, packed, prelude::*};
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8] = b"CKBCELLS";
+const VERSION: u8 = 1;
+// The size of the write batch flushed during importing.
+const IMPORT_BATCH_SIZE: usize = 64 * 1024 * 1024;
+
+// The columns which a snapshot is allowed to write.
+const SNAPSHOT_COLUMNS: &[Col] = &[
+    COLUMN_INDEX,
+    COLUMN_BLOCK_HEADER,
+    COLUMN_BLOCK_BODY,
+    COLUMN_BLOCK_UNCLE,
+    COLUMN_META,
+    COLUMN_TRANSACTION_INFO,
+    COLUMN_BLOCK_EXT,
+    COLUMN_BLOCK_PROPOSAL_IDS,
+    COLUMN_BLOCK_EPOCH,
+    COLUMN_EPOCH,
+    COLUMN_CELL,
+    COLUMN_UNCLES,
+    COLUMN_CELL_DATA,
+    COLUMN_NUMBER_HASH,
+    COLUMN_CELL_DATA_HASH,
+    COLUMN_BLOCK_EXTENSION,
+    COLUMN_CHAIN_ROOT_MMR,
+    COLUMN_BLOCK_FILTER,
+    COLUMN_BLOCK_FILTER_HASH,
+];
+
+// The columns keyed by the block hash which are exported for every main chain block, besides
+// the ones which may have been moved into the freezer.
+const BLOCK_COLUMNS: &[Col] = &[COLUMN_BLOCK_FILTER, COLUMN_BLOCK_FILTER_HASH];
+
+// The columns which are exported entirely.
+const FULL_COLUMNS: &[Col] = &[
+    COLUMN_EPOCH,
+    COLUMN_UNCLES,
+    COLUMN_CHAIN_ROOT_MMR,
+    COLUMN_CELL,
+    COLUMN_CELL_DATA,
+    COLUMN_CELL_DATA_HASH,
+];
+
+/// The summary of a live cell snapshot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellSnapshotInfo {
+    /// The number of the tip block.
+    pub number: BlockNumber,
+    /// The hash of the tip block.
+    pub block_hash: packed::Byte32,
+    /// The hash of the snapshot file.
+    pub hash: packed::Byte32,
+}
+
+/// Exports the live cell snapshot at the tip block of `store` into the file `path`.
+///
+/// The bodies and the transaction infos of the main chain blocks since `bodies_from` are included,
+/// the bodies of the older blocks except the genesis block are treated as pruned after importing.
+pub fn export_cell_snapshot<S: ChainStore>(
+    store: &S,
+    bodies_from: BlockNumber,
+    path: &Path,
+) -> Result<CellSnapshotInfo, Error> {
+    let tip_header = store
+        .get_tip_header()
+        .ok_or_else(|| InternalErrorKind::Database.other("the tip header is not found"))?;
+    let file = fs::OpenOptions::new()
+        .create_new(true)
+        .write(true)
+        .open(path)
+        .map_err(io_error)?;
+    let mut writer = SnapshotWriter::new(BufWriter::new(file))?;
+
+    for number in 0..=tip_header.number() {
+        let hash = store.get_block_hash(number).ok_or_else(|| {
+            InternalErrorKind::Database.other(format!("the block {number} is not indexed"))
+        })?;
+        let number_key: packed::Uint64 = number.pack();
+        writer.put(COLUMN_INDEX, number_key.as_slice(), hash.as_slice())?;
+        writer.put(COLUMN_INDEX, hash.as_slice(), number_key.as_slice())?;
+        // The getters serve the frozen data as well.
+        let header = store.get_block_header(&hash).ok_or_else(|| {
+            InternalErrorKind::Database.other(format!("the header of block {number} is not found"))
+        })?;
+        writer.put(
+            COLUMN_BLOCK_HEADER,
+            hash.as_slice(),
+            header.pack().as_slice(),
+        )?;
+        if let Some(epoch_index) = store.get_block_epoch_index(&hash) {
+            writer.put(COLUMN_BLOCK_EPOCH, hash.as_slice(), epoch_index.as_slice())?;
+        }
+        if let Some(ext) = store.get_block_ext(&hash) {
+            let ext: packed::BlockExtV1 = ext.pack();
+            writer.put(COLUMN_BLOCK_EXT, hash.as_slice(), ext.as_slice())?;
+        }
+        if let Some(uncles) = store.get_block_uncles(&hash) {
+            writer.put(
+                COLUMN_BLOCK_UNCLE,
+                hash.as_slice(),
+                uncles.pack().as_slice(),
+            )?;
+        }
+        if let Some(proposals) = store.get_block_proposal_txs_ids(&hash) {
+            writer.put(
+                COLUMN_BLOCK_PROPOSAL_IDS,
+                hash.as_slice(),
+                proposals.as_slice(),
+            )?;
+        }
+        if let Some(extension) = store.get_block_extension(&hash) {
+            writer.put(
+                COLUMN_BLOCK_EXTENSION,
+                hash.as_slice(),
+                extension.as_slice(),
+            )?;
+        }
+        for &col in BLOCK_COLUMNS {
+            if let Some(value) = store.get(col, hash.as_slice()) {
+                writer.put(col, hash.as_slice(), &value)?;
+            }
+        }
+        let number_hash = packed::NumberHash::new_builder()
+            .number(number_key)
+            .block_hash(hash.clone())
+            .build();
+        if let Some(value) = store.get(COLUMN_NUMBER_HASH, number_hash.as_slice()) {
+            writer.put(COLUMN_NUMBER_HASH, number_hash.as_slice(), &value)?;
+        }
+
+        if number == 0 || number >= bodies_from {
+            let prefix = hash.as_slice();
+            for (key, value) in store
+                .get_iter(
+                    COLUMN_BLOCK_BODY,
+                    IteratorMode::From(prefix, Direction::Forward),
+                )
+                .take_while(|(key, _)| key.starts_with(prefix))
+            {
+                writer.put(COLUMN_BLOCK_BODY, &key, &value)?;
+                let tx_hash = packed::TransactionViewReader::from_slice_should_be_ok(&value)
+                    .hash()
+                    .to_entity();
+                if let Some(info) = store.get(COLUMN_TRANSACTION_INFO, tx_hash.as_slice()) {
+                    writer.put(COLUMN_TRANSACTION_INFO, tx_hash.as_slice(), &info)?;
+                }
+            }
+        }
+    }
+
+    for &col in FULL_COLUMNS {
+        for (key, value) in store.get_iter(col, IteratorMode::Start) {
+            writer.put(col, &key, &value)?;
+        }
+    }
+
+    // The meta records are written last, the importer only writes them after all the other
+    // records have been written.
+    for key in [
+        META_TIP_HEADER_KEY,
+        META_CURRENT_EPOCH_KEY,
+        META_LATEST_BUILT_FILTER_DATA_KEY,
+    ] {
+        if let Some(value) = store.get(COLUMN_META, key) {
+            writer.put(COLUMN_META, key, &value)?;
+        }
+    }
+    let pruned = bodies_from.max(store.get_pruned_block_number().unwrap_or(1));
+    if pruned > 1 {
+        let pruned: packed::Uint64 = pruned.pack();
+        writer.put(COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY, pruned.as_slice())?;
+    }
+
+    let hash = writer.finish()?;
+    Ok(CellSnapshotInfo {
+        number: tip_header.number(),
+        block_hash: tip_header.hash(),
+        hash,
+    })
+}
+
+/// Computes the hash of the snapshot file `path`.
+pub fn cell_snapshot_hash(path: &Path) -> Result<packed::Byte32, Error> {
+    let mut reader = BufReader::new(fs::File::open(path).map_err(io_error)?);
+    let mut hasher = new_blake2b();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let len = reader.read(&mut buf).map_err(io_error)?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buf[..len]);
+    }
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    Ok(hash.pack())
+}
+
+/// Imports the snapshot file `path` into the empty database `db`.
+///
+/// The file is hashed while its records are imported, the snapshot is rejected if its hash is not
+/// `expected_hash`. The meta records, including the tip, are only written after the hash is
+/// verified, the other records written so far are removed if the import fails, so the database
+/// stays empty and the import can be retried.
+pub fn import_cell_snapshot(
+    db: &ChainDB,
+    path: &Path,
+    expected_hash: &packed::Byte32,
+) -> Result<CellSnapshotInfo, Error> {
+    let mut reader = HashingReader {
+        inner: BufReader::new(fs::File::open(path).map_err(io_error)?),
+        hasher: new_blake2b(),
+    };
+    let mut magic = [0u8; 9];
+    reader.read_exact(&mut magic).map_err(io_error)?;
+    if &magic[..MAGIC.len()] != MAGIC || magic[MAGIC.len()] != VERSION {
+        return Err(malformed("unknown magic bytes or version"));
+    }
+
+    let mut meta_batch = db.new_write_batch();
+    let imported = import_records(db, &mut reader, &mut meta_batch).and_then(|()| {
+        let hash = reader.finish();
+        if &hash != expected_hash {
+            return Err(InternalErrorKind::Config
+                .other(format!(
+                    "the hash of the cell snapshot {} is {}, but {} is expected",
+                    path.display(),
+                    hash,
+                    expected_hash
+                ))
+                .into());
+        }
+        Ok(hash)
+    });
+    let hash = match imported {
+        Ok(hash) => hash,
+        Err(err) => {
+            remove_imported_records(db)?;
+            return Err(err);
+        }
+    };
+    db.write_sync(&meta_batch)?;
+    load_pruned_block_number(db);
+
+    let tip_header = db
+        .get_tip_header()
+        .ok_or_else(|| malformed("the tip header is not found"))?;
+    Ok(CellSnapshotInfo {
+        number: tip_header.number(),
+        block_hash: tip_header.hash(),
+        hash,
+    })
+}
+
+// Writes the records except the meta ones, which are put into `meta_batch`.
+fn import_records<R: Read>(
+    db: &ChainDB,
+    reader: &mut R,
+    meta_batch: &mut StoreWriteBatch,
+) -> Result<(), Error> {
+    let mut batch = db.new_write_batch();
+    while let Some((col, key, value)) = read_record(reader)? {
+        if col == COLUMN_META {
+            meta_batch.put(col, &key, &value)?;
+            continue;
+        }
+        batch.put(col, &key, &value)?;
+        if batch.size_in_bytes() >= IMPORT_BATCH_SIZE {
+            db.write(&batch)?;
+            batch.clear()?;
+        }
+    }
+    db.write_sync(&batch)
+}
+
+// The snapshot is imported into an empty database, so all the records in the columns except the
+// meta one have been written by the failed import.
+fn remove_imported_records(db: &ChainDB) -> Result<(), Error> {
+    let mut batch = db.new_write_batch();
+    for &col in SNAPSHOT_COLUMNS.iter().filter(|&&col| col != COLUMN_META) {
+        for (key, _) in db.get_iter(col, IteratorMode::Start) {
+            batch.delete(col, &key)?;
+            if batch.size_in_bytes() >= IMPORT_BATCH_SIZE {
+                db.write(&batch)?;
+                batch.clear()?;
+            }
+        }
+    }
+    db.write_sync(&batch)
+}
+
+struct SnapshotWriter<W: Write> {
+    inner: W,
+    hasher: Blake2b,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    fn new(inner: W) -> Result<Self, Error> {
+        let mut writer = SnapshotWriter {
+            inner,
+            hasher: new_blake2b(),
+        };
+        writer.write(MAGIC)?;
+        writer.write(&[VERSION])?;
+        Ok(writer)
+    }
+
+    fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.write(&[column_id(col)])?;
+        self.write(&(key.len() as u32).to_le_bytes())?;
+        self.write(key)?;
+        self.write(&(value.len() as u32).to_le_bytes())?;
+        self.write(value)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.hasher.update(data);
+        self.inner.write_all(data).map_err(io_error)
+    }
+
+    fn finish(mut self) -> Result<packed::Byte32, Error> {
+        self.inner.flush().map_err(io_error)?;
+        let mut hash = [0u8; 32];
+        self.hasher.finalize(&mut hash);
+        Ok(hash.pack())
+    }
+}
+
+// Hashes the bytes while they are read, so the imported records are exactly the hashed ones.
+struct HashingReader<R: Read> {
+    inner: R,
+    hasher: Blake2b,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+impl<R: Read> HashingReader<R> {
+    fn finish(self) -> packed::Byte32 {
+        let mut hash = [0u8; 32];
+        self.hasher.finalize(&mut hash);
+        hash.pack()
+    }
+}
+
+type Record = (Col, Vec<u8>, Vec<u8>);
+
+// Returns `None` at the end of the file.
+fn read_record<R: Read>(reader: &mut R) -> Result<Option<Record>, Error> {
+    let mut id = [0u8; 1];
+    match reader.read_exact(&mut id) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(io_error(err)),
+    }
+    let col = SNAPSHOT_COLUMNS
+        .iter()
+        .copied()
+        .find(|col| column_id(col) == id[0])
+        .ok_or_else(|| malformed(format!("unexpected column {}", id[0])))?;
+    let key = read_bytes(reader)?;
+    let value = read_bytes(reader)?;
+    Ok(Some((col, key, value)))
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> Result<Vec<u8>, Error> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len).map_err(io_error)?;
+    let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut bytes).map_err(io_error)?;
+    Ok(bytes)
+}
+
+fn column_id(col: Col) -> u8 {
+    col.parse().expect("column ids are numbers")
+}
+
+fn io_error(err: io::Error) -> Error {
+    InternalErrorKind::System.because(err).into()
+}
+
+fn malformed<T: std::fmt::Display>(reason: T) -> Error {
+    InternalErrorKind::DataCorrupted
+        .other(format!("malformed cell snapshot: {reason}"))
+        .into()
+}
@@ -0,0 +1,168 @@
+//! Manual compaction of the chain database.
+//!
+//! RocksDB reclaims the space of the deleted keys only when the SST files containing them are
+//! compacted, the disk usage may stay high for a long time after large reorganizations or
+//! migrations. A `CompactionTask` compacts the chosen column families in steps and reports the
+//! progress before every step.
+//!
+//! A throttled task splits the key range of every column family at the first byte of the keys,
+//! into at most 256 sub-ranges, and pauses between the steps, so the compaction competes less
+//! with the running node.
+use crate::ChainDB;
+use ckb_db_schema::{column_by_name, column_name, Col, ALL_COLUMNS};
+use ckb_error::{Error, InternalErrorKind};
+use std::thread;
+use std::time::Duration;
+
+/// A step of a compaction task, compacting a key range of a column family.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactionStep {
+    /// The column family.
+    pub col: Col,
+    /// The first key of the range, unbounded if `None`.
+    pub start: Option<Vec<u8>>,
+    /// The key after the range, unbounded if `None`.
+    pub end: Option<Vec<u8>>,
+}
+
+/// The progress of a compaction task.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompactionProgress {
+    /// The names of the compacted column families.
+    pub columns: Vec<&'static str>,
+    /// The number of the steps of the task.
+    pub total_steps: usize,
+    /// The number of the completed steps.
+    pub completed_steps: usize,
+    /// The name of the column family being compacted.
+    pub current_column: Option<&'static str>,
+    /// The start time of the task, in milliseconds since the Unix epoch.
+    pub started_at: u64,
+    /// The finish time of the task, `None` if the task is still running.
+    pub finished_at: Option<u64>,
+    /// Whether the task is cancelled before completing all the steps.
+    pub cancelled: bool,
+}
+
+/// A manual compaction of the chain database.
+#[derive(Clone, Debug)]
+pub struct CompactionTask {
+    columns: Vec<Col>,
+    start: Option<Vec<u8>>,
+    end: Option<Vec<u8>>,
+    throttle: Option<Duration>,
+}
+
+impl CompactionTask {
+    /// Creates a task compacting the whole key space of the column families.
+    ///
+    /// The column families are referred by their names, e.g. "block_body", or their ids, all the
+    /// column families are compacted if `columns` is empty.
+    pub fn new<S: AsRef<str>>(columns: &[S]) -> Result<Self, Error> {
+        let columns = if columns.is_empty() {
+            ALL_COLUMNS.to_vec()
+        } else {
+            columns
+                .iter()
+                .map(|name| {
+                    column_by_name(name.as_ref()).ok_or_else(|| {
+                        InternalErrorKind::Database
+                            .other(format!("unknown column family {}", name.as_ref()))
+                            .into()
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?
+        };
+        Ok(CompactionTask {
+            columns,
+            start: None,
+            end: None,
+            throttle: None,
+        })
+    }
+
+    /// Compacts only the keys in the range `[start, end)`, the unbounded ends are `None`.
+    pub fn key_range(mut self, start: Option<Vec<u8>>, end: Option<Vec<u8>>) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// Pauses between the steps.
+    pub fn throttle(mut self, throttle: Duration) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Returns the steps of the task.
+    ///
+    /// Only a throttled task is split into sub-ranges.
+    pub fn steps(&self) -> Vec<CompactionStep> {
+        let mut bounds = vec![self.start.clone()];
+        if self.throttle.is_some() {
+            // split the key range at the single byte keys inside it
+            bounds.extend(
+                (1..=u8::MAX)
+                    .map(|byte| vec![byte])
+                    .filter(|key| {
+                        self.start.as_ref().map_or(true, |start| key > start)
+                            && self.end.as_ref().map_or(true, |end| key < end)
+                    })
+                    .map(Some),
+            );
+        }
+        bounds.push(self.end.clone());
+        self.columns
+            .iter()
+            .flat_map(|&col| {
+                bounds.windows(2).map(move |range| CompactionStep {
+                    col,
+                    start: range[0].clone(),
+                    end: range[1].clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the progress of the task before running it.
+    pub fn progress(&self) -> CompactionProgress {
+        CompactionProgress {
+            columns: self.columns.iter().map(|&col| column_name(col)).collect(),
+            total_steps: self.steps().len(),
+            started_at: ckb_systemtime::unix_time_as_millis(),
+            ..Default::default()
+        }
+    }
+
+    /// Runs the task in the current thread.
+    ///
+    /// `report` is called with the progress before every step and once more when the task
+    /// finishes, the task is cancelled when it returns false. Returns the final progress.
+    pub fn run<F>(&self, db: &ChainDB, mut report: F) -> Result<CompactionProgress, Error>
+    where
+        F: FnMut(&CompactionProgress) -> bool,
+    {
+        let steps = self.steps();
+        let mut progress = self.progress();
+
+        for (i, step) in steps.iter().enumerate() {
+            if i > 0 {
+                if let Some(throttle) = self.throttle {
+                    thread::sleep(throttle);
+                }
+            }
+            progress.current_column = Some(column_name(step.col));
+            if !report(&progress) {
+                progress.cancelled = true;
+                break;
+            }
+            db.compact_range(step.col, step.start.as_deref(), step.end.as_deref())?;
+            progress.completed_steps += 1;
+        }
+
+        progress.current_column = None;
+        progress.finished_at = Some(ckb_systemtime::unix_time_as_millis());
+        report(&progress);
+        Ok(progress)
+    }
+}
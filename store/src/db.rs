@@ -1,15 +1,12 @@
 use crate::cache::StoreCache;
 use crate::cell::attach_block_cell;
-use crate::store::ChainStore;
+use crate::store::{load_pruned_block_number, ChainStore};
 use crate::transaction::StoreTransaction;
 use crate::write_batch::StoreWriteBatch;
 use crate::StoreSnapshot;
 use ckb_app_config::StoreConfig;
 use ckb_chain_spec::{consensus::Consensus, versionbits::VersionbitsIndexer};
-use ckb_db::{
-    iter::{DBIter, DBIterator, IteratorMode},
-    DBPinnableSlice, RocksDB,
-};
+use ckb_db::{iter::IteratorMode, DBValue, KeyValueIter, KeyValueRead, KeyValueStore, RocksDB};
 use ckb_db_schema::{Col, CHAIN_SPEC_HASH_KEY, MIGRATION_VERSION_KEY};
 use ckb_error::{Error, InternalErrorKind};
 use ckb_freezer::Freezer;
@@ -19,17 +16,22 @@ use ckb_types::{
     prelude::*,
     utilities::merkle_mountain_range::ChainRootMMR,
 };
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
-/// A database of the chain store based on the RocksDB wrapper `RocksDB`
+/// A database of the chain store based on a key-value store, the RocksDB wrapper `RocksDB` by
+/// default.
+///
+/// The in-memory backend [`MemoryDB`](ckb_db::MemoryDB) runs the chain store without a data
+/// directory, e.g., `ChainDB::new(MemoryDB::open(COLUMNS), Default::default())` in tests.
 #[derive(Clone)]
-pub struct ChainDB {
-    db: RocksDB,
+pub struct ChainDB<DB = RocksDB> {
+    db: DB,
     freezer: Option<Freezer>,
     cache: Arc<StoreCache>,
 }
 
-impl ChainStore for ChainDB {
+impl<DB: KeyValueStore> ChainStore for ChainDB<DB> {
     fn cache(&self) -> Option<&StoreCache> {
         Some(&self.cache)
     }
@@ -38,18 +40,18 @@ impl ChainStore for ChainDB {
         self.freezer.as_ref()
     }
 
-    fn get(&self, col: Col, key: &[u8]) -> Option<DBPinnableSlice> {
-        self.db
-            .get_pinned(col, key)
-            .expect("db operation should be ok")
+    fn get(&self, col: Col, key: &[u8]) -> Option<DBValue> {
+        KeyValueRead::get(&self.db, col, key).expect("db operation should be ok")
     }
 
-    fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter {
-        self.db.iter(col, mode).expect("db operation should be ok")
+    fn get_iter(&self, col: Col, mode: IteratorMode) -> KeyValueIter {
+        self.db
+            .iterate(col, mode)
+            .expect("db operation should be ok")
     }
 }
 
-impl VersionbitsIndexer for ChainDB {
+impl<DB: KeyValueStore> VersionbitsIndexer for ChainDB<DB> {
     fn block_epoch_index(&self, block_hash: &packed::Byte32) -> Option<packed::Byte32> {
         ChainStore::get_block_epoch_index(self, block_hash)
     }
@@ -67,34 +69,38 @@ impl VersionbitsIndexer for ChainDB {
     }
 }
 
-impl ChainDB {
+impl<DB: KeyValueStore> ChainDB<DB> {
     /// Allocate a new ChainDB instance with the given config
-    pub fn new(db: RocksDB, config: StoreConfig) -> Self {
+    pub fn new(db: DB, config: StoreConfig) -> Self {
         let cache = StoreCache::from_config(config);
-        ChainDB {
+        let store = ChainDB {
             db,
             freezer: None,
             cache: Arc::new(cache),
-        }
+        };
+        load_pruned_block_number(&store);
+        store
     }
 
     /// Open new ChainDB with freezer instance
-    pub fn new_with_freezer(db: RocksDB, freezer: Freezer, config: StoreConfig) -> Self {
+    pub fn new_with_freezer(db: DB, freezer: Freezer, config: StoreConfig) -> Self {
         let cache = StoreCache::from_config(config);
-        ChainDB {
+        let store = ChainDB {
             db,
             freezer: Some(freezer),
             cache: Arc::new(cache),
-        }
+        };
+        load_pruned_block_number(&store);
+        store
     }
 
-    /// Return the inner RocksDB instance
-    pub fn db(&self) -> &RocksDB {
+    /// Return the inner key-value store
+    pub fn db(&self) -> &DB {
         &self.db
     }
 
-    /// Converts self into a `RocksDB`
-    pub fn into_inner(self) -> RocksDB {
+    /// Converts self into the inner key-value store
+    pub fn into_inner(self) -> DB {
         self.db
     }
 
@@ -106,20 +112,20 @@ impl ChainDB {
     /// Return the chain spec hash
     pub fn get_chain_spec_hash(&self) -> Option<packed::Byte32> {
         self.db
-            .get_pinned_default(CHAIN_SPEC_HASH_KEY)
+            .get_default(CHAIN_SPEC_HASH_KEY)
             .expect("db operation should be ok")
             .map(|raw| packed::Byte32Reader::from_slice_should_be_ok(raw.as_ref()).to_entity())
     }
 
     /// Return the chain spec hash
-    pub fn get_migration_version(&self) -> Option<DBPinnableSlice> {
+    pub fn get_migration_version(&self) -> Option<DBValue> {
         self.db
-            .get_pinned_default(MIGRATION_VERSION_KEY)
+            .get_default(MIGRATION_VERSION_KEY)
             .expect("db operation should be ok")
     }
 
     /// Set this snapshot at start of transaction
-    pub fn begin_transaction(&self) -> StoreTransaction {
+    pub fn begin_transaction(&self) -> StoreTransaction<DB> {
         StoreTransaction {
             inner: self.db.transaction(),
             freezer: self.freezer.clone(),
@@ -128,7 +134,7 @@ impl ChainDB {
     }
 
     /// Return `StoreSnapshot`
-    pub fn get_snapshot(&self) -> StoreSnapshot {
+    pub fn get_snapshot(&self) -> StoreSnapshot<DB> {
         StoreSnapshot {
             inner: self.db.get_snapshot(),
             freezer: self.freezer.clone(),
@@ -137,22 +143,35 @@ impl ChainDB {
     }
 
     /// Construct `StoreWriteBatch` with default option.
-    pub fn new_write_batch(&self) -> StoreWriteBatch {
+    pub fn new_write_batch(&self) -> StoreWriteBatch<DB> {
         StoreWriteBatch {
             inner: self.db.new_write_batch(),
+            pruned_block_number: None,
         }
     }
 
     /// Write batch into chain db.
-    pub fn write(&self, write_batch: &StoreWriteBatch) -> Result<(), Error> {
-        self.db.write(&write_batch.inner)
+    pub fn write(&self, write_batch: &StoreWriteBatch<DB>) -> Result<(), Error> {
+        self.db.write(&write_batch.inner)?;
+        self.cache_pruned_block_number(write_batch);
+        Ok(())
     }
 
     /// write options set_sync = true
     ///
     /// see [`RocksDB::write_sync`](ckb_db::RocksDB::write_sync).
-    pub fn write_sync(&self, write_batch: &StoreWriteBatch) -> Result<(), Error> {
-        self.db.write_sync(&write_batch.inner)
+    pub fn write_sync(&self, write_batch: &StoreWriteBatch<DB>) -> Result<(), Error> {
+        self.db.write_sync(&write_batch.inner)?;
+        self.cache_pruned_block_number(write_batch);
+        Ok(())
+    }
+
+    fn cache_pruned_block_number(&self, write_batch: &StoreWriteBatch<DB>) {
+        if let Some(number) = write_batch.pruned_block_number {
+            self.cache
+                .pruned_block_number
+                .store(number, Ordering::Release);
+        }
     }
 
     /// Force the data to go through the compaction in order to consolidate it
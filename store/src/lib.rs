@@ -3,11 +3,16 @@
 //! This Library contains the `ChainStore` traits
 //! which provides chain data store interface
 
+mod backup;
 mod cache;
 mod cell;
+mod cell_snapshot;
+mod compaction;
 pub mod data_loader_wrapper;
 mod db;
+mod read_only;
 mod snapshot;
+mod stats;
 mod store;
 mod transaction;
 mod write_batch;
@@ -15,12 +20,22 @@ mod write_batch;
 #[cfg(test)]
 mod tests;
 
+pub use backup::{
+    backup_chain_db, BackupManifest, BACKUP_ANCIENT_DIR, BACKUP_DB_DIR, BACKUP_MANIFEST_FILE,
+};
 pub use cache::StoreCache;
 pub use cell::{attach_block_cell, detach_block_cell};
+pub use cell_snapshot::{
+    cell_snapshot_hash, export_cell_snapshot, import_cell_snapshot, CellSnapshotInfo,
+};
+pub use compaction::{CompactionProgress, CompactionStep, CompactionTask};
 pub use db::ChainDB;
+pub use read_only::ReadOnlyChainDB;
 pub use snapshot::StoreSnapshot;
+pub use stats::DBStats;
 pub use store::ChainStore;
 pub use transaction::StoreTransaction;
 pub use write_batch::StoreWriteBatch;
 
+pub use ckb_db::{DBValue, KeyValueIter};
 pub use ckb_freezer::Freezer;
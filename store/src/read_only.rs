@@ -0,0 +1,96 @@
+use crate::cache::StoreCache;
+use crate::store::{load_pruned_block_number, ChainStore};
+use ckb_app_config::StoreConfig;
+use ckb_chain_spec::versionbits::VersionbitsIndexer;
+use ckb_db::{iter::IteratorMode, DBValue, KeyValueIter, KeyValueRead, ReadOnlyDB};
+use ckb_db_schema::{Col, ALL_COLUMNS};
+use ckb_error::Error;
+use ckb_freezer::Freezer;
+use ckb_merkle_mountain_range::{Error as MMRError, MMRStore, Result as MMRResult};
+use ckb_types::{
+    core::{EpochExt, HeaderView, TransactionView},
+    packed,
+};
+use std::path::Path;
+use std::sync::Arc;
+
+/// A chain store on a database opened in read-only mode.
+///
+/// It could be opened while a node is running on the same database, and only sees the data which
+/// had been flushed when it was opened.
+#[derive(Clone)]
+pub struct ReadOnlyChainDB {
+    db: Arc<ReadOnlyDB>,
+    freezer: Option<Freezer>,
+    cache: Arc<StoreCache>,
+}
+
+impl ReadOnlyChainDB {
+    /// Opens the database at the path in read-only mode, returns `None` if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        freezer: Option<Freezer>,
+        config: StoreConfig,
+    ) -> Result<Option<Self>, Error> {
+        let db = ReadOnlyDB::open_cf(path, ALL_COLUMNS)?;
+        Ok(db.map(|db| {
+            let store = ReadOnlyChainDB {
+                db: Arc::new(db),
+                freezer,
+                cache: Arc::new(StoreCache::from_config(config)),
+            };
+            load_pruned_block_number(&store);
+            store
+        }))
+    }
+}
+
+impl ChainStore for ReadOnlyChainDB {
+    fn cache(&self) -> Option<&StoreCache> {
+        Some(&self.cache)
+    }
+
+    fn freezer(&self) -> Option<&Freezer> {
+        self.freezer.as_ref()
+    }
+
+    fn get(&self, col: Col, key: &[u8]) -> Option<DBValue> {
+        KeyValueRead::get(self.db.as_ref(), col, key).expect("db operation should be ok")
+    }
+
+    fn get_iter(&self, col: Col, mode: IteratorMode) -> KeyValueIter {
+        self.db
+            .iterate(col, mode)
+            .expect("db operation should be ok")
+    }
+}
+
+impl VersionbitsIndexer for ReadOnlyChainDB {
+    fn block_epoch_index(&self, block_hash: &packed::Byte32) -> Option<packed::Byte32> {
+        ChainStore::get_block_epoch_index(self, block_hash)
+    }
+
+    fn epoch_ext(&self, index: &packed::Byte32) -> Option<EpochExt> {
+        ChainStore::get_epoch_ext(self, index)
+    }
+
+    fn block_header(&self, block_hash: &packed::Byte32) -> Option<HeaderView> {
+        ChainStore::get_block_header(self, block_hash)
+    }
+
+    fn cellbase(&self, block_hash: &packed::Byte32) -> Option<TransactionView> {
+        ChainStore::get_cellbase(self, block_hash)
+    }
+}
+
+impl MMRStore<packed::HeaderDigest> for &ReadOnlyChainDB {
+    fn get_elem(&self, pos: u64) -> MMRResult<Option<packed::HeaderDigest>> {
+        Ok(self.get_header_digest(pos))
+    }
+
+    fn append(&mut self, _pos: u64, _elems: Vec<packed::HeaderDigest>) -> MMRResult<()> {
+        Err(MMRError::StoreError(
+            "Failed to append to MMR, the read-only chain store is immutable".into(),
+        ))
+    }
+}
@@ -1,21 +1,18 @@
 use crate::cache::StoreCache;
 use crate::store::ChainStore;
-use ckb_db::{
-    iter::{DBIter, DBIterator, IteratorMode},
-    DBPinnableSlice, RocksDBSnapshot,
-};
+use ckb_db::{iter::IteratorMode, DBValue, KeyValueIter, KeyValueRead, KeyValueStore, RocksDB};
 use ckb_db_schema::Col;
 use ckb_freezer::Freezer;
 use std::sync::Arc;
 
 /// A snapshot of the chain store.
-pub struct StoreSnapshot {
-    pub(crate) inner: RocksDBSnapshot,
+pub struct StoreSnapshot<DB: KeyValueStore = RocksDB> {
+    pub(crate) inner: DB::Snapshot,
     pub(crate) freezer: Option<Freezer>,
     pub(crate) cache: Arc<StoreCache>,
 }
 
-impl ChainStore for StoreSnapshot {
+impl<DB: KeyValueStore> ChainStore for StoreSnapshot<DB> {
     fn cache(&self) -> Option<&StoreCache> {
         Some(&self.cache)
     }
@@ -24,15 +21,13 @@ impl ChainStore for StoreSnapshot {
         self.freezer.as_ref()
     }
 
-    fn get(&self, col: Col, key: &[u8]) -> Option<DBPinnableSlice> {
-        self.inner
-            .get_pinned(col, key)
-            .expect("db operation should be ok")
+    fn get(&self, col: Col, key: &[u8]) -> Option<DBValue> {
+        self.inner.get(col, key).expect("db operation should be ok")
     }
 
-    fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter {
+    fn get_iter(&self, col: Col, mode: IteratorMode) -> KeyValueIter {
         self.inner
-            .iter(col, mode)
+            .iterate(col, mode)
             .expect("db operation should be ok")
     }
 }
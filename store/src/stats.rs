@@ -0,0 +1,72 @@
+//! Disk usage statistics of the chain database and the freezer.
+use crate::{ChainDB, ChainStore};
+use ckb_db::ColumnFamilyStats;
+use ckb_db_schema::ALL_COLUMNS;
+use ckb_error::Error;
+use ckb_freezer::FreezerTableStats;
+
+/// The disk usage statistics of the chain database and the freezer.
+#[derive(Clone, Debug, Default)]
+pub struct DBStats {
+    /// The statistics of all the column families.
+    pub columns: Vec<ColumnFamilyStats>,
+    /// The statistics of the freezer tables, empty if the freezer is disabled.
+    pub freezer: Vec<FreezerTableStats>,
+}
+
+impl DBStats {
+    /// Collects the statistics of the chain database and the freezer.
+    pub fn collect(db: &ChainDB) -> Result<Self, Error> {
+        let columns = ALL_COLUMNS
+            .into_iter()
+            .map(|col| db.db().column_family_stats(col))
+            .collect::<Result<Vec<_>, _>>()?;
+        let freezer = match db.freezer() {
+            Some(freezer) => freezer.stats()?,
+            None => Vec::new(),
+        };
+        Ok(DBStats { columns, freezer })
+    }
+
+    /// Exposes the statistics through [ckb-metrics](../../ckb_metrics/index.html).
+    pub fn update_metrics(&self) {
+        let metrics = match ckb_metrics::handle() {
+            Some(metrics) => metrics,
+            None => return,
+        };
+        for stats in &self.columns {
+            for (key, value) in [
+                ("estimate-num-keys", stats.estimate_num_keys),
+                ("live-sst-files-size", stats.live_sst_files_size),
+                (
+                    "estimate-pending-compaction-bytes",
+                    stats.estimate_pending_compaction_bytes,
+                ),
+            ] {
+                metrics
+                    .ckb_db_cf_stats
+                    .with_label_values(&[key, stats.name()])
+                    .set(value as i64);
+            }
+            if let Some(ratio) = stats.compression_ratio() {
+                metrics
+                    .ckb_db_cf_compression_ratio
+                    .with_label_values(&[stats.name()])
+                    .set(ratio);
+            }
+        }
+        for stats in &self.freezer {
+            for (key, value) in [
+                ("number", stats.number),
+                ("files", stats.files),
+                ("data-size", stats.data_size),
+                ("index-size", stats.index_size),
+            ] {
+                metrics
+                    .ckb_freezer_table_stats
+                    .with_label_values(&[key, stats.name])
+                    .set(value as i64);
+            }
+        }
+    }
+}
@@ -1,17 +1,18 @@
 use crate::cache::StoreCache;
 use crate::data_loader_wrapper::BorrowedDataLoaderWrapper;
 use ckb_db::{
-    iter::{DBIter, Direction, IteratorMode},
-    DBPinnableSlice,
+    iter::{Direction, IteratorMode},
+    DBValue, KeyValueIter,
 };
 use ckb_db_schema::{
     Col, COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_EXTENSION,
     COLUMN_BLOCK_FILTER, COLUMN_BLOCK_FILTER_HASH, COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS,
     COLUMN_BLOCK_UNCLE, COLUMN_CELL, COLUMN_CELL_DATA, COLUMN_CELL_DATA_HASH,
     COLUMN_CHAIN_ROOT_MMR, COLUMN_EPOCH, COLUMN_INDEX, COLUMN_META, COLUMN_TRANSACTION_INFO,
-    COLUMN_UNCLES, META_CURRENT_EPOCH_KEY, META_LATEST_BUILT_FILTER_DATA_KEY, META_TIP_HEADER_KEY,
+    COLUMN_UNCLES, META_CURRENT_EPOCH_KEY, META_LATEST_BUILT_FILTER_DATA_KEY,
+    META_PRUNED_BLOCK_NUMBER_KEY, META_TIP_HEADER_KEY,
 };
-use ckb_freezer::Freezer;
+use ckb_freezer::{Freezer, FreezerTable};
 use ckb_types::{
     bytes::Bytes,
     core::{
@@ -21,6 +22,7 @@ use ckb_types::{
     packed::{self, OutPoint},
     prelude::*,
 };
+use std::sync::atomic::Ordering;
 
 /// The `ChainStore` trait provides chain data store interface
 pub trait ChainStore: Send + Sync + Sized {
@@ -29,9 +31,9 @@ pub trait ChainStore: Send + Sync + Sized {
     /// Return freezer reference
     fn freezer(&self) -> Option<&Freezer>;
     /// Return the bytes associated with a key value and the given column family.
-    fn get(&self, col: Col, key: &[u8]) -> Option<DBPinnableSlice>;
+    fn get(&self, col: Col, key: &[u8]) -> Option<DBValue>;
     /// Return an iterator over the database key-value pairs in the given column family.
-    fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter;
+    fn get_iter(&self, col: Col, mode: IteratorMode) -> KeyValueIter;
     /// Return the borrowed data loader wrapper
     fn borrow_as_data_loader(&self) -> BorrowedDataLoaderWrapper<Self> {
         BorrowedDataLoaderWrapper::new(self)
@@ -40,6 +42,9 @@ pub trait ChainStore: Send + Sync + Sized {
     /// Get block by block header hash
     fn get_block(&self, h: &packed::Byte32) -> Option<BlockView> {
         let header = self.get_block_header(h)?;
+        if self.is_block_body_pruned(header.number()) {
+            return None;
+        }
         if let Some(freezer) = self.freezer() {
             if header.number() > 0 && header.number() < freezer.number() {
                 let raw_block = freezer.retrieve(header.number()).expect("block frozen")?;
@@ -73,10 +78,18 @@ pub trait ChainStore: Send + Sync + Sized {
                 return Some(header.clone());
             }
         };
-        let ret = self.get(COLUMN_BLOCK_HEADER, hash.as_slice()).map(|slice| {
-            let reader = packed::HeaderViewReader::from_slice_should_be_ok(slice.as_ref());
-            Unpack::<HeaderView>::unpack(&reader)
-        });
+        let ret = self
+            .get(COLUMN_BLOCK_HEADER, hash.as_slice())
+            .map(|slice| {
+                let reader = packed::HeaderViewReader::from_slice_should_be_ok(slice.as_ref());
+                Unpack::<HeaderView>::unpack(&reader)
+            })
+            .or_else(|| {
+                get_frozen_meta(self, FreezerTable::Headers, hash).map(|raw| {
+                    let reader = packed::HeaderViewReader::from_slice_should_be_ok(&raw);
+                    Unpack::<HeaderView>::unpack(&reader)
+                })
+            });
 
         if let Some(cache) = self.cache() {
             ret.map(|header| {
@@ -91,45 +104,46 @@ pub trait ChainStore: Send + Sync + Sized {
     /// Get block body by block header hash
     fn get_block_body(&self, hash: &packed::Byte32) -> Vec<TransactionView> {
         let prefix = hash.as_slice();
-        self.get_iter(
-            COLUMN_BLOCK_BODY,
-            IteratorMode::From(prefix, Direction::Forward),
-        )
-        .take_while(|(key, _)| key.starts_with(prefix))
-        .map(|(_key, value)| {
-            let reader = packed::TransactionViewReader::from_slice_should_be_ok(value.as_ref());
-            Unpack::<TransactionView>::unpack(&reader)
-        })
-        .collect()
+        let body: Vec<_> = self
+            .get_iter(
+                COLUMN_BLOCK_BODY,
+                IteratorMode::From(prefix, Direction::Forward),
+            )
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(_key, value)| {
+                let reader = packed::TransactionViewReader::from_slice_should_be_ok(value.as_ref());
+                Unpack::<TransactionView>::unpack(&reader)
+            })
+            .collect();
+        // a block always contains the cellbase, the body is empty only if it's frozen or missing
+        if body.is_empty() {
+            if let Some(block) = get_frozen_block(self, hash) {
+                return block.into_view().transactions();
+            }
+        }
+        body
     }
 
     /// Get unfrozen block from ky-store with given hash
+    ///
+    /// Returns `None` if the body of the block has been wiped out after frozen. The header may
+    /// be served by the freezer, e.g., when the blocks are re-frozen after a repair.
     fn get_unfrozen_block(&self, hash: &packed::Byte32) -> Option<BlockView> {
-        let header = self
-            .get(COLUMN_BLOCK_HEADER, hash.as_slice())
-            .map(|slice| {
-                let reader = packed::HeaderViewReader::from_slice_should_be_ok(slice.as_ref());
-                Unpack::<HeaderView>::unpack(&reader)
-            })?;
+        let header = self.get_block_header(hash)?;
 
         let body = self.get_block_body(hash);
 
-        let uncles = self
-            .get(COLUMN_BLOCK_UNCLE, hash.as_slice())
-            .map(|slice| {
-                let reader =
-                    packed::UncleBlockVecViewReader::from_slice_should_be_ok(slice.as_ref());
-                Unpack::<UncleBlockVecView>::unpack(&reader)
-            })
-            .expect("block uncles must be stored");
+        let uncles = self.get(COLUMN_BLOCK_UNCLE, hash.as_slice()).map(|slice| {
+            let reader = packed::UncleBlockVecViewReader::from_slice_should_be_ok(slice.as_ref());
+            Unpack::<UncleBlockVecView>::unpack(&reader)
+        })?;
 
         let proposals = self
             .get(COLUMN_BLOCK_PROPOSAL_IDS, hash.as_slice())
             .map(|slice| {
                 packed::ProposalShortIdVecReader::from_slice_should_be_ok(slice.as_ref())
                     .to_entity()
-            })
-            .expect("block proposal_ids must be stored");
+            })?;
 
         let extension_opt = self
             .get(COLUMN_BLOCK_EXTENSION, hash.as_slice())
@@ -153,7 +167,7 @@ pub trait ChainStore: Send + Sync + Sized {
         };
 
         let prefix = hash.as_slice();
-        let ret: Vec<_> = self
+        let mut ret: Vec<_> = self
             .get_iter(
                 COLUMN_BLOCK_BODY,
                 IteratorMode::From(prefix, Direction::Forward),
@@ -164,6 +178,16 @@ pub trait ChainStore: Send + Sync + Sized {
                 reader.hash().to_entity()
             })
             .collect();
+        if ret.is_empty() {
+            if let Some(raw) = get_frozen_meta(self, FreezerTable::TxHashes, hash) {
+                ret = packed::Byte32VecReader::from_slice_should_be_ok(&raw)
+                    .to_entity()
+                    .into_iter()
+                    .collect();
+            } else if let Some(block) = get_frozen_block(self, hash) {
+                ret = block.into_view().tx_hashes().to_vec();
+            }
+        }
 
         if let Some(cache) = self.cache() {
             cache.block_tx_hashes.lock().put(hash.clone(), ret.clone());
@@ -188,7 +212,8 @@ pub trait ChainStore: Send + Sync + Sized {
             .map(|slice| {
                 packed::ProposalShortIdVecReader::from_slice_should_be_ok(slice.as_ref())
                     .to_entity()
-            });
+            })
+            .or_else(|| get_frozen_block(self, hash).map(|block| block.proposals()));
 
         if let Some(cache) = self.cache() {
             ret.map(|data| {
@@ -208,10 +233,14 @@ pub trait ChainStore: Send + Sync + Sized {
             }
         };
 
-        let ret = self.get(COLUMN_BLOCK_UNCLE, hash.as_slice()).map(|slice| {
-            let reader = packed::UncleBlockVecViewReader::from_slice_should_be_ok(slice.as_ref());
-            Unpack::<UncleBlockVecView>::unpack(&reader)
-        });
+        let ret = self
+            .get(COLUMN_BLOCK_UNCLE, hash.as_slice())
+            .map(|slice| {
+                let reader =
+                    packed::UncleBlockVecViewReader::from_slice_should_be_ok(slice.as_ref());
+                Unpack::<UncleBlockVecView>::unpack(&reader)
+            })
+            .or_else(|| get_frozen_block(self, hash).map(|block| block.into_view().uncles()));
 
         if let Some(cache) = self.cache() {
             ret.map(|uncles| {
@@ -233,7 +262,10 @@ pub trait ChainStore: Send + Sync + Sized {
 
         let ret = self
             .get(COLUMN_BLOCK_EXTENSION, hash.as_slice())
-            .map(|slice| packed::BytesReader::from_slice_should_be_ok(slice.as_ref()).to_entity());
+            .map(|slice| packed::BytesReader::from_slice_should_be_ok(slice.as_ref()).to_entity())
+            .or_else(|| {
+                get_frozen_block(self, hash).and_then(|block| block.into_view().extension())
+            });
 
         if let Some(cache) = self.cache() {
             cache.block_extensions.lock().put(hash.clone(), ret.clone());
@@ -246,19 +278,10 @@ pub trait ChainStore: Send + Sync + Sized {
     /// Since v0.106, `BlockExt` added two option fields, so we have to use compatibility mode to read
     fn get_block_ext(&self, block_hash: &packed::Byte32) -> Option<BlockExt> {
         self.get(COLUMN_BLOCK_EXT, block_hash.as_slice())
-            .map(|slice| {
-                let reader =
-                    packed::BlockExtReader::from_compatible_slice_should_be_ok(slice.as_ref());
-                match reader.count_extra_fields() {
-                    0 => reader.unpack(),
-                    2 => packed::BlockExtV1Reader::from_slice_should_be_ok(slice.as_ref()).unpack(),
-                    _ => {
-                        panic!(
-                            "BlockExt storage field count doesn't match, expect 7 or 5, actual {}",
-                            reader.field_count()
-                        )
-                    }
-                }
+            .map(|slice| unpack_block_ext(slice.as_ref()))
+            .or_else(|| {
+                get_frozen_meta(self, FreezerTable::BlockExts, block_hash)
+                    .map(|raw| unpack_block_ext(&raw))
             })
     }
 
@@ -306,12 +329,17 @@ pub trait ChainStore: Send + Sync + Sized {
     }
 
     /// TODO(doc): @quake
+    ///
+    /// The info of a transaction in a frozen block is stored as the block number only, the rest
+    /// is recovered from the freezer.
     fn get_transaction_info(&self, hash: &packed::Byte32) -> Option<TransactionInfo> {
-        self.get(COLUMN_TRANSACTION_INFO, hash.as_slice())
-            .map(|slice| {
-                let reader = packed::TransactionInfoReader::from_slice_should_be_ok(slice.as_ref());
-                Unpack::<TransactionInfo>::unpack(&reader)
-            })
+        let slice = self.get(COLUMN_TRANSACTION_INFO, hash.as_slice())?;
+        if slice.as_ref().len() == packed::Uint64::TOTAL_SIZE {
+            let number = packed::Uint64Reader::from_slice_should_be_ok(slice.as_ref()).unpack();
+            return get_frozen_transaction_info(self, hash, number);
+        }
+        let reader = packed::TransactionInfoReader::from_slice_should_be_ok(slice.as_ref());
+        Some(Unpack::<TransactionInfo>::unpack(&reader))
     }
 
     /// Gets transaction and associated info with correspond hash
@@ -451,6 +479,10 @@ pub trait ChainStore: Send + Sync + Sized {
     fn get_block_epoch_index(&self, block_hash: &packed::Byte32) -> Option<packed::Byte32> {
         self.get(COLUMN_BLOCK_EPOCH, block_hash.as_slice())
             .map(|raw| packed::Byte32Reader::from_slice_should_be_ok(raw.as_ref()).to_entity())
+            .or_else(|| {
+                get_frozen_meta(self, FreezerTable::BlockEpochs, block_hash)
+                    .map(|raw| packed::Byte32Reader::from_slice_should_be_ok(&raw).to_entity())
+            })
     }
 
     /// TODO(doc): @quake
@@ -480,6 +512,7 @@ pub trait ChainStore: Send + Sync + Sized {
             }
         };
         self.get(COLUMN_BLOCK_HEADER, hash.as_slice()).is_some()
+            || get_frozen_meta_number(self, hash).is_some()
     }
 
     /// Gets cellbase by block hash
@@ -487,10 +520,34 @@ pub trait ChainStore: Send + Sync + Sized {
         let key = packed::TransactionKey::new_builder()
             .block_hash(hash.to_owned())
             .build();
-        self.get(COLUMN_BLOCK_BODY, key.as_slice()).map(|slice| {
-            let reader = packed::TransactionViewReader::from_slice_should_be_ok(slice.as_ref());
-            Unpack::<TransactionView>::unpack(&reader)
-        })
+        self.get(COLUMN_BLOCK_BODY, key.as_slice())
+            .map(|slice| {
+                let reader = packed::TransactionViewReader::from_slice_should_be_ok(slice.as_ref());
+                Unpack::<TransactionView>::unpack(&reader)
+            })
+            .or_else(|| {
+                get_frozen_block(self, hash)
+                    .and_then(|block| block.into_view().transactions().into_iter().next())
+            })
+    }
+
+    /// Gets the number below which the bodies of the main chain blocks have been pruned
+    fn get_pruned_block_number(&self) -> Option<BlockNumber> {
+        if let Some(cache) = self.cache() {
+            let number = cache.pruned_block_number.load(Ordering::Acquire);
+            return if number > 0 { Some(number) } else { None };
+        }
+        self.get(COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY)
+            .map(|raw| packed::Uint64Reader::from_slice_should_be_ok(raw.as_ref()).unpack())
+    }
+
+    /// Returns true if the body of the block with the given number has been pruned, the genesis
+    /// block is never pruned
+    fn is_block_body_pruned(&self, number: BlockNumber) -> bool {
+        number > 0
+            && self
+                .get_pruned_block_number()
+                .map_or(false, |pruned| number < pruned)
     }
 
     /// Gets latest built filter data block hash
@@ -513,12 +570,21 @@ pub trait ChainStore: Send + Sync + Sized {
 
     /// Gets block bytes by block hash
     fn get_packed_block(&self, hash: &packed::Byte32) -> Option<packed::Block> {
-        let header = self
-            .get(COLUMN_BLOCK_HEADER, hash.as_slice())
-            .map(|slice| {
+        let header = match self.get(COLUMN_BLOCK_HEADER, hash.as_slice()) {
+            Some(slice) => {
                 let reader = packed::HeaderViewReader::from_slice_should_be_ok(slice.as_ref());
                 reader.data().to_entity()
-            })?;
+            }
+            // the header is wiped out after frozen
+            None => return get_frozen_block(self, hash),
+        };
+        // the body is wiped out after frozen, check the cached frozen number with the number in
+        // the header before reading the body
+        if let Some(block) = self.freezer().and_then(|freezer| {
+            retrieve_frozen_block(freezer, header.raw().number().unpack(), hash)
+        }) {
+            return Some(block);
+        }
 
         let prefix = hash.as_slice();
         let transactions: packed::TransactionVec = self
@@ -560,10 +626,17 @@ pub trait ChainStore: Send + Sync + Sized {
 
     /// Gets block header bytes by block hash
     fn get_packed_block_header(&self, hash: &packed::Byte32) -> Option<packed::Header> {
-        self.get(COLUMN_BLOCK_HEADER, hash.as_slice()).map(|slice| {
-            let reader = packed::HeaderViewReader::from_slice_should_be_ok(slice.as_ref());
-            reader.data().to_entity()
-        })
+        self.get(COLUMN_BLOCK_HEADER, hash.as_slice())
+            .map(|slice| {
+                let reader = packed::HeaderViewReader::from_slice_should_be_ok(slice.as_ref());
+                reader.data().to_entity()
+            })
+            .or_else(|| {
+                get_frozen_meta(self, FreezerTable::Headers, hash).map(|raw| {
+                    let reader = packed::HeaderViewReader::from_slice_should_be_ok(&raw);
+                    reader.data().to_entity()
+                })
+            })
     }
 
     /// Gets a header digest.
@@ -607,3 +680,95 @@ fn build_cell_meta_from_reader(out_point: OutPoint, reader: packed::CellEntryRea
         mem_cell_data_hash: None,
     }
 }
+
+fn unpack_block_ext(slice: &[u8]) -> BlockExt {
+    let reader = packed::BlockExtReader::from_compatible_slice_should_be_ok(slice);
+    match reader.count_extra_fields() {
+        0 => reader.unpack(),
+        2 => packed::BlockExtV1Reader::from_slice_should_be_ok(slice).unpack(),
+        _ => {
+            panic!(
+                "BlockExt storage field count doesn't match, expect 7 or 5, actual {}",
+                reader.field_count()
+            )
+        }
+    }
+}
+
+// Returns the main chain block if its body has been moved into the freezer.
+fn get_frozen_block<S: ChainStore>(store: &S, hash: &packed::Byte32) -> Option<packed::Block> {
+    let freezer = store.freezer()?;
+    // nothing is frozen, skip reading the block number
+    if freezer.number() <= 1 {
+        return None;
+    }
+    let number = store.get_block_number(hash)?;
+    retrieve_frozen_block(freezer, number, hash)
+}
+
+// Returns the frozen block with the number if it's the block with the hash, a block out of the
+// main chain is never frozen.
+fn retrieve_frozen_block(
+    freezer: &Freezer,
+    number: BlockNumber,
+    hash: &packed::Byte32,
+) -> Option<packed::Block> {
+    if number == 0 || number >= freezer.number() {
+        return None;
+    }
+    let raw_block = freezer.retrieve(number).expect("block frozen")?;
+    let block = packed::BlockReader::from_compatible_slice(&raw_block).expect("checked data");
+    (block.header().calc_header_hash() == *hash).then(|| block.to_entity())
+}
+
+// Returns the number of the main chain block if its header, block ext, transaction hashes and
+// epoch index have been moved into the freezer.
+fn get_frozen_meta_number<S: ChainStore>(store: &S, hash: &packed::Byte32) -> Option<BlockNumber> {
+    let freezer = store.freezer()?;
+    let number = store.get_block_number(hash)?;
+    (number > 0 && number < freezer.meta_number()).then_some(number)
+}
+
+fn get_frozen_meta<S: ChainStore>(
+    store: &S,
+    table: FreezerTable,
+    hash: &packed::Byte32,
+) -> Option<Vec<u8>> {
+    let number = get_frozen_meta_number(store, hash)?;
+    // the corrupted item is logged by the freezer, treat it as missing
+    store.freezer()?.retrieve_meta(table, number).ok().flatten()
+}
+
+fn get_frozen_transaction_info<S: ChainStore>(
+    store: &S,
+    tx_hash: &packed::Byte32,
+    number: BlockNumber,
+) -> Option<TransactionInfo> {
+    let raw = store
+        .freezer()?
+        .retrieve_meta(FreezerTable::TxHashes, number)
+        .ok()
+        .flatten()?;
+    let index = packed::Byte32VecReader::from_slice_should_be_ok(&raw)
+        .iter()
+        .position(|hash| hash.as_slice() == tx_hash.as_slice())?;
+    let block_hash = store.get_block_hash(number)?;
+    let header = store.get_block_header(&block_hash)?;
+    Some(TransactionInfo {
+        block_hash,
+        block_number: number,
+        block_epoch: header.epoch(),
+        index,
+    })
+}
+
+// Caches the pruned block number of the store, it's read by every `get_block`.
+pub(crate) fn load_pruned_block_number<S: ChainStore>(store: &S) {
+    if let Some(cache) = store.cache() {
+        let number = store
+            .get(COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY)
+            .map(|raw| packed::Uint64Reader::from_slice_should_be_ok(raw.as_ref()).unpack())
+            .unwrap_or(0);
+        cache.pruned_block_number.store(number, Ordering::Release);
+    }
+}
@@ -1,11 +1,17 @@
 use ckb_chain_spec::consensus::ConsensusBuilder;
-use ckb_db::RocksDB;
-use ckb_db_schema::{COLUMNS, COLUMN_BLOCK_HEADER};
+use ckb_db::{IteratorMode, MemoryDB, RocksDB};
+use ckb_db_schema::{
+    COLUMNS, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_HEADER, COLUMN_CELL,
+};
 use ckb_freezer::Freezer;
 use ckb_types::{core::BlockExt, packed, prelude::*};
+use std::time::Duration;
 use tempfile::TempDir;
 
-use crate::{db::ChainDB, store::ChainStore};
+use crate::{
+    backup_chain_db, db::ChainDB, export_cell_snapshot, import_cell_snapshot, store::ChainStore,
+    BackupManifest, CompactionTask, DBStats, ReadOnlyChainDB, BACKUP_ANCIENT_DIR, BACKUP_DB_DIR,
+};
 
 #[test]
 fn save_and_get_block() {
@@ -90,6 +96,82 @@ fn index_store() {
     assert_eq!(block.header(), store.get_tip_header().unwrap());
 }
 
+#[test]
+fn read_only_store() {
+    let tmp_dir = TempDir::new().unwrap();
+    assert!(
+        ReadOnlyChainDB::open(tmp_dir.path().join("none"), None, Default::default())
+            .unwrap()
+            .is_none()
+    );
+
+    let db = RocksDB::open_in(&tmp_dir, COLUMNS);
+    let store = ChainDB::new(db, Default::default());
+    let consensus = ConsensusBuilder::default().build();
+    let genesis = consensus.genesis_block();
+    store.init(&consensus).unwrap();
+
+    // opened while the database is still opened by the writer
+    let read_only = ReadOnlyChainDB::open(&tmp_dir, None, Default::default())
+        .unwrap()
+        .unwrap();
+    assert_eq!(genesis.header(), read_only.get_tip_header().unwrap());
+    assert_eq!(genesis, &read_only.get_block(&genesis.hash()).unwrap());
+    assert_eq!(
+        store.get_block_ext(&genesis.hash()),
+        read_only.get_block_ext(&genesis.hash())
+    );
+}
+
+#[test]
+fn memory_backend() {
+    let store = ChainDB::new(MemoryDB::open(COLUMNS), Default::default());
+    let consensus = ConsensusBuilder::default().build();
+    let genesis = consensus.genesis_block();
+    store.init(&consensus).unwrap();
+    assert_eq!(genesis.hash(), store.get_block_hash(0).unwrap());
+    assert_eq!(genesis.header(), store.get_tip_header().unwrap());
+    assert_eq!(genesis, &store.get_block(&genesis.hash()).unwrap());
+
+    let snapshot = store.get_snapshot();
+    let raw = packed::RawHeader::new_builder()
+        .number(1u64.pack())
+        .parent_hash(genesis.hash())
+        .build();
+    let block = packed::Block::new_builder()
+        .header(packed::Header::new_builder().raw(raw).build())
+        .build()
+        .into_view();
+    let txn = store.begin_transaction();
+    assert_eq!(txn.get_update_for_tip_hash(), Some(genesis.hash()));
+    txn.insert_block(&block).unwrap();
+    txn.attach_block(&block).unwrap();
+    txn.insert_tip_header(&block.header()).unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(block.header(), store.get_tip_header().unwrap());
+    assert_eq!(genesis.header(), snapshot.get_tip_header().unwrap());
+    assert!(snapshot
+        .get(COLUMN_BLOCK_HEADER, block.hash().as_slice())
+        .is_none());
+
+    // the transaction conflicts with the tip updated after it began
+    let txn = store.begin_transaction();
+    txn.get_update_for_tip_hash();
+    let other = store.begin_transaction();
+    other.insert_tip_header(&genesis.header()).unwrap();
+    other.commit().unwrap();
+    txn.insert_tip_header(&block.header()).unwrap();
+    assert!(txn.commit().is_err());
+
+    let mut batch = store.new_write_batch();
+    batch.delete_block(1, &block.hash(), 0).unwrap();
+    store.write(&batch).unwrap();
+    assert!(store
+        .get(COLUMN_BLOCK_HEADER, block.hash().as_slice())
+        .is_none());
+}
+
 #[test]
 fn freeze_blockv0() {
     let tmp_dir = TempDir::new().unwrap();
@@ -159,3 +241,364 @@ fn freeze_blockv1_with_extension() {
     let block = store.get_block(&block_hash).expect("get_block");
     assert_eq!(store.get_block(&block_hash), Some(block));
 }
+
+#[test]
+fn freeze_block_meta() {
+    let tmp_dir = TempDir::new().unwrap();
+    let tmp_dir2 = TempDir::new().unwrap();
+
+    let raw = packed::RawHeader::new_builder().number(1u64.pack()).build();
+    let block = packed::Block::new_builder()
+        .header(packed::Header::new_builder().raw(raw).build())
+        .transactions(
+            (0..3u32)
+                .map(|i| {
+                    packed::Transaction::new_builder()
+                        .witnesses(vec![i.pack().as_bytes().pack()].pack())
+                        .build()
+                })
+                .collect::<Vec<_>>()
+                .pack(),
+        )
+        .build()
+        .into_view();
+    let block_hash = block.hash();
+    let tx_hashes = block.tx_hashes().to_vec();
+    let ext = BlockExt {
+        received_at: 1,
+        total_difficulty: 2u64.into(),
+        total_uncles_count: 0,
+        verified: Some(true),
+        txs_fees: vec![],
+        cycles: None,
+        txs_sizes: None,
+    };
+    let epoch_index = packed::Byte32::new([3u8; 32]);
+
+    {
+        let db = RocksDB::open_in(&tmp_dir, COLUMNS);
+        let freezer = Freezer::open_in(&tmp_dir2).expect("tmp freezer");
+        let store = ChainDB::new_with_freezer(db, freezer.clone(), Default::default());
+        let txn = store.begin_transaction();
+        txn.insert_block(&block).unwrap();
+        txn.attach_block(&block).unwrap();
+        txn.insert_block_ext(&block_hash, &ext).unwrap();
+        txn.insert_block_epoch_index(&block_hash, &epoch_index)
+            .unwrap();
+        txn.commit().unwrap();
+
+        freezer
+            .freeze(2, |_number| store.get_unfrozen_block(&block_hash))
+            .expect("freeze");
+        let frozen = freezer
+            .freeze_meta(2, |hash| {
+                let ext = store
+                    .get(COLUMN_BLOCK_EXT, hash.as_slice())
+                    .map(|slice| slice.as_ref().to_vec())?;
+                Some((ext, store.get_block_epoch_index(hash)?))
+            })
+            .expect("freeze meta");
+        assert_eq!(frozen.get(&block_hash), Some(&(1, tx_hashes.clone())));
+        assert_eq!(freezer.meta_number(), 2);
+
+        // the body is served by the freezer while the header is still in the kv-db
+        let mut batch = store.new_write_batch();
+        batch.delete_block_body(1, &block_hash, 3).unwrap();
+        store.write(&batch).unwrap();
+        assert_eq!(store.get_packed_block(&block_hash), Some(block.data()));
+
+        let mut batch = store.new_write_batch();
+        batch
+            .wipe_frozen_block_meta(1, &block_hash, &tx_hashes)
+            .unwrap();
+        store.write(&batch).unwrap();
+    }
+
+    // reopen to bypass the cache
+    let db = RocksDB::open_in(&tmp_dir, COLUMNS);
+    let freezer = Freezer::open_in(&tmp_dir2).expect("tmp freezer");
+    assert_eq!(freezer.meta_number(), 2);
+    let store = ChainDB::new_with_freezer(db, freezer, Default::default());
+    assert!(store
+        .get(COLUMN_BLOCK_HEADER, block_hash.as_slice())
+        .is_none());
+    assert!(store.get(COLUMN_BLOCK_EXT, block_hash.as_slice()).is_none());
+    assert!(store
+        .get(COLUMN_BLOCK_EPOCH, block_hash.as_slice())
+        .is_none());
+    assert_eq!(store.get_block_epoch_index(&block_hash), Some(epoch_index));
+
+    assert!(store.block_exists(&block_hash));
+    assert_eq!(store.get_block_header(&block_hash), Some(block.header()));
+    assert_eq!(store.get_block_ext(&block_hash), Some(ext));
+    assert_eq!(store.get_block(&block_hash), Some(block.clone()));
+    assert_eq!(store.get_packed_block(&block_hash), Some(block.data()));
+    assert_eq!(store.get_block_txs_hashes(&block_hash), tx_hashes);
+    assert_eq!(
+        store
+            .get_block_uncles(&block_hash)
+            .map(|uncles| uncles.data()),
+        Some(block.uncles().data())
+    );
+    for (index, tx_hash) in tx_hashes.iter().enumerate() {
+        let info = store.get_transaction_info(tx_hash).expect("tx info");
+        assert_eq!(info.block_hash, block_hash);
+        assert_eq!(info.block_number, 1);
+        assert_eq!(info.index, index);
+        let (tx, _) = store.get_transaction_with_info(tx_hash).expect("tx");
+        assert_eq!(&tx.hash(), tx_hash);
+    }
+}
+
+#[test]
+fn prune_block_body() {
+    let tmp_dir = TempDir::new().unwrap();
+    let db = RocksDB::open_in(&tmp_dir, COLUMNS);
+    let store = ChainDB::new(db, Default::default());
+
+    let extension: packed::Bytes = [1u8; 96].pack();
+    let raw = packed::RawHeader::new_builder().number(1u64.pack()).build();
+    let block = packed::BlockV1::new_builder()
+        .header(packed::Header::new_builder().raw(raw).build())
+        .transactions(
+            (0..3u32)
+                .map(|i| {
+                    packed::Transaction::new_builder()
+                        .witnesses(vec![i.pack().as_bytes().pack()].pack())
+                        .build()
+                })
+                .collect::<Vec<_>>()
+                .pack(),
+        )
+        .extension(extension.clone())
+        .build()
+        .as_v0()
+        .into_view();
+
+    let block_hash = block.hash();
+    let tx_hashes = block.tx_hashes().to_vec();
+    let txn = store.begin_transaction();
+    txn.insert_block(&block).unwrap();
+    txn.attach_block(&block).unwrap();
+    txn.commit().unwrap();
+    assert!(!store.is_block_body_pruned(1));
+
+    let mut batch = store.new_write_batch();
+    batch.prune_block_body(&block_hash, &tx_hashes).unwrap();
+    batch.set_pruned_block_number(2).unwrap();
+    store.write(&batch).unwrap();
+
+    assert!(store.is_block_body_pruned(1));
+    assert!(!store.is_block_body_pruned(2));
+    assert_eq!(store.get_block(&block_hash), None);
+    assert_eq!(store.get_block_header(&block_hash), Some(block.header()));
+    assert_eq!(store.get_block_extension(&block_hash), Some(extension));
+    for tx_hash in &tx_hashes {
+        assert!(store.get_transaction_info(tx_hash).is_none());
+    }
+
+    // the pruned block number is loaded when the store is opened
+    let store = ChainDB::new(store.into_inner(), Default::default());
+    assert_eq!(store.get_pruned_block_number(), Some(2));
+    assert!(store.is_block_body_pruned(1));
+}
+
+#[test]
+fn export_and_import_cell_snapshot() {
+    let tmp_dir = TempDir::new().unwrap();
+    let db = RocksDB::open_in(tmp_dir.path().join("source"), COLUMNS);
+    let store = ChainDB::new(db, Default::default());
+    let consensus = ConsensusBuilder::default().build();
+    store.init(&consensus).unwrap();
+
+    let path = tmp_dir.path().join("cells");
+    let info = export_cell_snapshot(&store, 1, &path).unwrap();
+    assert_eq!(info.number, 0);
+    assert_eq!(info.block_hash, consensus.genesis_hash());
+
+    let cells = |store: &ChainDB| {
+        store
+            .get_iter(COLUMN_CELL, IteratorMode::Start)
+            .collect::<Vec<_>>()
+    };
+    // the records written by the failed import are removed
+    let is_empty = |store: &ChainDB| {
+        store.get_tip_header().is_none()
+            && cells(store).is_empty()
+            && store
+                .get_iter(COLUMN_BLOCK_HEADER, IteratorMode::Start)
+                .next()
+                .is_none()
+    };
+
+    // rejects the snapshot whose hash is unexpected
+    let db = RocksDB::open_in(tmp_dir.path().join("target"), COLUMNS);
+    let imported = ChainDB::new(db, Default::default());
+    assert!(import_cell_snapshot(&imported, &path, &packed::Byte32::zero()).is_err());
+    assert!(is_empty(&imported));
+
+    // rejects the snapshot file which is modified after exporting
+    let tampered = tmp_dir.path().join("tampered");
+    let mut bytes = std::fs::read(&path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    std::fs::write(&tampered, bytes).unwrap();
+    assert!(import_cell_snapshot(&imported, &tampered, &info.hash).is_err());
+    assert!(is_empty(&imported));
+
+    assert_eq!(
+        import_cell_snapshot(&imported, &path, &info.hash).unwrap(),
+        info
+    );
+    assert_eq!(imported.get_tip_header(), store.get_tip_header());
+    assert_eq!(
+        imported.get_current_epoch_ext(),
+        store.get_current_epoch_ext()
+    );
+    let genesis = consensus.genesis_block();
+    assert_eq!(imported.get_block(&genesis.hash()).as_ref(), Some(genesis));
+    assert_eq!(cells(&imported), cells(&store));
+}
+
+#[test]
+fn backup_and_restore() {
+    let tmp_dir = TempDir::new().unwrap();
+    let db = RocksDB::open_in(tmp_dir.path().join("db"), COLUMNS);
+    let freezer = Freezer::open_in(tmp_dir.path().join("ancient")).expect("tmp freezer");
+    let store = ChainDB::new_with_freezer(db, freezer.clone(), Default::default());
+    let consensus = ConsensusBuilder::default().build();
+    let genesis = consensus.genesis_block();
+    let raw = packed::RawHeader::new_builder()
+        .number(1u64.pack())
+        .parent_hash(genesis.hash())
+        .build();
+    let block = packed::Block::new_builder()
+        .header(packed::Header::new_builder().raw(raw).build())
+        .build()
+        .into_view();
+    let txn = store.begin_transaction();
+    for block in [genesis, &block] {
+        txn.insert_block(block).unwrap();
+        txn.attach_block(block).unwrap();
+    }
+    txn.insert_tip_header(&block.header()).unwrap();
+    txn.commit().unwrap();
+    freezer
+        .freeze(2, |number| {
+            store
+                .get_block_hash(number)
+                .and_then(|hash| store.get_unfrozen_block(&hash))
+        })
+        .expect("freeze");
+
+    let target = tmp_dir.path().join("backup");
+    let manifest = backup_chain_db(&store, &target).unwrap();
+    assert_eq!(manifest.tip_number, 1);
+    assert_eq!(manifest.tip_hash, block.hash().unpack());
+    assert_eq!(manifest.frozen_number, 2);
+    assert_eq!(BackupManifest::load(&target).unwrap(), manifest);
+    // the target must not exist
+    assert!(backup_chain_db(&store, &target).is_err());
+    drop(store);
+    drop(freezer);
+
+    let db = RocksDB::open_in(target.join(BACKUP_DB_DIR), COLUMNS);
+    let freezer = Freezer::open_in(target.join(BACKUP_ANCIENT_DIR)).expect("backup freezer");
+    assert_eq!(freezer.number(), 2);
+    assert_eq!(
+        freezer.retrieve(1).unwrap(),
+        Some(block.data().as_slice().to_vec())
+    );
+    let store = ChainDB::new_with_freezer(db, freezer, Default::default());
+    assert_eq!(store.get_tip_header(), Some(block.header()));
+    assert_eq!(store.get_block(&block.hash()), Some(block));
+}
+
+#[test]
+fn collect_db_stats() {
+    let tmp_dir = TempDir::new().unwrap();
+    let db = RocksDB::open_in(tmp_dir.path().join("db"), COLUMNS);
+    let freezer = Freezer::open_in(tmp_dir.path().join("ancient")).expect("tmp freezer");
+    let store = ChainDB::new_with_freezer(db, freezer, Default::default());
+    let consensus = ConsensusBuilder::default().build();
+    let txn = store.begin_transaction();
+    txn.insert_block(consensus.genesis_block()).unwrap();
+    txn.commit().unwrap();
+    store
+        .db()
+        .compact_range(COLUMN_BLOCK_HEADER, None, None)
+        .unwrap();
+
+    let stats = DBStats::collect(&store).unwrap();
+    assert_eq!(stats.columns.len(), COLUMNS as usize);
+    let header_stats = &stats.columns[1];
+    assert_eq!(header_stats.col, COLUMN_BLOCK_HEADER);
+    assert_eq!(header_stats.estimate_num_keys, 1);
+    assert!(header_stats.live_sst_files_size > 0);
+    let names: Vec<_> = stats.freezer.iter().map(|stats| stats.name).collect();
+    assert_eq!(names, vec!["blocks", "headers", "block_exts", "tx_hashes"]);
+    assert!(stats.freezer.iter().all(|stats| stats.number == 1));
+}
+
+#[test]
+fn compact_database() {
+    let tmp_dir = TempDir::new().unwrap();
+    let db = RocksDB::open_in(tmp_dir.path().join("db"), COLUMNS);
+    let store = ChainDB::new(db, Default::default());
+    let consensus = ConsensusBuilder::default().build();
+    let txn = store.begin_transaction();
+    txn.insert_block(consensus.genesis_block()).unwrap();
+    txn.commit().unwrap();
+
+    assert!(CompactionTask::new(&["no_such_column"]).is_err());
+
+    let task = CompactionTask::new(&["block_header", "2"]).unwrap();
+    assert_eq!(task.steps().len(), 2);
+    let progress = task.run(&store, |_| true).unwrap();
+    assert_eq!(progress.columns, vec!["block_header", "block_body"]);
+    assert_eq!(progress.completed_steps, 2);
+    assert!(progress.finished_at.is_some());
+    assert!(!progress.cancelled);
+    assert!(store.get_block_header(&consensus.genesis_hash()).is_some());
+
+    let task = CompactionTask::new(&["block_header"])
+        .unwrap()
+        .throttle(Duration::from_millis(0));
+    let steps = task.steps();
+    assert_eq!(steps.len(), 256);
+    assert_eq!(steps[0].start, None);
+    assert_eq!(steps[255].end, None);
+    let mut reports = 0;
+    let progress = task
+        .run(&store, |_| {
+            reports += 1;
+            reports <= 3
+        })
+        .unwrap();
+    assert_eq!(progress.total_steps, 256);
+    assert_eq!(progress.completed_steps, 3);
+    assert!(progress.cancelled);
+
+    // the key range is split too
+    let task = CompactionTask::new(&["block_header"])
+        .unwrap()
+        .key_range(Some(vec![0x10, 0x20]), Some(vec![0x12]))
+        .throttle(Duration::from_millis(0));
+    let steps = task.steps();
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps[0].start, Some(vec![0x10, 0x20]));
+    assert_eq!(steps[0].end, Some(vec![0x11]));
+    assert_eq!(steps[1].start, Some(vec![0x11]));
+    assert_eq!(steps[1].end, Some(vec![0x12]));
+    let task = CompactionTask::new(&["block_header"])
+        .unwrap()
+        .key_range(Some(vec![0x10]), None)
+        .throttle(Duration::from_millis(0));
+    let steps = task.steps();
+    assert_eq!(steps.len(), 240);
+    assert_eq!(steps[0].start, Some(vec![0x10]));
+    assert_eq!(steps[239].end, None);
+
+    let all = CompactionTask::new::<&str>(&[]).unwrap();
+    assert_eq!(all.steps().len(), COLUMNS as usize);
+}
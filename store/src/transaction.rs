@@ -2,8 +2,8 @@ use crate::cache::StoreCache;
 use crate::store::ChainStore;
 use ckb_chain_spec::versionbits::VersionbitsIndexer;
 use ckb_db::{
-    iter::{DBIter, DBIterator, IteratorMode},
-    DBPinnableSlice, RocksDBTransaction, RocksDBTransactionSnapshot,
+    iter::IteratorMode, DBValue, KeyValueIter, KeyValueRead, KeyValueStore, KeyValueTransaction,
+    RocksDB,
 };
 use ckb_db_schema::{
     Col, COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_EXTENSION,
@@ -28,13 +28,13 @@ use ckb_types::{
 use std::sync::Arc;
 
 /// A Transaction DB
-pub struct StoreTransaction {
-    pub(crate) inner: RocksDBTransaction,
+pub struct StoreTransaction<DB: KeyValueStore = RocksDB> {
+    pub(crate) inner: DB::Transaction,
     pub(crate) freezer: Option<Freezer>,
     pub(crate) cache: Arc<StoreCache>,
 }
 
-impl ChainStore for StoreTransaction {
+impl<DB: KeyValueStore> ChainStore for StoreTransaction<DB> {
     fn cache(&self) -> Option<&StoreCache> {
         Some(&self.cache)
     }
@@ -43,20 +43,18 @@ impl ChainStore for StoreTransaction {
         self.freezer.as_ref()
     }
 
-    fn get(&self, col: Col, key: &[u8]) -> Option<DBPinnableSlice<'_>> {
-        self.inner
-            .get_pinned(col, key)
-            .expect("db operation should be ok")
+    fn get(&self, col: Col, key: &[u8]) -> Option<DBValue> {
+        KeyValueRead::get(&self.inner, col, key).expect("db operation should be ok")
     }
 
-    fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter {
+    fn get_iter(&self, col: Col, mode: IteratorMode) -> KeyValueIter {
         self.inner
-            .iter(col, mode)
+            .iterate(col, mode)
             .expect("db operation should be ok")
     }
 }
 
-impl VersionbitsIndexer for StoreTransaction {
+impl<DB: KeyValueStore> VersionbitsIndexer for StoreTransaction<DB> {
     fn block_epoch_index(&self, block_hash: &Byte32) -> Option<Byte32> {
         ChainStore::get_block_epoch_index(self, block_hash)
     }
@@ -74,7 +72,7 @@ impl VersionbitsIndexer for StoreTransaction {
     }
 }
 
-impl CellProvider for StoreTransaction {
+impl<DB: KeyValueStore> CellProvider for StoreTransaction<DB> {
     fn cell(&self, out_point: &OutPoint, eager_load: bool) -> CellStatus {
         match self.get_cell(out_point) {
             Some(mut cell_meta) => {
@@ -91,7 +89,7 @@ impl CellProvider for StoreTransaction {
     }
 }
 
-impl CellChecker for StoreTransaction {
+impl<DB: KeyValueStore> CellChecker for StoreTransaction<DB> {
     fn is_live(&self, out_point: &OutPoint) -> Option<bool> {
         if self.have_cell(out_point) {
             Some(true)
@@ -101,35 +99,7 @@ impl CellChecker for StoreTransaction {
     }
 }
 
-pub struct StoreTransactionSnapshot<'a> {
-    pub(crate) inner: RocksDBTransactionSnapshot<'a>,
-    pub(crate) freezer: Option<Freezer>,
-    pub(crate) cache: Arc<StoreCache>,
-}
-
-impl<'a> ChainStore for StoreTransactionSnapshot<'a> {
-    fn cache(&self) -> Option<&StoreCache> {
-        Some(&self.cache)
-    }
-
-    fn freezer(&self) -> Option<&Freezer> {
-        self.freezer.as_ref()
-    }
-
-    fn get(&self, col: Col, key: &[u8]) -> Option<DBPinnableSlice> {
-        self.inner
-            .get_pinned(col, key)
-            .expect("db operation should be ok")
-    }
-
-    fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter {
-        self.inner
-            .iter(col, mode)
-            .expect("db operation should be ok")
-    }
-}
-
-impl StoreTransaction {
+impl<DB: KeyValueStore> StoreTransaction<DB> {
     /// TODO(doc): @quake
     pub fn insert_raw(&self, col: Col, key: &[u8], value: &[u8]) -> Result<(), Error> {
         self.inner.put(col, key, value)
@@ -146,21 +116,9 @@ impl StoreTransaction {
     }
 
     /// TODO(doc): @quake
-    pub fn get_snapshot(&self) -> StoreTransactionSnapshot<'_> {
-        StoreTransactionSnapshot {
-            inner: self.inner.get_snapshot(),
-            freezer: self.freezer.clone(),
-            cache: Arc::clone(&self.cache),
-        }
-    }
-
-    /// TODO(doc): @quake
-    pub fn get_update_for_tip_hash(
-        &self,
-        snapshot: &StoreTransactionSnapshot<'_>,
-    ) -> Option<packed::Byte32> {
+    pub fn get_update_for_tip_hash(&self) -> Option<packed::Byte32> {
         self.inner
-            .get_for_update(COLUMN_META, META_TIP_HEADER_KEY, &snapshot.inner)
+            .get_for_update(COLUMN_META, META_TIP_HEADER_KEY)
             .expect("db operation should be ok")
             .map(|slice| packed::Byte32Reader::from_slice_should_be_ok(slice.as_ref()).to_entity())
     }
@@ -408,7 +366,7 @@ impl StoreTransaction {
     }
 }
 
-impl MMRStore<packed::HeaderDigest> for &StoreTransaction {
+impl<DB: KeyValueStore> MMRStore<packed::HeaderDigest> for &StoreTransaction<DB> {
     fn get_elem(&self, pos: u64) -> MMRResult<Option<packed::HeaderDigest>> {
         Ok(self.get_header_digest(pos))
     }
@@ -1,17 +1,21 @@
-use ckb_db::RocksDBWriteBatch;
+use ckb_db::{KeyValueStore, KeyValueWriteBatch, RocksDB};
 use ckb_db_schema::{
-    Col, COLUMN_BLOCK_BODY, COLUMN_BLOCK_EXTENSION, COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS,
-    COLUMN_BLOCK_UNCLE, COLUMN_CELL, COLUMN_CELL_DATA, COLUMN_CELL_DATA_HASH, COLUMN_NUMBER_HASH,
+    Col, COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_EXTENSION,
+    COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS, COLUMN_BLOCK_UNCLE, COLUMN_CELL,
+    COLUMN_CELL_DATA, COLUMN_CELL_DATA_HASH, COLUMN_META, COLUMN_NUMBER_HASH,
+    COLUMN_TRANSACTION_INFO, META_PRUNED_BLOCK_NUMBER_KEY,
 };
 use ckb_error::Error;
 use ckb_types::{core::BlockNumber, packed, prelude::*};
 
-/// Wrapper of `RocksDBWriteBatch`, provides atomic batch of write operations.
-pub struct StoreWriteBatch {
-    pub(crate) inner: RocksDBWriteBatch,
+/// Wrapper of the write batch of the key-value store, provides atomic batch of write operations.
+pub struct StoreWriteBatch<DB: KeyValueStore = RocksDB> {
+    pub(crate) inner: DB::WriteBatch,
+    // the pruned block number recorded in the batch, cached by the store once written
+    pub(crate) pruned_block_number: Option<BlockNumber>,
 }
 
-impl StoreWriteBatch {
+impl<DB: KeyValueStore> StoreWriteBatch<DB> {
     /// Write the bytes into the given column with associated key.
     pub fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<(), Error> {
         self.inner.put(col, key, value)
@@ -39,6 +43,7 @@ impl StoreWriteBatch {
 
     /// Clear all updates buffered in this batch.
     pub fn clear(&mut self) -> Result<(), Error> {
+        self.pruned_block_number = None;
         self.inner.clear()
     }
 
@@ -117,6 +122,64 @@ impl StoreWriteBatch {
         Ok(())
     }
 
+    /// Removes the transactions and their infos of the block with corresponding hash and
+    /// transaction hashes, the header, uncles, proposals and extension are kept
+    pub fn prune_block_body(
+        &mut self,
+        hash: &packed::Byte32,
+        tx_hashes: &[packed::Byte32],
+    ) -> Result<(), Error> {
+        for tx_hash in tx_hashes {
+            self.inner
+                .delete(COLUMN_TRANSACTION_INFO, tx_hash.as_slice())?;
+        }
+
+        let key_range = (0u32..tx_hashes.len() as u32).map(|i| {
+            packed::TransactionKey::new_builder()
+                .block_hash(hash.clone())
+                .index(i.pack())
+                .build()
+        });
+
+        self.inner.delete_range(COLUMN_BLOCK_BODY, key_range)?;
+        Ok(())
+    }
+
+    /// Removes the header, the block ext and the epoch index of the block whose meta has been
+    /// frozen, the infos of its transactions are shrunk to the block number, the rest is served by
+    /// the freezer
+    pub fn wipe_frozen_block_meta(
+        &mut self,
+        number: BlockNumber,
+        hash: &packed::Byte32,
+        tx_hashes: &[packed::Byte32],
+    ) -> Result<(), Error> {
+        self.inner.delete(COLUMN_BLOCK_HEADER, hash.as_slice())?;
+        self.inner.delete(COLUMN_BLOCK_EXT, hash.as_slice())?;
+        self.inner.delete(COLUMN_BLOCK_EPOCH, hash.as_slice())?;
+        let number: packed::Uint64 = number.pack();
+        for tx_hash in tx_hashes {
+            self.inner.put(
+                COLUMN_TRANSACTION_INFO,
+                tx_hash.as_slice(),
+                number.as_slice(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Records the number below which the main chain block bodies have been pruned
+    pub fn set_pruned_block_number(&mut self, number: BlockNumber) -> Result<(), Error> {
+        let packed_number: packed::Uint64 = number.pack();
+        self.inner.put(
+            COLUMN_META,
+            META_PRUNED_BLOCK_NUMBER_KEY,
+            packed_number.as_slice(),
+        )?;
+        self.pruned_block_number = Some(number);
+        Ok(())
+    }
+
     /// Removes the entire block from database with corresponding hash, number and txs number
     pub fn delete_block(
         &mut self,
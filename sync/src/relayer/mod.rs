@@ -30,7 +30,7 @@ use ckb_chain::chain::ChainController;
 use ckb_constant::sync::BAD_MESSAGE_BAN_TIME;
 use ckb_logger::{debug_target, error_target, info_target, trace_target, warn_target};
 use ckb_network::{
-    async_trait, bytes::Bytes, tokio, CKBProtocolContext, CKBProtocolHandler, PeerIndex,
+    async_trait, bytes::Bytes, tokio, Behaviour, CKBProtocolContext, CKBProtocolHandler, PeerIndex,
     SupportProtocols, TargetSession,
 };
 use ckb_systemtime::unix_time_as_millis;
@@ -75,6 +75,8 @@ pub struct Relayer {
     chain: ChainController,
     pub(crate) shared: Arc<SyncShared>,
     rate_limiter: Arc<Mutex<RateLimiter<(PeerIndex, u32)>>>,
+    // limits the credit for the fresh transactions, so that a peer can't raise its score quickly
+    fresh_txs_limiter: Arc<Mutex<RateLimiter<PeerIndex>>>,
     v3: bool,
 }
 
@@ -87,10 +89,14 @@ impl Relayer {
         // current max rps is 10 (ASK_FOR_TXS_TOKEN / TX_PROPOSAL_TOKEN), 30 is a flexible hard cap with buffer
         let quota = governor::Quota::per_second(std::num::NonZeroU32::new(30).unwrap());
         let rate_limiter = Arc::new(Mutex::new(RateLimiter::keyed(quota)));
+        // credit each peer at most once per minute for the transactions accepted by the tx-pool
+        let fresh_txs_quota = governor::Quota::per_minute(std::num::NonZeroU32::new(1).unwrap());
+        let fresh_txs_limiter = Arc::new(Mutex::new(RateLimiter::keyed(fresh_txs_quota)));
         Relayer {
             chain,
             shared,
             rate_limiter,
+            fresh_txs_limiter,
             v3: false,
         }
     }
@@ -206,6 +212,9 @@ impl Relayer {
             item_bytes,
         );
 
+        if let Some(behaviour) = status.behaviour() {
+            nc.report_peer(peer, behaviour);
+        }
         if let Some(ban_time) = status.should_ban() {
             error_target!(
                 crate::LOG_TARGET_RELAY,
@@ -300,7 +309,10 @@ impl Relayer {
             .shared()
             .insert_new_block(&self.chain, Arc::clone(&boxed))
         {
-            Ok(true) => self.broadcast_compact_block(nc, peer, &boxed),
+            Ok(true) => {
+                nc.report_peer(peer, Behaviour::UsefulBlock);
+                self.broadcast_compact_block(nc, peer, &boxed);
+            }
             Ok(false) => debug_target!(
                 crate::LOG_TARGET_RELAY,
                 "Relayer accept_block received an uncle block, don't broadcast compact block"
@@ -699,6 +711,15 @@ impl Relayer {
         }
     }
 
+    // The peer relayed the transactions accepted by the tx-pool
+    fn credit_fresh_txs(&self, nc: &dyn CKBProtocolContext, original_peer: Option<PeerIndex>) {
+        if let Some(peer) = original_peer {
+            if self.fresh_txs_limiter.lock().check_key(&peer).is_ok() {
+                nc.report_peer(peer, Behaviour::FreshTransactions);
+            }
+        }
+    }
+
     /// Send bulk of tx hashes to selected peers
     pub fn send_bulk_of_tx_hashes(&self, nc: &dyn CKBProtocolContext) {
         const BUFFER_SIZE: usize = 42;
@@ -716,40 +737,58 @@ impl Relayer {
         let mut selected: HashMap<PeerIndex, Vec<Byte32>> = HashMap::default();
         {
             for tx_verify_result in tx_verify_results {
-                match tx_verify_result {
+                // the txs of a package are relayed together, so that peers request and
+                // submit them as a unit
+                let (original_peer, tx_hashes) = match tx_verify_result {
                     TxVerificationResult::Ok {
                         original_peer,
                         with_vm_2023,
                         tx_hash,
                     } => {
+                        self.credit_fresh_txs(nc, original_peer);
                         // must all fork or all no-fork
                         if ckb2023 != with_vm_2023 {
                             continue;
                         }
-                        for target in &connected_peers {
-                            match original_peer {
-                                Some(peer) => {
-                                    // broadcast tx hash to all connected peers except original peer
-                                    if peer != *target {
-                                        let hashes = selected
-                                            .entry(*target)
-                                            .or_insert_with(|| Vec::with_capacity(BUFFER_SIZE));
-                                        hashes.push(tx_hash.clone());
-                                    }
-                                }
-                                None => {
-                                    // since this tx is submitted through local rpc, it is assumed to be a new tx for all connected peers
-                                    let hashes = selected
-                                        .entry(*target)
-                                        .or_insert_with(|| Vec::with_capacity(BUFFER_SIZE));
-                                    hashes.push(tx_hash.clone());
-                                    self.shared.state().mark_as_known_tx(tx_hash.clone());
-                                }
-                            }
+                        (original_peer, vec![tx_hash])
+                    }
+                    TxVerificationResult::PackageOk {
+                        original_peer,
+                        with_vm_2023,
+                        tx_hashes,
+                    } => {
+                        self.credit_fresh_txs(nc, original_peer);
+                        if ckb2023 != with_vm_2023 {
+                            continue;
                         }
+                        (original_peer, tx_hashes)
                     }
                     TxVerificationResult::Reject { tx_hash } => {
                         self.shared.state().remove_from_known_txs(&tx_hash);
+                        continue;
+                    }
+                };
+                for target in &connected_peers {
+                    match original_peer {
+                        Some(peer) => {
+                            // broadcast tx hash to all connected peers except original peer
+                            if peer != *target {
+                                let hashes = selected
+                                    .entry(*target)
+                                    .or_insert_with(|| Vec::with_capacity(BUFFER_SIZE));
+                                hashes.extend(tx_hashes.iter().cloned());
+                            }
+                        }
+                        None => {
+                            // since this tx is submitted through local rpc, it is assumed to be a new tx for all connected peers
+                            let hashes = selected
+                                .entry(*target)
+                                .or_insert_with(|| Vec::with_capacity(BUFFER_SIZE));
+                            hashes.extend(tx_hashes.iter().cloned());
+                            self.shared
+                                .state()
+                                .mark_as_known_txs(tx_hashes.iter().cloned());
+                        }
                     }
                 }
             }
@@ -915,6 +954,7 @@ impl CKBProtocolHandler for Relayer {
         );
         // Retains all keys in the rate limiter that were used recently enough.
         self.rate_limiter.lock().retain_recent();
+        self.fresh_txs_limiter.lock().retain_recent();
     }
 
     async fn notify(&mut self, nc: Arc<dyn CKBProtocolContext + Sync>, token: u64) {
@@ -2,6 +2,7 @@ use crate::relayer::Relayer;
 use crate::Status;
 use ckb_logger::error;
 use ckb_network::{CKBProtocolContext, PeerIndex};
+use ckb_tx_pool::{service::MAX_PACKAGE_TXS_COUNT, split_packages};
 use ckb_types::{
     core::{Cycle, TransactionView},
     packed,
@@ -87,12 +88,17 @@ impl<'a> TransactionsProcess<'a> {
             .shared()
             .async_handle()
             .spawn(async move {
-                for (tx, declared_cycles) in txs {
-                    if let Err(e) = tx_pool
-                        .submit_remote_tx(tx.clone(), declared_cycles, peer)
-                        .await
-                    {
-                        error!("submit_tx error {}", e);
+                for package in split_packages(txs) {
+                    if package.len() == 1 || package.len() > MAX_PACKAGE_TXS_COUNT {
+                        for (tx, declared_cycles) in package {
+                            if let Err(e) =
+                                tx_pool.submit_remote_tx(tx, declared_cycles, peer).await
+                            {
+                                error!("submit_tx error {}", e);
+                            }
+                        }
+                    } else if let Err(e) = tx_pool.submit_remote_package(package, peer).await {
+                        error!("submit_package error {}", e);
                     }
                 }
             });
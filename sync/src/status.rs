@@ -1,4 +1,5 @@
 use ckb_constant::sync::{BAD_MESSAGE_BAN_TIME, SYNC_USELESS_BAN_TIME};
+use ckb_network::Behaviour;
 use std::fmt::{self, Display, Formatter};
 use std::time::Duration;
 
@@ -65,6 +66,8 @@ pub enum StatusCode {
     BlocksInFlightReachLimit = 109,
     /// Generic rate limit error
     TooManyRequests = 110,
+    /// The bodies of some requested blocks have been pruned, the rest are served
+    RequestPrunedBlock = 111,
 
     ///////////////////////////////////
     //      Malformed Errors 4xx     //
@@ -171,6 +174,16 @@ impl Status {
         }
     }
 
+    /// The behaviour to report to the peer store, along with the ban if any
+    pub fn behaviour(&self) -> Option<Behaviour> {
+        match self.code {
+            StatusCode::BlockIsInvalid
+            | StatusCode::CompactBlockHasInvalidHeader
+            | StatusCode::HeadersIsInvalid => Some(Behaviour::InvalidBlock),
+            _ => None,
+        }
+    }
+
     /// Whether should output a warning log
     pub fn should_warn(&self) -> bool {
         self.code as u16 >= 500
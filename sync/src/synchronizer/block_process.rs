@@ -1,12 +1,13 @@
 use crate::{synchronizer::Synchronizer, utils::is_internal_db_error, Status, StatusCode};
 use ckb_logger::debug;
-use ckb_network::PeerIndex;
+use ckb_network::{Behaviour, CKBProtocolContext, PeerIndex};
 use ckb_types::{packed, prelude::*};
 
 pub struct BlockProcess<'a> {
     message: packed::SendBlockReader<'a>,
     synchronizer: &'a Synchronizer,
-    _peer: PeerIndex,
+    peer: PeerIndex,
+    nc: &'a dyn CKBProtocolContext,
 }
 
 impl<'a> BlockProcess<'a> {
@@ -14,11 +15,13 @@ impl<'a> BlockProcess<'a> {
         message: packed::SendBlockReader<'a>,
         synchronizer: &'a Synchronizer,
         peer: PeerIndex,
+        nc: &'a dyn CKBProtocolContext,
     ) -> Self {
         BlockProcess {
             message,
             synchronizer,
-            _peer: peer,
+            peer,
+            nc,
         }
     }
 
@@ -33,15 +36,27 @@ impl<'a> BlockProcess<'a> {
         let state = shared.state();
 
         if state.new_block_received(&block) {
-            if let Err(err) = self.synchronizer.process_new_block(block.clone()) {
-                if !is_internal_db_error(&err) {
-                    return StatusCode::BlockIsInvalid.with_context(format!(
-                        "{}, error: {}",
-                        block.hash(),
-                        err,
-                    ));
+            match self.synchronizer.process_new_block(block.clone()) {
+                Ok(true) => self.nc.report_peer(self.peer, Behaviour::UsefulBlock),
+                Ok(false) => {}
+                Err(err) => {
+                    if !is_internal_db_error(&err) {
+                        return StatusCode::BlockIsInvalid.with_context(format!(
+                            "{}, error: {}",
+                            block.hash(),
+                            err,
+                        ));
+                    }
                 }
             }
+        } else if !state
+            .write_inflight_blocks()
+            .remove_timed_out(self.peer, &(block.number(), block.hash()).into())
+        {
+            // the block is not in flight and it's not a late delivery, which has already been
+            // penalised by the timeout, so the peer sends a block never requested from it
+            self.nc
+                .report_peer(self.peer, Behaviour::UnrequestedMessage);
         }
 
         Status::ok()
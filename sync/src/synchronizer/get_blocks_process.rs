@@ -45,6 +45,7 @@ impl<'a> GetBlocksProcess<'a> {
         let iter = block_hashes.iter().take(INIT_BLOCKS_IN_TRANSIT_PER_PEER);
 
         let mut dedup = HashSet::new();
+        let mut pruned = 0;
         for block_hash in iter {
             debug!("get_blocks {} from peer {:?}", block_hash, self.peer);
             let block_hash = block_hash.to_entity();
@@ -65,6 +66,15 @@ impl<'a> GetBlocksProcess<'a> {
                 continue;
             }
 
+            if active_chain.is_block_body_pruned(&block_hash) {
+                debug!(
+                    "Ignoring get_block {} request from peer={} as its body is pruned.",
+                    block_hash, self.peer
+                );
+                pruned += 1;
+                continue;
+            }
+
             if let Some(block) = active_chain.get_block(&block_hash) {
                 debug!(
                     "respond_block {} {} to peer {:?}",
@@ -88,6 +98,10 @@ impl<'a> GetBlocksProcess<'a> {
             }
         }
 
+        if pruned > 0 {
+            return StatusCode::RequestPrunedBlock
+                .with_context(format!("Request {pruned} pruned blocks"));
+        }
         Status::ok()
     }
 }
@@ -35,7 +35,7 @@ use ckb_constant::sync::{
 use ckb_error::Error as CKBError;
 use ckb_logger::{debug, error, info, trace, warn};
 use ckb_network::{
-    async_trait, bytes::Bytes, tokio, CKBProtocolContext, CKBProtocolHandler, PeerIndex,
+    async_trait, bytes::Bytes, tokio, Behaviour, CKBProtocolContext, CKBProtocolHandler, PeerIndex,
     ServiceControl, SupportProtocols,
 };
 use ckb_stop_handler::{new_crossbeam_exit_rx, register_thread};
@@ -268,7 +268,7 @@ impl Synchronizer {
             }
             packed::SyncMessageUnionReader::SendBlock(reader) => {
                 if reader.check_data() {
-                    BlockProcess::new(reader, self, peer).execute()
+                    BlockProcess::new(reader, self, peer, nc).execute()
                 } else {
                     StatusCode::ProtocolMessageIsMalformed.with_context("SendBlock is invalid")
                 }
@@ -295,6 +295,9 @@ impl Synchronizer {
             item_bytes,
         );
 
+        if let Some(behaviour) = status.behaviour() {
+            nc.report_peer(peer, behaviour);
+        }
         if let Some(ban_time) = status.should_ban() {
             error!(
                 "Receive {} from {}. Ban {:?} for {}",
@@ -484,6 +487,7 @@ impl Synchronizer {
         }
         for peer in eviction {
             info!("Timeout eviction peer={}", peer);
+            nc.report_peer(peer, Behaviour::Timeout);
             if let Err(err) = nc.disconnect(peer, "sync timeout eviction") {
                 debug!("synchronizer disconnect error: {:?}", err);
             }
@@ -580,8 +584,10 @@ impl Synchronizer {
     fn find_blocks_to_fetch(&mut self, nc: &dyn CKBProtocolContext, ibd: IBDState) {
         let tip = self.shared.active_chain().tip_number();
 
+        // the peers which didn't deliver the blocks in time
+        let timeout_list = self.shared().state().write_inflight_blocks().prune(tip);
         let disconnect_list = {
-            let mut list = self.shared().state().write_inflight_blocks().prune(tip);
+            let mut list = timeout_list.clone();
             if let IBDState::In = ibd {
                 // best known < tip and in IBD state, and unknown list is empty,
                 // these node can be disconnect
@@ -609,6 +615,9 @@ impl Synchronizer {
             {
                 continue;
             }
+            if timeout_list.contains(peer) {
+                nc.report_peer(*peer, Behaviour::Timeout);
+            }
             if let Err(err) = nc.disconnect(*peer, "sync disconnect") {
                 debug!("synchronizer disconnect error: {:?}", err);
             }
@@ -195,3 +195,34 @@ fn inflight_trace_number_state() {
     assert_eq!(inflight_blocks.peer_can_fetch_count(3.into()), 32 >> 1);
     assert_eq!(inflight_blocks.peer_can_fetch_count(4.into()), 32 >> 1);
 }
+
+#[test]
+fn inflight_blocks_late_delivery() {
+    let _faketime_guard = ckb_systemtime::faketime();
+    _faketime_guard.set_faketime(0);
+    let mut inflight_blocks = InflightBlocks::default();
+    let block: BlockNumberAndHash = (1, h256!("0x1").pack()).into();
+    let other: BlockNumberAndHash = (2, h256!("0x2").pack()).into();
+
+    assert!(inflight_blocks.insert(1.into(), block.clone()));
+    assert!(inflight_blocks.insert(2.into(), other.clone()));
+
+    _faketime_guard.set_faketime(BLOCK_DOWNLOAD_TIMEOUT + 1);
+    inflight_blocks.prune(0);
+    assert!(!inflight_blocks.remove_by_block(block.clone()));
+
+    // only the peer the block was requested from delivers it late
+    assert!(!inflight_blocks.remove_timed_out(2.into(), &block));
+    assert!(!inflight_blocks.remove_timed_out(1.into(), &other));
+    assert!(inflight_blocks.remove_timed_out(1.into(), &block));
+    // and only once
+    assert!(!inflight_blocks.remove_timed_out(1.into(), &block));
+
+    // the records of the timed out blocks expire
+    let late: BlockNumberAndHash = (3, h256!("0x3").pack()).into();
+    assert!(inflight_blocks.insert(1.into(), late.clone()));
+    _faketime_guard.set_faketime(2 * BLOCK_DOWNLOAD_TIMEOUT + 2);
+    inflight_blocks.prune(0);
+    assert!(!inflight_blocks.remove_timed_out(2.into(), &other));
+    assert!(inflight_blocks.remove_timed_out(1.into(), &late));
+}
@@ -534,6 +534,9 @@ pub struct DownloadScheduler {
     task_count: usize,
     timeout_count: usize,
     hashes: HashSet<BlockNumberAndHash>,
+    // The blocks requested from the peer which timed out, with the time of timeout, a late
+    // delivery of them has already been penalised
+    timed_out: HashMap<BlockNumberAndHash, u64>,
 }
 
 impl Default for DownloadScheduler {
@@ -542,6 +545,7 @@ impl Default for DownloadScheduler {
             hashes: HashSet::default(),
             task_count: INIT_BLOCKS_IN_TRANSIT_PER_PEER,
             timeout_count: 0,
+            timed_out: HashMap::default(),
         }
     }
 }
@@ -716,6 +720,7 @@ impl InflightBlocks {
             if value.timestamp + BLOCK_DOWNLOAD_TIMEOUT < now {
                 if let Some(set) = download_schedulers.get_mut(&value.peer) {
                     set.hashes.remove(key);
+                    set.timed_out.insert(key.clone(), now);
                     if should_punish && adjustment {
                         set.punish(2);
                     }
@@ -732,6 +737,8 @@ impl InflightBlocks {
         }
 
         download_schedulers.retain(|k, v| {
+            v.timed_out
+                .retain(|_, time| *time + BLOCK_DOWNLOAD_TIMEOUT >= now);
             // task number zero means this peer's response is very slow
             if v.task_count == 0 {
                 disconnect_list.insert(*k);
@@ -768,6 +775,7 @@ impl InflightBlocks {
                             d.punish(1);
                         }
                         d.hashes.remove(key);
+                        d.timed_out.insert(key.clone(), now);
                     };
                 }
 
@@ -801,6 +809,15 @@ impl InflightBlocks {
         download_scheduler.hashes.insert(block)
     }
 
+    /// Returns true if the block was requested from the peer but timed out recently, i.e. it's
+    /// delivered late rather than unrequested
+    pub fn remove_timed_out(&mut self, peer: PeerIndex, block: &BlockNumberAndHash) -> bool {
+        self.download_schedulers
+            .get_mut(&peer)
+            .map(|set| set.timed_out.remove(block).is_some())
+            .unwrap_or(false)
+    }
+
     pub fn remove_by_peer(&mut self, peer: PeerIndex) -> bool {
         let trace = &mut self.trace_number;
         let state = &mut self.inflight_states;
@@ -2068,6 +2085,12 @@ impl ActiveChain {
         self.store().get_block_header(h)
     }
 
+    pub fn is_block_body_pruned(&self, h: &packed::Byte32) -> bool {
+        self.get_block_header(h).map_or(false, |header| {
+            self.store().is_block_body_pruned(header.number())
+        })
+    }
+
     pub fn get_block_ext(&self, h: &packed::Byte32) -> Option<core::BlockExt> {
         self.snapshot().get_block_ext(h)
     }
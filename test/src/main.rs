@@ -494,6 +494,9 @@ fn all_specs() -> Vec<Box<dyn Spec>> {
         Box::new(InvalidLocatorSize),
         Box::new(SizeLimit),
         Box::new(TxPoolLimitAncestorCount),
+        Box::new(SendTransactionPackageCpfp),
+        Box::new(SendTransactionPackageRollback),
+        Box::new(SendTransactionPackageSizeLimit),
         Box::new(SendDefectedBinary::new(
             "send_defected_binary_reject_known_bugs",
             true,
@@ -210,6 +210,14 @@ impl RpcClient {
             .send_transaction(tx, Some("passthrough".to_string()))
     }
 
+    pub fn send_transaction_package_result(
+        &self,
+        txs: Vec<Transaction>,
+    ) -> Result<Vec<H256>, AnyError> {
+        self.inner
+            .send_transaction_package(txs, Some("passthrough".to_string()))
+    }
+
     pub fn remove_transaction(&self, tx_hash: Byte32) -> bool {
         self.inner
             .remove_transaction(tx_hash.unpack())
@@ -346,6 +354,7 @@ jsonrpc!(
     pub fn get_block_median_time(&self, block_hash: H256) -> Option<Timestamp>;
     pub fn estimate_cycles(&self, _tx: Transaction) -> EstimateCycles;
     pub fn send_transaction(&self, tx: Transaction, outputs_validator: Option<String>) -> H256;
+    pub fn send_transaction_package(&self, transactions: Vec<Transaction>, outputs_validator: Option<String>) -> Vec<H256>;
     pub fn remove_transaction(&self, tx_hash: H256) -> bool;
     pub fn tx_pool_info(&self) -> TxPoolInfo;
     pub fn get_raw_tx_pool(&self, verbose: Option<bool>) -> RawTxPool;
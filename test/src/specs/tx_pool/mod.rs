@@ -21,6 +21,7 @@ mod send_large_cycles_tx;
 mod send_low_fee_rate_tx;
 mod send_multisig_secp_tx;
 mod send_secp_tx;
+mod send_transaction_package;
 mod send_tx_chain;
 mod txs_relay_order;
 mod utils;
@@ -49,6 +50,7 @@ pub use send_large_cycles_tx::*;
 pub use send_low_fee_rate_tx::*;
 pub use send_multisig_secp_tx::*;
 pub use send_secp_tx::*;
+pub use send_transaction_package::*;
 pub use send_tx_chain::*;
 pub use txs_relay_order::*;
 pub use valid_since::*;
@@ -1,3 +1,4 @@
+use ckb_jsonrpc_types::RemovalReason;
 use ckb_network::SupportProtocols;
 
 use crate::{
@@ -78,6 +79,12 @@ impl Spec for RemoveTx {
             });
             assert!(result, "remove a tx from pending tx pool");
             node0.assert_tx_pool_statics(0, 0);
+            let removal = node0
+                .rpc_client()
+                .get_transaction(tx.hash())
+                .removal
+                .expect("record the removed tx");
+            assert_eq!(removal.reason, RemovalReason::Removed);
 
             (tx_size, tx_cycles)
         };
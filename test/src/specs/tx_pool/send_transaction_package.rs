@@ -0,0 +1,171 @@
+use crate::{util::cell::gen_spendable, Node, Spec};
+use ckb_jsonrpc_types::Status;
+use ckb_logger::info;
+use ckb_types::{
+    core::{cell::CellMeta, Capacity, FeeRate, TransactionBuilder, TransactionView},
+    packed::{CellInput, CellOutput, OutPoint},
+    prelude::*,
+};
+
+const MIN_FEE_RATE: u64 = 1_000;
+
+// Spends the cell, paying `fee` shannons
+fn transaction_with_fee(
+    node: &Node,
+    out_point: OutPoint,
+    output: CellOutput,
+    fee: u64,
+) -> TransactionView {
+    let capacity = Capacity::shannons(output.capacity().unpack())
+        .safe_sub(fee)
+        .unwrap();
+    TransactionBuilder::default()
+        .input(CellInput::new(out_point, 0))
+        .output(output.as_builder().capacity(capacity.pack()).build())
+        .output_data(Default::default())
+        .cell_dep(node.always_success_cell_dep())
+        .build()
+}
+
+fn parent_transaction(node: &Node, cell: &CellMeta, fee: u64) -> TransactionView {
+    transaction_with_fee(
+        node,
+        cell.out_point.clone(),
+        cell.cell_output.clone(),
+        fee,
+    )
+}
+
+fn child_transaction(node: &Node, parent: &TransactionView, fee: u64) -> TransactionView {
+    transaction_with_fee(
+        node,
+        OutPoint::new(parent.hash(), 0),
+        parent.output(0).unwrap(),
+        fee,
+    )
+}
+
+fn send_package(node: &Node, txs: &[&TransactionView]) -> Result<(), String> {
+    node.rpc_client()
+        .send_transaction_package_result(txs.iter().map(|tx| tx.data().into()).collect())
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+fn assert_tx_status(node: &Node, tx: &TransactionView, status: Status) {
+    let ret = node.rpc_client().get_transaction(tx.hash());
+    assert_eq!(ret.tx_status.status, status, "tx {}", tx.hash());
+}
+
+pub struct SendTransactionPackageCpfp;
+
+impl Spec for SendTransactionPackageCpfp {
+    fn run(&self, nodes: &mut Vec<Node>) {
+        let node0 = &nodes[0];
+        let cells = gen_spendable(node0, 1);
+
+        info!("The zero fee parent is rejected alone");
+        let parent = parent_transaction(node0, &cells[0], 0);
+        let ret = node0
+            .rpc_client()
+            .send_transaction_result(parent.data().into());
+        assert!(ret
+            .unwrap_err()
+            .to_string()
+            .contains("PoolRejectedTransactionByMinFeeRate"));
+
+        info!("The child doesn't pay enough for the parent");
+        let child = child_transaction(node0, &parent, 1);
+        let ret = send_package(node0, &[&parent, &child]);
+        assert!(ret
+            .unwrap_err()
+            .contains("PoolRejectedTransactionByMinFeeRate"));
+        node0.assert_tx_pool_size(0, 0);
+
+        info!("The child pays for the parent");
+        let child = child_transaction(node0, &parent, 10 * MIN_FEE_RATE);
+        send_package(node0, &[&parent, &child]).unwrap();
+        assert_tx_status(node0, &parent, Status::Pending);
+        assert_tx_status(node0, &child, Status::Pending);
+        node0.assert_tx_pool_size(2, 0);
+
+        node0.mine_until_transactions_confirm();
+        assert_tx_status(node0, &parent, Status::Committed);
+        assert_tx_status(node0, &child, Status::Committed);
+    }
+
+    fn modify_app_config(&self, config: &mut ckb_app_config::CKBAppConfig) {
+        config.tx_pool.min_fee_rate = FeeRate::from_u64(MIN_FEE_RATE);
+    }
+}
+
+pub struct SendTransactionPackageRollback;
+
+impl Spec for SendTransactionPackageRollback {
+    fn run(&self, nodes: &mut Vec<Node>) {
+        let node0 = &nodes[0];
+        let cells = gen_spendable(node0, 1);
+
+        info!("The valid parent is rolled back with the invalid child");
+        let parent = parent_transaction(node0, &cells[0], 10 * MIN_FEE_RATE);
+        let output = parent.output(0).unwrap();
+        let capacity: u64 = output.capacity().unpack();
+        // the child creates more capacity than its input
+        let child = child_transaction(node0, &parent, 0)
+            .as_advanced_builder()
+            .set_outputs(vec![output
+                .as_builder()
+                .capacity((capacity + 1).pack())
+                .build()])
+            .build();
+        assert!(send_package(node0, &[&parent, &child]).is_err());
+        assert_tx_status(node0, &parent, Status::Unknown);
+        assert_tx_status(node0, &child, Status::Unknown);
+        node0.assert_tx_pool_size(0, 0);
+
+        info!("The parent is accepted alone");
+        node0.submit_transaction(&parent);
+        assert_tx_status(node0, &parent, Status::Pending);
+    }
+
+    fn modify_app_config(&self, config: &mut ckb_app_config::CKBAppConfig) {
+        config.tx_pool.min_fee_rate = FeeRate::from_u64(MIN_FEE_RATE);
+    }
+}
+
+pub struct SendTransactionPackageSizeLimit;
+
+const MAX_MEM_SIZE_FOR_PACKAGE_SIZE_LIMIT: usize = 2000;
+
+impl Spec for SendTransactionPackageSizeLimit {
+    fn run(&self, nodes: &mut Vec<Node>) {
+        let node0 = &nodes[0];
+        let cells = gen_spendable(node0, 16);
+        let (package_cell, cells) = cells.split_last().unwrap();
+
+        info!("Fill the pool with the txs paying a high fee rate");
+        node0.submit_transaction(&parent_transaction(node0, &cells[0], 1000 * MIN_FEE_RATE));
+        let one_tx_size = node0.get_tip_tx_pool_info().total_tx_size.value();
+        let max_tx_num = MAX_MEM_SIZE_FOR_PACKAGE_SIZE_LIMIT as u64 / one_tx_size;
+        assert!(max_tx_num >= 2 && max_tx_num as usize <= cells.len());
+        for cell in &cells[1..max_tx_num as usize] {
+            node0.submit_transaction(&parent_transaction(node0, cell, 1000 * MIN_FEE_RATE));
+        }
+        node0.assert_tx_pool_serialized_size(max_tx_num * one_tx_size);
+
+        info!("The package is evicted by the size limit, so it's rejected as a whole");
+        let parent = parent_transaction(node0, package_cell, 0);
+        let child = child_transaction(node0, &parent, 10 * MIN_FEE_RATE);
+        let ret = send_package(node0, &[&parent, &child]);
+        assert!(ret.unwrap_err().contains("PoolIsFull"));
+        assert_tx_status(node0, &parent, Status::Rejected);
+        assert_tx_status(node0, &child, Status::Rejected);
+        // the txs paying more are kept
+        node0.assert_tx_pool_serialized_size(max_tx_num * one_tx_size);
+    }
+
+    fn modify_app_config(&self, config: &mut ckb_app_config::CKBAppConfig) {
+        config.tx_pool.max_tx_pool_size = MAX_MEM_SIZE_FOR_PACKAGE_SIZE_LIMIT;
+        config.tx_pool.min_fee_rate = FeeRate::from_u64(MIN_FEE_RATE);
+    }
+}
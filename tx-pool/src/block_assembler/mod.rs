@@ -6,6 +6,7 @@ mod process;
 #[cfg(test)]
 mod tests;
 
+use crate::callback::Callbacks;
 use crate::component::entry::TxEntry;
 use crate::error::BlockAssemblerError;
 pub use candidate_uncles::CandidateUncles;
@@ -163,7 +164,11 @@ impl BlockAssembler {
         }
     }
 
-    pub(crate) async fn update_full(&self, tx_pool: &RwLock<TxPool>) -> Result<(), AnyError> {
+    pub(crate) async fn update_full(
+        &self,
+        tx_pool: &RwLock<TxPool>,
+        callbacks: &Callbacks,
+    ) -> Result<(), AnyError> {
         let mut current = self.current.lock().await;
         let consensus = current.snapshot.consensus();
         let max_block_bytes = consensus.max_block_bytes() as usize;
@@ -206,7 +211,7 @@ impl BlockAssembler {
         if !failed_txs.is_empty() {
             let mut tx_pool_writer = tx_pool.write().await;
             for id in failed_txs {
-                tx_pool_writer.remove_tx(&id);
+                tx_pool_writer.remove_tx(&id, "failed to calculate the dao field", callbacks);
             }
         }
 
@@ -1,6 +1,8 @@
 use super::component::TxEntry;
 use crate::error::Reject;
 use crate::pool::TxPool;
+use ckb_types::core::service::PoolTransactionChange;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Callback boxed fn pointer wrapper
 pub type PendingCallback = Box<dyn Fn(&TxEntry) + Sync + Send>;
@@ -8,12 +10,17 @@ pub type PendingCallback = Box<dyn Fn(&TxEntry) + Sync + Send>;
 pub type ProposedCallback = Box<dyn Fn(&TxEntry) + Sync + Send>;
 /// Reject Callback boxed fn pointer wrapper
 pub type RejectCallback = Box<dyn Fn(&mut TxPool, &TxEntry, Reject) + Sync + Send>;
+/// Diff Callback boxed fn pointer wrapper, called with the sequence of the change
+pub type DiffCallback = Box<dyn Fn(u64, &TxEntry, PoolTransactionChange) + Sync + Send>;
 
 /// Struct hold callbacks
 pub struct Callbacks {
     pub(crate) pending: Option<PendingCallback>,
     pub(crate) proposed: Option<ProposedCallback>,
     pub(crate) reject: Option<RejectCallback>,
+    pub(crate) diff: Option<DiffCallback>,
+    // the sequence of the last change, all changes are made under the tx-pool write lock
+    sequence: AtomicU64,
 }
 
 impl Default for Callbacks {
@@ -29,6 +36,8 @@ impl Callbacks {
             pending: None,
             proposed: None,
             reject: None,
+            diff: None,
+            sequence: AtomicU64::new(0),
         }
     }
 
@@ -47,11 +56,30 @@ impl Callbacks {
         self.reject = Some(callback);
     }
 
+    /// Register a new diff callback
+    pub fn register_diff(&mut self, callback: DiffCallback) {
+        self.diff = Some(callback);
+    }
+
+    /// The sequence of the last change
+    pub fn sequence(&self) -> u64 {
+        self.sequence.load(Ordering::SeqCst)
+    }
+
     /// Call on after pending
     pub fn call_pending(&self, entry: &TxEntry) {
         if let Some(call) = &self.pending {
             call(entry)
         }
+        self.call_diff(entry, PoolTransactionChange::Pending);
+    }
+
+    /// Call on after gap, which is notified as pending too
+    pub fn call_gap(&self, entry: &TxEntry) {
+        if let Some(call) = &self.pending {
+            call(entry)
+        }
+        self.call_diff(entry, PoolTransactionChange::Gap);
     }
 
     /// Call on after proposed
@@ -59,12 +87,22 @@ impl Callbacks {
         if let Some(call) = &self.proposed {
             call(entry)
         }
+        self.call_diff(entry, PoolTransactionChange::Proposed);
     }
 
     /// Call on after reject
     pub fn call_reject(&self, tx_pool: &mut TxPool, entry: &TxEntry, reject: Reject) {
+        self.call_diff(entry, PoolTransactionChange::Rejected(reject.clone()));
         if let Some(call) = &self.reject {
             call(tx_pool, entry, reject)
         }
     }
+
+    /// Call on after a change of the tx-pool, the sequence increases even if no diff callback
+    pub fn call_diff(&self, entry: &TxEntry, change: PoolTransactionChange) {
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(call) = &self.diff {
+            call(sequence, entry, change)
+        }
+    }
 }
@@ -0,0 +1,199 @@
+//! Fee estimator which tracks how many blocks the pool transactions at each fee rate bucket
+//! take to get committed, in the spirit of Bitcoin's smart fee estimation.
+use ckb_types::{
+    core::{BlockNumber, FeeRate},
+    packed::ProposalShortId,
+};
+use std::collections::HashMap;
+
+/// The max confirmation target in blocks which the estimator is able to track.
+pub const MAX_TARGET_BLOCKS: BlockNumber = 128;
+/// The lowest bucket boundary, unit: shannons/KW.
+const MIN_BUCKET_FEE_RATE: f64 = 1_000.0;
+/// The highest bucket boundary, unit: shannons/KW.
+const MAX_BUCKET_FEE_RATE: f64 = 10_000_000.0;
+/// The spacing between two adjacent bucket boundaries.
+const BUCKET_SPACING: f64 = 1.1;
+/// The decay applied to the historical data on every new block, the half-life is about 3466 blocks.
+const DECAY: f64 = 0.9998;
+/// The least (decayed) count of transactions required before trusting a range of buckets.
+const MIN_SAMPLES: f64 = 10.0;
+/// The least ratio of transactions which must be committed within the target.
+const SUCCESS_THRESHOLD: f64 = 0.85;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TrackedTx {
+    height: BlockNumber,
+    bucket: usize,
+}
+
+/// Historical inclusion delays of the pool transactions grouped by fee rate buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeEstimator {
+    /// The lower boundary of each bucket, in ascending order.
+    buckets: Vec<f64>,
+    /// Decayed count of the resolved transactions in each bucket, either committed or given up.
+    resolved: Vec<f64>,
+    /// `confirmed[target - 1][bucket]` is the decayed count of the transactions in the bucket
+    /// which were committed within `target` blocks.
+    confirmed: Vec<Vec<f64>>,
+    /// Pending transactions which are still waiting to be committed.
+    tracked: HashMap<ProposalShortId, TrackedTx>,
+    /// The number of the last processed block.
+    best_height: BlockNumber,
+}
+
+impl Default for FeeEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeeEstimator {
+    /// Creates an empty fee estimator.
+    pub fn new() -> Self {
+        let mut buckets = Vec::new();
+        let mut boundary = MIN_BUCKET_FEE_RATE;
+        while boundary <= MAX_BUCKET_FEE_RATE {
+            buckets.push(boundary);
+            boundary *= BUCKET_SPACING;
+        }
+        let len = buckets.len();
+        FeeEstimator {
+            buckets,
+            resolved: vec![0f64; len],
+            confirmed: vec![vec![0f64; len]; MAX_TARGET_BLOCKS as usize],
+            tracked: HashMap::default(),
+            best_height: 0,
+        }
+    }
+
+    fn bucket_index(&self, fee_rate: FeeRate) -> usize {
+        let fee_rate = fee_rate.as_u64() as f64;
+        self.buckets
+            .iter()
+            .rposition(|boundary| *boundary <= fee_rate)
+            .unwrap_or(0)
+    }
+
+    /// Starts tracking a transaction which enters the pool when the tip is `height`.
+    pub fn accept_tx(&mut self, id: ProposalShortId, fee_rate: FeeRate, height: BlockNumber) {
+        let bucket = self.bucket_index(fee_rate);
+        self.tracked
+            .entry(id)
+            .or_insert(TrackedTx { height, bucket });
+    }
+
+    /// Records the transactions committed in the block `height`.
+    ///
+    /// Transactions which are still not committed after `MAX_TARGET_BLOCKS` blocks are given up
+    /// and counted as failures of every target.
+    pub fn process_block<'a>(
+        &mut self,
+        height: BlockNumber,
+        committed: impl Iterator<Item = &'a ProposalShortId>,
+    ) {
+        self.decay();
+        for id in committed {
+            if let Some(tracked) = self.tracked.remove(id) {
+                let blocks = std::cmp::max(height.saturating_sub(tracked.height), 1);
+                self.resolved[tracked.bucket] += 1f64;
+                for confirmed in self.confirmed.iter_mut().skip(blocks as usize - 1) {
+                    confirmed[tracked.bucket] += 1f64;
+                }
+            }
+        }
+
+        let resolved = &mut self.resolved;
+        self.tracked.retain(|_, tracked| {
+            let keep = tracked.height.saturating_add(MAX_TARGET_BLOCKS) >= height;
+            if !keep {
+                resolved[tracked.bucket] += 1f64;
+            }
+            keep
+        });
+        self.best_height = height;
+    }
+
+    fn decay(&mut self) {
+        for resolved in self.resolved.iter_mut() {
+            *resolved *= DECAY;
+        }
+        for confirmed in self.confirmed.iter_mut().flat_map(|c| c.iter_mut()) {
+            *confirmed *= DECAY;
+        }
+    }
+
+    /// Returns the lowest fee rate whose transactions were committed within `target_blocks`
+    /// blocks in at least 85% of the cases, or `None` when there is not enough data.
+    pub fn estimate(&self, target_blocks: BlockNumber) -> Option<FeeRate> {
+        let target = target_blocks.clamp(1, MAX_TARGET_BLOCKS) as usize;
+        let confirmed = &self.confirmed[target - 1];
+
+        let mut best = None;
+        let mut confirmed_sum = 0f64;
+        let mut resolved_sum = 0f64;
+        // Group buckets from the highest fee rate until they have enough samples, and stop at
+        // the first group which fails to reach the threshold.
+        for bucket in (0..self.buckets.len()).rev() {
+            confirmed_sum += confirmed[bucket];
+            resolved_sum += self.resolved[bucket];
+            if resolved_sum >= MIN_SAMPLES {
+                if confirmed_sum / resolved_sum < SUCCESS_THRESHOLD {
+                    break;
+                }
+                best = Some(FeeRate::from_u64(self.buckets[bucket] as u64));
+                confirmed_sum = 0f64;
+                resolved_sum = 0f64;
+            }
+        }
+        best
+    }
+
+    /// Serializes the historical data, the tracked transactions are not included.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.buckets.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&MAX_TARGET_BLOCKS.to_le_bytes());
+        bytes.extend_from_slice(&self.best_height.to_le_bytes());
+        for value in self
+            .resolved
+            .iter()
+            .chain(self.confirmed.iter().flat_map(|c| c.iter()))
+        {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserializes the historical data produced by `encode`.
+    ///
+    /// Returns `None` if the data is broken or produced with different bucket settings.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut estimator = Self::new();
+        let mut chunks = bytes.chunks_exact(8);
+        let mut next = || {
+            chunks
+                .next()
+                .map(|chunk| <[u8; 8]>::try_from(chunk).expect("checked length"))
+        };
+
+        if u64::from_le_bytes(next()?) != estimator.buckets.len() as u64
+            || u64::from_le_bytes(next()?) != MAX_TARGET_BLOCKS
+        {
+            return None;
+        }
+        estimator.best_height = u64::from_le_bytes(next()?);
+        for value in estimator
+            .resolved
+            .iter_mut()
+            .chain(estimator.confirmed.iter_mut().flat_map(|c| c.iter_mut()))
+        {
+            *value = f64::from_le_bytes(next()?);
+        }
+        if next().is_some() || bytes.len() % 8 != 0 {
+            return None;
+        }
+        Some(estimator)
+    }
+}
@@ -3,10 +3,12 @@ pub mod entry;
 
 pub(crate) mod chunk;
 pub(crate) mod edges;
+pub(crate) mod fee_estimator;
 pub(crate) mod links;
 pub(crate) mod orphan;
 pub(crate) mod pool_map;
 pub(crate) mod recent_reject;
+pub(crate) mod removal_history;
 pub(crate) mod sort_key;
 
 #[cfg(test)]
@@ -9,7 +9,7 @@ use crate::error::Reject;
 use crate::TxEntry;
 use ckb_logger::{debug, error, trace};
 use ckb_types::core::error::OutPointError;
-use ckb_types::core::Cycle;
+use ckb_types::core::{tx_pool::get_transaction_weight, Cycle, FeeRate};
 use ckb_types::packed::OutPoint;
 use ckb_types::prelude::*;
 use ckb_types::{
@@ -181,6 +181,24 @@ impl PoolMap {
         self.links.calc_descendants(short_id)
     }
 
+    /// Returns the highest ancestors fee rate among the descendants of the entry within the
+    /// package, which is the fee rate a child pays for the entry.
+    pub(crate) fn max_descendants_ancestors_fee_rate(
+        &self,
+        short_id: &ProposalShortId,
+        package: &HashSet<ProposalShortId>,
+    ) -> Option<FeeRate> {
+        self.calc_descendants(short_id)
+            .iter()
+            .filter(|id| package.contains(id))
+            .filter_map(|id| self.get(id))
+            .map(|entry| {
+                let weight = get_transaction_weight(entry.ancestors_size, entry.ancestors_cycles);
+                FeeRate::calculate(entry.ancestors_fee, weight)
+            })
+            .max()
+    }
+
     pub(crate) fn get_output_with_data(&self, out_point: &OutPoint) -> Option<(CellOutput, Bytes)> {
         self.get(&ProposalShortId::from_tx_hash(&out_point.tx_hash()))
             .and_then(|entry| {
@@ -0,0 +1,122 @@
+//! The records of the transactions recently removed from the tx-pool.
+//!
+//! Unlike `RecentReject`, which only keeps the formatted reject message of every rejected
+//! transaction on disk, the history keeps the structured removal records in memory, so the
+//! clients can learn which transaction replaced theirs and which descendants were dropped with it.
+use crate::component::entry::TxEntry;
+use ckb_types::{
+    core::{tx_pool::RemovedTransaction, BlockNumber},
+    packed::Byte32,
+};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone)]
+pub(crate) struct RemovalHistory {
+    limit: usize,
+    next_seq: u64,
+    // tx_hash => (seq, record)
+    records: HashMap<Byte32, (u64, RemovedTransaction)>,
+    // seq => tx_hash, to drop the oldest record
+    by_seq: BTreeMap<u64, Byte32>,
+    // (block_number, seq) => tx_hash, to query the records by block number
+    by_number: BTreeMap<(BlockNumber, u64), Byte32>,
+}
+
+impl RemovalHistory {
+    pub(crate) fn new(limit: usize) -> Self {
+        RemovalHistory {
+            limit,
+            next_seq: 0,
+            records: HashMap::default(),
+            by_seq: BTreeMap::default(),
+            by_number: BTreeMap::default(),
+        }
+    }
+
+    /// Insert the record, it replaces the former record of the same transaction.
+    pub(crate) fn insert(&mut self, record: RemovedTransaction) {
+        let tx_hash = record.tx_hash.clone();
+        self.remove(&tx_hash);
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.by_seq.insert(seq, tx_hash.clone());
+        self.by_number
+            .insert((record.block_number, seq), tx_hash.clone());
+        self.records.insert(tx_hash, (seq, record));
+
+        while self.records.len() > self.limit {
+            let oldest = self.by_seq.values().next().cloned().expect("checked len");
+            self.remove(&oldest);
+        }
+    }
+
+    pub(crate) fn get(&self, tx_hash: &Byte32) -> Option<&RemovedTransaction> {
+        self.records.get(tx_hash).map(|(_, record)| record)
+    }
+
+    /// Returns the records of the transactions removed when the tip block number is in
+    /// `[start, end)`, ordered by the block number and then the removal order.
+    pub(crate) fn range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> impl Iterator<Item = &RemovedTransaction> {
+        let range = if start < end {
+            Some(self.by_number.range((start, 0)..(end, 0)))
+        } else {
+            None
+        };
+        range
+            .into_iter()
+            .flatten()
+            .filter_map(|(_, tx_hash)| self.get(tx_hash))
+    }
+
+    fn remove(&mut self, tx_hash: &Byte32) {
+        if let Some((seq, record)) = self.records.remove(tx_hash) {
+            self.by_seq.remove(&seq);
+            self.by_number.remove(&(record.block_number, seq));
+        }
+    }
+}
+
+/// Returns the hashes of the descendants of each entry, only considering the given entries.
+pub(crate) fn descendants_within(entries: &[&TxEntry]) -> Vec<Vec<Byte32>> {
+    let positions: HashMap<Byte32, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| (entry.transaction().hash(), i))
+        .collect();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); entries.len()];
+    for (i, entry) in entries.iter().enumerate() {
+        let mut parents: Vec<usize> = entry
+            .transaction()
+            .input_pts_iter()
+            .chain(entry.related_dep_out_points().cloned())
+            .filter_map(|out_point| positions.get(&out_point.tx_hash()).copied())
+            .filter(|parent| *parent != i)
+            .collect();
+        parents.sort_unstable();
+        parents.dedup();
+        for parent in parents {
+            children[parent].push(i);
+        }
+    }
+
+    (0..entries.len())
+        .map(|i| {
+            let mut visited = vec![false; entries.len()];
+            let mut stack = children[i].clone();
+            let mut descendants = Vec::new();
+            while let Some(child) = stack.pop() {
+                if !visited[child] {
+                    visited[child] = true;
+                    descendants.push(entries[child].transaction().hash());
+                    stack.extend(children[child].iter().copied());
+                }
+            }
+            descendants
+        })
+        .collect()
+}
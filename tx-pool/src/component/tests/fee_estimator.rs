@@ -0,0 +1,66 @@
+use ckb_types::{core::FeeRate, packed::ProposalShortId};
+
+use crate::component::fee_estimator::{FeeEstimator, MAX_TARGET_BLOCKS};
+
+fn short_id(i: u8) -> ProposalShortId {
+    ProposalShortId::from_slice(&[i; 10]).unwrap()
+}
+
+#[test]
+fn test_estimate_without_data() {
+    let estimator = FeeEstimator::new();
+    for target in [0, 1, 10, MAX_TARGET_BLOCKS, MAX_TARGET_BLOCKS + 1] {
+        assert_eq!(estimator.estimate(target), None);
+    }
+}
+
+#[test]
+fn test_estimate() {
+    let mut estimator = FeeEstimator::new();
+    let high: Vec<_> = (0..20).map(short_id).collect();
+    let low: Vec<_> = (20..40).map(short_id).collect();
+    for id in &high {
+        estimator.accept_tx(id.clone(), FeeRate::from_u64(5000), 100);
+    }
+    for id in &low {
+        estimator.accept_tx(id.clone(), FeeRate::from_u64(1000), 100);
+    }
+
+    // high fee rate txs are committed after 3 blocks
+    estimator.process_block(103, high.iter());
+    // low fee rate txs are given up
+    estimator.process_block(100 + MAX_TARGET_BLOCKS + 1, low.iter().take(0));
+
+    let estimated = estimator.estimate(3).unwrap();
+    assert!(estimated > FeeRate::from_u64(1000));
+    assert!(estimated <= FeeRate::from_u64(5000));
+    assert_eq!(estimator.estimate(MAX_TARGET_BLOCKS), Some(estimated));
+    assert_eq!(estimator.estimate(2), None);
+
+    // committing low fee rate txs after they are given up takes no effect
+    estimator.process_block(100 + MAX_TARGET_BLOCKS + 2, low.iter());
+    assert_eq!(estimator.estimate(3), Some(estimated));
+}
+
+#[test]
+fn test_encode_and_decode() {
+    let mut estimator = FeeEstimator::new();
+    let ids: Vec<_> = (0..20).map(short_id).collect();
+    for id in &ids {
+        estimator.accept_tx(id.clone(), FeeRate::from_u64(2000), 1);
+    }
+    estimator.process_block(5, ids.iter());
+
+    let bytes = estimator.encode();
+    let decoded = FeeEstimator::decode(&bytes).unwrap();
+    assert_eq!(decoded, estimator);
+    assert_eq!(decoded.estimate(4), estimator.estimate(4));
+
+    assert_eq!(FeeEstimator::decode(&bytes[..bytes.len() - 1]), None);
+    assert_eq!(FeeEstimator::decode(&bytes[..bytes.len() - 8]), None);
+    assert_eq!(
+        FeeEstimator::decode(&[bytes.clone(), vec![0; 8]].concat()),
+        None
+    );
+    assert_eq!(FeeEstimator::decode(&[]), None);
+}
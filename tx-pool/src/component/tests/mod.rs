@@ -1,9 +1,12 @@
 mod chunk;
 mod entry;
+mod fee_estimator;
 mod links;
 mod orphan;
+mod package;
 mod pending;
 mod proposed;
 mod recent_reject;
+mod removal_history;
 mod score_key;
 mod util;
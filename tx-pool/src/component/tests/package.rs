@@ -0,0 +1,96 @@
+use crate::component::tests::util::{build_tx, build_tx_with_dep};
+use crate::service::MAX_PACKAGE_TXS_COUNT;
+use crate::util::{check_package, split_packages};
+use ckb_types::{core::TransactionView, packed::Byte32, prelude::*};
+
+#[test]
+fn test_check_package() {
+    let parent = build_tx(vec![(&Byte32::zero(), 0)], 2);
+    let child = build_tx(vec![(&parent.hash(), 0)], 1);
+    let sibling = build_tx(vec![(&parent.hash(), 1)], 1);
+    let unrelated = build_tx(vec![(&Byte32::zero(), 1)], 1);
+
+    assert!(check_package(&[parent.clone()]).is_ok());
+    assert!(check_package(&[parent.clone(), child.clone()]).is_ok());
+    assert!(check_package(&[parent.clone(), child.clone(), sibling.clone()]).is_ok());
+
+    // not connected
+    assert!(check_package(&[parent.clone(), child.clone(), unrelated.clone()]).is_err());
+    assert!(check_package(&[unrelated, parent.clone(), child.clone()]).is_err());
+    // siblings are connected through their parent only
+    assert!(check_package(&[child.clone(), sibling]).is_err());
+
+    // empty
+    assert!(check_package(&[]).is_err());
+    // not sorted
+    assert!(check_package(&[child.clone(), parent.clone()]).is_err());
+    // duplicated
+    assert!(check_package(&[parent.clone(), child.clone(), child]).is_err());
+
+    // too many
+    let mut txs = vec![parent];
+    for _ in 0..MAX_PACKAGE_TXS_COUNT {
+        let tx = build_tx(vec![(&txs.last().unwrap().hash(), 0)], 1);
+        txs.push(tx);
+    }
+    assert!(check_package(&txs).is_err());
+    txs.pop();
+    assert!(check_package(&txs).is_ok());
+}
+
+#[test]
+fn test_split_packages() {
+    let parent = build_tx(vec![(&Byte32::zero(), 0)], 2);
+    let child = build_tx(vec![(&parent.hash(), 0)], 1);
+    let sibling = build_tx(vec![(&parent.hash(), 1)], 1);
+    let grandchild = build_tx(vec![(&child.hash(), 0)], 1);
+    let unrelated = build_tx(vec![(&Byte32::zero(), 1)], 1);
+    let dep_child = build_tx_with_dep(vec![(&Byte32::zero(), 2)], vec![(&unrelated.hash(), 0)], 1);
+    let single = build_tx(vec![(&Byte32::zero(), 3)], 1);
+
+    let hashes = |packages: Vec<Vec<(TransactionView, u64)>>| -> Vec<Vec<Byte32>> {
+        packages
+            .into_iter()
+            .map(|package| package.into_iter().map(|(tx, _)| tx.hash()).collect())
+            .collect()
+    };
+
+    // the relayed txs are in any order, the packages are sorted topologically
+    let txs = vec![
+        (grandchild.clone(), 4),
+        (dep_child.clone(), 6),
+        (single.clone(), 7),
+        (sibling.clone(), 3),
+        (child.clone(), 2),
+        (unrelated.clone(), 5),
+        (parent.clone(), 1),
+    ];
+    let packages = split_packages(txs);
+    assert_eq!(
+        hashes(packages.clone()),
+        vec![
+            vec![single.hash()],
+            vec![unrelated.hash(), dep_child.hash()],
+            vec![
+                parent.hash(),
+                sibling.hash(),
+                child.hash(),
+                grandchild.hash()
+            ],
+        ]
+    );
+    // the declared cycles are kept
+    assert_eq!(packages[0][0].1, 7);
+    for package in &packages {
+        let txs: Vec<_> = package.iter().map(|(tx, _)| tx.clone()).collect();
+        assert!(check_package(&txs).is_ok());
+    }
+
+    // the unrelated txs are split
+    let packages = split_packages(vec![(unrelated.clone(), 0), (single.clone(), 0)]);
+    assert_eq!(
+        hashes(packages),
+        vec![vec![unrelated.hash()], vec![single.hash()]]
+    );
+    assert!(split_packages(vec![]).is_empty());
+}
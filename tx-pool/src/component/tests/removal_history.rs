@@ -0,0 +1,99 @@
+use ckb_hash::blake2b_256;
+use ckb_types::{
+    core::{
+        tx_pool::{RemovalReason, RemovedTransaction},
+        BlockNumber, FeeRate,
+    },
+    packed::Byte32,
+};
+
+use crate::component::removal_history::RemovalHistory;
+
+fn tx_hash(i: u64) -> Byte32 {
+    Byte32::new(blake2b_256(i.to_le_bytes()))
+}
+
+fn record(i: u64, block_number: BlockNumber) -> RemovedTransaction {
+    RemovedTransaction {
+        tx_hash: tx_hash(i),
+        reason: RemovalReason::Expired,
+        description: i.to_string(),
+        conflicting_tx: None,
+        removed_descendants: vec![],
+        block_number,
+        fee_rate: FeeRate::from_u64(1000),
+        timestamp: i,
+    }
+}
+
+fn range_hashes(history: &RemovalHistory, start: BlockNumber, end: BlockNumber) -> Vec<Byte32> {
+    history
+        .range(start, end)
+        .map(|record| record.tx_hash.clone())
+        .collect()
+}
+
+#[test]
+fn test_size_limit() {
+    let mut history = RemovalHistory::new(10);
+    for i in 0..25u64 {
+        history.insert(record(i, i));
+    }
+
+    assert_eq!(range_hashes(&history, 0, 100).len(), 10);
+    // only the most recent records are kept
+    for i in 0..15u64 {
+        assert!(history.get(&tx_hash(i)).is_none());
+    }
+    for i in 15..25u64 {
+        assert_eq!(history.get(&tx_hash(i)), Some(&record(i, i)));
+    }
+}
+
+#[test]
+fn test_evict_in_insertion_order() {
+    let mut history = RemovalHistory::new(3);
+    // the block numbers don't affect the eviction order
+    history.insert(record(0, 30));
+    history.insert(record(1, 10));
+    history.insert(record(2, 20));
+
+    // re-inserting a record refreshes it
+    history.insert(record(0, 40));
+    history.insert(record(3, 5));
+    assert!(history.get(&tx_hash(1)).is_none());
+    assert_eq!(history.get(&tx_hash(0)), Some(&record(0, 40)));
+
+    history.insert(record(4, 5));
+    assert!(history.get(&tx_hash(2)).is_none());
+    assert_eq!(
+        range_hashes(&history, 0, 100),
+        vec![tx_hash(3), tx_hash(4), tx_hash(0)]
+    );
+}
+
+#[test]
+fn test_range() {
+    let mut history = RemovalHistory::new(100);
+    history.insert(record(0, 10));
+    history.insert(record(1, 11));
+    history.insert(record(2, 10));
+    history.insert(record(3, 12));
+
+    // ordered by the block number and then the removal order
+    assert_eq!(range_hashes(&history, 10, 11), vec![tx_hash(0), tx_hash(2)]);
+    assert_eq!(
+        range_hashes(&history, 10, 13),
+        vec![tx_hash(0), tx_hash(2), tx_hash(1), tx_hash(3)]
+    );
+    // the end is exclusive
+    assert_eq!(range_hashes(&history, 11, 12), vec![tx_hash(1)]);
+    assert!(range_hashes(&history, 12, 12).is_empty());
+    assert!(range_hashes(&history, 13, 12).is_empty());
+    assert!(range_hashes(&history, 0, 10).is_empty());
+
+    // the replaced record moves to the new block number
+    history.insert(record(0, 12));
+    assert_eq!(range_hashes(&history, 10, 11), vec![tx_hash(2)]);
+    assert_eq!(range_hashes(&history, 12, 13), vec![tx_hash(3), tx_hash(0)]);
+}
@@ -1,3 +1,4 @@
+use crate::component::fee_estimator::FeeEstimator;
 use crate::TxPool;
 use ckb_error::{AnyError, OtherError};
 use ckb_types::{
@@ -12,6 +13,8 @@ use std::{
 
 /// The version of the persisted tx-pool data.
 pub(crate) const VERSION: u32 = 1;
+/// The version of the persisted fee estimator data.
+pub(crate) const FEE_ESTIMATOR_VERSION: u32 = 1;
 
 impl TxPool {
     pub(crate) fn load_from_file(&self) -> Result<Vec<TransactionView>, AnyError> {
@@ -88,4 +91,67 @@ impl TxPool {
         })?;
         Ok(())
     }
+
+    pub(crate) fn load_fee_estimator_from_file(&mut self) -> Result<(), AnyError> {
+        let mut persisted_data_file = self.config.persisted_data.clone();
+        persisted_data_file.set_extension(format!("fee_estimator.v{FEE_ESTIMATOR_VERSION}"));
+
+        if persisted_data_file.exists() {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .open(&persisted_data_file)
+                .map_err(|err| {
+                    let errmsg = format!(
+                        "Failed to open the fee estimator persisted data file [{persisted_data_file:?}], cause: {err}"
+                    );
+                    OtherError::new(errmsg)
+                })?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer).map_err(|err| {
+                let errmsg = format!(
+                    "Failed to read the fee estimator persisted data file [{persisted_data_file:?}], cause: {err}"
+                );
+                OtherError::new(errmsg)
+            })?;
+
+            self.fee_estimator = FeeEstimator::decode(&buffer).ok_or_else(|| {
+                let errmsg = format!(
+                    "The fee estimator persisted data file [{persisted_data_file:?}] is broken"
+                );
+                OtherError::new(errmsg)
+            })?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn save_fee_estimator_into_file(&self) -> Result<(), AnyError> {
+        let mut persisted_data_file = self.config.persisted_data.clone();
+        persisted_data_file.set_extension(format!("fee_estimator.v{FEE_ESTIMATOR_VERSION}"));
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&persisted_data_file)
+            .map_err(|err| {
+                let errmsg = format!(
+                    "Failed to open the fee estimator persisted data file [{persisted_data_file:?}], cause: {err}"
+                );
+                OtherError::new(errmsg)
+            })?;
+
+        file.write_all(&self.fee_estimator.encode()).map_err(|err| {
+            let errmsg = format!(
+                "Failed to write the fee estimator persisted data into file [{persisted_data_file:?}], cause: {err}"
+            );
+            OtherError::new(errmsg)
+        })?;
+        file.sync_all().map_err(|err| {
+            let errmsg = format!(
+                "Failed to sync the fee estimator persisted data file [{persisted_data_file:?}], cause: {err}"
+            );
+            OtherError::new(errmsg)
+        })?;
+        Ok(())
+    }
 }
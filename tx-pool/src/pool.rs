@@ -3,22 +3,30 @@ extern crate rustc_hash;
 extern crate slab;
 use super::component::{commit_txs_scanner::CommitTxsScanner, TxEntry};
 use crate::callback::Callbacks;
+use crate::component::fee_estimator::FeeEstimator;
 use crate::component::pool_map::{PoolEntry, PoolMap, Status};
 use crate::component::recent_reject::RecentReject;
+use crate::component::removal_history::{descendants_within, RemovalHistory};
 use crate::error::Reject;
 use crate::pool_cell::PoolCell;
 use ckb_app_config::TxPoolConfig;
 use ckb_logger::{debug, error, warn};
 use ckb_snapshot::Snapshot;
 use ckb_store::ChainStore;
+use ckb_types::core::service::PoolTransactionChange;
 use ckb_types::core::tx_pool::PoolTxDetailInfo;
 use ckb_types::core::CapacityError;
 use ckb_types::packed::OutPoint;
 use ckb_types::{
     core::{
-        cell::{resolve_transaction, OverlayCellChecker, OverlayCellProvider, ResolvedTransaction},
-        tx_pool::{TxPoolEntryInfo, TxPoolIds},
-        Capacity, Cycle, TransactionView, UncleBlockView,
+        cell::{
+            resolve_transaction, OverlayCellChecker, OverlayCellProvider, ResolvedTransaction,
+            TransactionsProvider,
+        },
+        tx_pool::{
+            get_transaction_weight, RemovalReason, RemovedTransaction, TxPoolEntryInfo, TxPoolIds,
+        },
+        BlockNumber, Capacity, Cycle, FeeRate, TransactionView, UncleBlockView,
     },
     packed::{Byte32, ProposalShortId},
 };
@@ -29,6 +37,7 @@ use std::sync::Arc;
 const COMMITTED_HASH_CACHE_SIZE: usize = 100_000;
 const CONFLICTES_CACHE_SIZE: usize = 10_000;
 const MAX_REPLACEMENT_CANDIDATES: usize = 100;
+const REMOVAL_HISTORY_SIZE: usize = 10_000;
 
 /// Tx-pool implementation
 pub struct TxPool {
@@ -44,6 +53,10 @@ pub struct TxPool {
     pub(crate) expiry: u64,
     // conflicted transaction cache
     pub(crate) conflicts_cache: lru::LruCache<ProposalShortId, TransactionView>,
+    /// historical inclusion delays for fee estimation
+    pub(crate) fee_estimator: FeeEstimator,
+    /// structured records of recently removed transactions
+    pub(crate) removal_history: RemovalHistory,
 }
 
 impl TxPool {
@@ -59,6 +72,8 @@ impl TxPool {
             recent_reject,
             expiry,
             conflicts_cache: LruCache::new(CONFLICTES_CACHE_SIZE),
+            fee_estimator: FeeEstimator::new(),
+            removal_history: RemovalHistory::new(REMOVAL_HISTORY_SIZE),
         }
     }
 
@@ -220,25 +235,31 @@ impl TxPool {
         detached_headers: &HashSet<Byte32>,
         callbacks: &Callbacks,
     ) {
-        for (entry, reject) in self.pool_map.resolve_conflict_header_dep(detached_headers) {
-            callbacks.call_reject(self, &entry, reject);
-        }
+        let removed = self.pool_map.resolve_conflict_header_dep(detached_headers);
+        self.reject_removed(removed, RemovalReason::Detached, None, callbacks);
     }
 
     fn remove_committed_tx(&mut self, tx: &TransactionView, callbacks: &Callbacks) {
         let short_id = tx.proposal_short_id();
-        if let Some(_entry) = self.pool_map.remove_entry(&short_id) {
+        if let Some(entry) = self.pool_map.remove_entry(&short_id) {
             debug!("remove_committed_tx for {}", tx.hash());
+            callbacks.call_diff(&entry, PoolTransactionChange::Committed);
         }
         {
-            for (entry, reject) in self.pool_map.resolve_conflict(tx) {
+            let removed = self.pool_map.resolve_conflict(tx);
+            for (entry, _) in &removed {
                 debug!(
                     "removed {} for commited: {}",
                     entry.transaction().hash(),
                     tx.hash()
                 );
-                callbacks.call_reject(self, &entry, reject);
             }
+            self.reject_removed(
+                removed,
+                RemovalReason::Conflicted,
+                Some(tx.hash()),
+                callbacks,
+            );
         }
     }
 
@@ -253,13 +274,17 @@ impl TxPool {
             .map(|entry| entry.inner.clone())
             .collect();
 
-        for entry in removed {
-            let tx_hash = entry.transaction().hash();
-            debug!("remove_expired {} timestamp({})", tx_hash, entry.timestamp);
-            self.pool_map.remove_entry(&entry.proposal_short_id());
-            let reject = Reject::Expiry(entry.timestamp);
-            callbacks.call_reject(self, &entry, reject);
-        }
+        let removed = removed
+            .into_iter()
+            .map(|entry| {
+                let tx_hash = entry.transaction().hash();
+                debug!("remove_expired {} timestamp({})", tx_hash, entry.timestamp);
+                self.pool_map.remove_entry(&entry.proposal_short_id());
+                let reject = Reject::Expiry(entry.timestamp);
+                (entry, reject)
+            })
+            .collect();
+        self.reject_removed(removed, RemovalReason::Expired, None, callbacks);
     }
 
     // Remove transactions from the pool until total size <= size_limit.
@@ -273,19 +298,24 @@ impl TxPool {
             };
 
             if let Some(id) = next_evict_entry() {
-                let removed = self.pool_map.remove_entry_and_descendants(&id);
-                for entry in removed {
-                    let tx_hash = entry.transaction().hash();
-                    debug!(
-                        "Removed by size limit {} timestamp({})",
-                        tx_hash, entry.timestamp
-                    );
-                    let reject = Reject::Full(format!(
-                        "the fee_rate for this transaction is: {}",
-                        entry.fee_rate()
-                    ));
-                    callbacks.call_reject(self, &entry, reject);
-                }
+                let removed = self
+                    .pool_map
+                    .remove_entry_and_descendants(&id)
+                    .into_iter()
+                    .map(|entry| {
+                        let tx_hash = entry.transaction().hash();
+                        debug!(
+                            "Removed by size limit {} timestamp({})",
+                            tx_hash, entry.timestamp
+                        );
+                        let reject = Reject::Full(format!(
+                            "the fee_rate for this transaction is: {}",
+                            entry.fee_rate()
+                        ));
+                        (entry, reject)
+                    })
+                    .collect();
+                self.reject_removed(removed, RemovalReason::Evicted, None, callbacks);
             }
         }
         self.pool_map.entries.shrink_to_fit();
@@ -296,6 +326,7 @@ impl TxPool {
     pub(crate) fn remove_by_detached_proposal<'a>(
         &mut self,
         ids: impl Iterator<Item = &'a ProposalShortId>,
+        callbacks: &Callbacks,
     ) {
         for id in ids {
             if let Some(e) = self.pool_map.get_by_id(id) {
@@ -308,19 +339,87 @@ impl TxPool {
                 for mut entry in entries {
                     let tx_hash = entry.transaction().hash();
                     entry.reset_statistic_state();
-                    let ret = self.add_pending(entry);
+                    let ret = self.add_pending(entry.clone());
                     debug!(
                         "remove_by_detached_proposal from {:?} {} add_pending {:?}",
                         status, tx_hash, ret
                     );
+                    match ret {
+                        Ok(_) => callbacks.call_diff(&entry, PoolTransactionChange::Pending),
+                        Err(reject) => self.reject_removed(
+                            vec![(entry, reject)],
+                            RemovalReason::Detached,
+                            None,
+                            callbacks,
+                        ),
+                    }
                 }
             }
         }
     }
 
-    pub(crate) fn remove_tx(&mut self, id: &ProposalShortId) -> bool {
-        let entries = self.pool_map.remove_entry_and_descendants(id);
-        !entries.is_empty()
+    /// Records the entries removed together for the same reason into the removal history, then
+    /// calls the reject callbacks.
+    pub(crate) fn reject_removed(
+        &mut self,
+        removed: Vec<(TxEntry, Reject)>,
+        reason: RemovalReason,
+        conflicting_tx: Option<Byte32>,
+        callbacks: &Callbacks,
+    ) {
+        for (entry, reject) in self.record_removed(removed, reason, conflicting_tx) {
+            callbacks.call_reject(self, &entry, reject);
+        }
+    }
+
+    /// Records the entries removed together for the same reason into the removal history, the
+    /// entries are returned in the same order.
+    fn record_removed<D: ToString>(
+        &mut self,
+        removed: Vec<(TxEntry, D)>,
+        reason: RemovalReason,
+        conflicting_tx: Option<Byte32>,
+    ) -> Vec<(TxEntry, D)> {
+        let descendants = {
+            let entries: Vec<_> = removed.iter().map(|(entry, _)| entry).collect();
+            descendants_within(&entries)
+        };
+        let block_number = self.snapshot.tip_number();
+        let timestamp = ckb_systemtime::unix_time_as_millis();
+        for ((entry, description), removed_descendants) in removed.iter().zip(descendants) {
+            self.removal_history.insert(RemovedTransaction {
+                tx_hash: entry.transaction().hash(),
+                reason,
+                description: description.to_string(),
+                conflicting_tx: conflicting_tx.clone(),
+                removed_descendants,
+                block_number,
+                fee_rate: entry.fee_rate(),
+                timestamp,
+            });
+        }
+        removed
+    }
+
+    /// Removes the transaction and its descendants, which are recorded into the removal history
+    /// with the description.
+    pub(crate) fn remove_tx(
+        &mut self,
+        id: &ProposalShortId,
+        description: &str,
+        callbacks: &Callbacks,
+    ) -> bool {
+        let removed: Vec<_> = self
+            .pool_map
+            .remove_entry_and_descendants(id)
+            .into_iter()
+            .map(|entry| (entry, description))
+            .collect();
+        let removed = self.record_removed(removed, RemovalReason::Removed, None);
+        for (entry, _) in &removed {
+            callbacks.call_diff(entry, PoolTransactionChange::Removed);
+        }
+        !removed.is_empty()
     }
 
     pub(crate) fn check_rtx_from_pool(&self, rtx: &ResolvedTransaction) -> Result<(), Reject> {
@@ -346,6 +445,23 @@ impl TxPool {
             .map_err(Reject::Resolve)
     }
 
+    /// Resolves a package tx against the pool, overlaid with the outputs of the package txs
+    /// before it, which are not in the pool yet.
+    pub(crate) fn resolve_package_tx_from_pool(
+        &self,
+        tx: TransactionView,
+        package: &TransactionsProvider,
+    ) -> Result<Arc<ResolvedTransaction>, Reject> {
+        let snapshot = self.snapshot();
+        let pool_cell = PoolCell::new(&self.pool_map, false);
+        let pool_provider = OverlayCellProvider::new(&pool_cell, snapshot);
+        let provider = OverlayCellProvider::new(package, &pool_provider);
+        let mut seen_inputs = HashSet::new();
+        resolve_transaction(tx, &mut seen_inputs, &provider, snapshot)
+            .map(Arc::new)
+            .map_err(Reject::Resolve)
+    }
+
     pub(crate) fn gap_rtx(&mut self, short_id: &ProposalShortId) -> Result<(), Reject> {
         match self.get_pool_entry(short_id) {
             Some(entry) => {
@@ -479,7 +595,10 @@ impl TxPool {
         txs
     }
 
-    pub(crate) fn clear(&mut self, snapshot: Arc<Snapshot>) {
+    pub(crate) fn clear(&mut self, snapshot: Arc<Snapshot>, callbacks: &Callbacks) {
+        for entry in self.pool_map.iter() {
+            callbacks.call_diff(&entry.inner, PoolTransactionChange::Removed);
+        }
         self.pool_map.clear();
         self.snapshot = snapshot;
         self.committed_txs_hash_cache = LruCache::new(COMMITTED_HASH_CACHE_SIZE);
@@ -519,6 +638,40 @@ impl TxPool {
         (entries, size, cycles)
     }
 
+    /// Estimates the fee rate for a transaction to get committed within `target_blocks` blocks.
+    ///
+    /// The result is the max of the fee rate required to be packaged ahead of the transactions
+    /// which can't fit into the next `target_blocks` blocks, the historical estimation and the
+    /// pool min fee rate.
+    pub(crate) fn estimate_fee_rate(
+        &self,
+        target_blocks: BlockNumber,
+        max_block_bytes: u64,
+    ) -> FeeRate {
+        let capacity = target_blocks.saturating_mul(max_block_bytes);
+        let mut weight = 0u64;
+        let pool_fee_rate = self
+            .pool_map
+            .score_sorted_iter_by(vec![Status::Proposed, Status::Gap, Status::Pending])
+            .find(|entry| {
+                weight = weight.saturating_add(get_transaction_weight(entry.size, entry.cycles));
+                weight > capacity
+            })
+            .map(|entry| {
+                let (fee, weight) = entry.as_score_key().min_fee_and_weight();
+                FeeRate::calculate(fee, weight)
+            })
+            .unwrap_or_else(FeeRate::zero);
+        let history_fee_rate = self
+            .fee_estimator
+            .estimate(target_blocks)
+            .unwrap_or_else(FeeRate::zero);
+
+        pool_fee_rate
+            .max(history_fee_rate)
+            .max(self.config.min_fee_rate)
+    }
+
     pub(crate) fn check_rbf(
         &self,
         snapshot: &Snapshot,
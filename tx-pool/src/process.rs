@@ -7,8 +7,8 @@ use crate::pool::TxPool;
 use crate::service::{BlockAssemblerMessage, TxPoolService, TxVerificationResult};
 use crate::try_or_return_with_snapshot;
 use crate::util::{
-    after_delay_window, check_tx_fee, check_txid_collision, is_missing_input,
-    non_contextual_verify, time_relative_verify, verify_rtx,
+    after_delay_window, calculate_tx_fee, check_package, check_tx_fee, check_txid_collision,
+    is_missing_input, non_contextual_verify, time_relative_verify, verify_rtx,
 };
 use ckb_chain_spec::consensus::MAX_BLOCK_PROPOSALS_LIMIT;
 use ckb_error::{AnyError, InternalErrorKind};
@@ -20,8 +20,13 @@ use ckb_snapshot::Snapshot;
 use ckb_store::data_loader_wrapper::AsDataLoader;
 use ckb_store::ChainStore;
 use ckb_types::core::error::OutPointError;
+use ckb_types::core::service::PoolTransactionChange;
 use ckb_types::{
-    core::{cell::ResolvedTransaction, BlockView, Capacity, Cycle, HeaderView, TransactionView},
+    core::{
+        cell::{ResolvedTransaction, TransactionsProvider},
+        tx_pool::{get_transaction_weight, RemovalReason},
+        BlockView, Capacity, Cycle, HeaderView, TransactionView,
+    },
     packed::{Byte32, ProposalShortId},
 };
 use ckb_util::LinkedHashSet;
@@ -46,7 +51,7 @@ pub enum PlugTarget {
     Proposed,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TxStatus {
     Fresh,
     Gap,
@@ -138,32 +143,34 @@ impl TxPoolService {
                 }
 
                 // try to remove conflicted tx here
+                let tx_hash = entry.transaction().hash();
                 for id in conflicts.iter() {
-                    let removed = tx_pool.pool_map.remove_entry_and_descendants(id);
-                    for old in removed {
-                        debug!(
-                            "remove conflict tx {} for RBF by new tx {}",
-                            old.transaction().hash(),
-                            entry.transaction().hash()
-                        );
-                        let reject = Reject::RBFRejected(format!(
-                            "replaced by tx {}",
-                            entry.transaction().hash()
-                        ));
-                        // RBF replace successfully, put old transactions into conflicts pool
-                        tx_pool.record_conflict(old.transaction().clone());
-                        // after removing old tx from tx_pool, we call reject callbacks manually
-                        self.callbacks.call_reject(tx_pool, &old, reject);
-                    }
+                    let removed = tx_pool
+                        .pool_map
+                        .remove_entry_and_descendants(id)
+                        .into_iter()
+                        .map(|old| {
+                            debug!(
+                                "remove conflict tx {} for RBF by new tx {}",
+                                old.transaction().hash(),
+                                tx_hash
+                            );
+                            let reject = Reject::RBFRejected(format!("replaced by tx {}", tx_hash));
+                            // RBF replace successfully, put old transactions into conflicts pool
+                            tx_pool.record_conflict(old.transaction().clone());
+                            (old, reject)
+                        })
+                        .collect();
+                    // after removing old tx from tx_pool, we call reject callbacks manually
+                    tx_pool.reject_removed(
+                        removed,
+                        RemovalReason::Replaced,
+                        Some(tx_hash.clone()),
+                        &self.callbacks,
+                    );
                 }
                 let evicted = _submit_entry(tx_pool, status, entry.clone(), &self.callbacks)?;
-                for evict in evicted {
-                    let reject = Reject::Invalidated(format!(
-                        "invalidated by tx {}",
-                        evict.transaction().hash()
-                    ));
-                    self.callbacks.call_reject(tx_pool, &evict, reject);
-                }
+                _reject_evicted(tx_pool, evicted, &tx_hash, &self.callbacks);
                 tx_pool.remove_conflict(&entry.proposal_short_id());
 
                 Ok(())
@@ -359,6 +366,228 @@ impl TxPoolService {
         }
     }
 
+    /// Processes a package of transactions atomically, the transactions are either all accepted
+    /// or all rejected.
+    ///
+    /// A transaction below `min_fee_rate` is accepted if a child in the package pays for it, i.e.
+    /// the child's ancestors fee rate, which is evaluated by the ancestor accounting of the pool,
+    /// reaches `min_fee_rate`.
+    pub(crate) async fn process_package(
+        &self,
+        package: Vec<(TransactionView, Option<Cycle>)>,
+        peer: Option<PeerIndex>,
+    ) -> Result<(), Reject> {
+        let txs: Vec<TransactionView> = package.iter().map(|(tx, _)| tx.clone()).collect();
+        // The relayed packages are assembled by the relayer, a malformed one doesn't imply that
+        // the peer misbehaves, so it's rejected without banning
+        check_package(&txs)?;
+        let ret = self._process_package(&package, peer).await;
+        self.after_process_package(txs, peer, ret.clone()).await;
+        ret.map(|_snapshot| ())
+    }
+
+    async fn _process_package(
+        &self,
+        package: &[(TransactionView, Option<Cycle>)],
+        peer: Option<PeerIndex>,
+    ) -> Result<Arc<Snapshot>, Reject> {
+        let txs: Vec<TransactionView> = package.iter().map(|(tx, _)| tx.clone()).collect();
+        for tx in &txs {
+            non_contextual_verify(&self.consensus, tx)?;
+        }
+
+        // Acquire read lock to resolve the package, the txs already in the pool are skipped
+        let (ret, snapshot) = self
+            .with_tx_pool_read_lock(|tx_pool, snapshot| {
+                let mut provider = TransactionsProvider::default();
+                let mut resolved = Vec::with_capacity(package.len());
+                for (tx, declared_cycles) in package {
+                    if check_txid_collision(tx_pool, tx).is_err() {
+                        continue;
+                    }
+                    let rtx = tx_pool.resolve_package_tx_from_pool(tx.clone(), &provider)?;
+                    let status = get_tx_status(&snapshot, &tx.proposal_short_id());
+                    let fee = calculate_tx_fee(&snapshot, &rtx)?;
+                    provider.insert(tx);
+                    resolved.push((rtx, status, fee, *declared_cycles));
+                }
+                Ok::<_, Reject>(resolved)
+            })
+            .await;
+        let resolved = ret?;
+        if resolved.is_empty() {
+            return Err(Reject::Duplicated(txs[txs.len() - 1].hash()));
+        }
+
+        // Verify the txs without holding the tx-pool lock
+        let fetched_cache = self.fetch_txs_verify_cache(txs.iter()).await;
+        let tip_header = snapshot.tip_header();
+        let mut entries = Vec::with_capacity(resolved.len());
+        for (rtx, status, fee, declared_cycles) in resolved {
+            let tx_hash = rtx.transaction.hash();
+            let tx_size = rtx.transaction.data().serialized_size_in_block();
+            let max_cycles = declared_cycles.unwrap_or_else(|| self.consensus.max_block_cycles());
+            let tx_env = Arc::new(status.with_env(tip_header));
+            let verified = verify_rtx(
+                Arc::clone(&snapshot),
+                Arc::clone(&rtx),
+                tx_env,
+                &fetched_cache.get(&tx_hash).cloned(),
+                max_cycles,
+            )?;
+            if let Some(declared) = declared_cycles {
+                if declared != verified.cycles {
+                    return Err(Reject::DeclaredWrongCycles(declared, verified.cycles));
+                }
+            }
+            entries.push(TxEntry::new(rtx, verified.cycles, fee, tx_size));
+        }
+
+        // Acquire write lock only to insert the verified txs
+        let pre_resolve_tip = snapshot.tip_hash();
+        let (ret, snapshot) = self
+            .with_tx_pool_write_lock(|tx_pool, snapshot| {
+                let mut added = Vec::with_capacity(entries.len());
+                let mut evicted = Vec::new();
+                for entry in &entries {
+                    match _submit_package_entry(tx_pool, &snapshot, &pre_resolve_tip, entry.clone())
+                    {
+                        Ok(Some((status, evicts))) => {
+                            let tx_hash = entry.transaction().hash();
+                            evicted.push((evicts, tx_hash));
+                            added.push((entry.clone(), status));
+                        }
+                        Ok(None) => {}
+                        Err(reject) => {
+                            _rollback_package(tx_pool, &snapshot, &added, evicted);
+                            return Err(reject);
+                        }
+                    }
+                }
+                if let Err(reject) = _check_package_fee_rate(tx_pool, &added) {
+                    _rollback_package(tx_pool, &snapshot, &added, evicted);
+                    return Err(reject);
+                }
+
+                // The package is committed, now it's safe to reject the evicted txs
+                for (evicts, tx_hash) in evicted {
+                    _reject_evicted(tx_pool, evicts, &tx_hash, &self.callbacks);
+                }
+                let tip_number = snapshot.tip_number();
+                for (entry, status) in &added {
+                    tx_pool.fee_estimator.accept_tx(
+                        entry.proposal_short_id(),
+                        entry.fee_rate(),
+                        tip_number,
+                    );
+                    match status {
+                        TxStatus::Fresh => self.callbacks.call_pending(entry),
+                        TxStatus::Gap => self.callbacks.call_gap(entry),
+                        TxStatus::Proposed => self.callbacks.call_proposed(entry),
+                    }
+                }
+                tx_pool.limit_size(&self.callbacks);
+                // The package is all rejected if any of its txs is evicted by the size limit
+                if let Some((evicted, _)) = added
+                    .iter()
+                    .find(|(entry, _)| !tx_pool.pool_map.contains_key(&entry.proposal_short_id()))
+                {
+                    let reject = Reject::Full(format!(
+                        "the fee_rate for this transaction is: {}",
+                        evicted.fee_rate()
+                    ));
+                    let mut removed = Vec::new();
+                    for (entry, _) in &added {
+                        let id = entry.proposal_short_id();
+                        if tx_pool.pool_map.contains_key(&id) {
+                            removed.extend(
+                                tx_pool
+                                    .pool_map
+                                    .remove_entry_and_descendants(&id)
+                                    .into_iter()
+                                    .map(|entry| (entry, reject.clone())),
+                            );
+                        }
+                    }
+                    tx_pool.reject_removed(removed, RemovalReason::Evicted, None, &self.callbacks);
+                    return Err(reject);
+                }
+                Ok(added
+                    .into_iter()
+                    .map(|(_, status)| status)
+                    .collect::<HashSet<_>>())
+            })
+            .await;
+
+        // The package txs which were waiting in the orphan pool or the chunk are accepted now
+        let ids: Vec<ProposalShortId> = txs.iter().map(|tx| tx.proposal_short_id()).collect();
+        if ret.is_ok() {
+            self.orphan
+                .write()
+                .await
+                .remove_orphan_txs(ids.iter().cloned());
+            self.chunk.write().await.remove_chunk_txs(ids.into_iter());
+        }
+        for status in ret? {
+            self.notify_block_assembler(status).await;
+        }
+        Ok(snapshot)
+    }
+
+    async fn after_process_package(
+        &self,
+        txs: Vec<TransactionView>,
+        peer: Option<PeerIndex>,
+        ret: Result<Arc<Snapshot>, Reject>,
+    ) {
+        match ret {
+            Ok(snapshot) => {
+                let tx_hashes: Vec<Byte32> = txs.iter().map(|tx| tx.hash()).collect();
+                debug!(
+                    "after_process_package send_result_to_relayer {:?}",
+                    tx_hashes
+                );
+                self.send_result_to_relayer(TxVerificationResult::PackageOk {
+                    original_peer: peer,
+                    with_vm_2023: self.with_vm_2023(&snapshot),
+                    tx_hashes,
+                });
+                for tx in &txs {
+                    self.process_orphan_tx(tx).await;
+                }
+            }
+            Err(reject) => {
+                debug!("after_process_package reject: {}", reject);
+                if let Some(peer) = peer {
+                    if reject.is_malformed_tx() {
+                        self.ban_malformed(peer, format!("reject package {reject}"));
+                    }
+                    if reject.is_allowed_relay() {
+                        for tx in &txs {
+                            self.send_result_to_relayer(TxVerificationResult::Reject {
+                                tx_hash: tx.hash(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // The network protocol is switched after tx-pool confirms the cache,
+    // there will be no problem with the current state as the choice of the broadcast protocol.
+    fn with_vm_2023(&self, snapshot: &Snapshot) -> bool {
+        let epoch = snapshot
+            .tip_header()
+            .epoch()
+            .minimum_epoch_number_after_n_blocks(1);
+
+        self.consensus
+            .hardfork_switch
+            .ckb2023
+            .is_vm_version_2_and_syscalls_3_enabled(epoch)
+    }
+
     pub(crate) async fn put_recent_reject(&self, tx_hash: &Byte32, reject: &Reject) {
         let mut tx_pool = self.tx_pool.write().await;
         if let Some(ref mut recent_reject) = tx_pool.recent_reject {
@@ -386,7 +615,11 @@ impl TxPoolService {
             }
         }
         let mut tx_pool = self.tx_pool.write().await;
-        tx_pool.remove_tx(&id)
+        tx_pool.remove_tx(
+            &id,
+            "removed by the RPC remove_transaction",
+            &self.callbacks,
+        )
     }
 
     pub(crate) async fn after_process(
@@ -397,20 +630,7 @@ impl TxPoolService {
         ret: &Result<Completed, Reject>,
     ) {
         let tx_hash = tx.hash();
-
-        // The network protocol is switched after tx-pool confirms the cache,
-        // there will be no problem with the current state as the choice of the broadcast protocol.
-        let with_vm_2023 = {
-            let epoch = snapshot
-                .tip_header()
-                .epoch()
-                .minimum_epoch_number_after_n_blocks(1);
-
-            self.consensus
-                .hardfork_switch
-                .ckb2023
-                .is_vm_version_2_and_syscalls_3_enabled(epoch)
-        };
+        let with_vm_2023 = self.with_vm_2023(snapshot);
 
         // log tx verification result for monitor node
         if log_enabled_target!("ckb_tx_monitor", Trace) {
@@ -1011,10 +1231,14 @@ impl TxPoolService {
                         max_cycles,
                     ) {
                         let entry = TxEntry::new(rtx, verified.cycles, fee, tx_size);
-                        if let Err(e) = _submit_entry(tx_pool, status, entry, &self.callbacks) {
-                            error!("readd_detached_tx submit_entry {} error {}", tx_hash, e);
-                        } else {
-                            debug!("readd_detached_tx submit_entry {}", tx_hash);
+                        match _submit_entry(tx_pool, status, entry, &self.callbacks) {
+                            Ok(evicted) => {
+                                debug!("readd_detached_tx submit_entry {}", tx_hash);
+                                _reject_evicted(tx_pool, evicted, &tx_hash, &self.callbacks);
+                            }
+                            Err(e) => {
+                                error!("readd_detached_tx submit_entry {} error {}", tx_hash, e);
+                            }
                         }
                     }
                 }
@@ -1025,7 +1249,7 @@ impl TxPoolService {
     pub(crate) async fn clear_pool(&mut self, new_snapshot: Arc<Snapshot>) {
         {
             let mut tx_pool = self.tx_pool.write().await;
-            tx_pool.clear(Arc::clone(&new_snapshot));
+            tx_pool.clear(Arc::clone(&new_snapshot), &self.callbacks);
         }
         // reset block_assembler
         if self
@@ -1040,6 +1264,9 @@ impl TxPoolService {
 
     pub(crate) async fn save_pool(&self) {
         let mut tx_pool = self.tx_pool.write().await;
+        if let Err(err) = tx_pool.save_fee_estimator_into_file() {
+            error!("failed to save fee estimator, error: {:?}", err)
+        }
         if let Err(err) = tx_pool.save_into_file() {
             error!("failed to save pool, error: {:?}", err)
         } else {
@@ -1131,15 +1358,142 @@ fn _submit_entry(
         TxStatus::Proposed => tx_pool.add_proposed(entry.clone())?,
     };
     if succ {
+        let tip_number = tx_pool.snapshot().tip_number();
+        tx_pool
+            .fee_estimator
+            .accept_tx(entry.proposal_short_id(), entry.fee_rate(), tip_number);
         match status {
             TxStatus::Fresh => callbacks.call_pending(&entry),
-            TxStatus::Gap => callbacks.call_pending(&entry),
+            TxStatus::Gap => callbacks.call_gap(&entry),
             TxStatus::Proposed => callbacks.call_proposed(&entry),
         }
     }
     Ok(evicts)
 }
 
+// Inserts a verified package tx, returns `None` if the tx is already in the pool.
+// The evicted txs are returned instead of being rejected, until the whole package is accepted.
+fn _submit_package_entry(
+    tx_pool: &mut TxPool,
+    snapshot: &Arc<Snapshot>,
+    pre_resolve_tip: &Byte32,
+    entry: TxEntry,
+) -> Result<Option<(TxStatus, HashSet<TxEntry>)>, Reject> {
+    if check_txid_collision(tx_pool, entry.transaction()).is_err() {
+        return Ok(None);
+    }
+    // the pool may be changed since the package was resolved
+    let status = check_rtx(tx_pool, snapshot, &entry.rtx)?;
+    // if snapshot changed by context switch we need redo time_relative verify
+    if pre_resolve_tip != &snapshot.tip_hash() {
+        let tx_env = status.with_env(snapshot.tip_header());
+        time_relative_verify(Arc::clone(snapshot), Arc::clone(&entry.rtx), tx_env)?;
+    }
+    let (_succ, evicts) = match status {
+        TxStatus::Fresh => tx_pool.add_pending(entry)?,
+        TxStatus::Gap => tx_pool.add_gap(entry)?,
+        TxStatus::Proposed => tx_pool.add_proposed(entry)?,
+    };
+    Ok(Some((status, evicts)))
+}
+
+// The evicted txs are the ones whose cell deps are consumed by the new tx
+fn _reject_evicted(
+    tx_pool: &mut TxPool,
+    evicted: HashSet<TxEntry>,
+    tx_hash: &Byte32,
+    callbacks: &Callbacks,
+) {
+    let removed = evicted
+        .into_iter()
+        .map(|evict| {
+            let reject = Reject::Invalidated(format!("invalidated by tx {}", tx_hash));
+            (evict, reject)
+        })
+        .collect();
+    tx_pool.reject_removed(
+        removed,
+        RemovalReason::Invalidated,
+        Some(tx_hash.clone()),
+        callbacks,
+    );
+}
+
+// Every package tx must pay `min_fee_rate` by itself, or be paid by a child in the package.
+fn _check_package_fee_rate(
+    tx_pool: &TxPool,
+    entries: &[(TxEntry, TxStatus)],
+) -> Result<(), Reject> {
+    let min_fee_rate = tx_pool.config.min_fee_rate;
+    let package: HashSet<ProposalShortId> = entries
+        .iter()
+        .map(|(entry, _)| entry.proposal_short_id())
+        .collect();
+    for (entry, _) in entries {
+        let weight = get_transaction_weight(entry.size, entry.cycles);
+        if entry.fee >= min_fee_rate.fee(weight) {
+            continue;
+        }
+        let paid_by_child = tx_pool
+            .pool_map
+            .max_descendants_ancestors_fee_rate(&entry.proposal_short_id(), &package)
+            .map_or(false, |fee_rate| fee_rate >= min_fee_rate);
+        if !paid_by_child {
+            return Err(Reject::LowFeeRate(
+                min_fee_rate,
+                min_fee_rate.fee(weight).as_u64(),
+                entry.fee.as_u64(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Removes the added package txs and puts the evicted txs back, parents first
+fn _rollback_package(
+    tx_pool: &mut TxPool,
+    snapshot: &Snapshot,
+    added: &[(TxEntry, TxStatus)],
+    evicted: Vec<(HashSet<TxEntry>, Byte32)>,
+) {
+    let package: HashSet<ProposalShortId> = added
+        .iter()
+        .map(|(entry, _)| entry.proposal_short_id())
+        .collect();
+    for (entry, _) in added.iter().rev() {
+        tx_pool.pool_map.remove_entry(&entry.proposal_short_id());
+    }
+
+    let mut evicted: Vec<TxEntry> = evicted
+        .into_iter()
+        .flat_map(|(evicts, _)| evicts)
+        .filter(|entry| !package.contains(&entry.proposal_short_id()))
+        .collect();
+    evicted.sort_by_key(|entry| entry.ancestors_count);
+    for evict in evicted {
+        let status = get_tx_status(snapshot, &evict.proposal_short_id());
+        let entry = TxEntry::new_with_timestamp(
+            Arc::clone(&evict.rtx),
+            evict.cycles,
+            evict.fee,
+            evict.size,
+            evict.timestamp,
+        );
+        let ret = match status {
+            TxStatus::Fresh => tx_pool.add_pending(entry),
+            TxStatus::Gap => tx_pool.add_gap(entry),
+            TxStatus::Proposed => tx_pool.add_proposed(entry),
+        };
+        if let Err(err) = ret {
+            error!(
+                "failed to restore {} evicted by a rejected package: {}",
+                evict.transaction().hash(),
+                err
+            );
+        }
+    }
+}
+
 fn _update_tx_pool_for_reorg(
     tx_pool: &mut TxPool,
     attached: &LinkedHashSet<TransactionView>,
@@ -1151,13 +1505,20 @@ fn _update_tx_pool_for_reorg(
 ) {
     tx_pool.snapshot = Arc::clone(&snapshot);
 
+    // All the attached txs are regarded as committed at the new tip, which is accurate
+    // except for the rare reorgs attaching multiple blocks.
+    let committed_ids: Vec<_> = attached.iter().map(|tx| tx.proposal_short_id()).collect();
+    tx_pool
+        .fee_estimator
+        .process_block(snapshot.tip_number(), committed_ids.iter());
+
     // NOTE: `remove_by_detached_proposal` will try to re-put the given expired/detached proposals into
     // pending-pool if they can be found within txpool. As for a transaction
     // which is both expired and committed at the one time(commit at its end of commit-window),
     // we should treat it as a committed and not re-put into pending-pool. So we should ensure
     // that involves `remove_committed_txs` before `remove_expired`.
     tx_pool.remove_committed_txs(attached.iter(), callbacks, detached_headers);
-    tx_pool.remove_by_detached_proposal(detached_proposal_id.iter());
+    tx_pool.remove_by_detached_proposal(detached_proposal_id.iter(), callbacks);
 
     // mine mode:
     // pending ---> gap ----> proposed
@@ -1191,7 +1552,7 @@ fn _update_tx_pool_for_reorg(
                     entry.transaction().hash(),
                     e
                 );
-                callbacks.call_reject(tx_pool, &entry, e);
+                tx_pool.reject_removed(vec![(entry, e)], RemovalReason::Detached, None, callbacks);
             } else {
                 callbacks.call_proposed(&entry)
             }
@@ -1205,7 +1566,9 @@ fn _update_tx_pool_for_reorg(
                     entry.transaction().hash(),
                     e
                 );
-                callbacks.call_reject(tx_pool, &entry, e.clone());
+                tx_pool.reject_removed(vec![(entry, e)], RemovalReason::Detached, None, callbacks);
+            } else {
+                callbacks.call_diff(&entry, PoolTransactionChange::Gap);
             }
         }
     }
@@ -1,7 +1,7 @@
 //! Tx-pool background service
 
 use crate::block_assembler::{self, BlockAssembler};
-use crate::callback::{Callbacks, PendingCallback, ProposedCallback, RejectCallback};
+use crate::callback::{Callbacks, DiffCallback, PendingCallback, ProposedCallback, RejectCallback};
 use crate::chunk_process::ChunkCommand;
 use crate::component::pool_map::{PoolEntry, Status};
 use crate::component::{chunk::ChunkQueue, orphan::OrphanPool};
@@ -21,8 +21,11 @@ use ckb_stop_handler::new_tokio_exit_rx;
 use ckb_types::core::tx_pool::{PoolTxDetailInfo, TransactionWithStatus, TxStatus};
 use ckb_types::{
     core::{
-        tx_pool::{Reject, TxPoolEntryInfo, TxPoolIds, TxPoolInfo, TRANSACTION_SIZE_LIMIT},
-        BlockView, Cycle, TransactionView, UncleBlockView, Version,
+        tx_pool::{
+            Reject, RemovedTransaction, TxPoolEntryInfo, TxPoolIds, TxPoolInfo,
+            TRANSACTION_SIZE_LIMIT,
+        },
+        BlockNumber, BlockView, Cycle, FeeRate, TransactionView, UncleBlockView, Version,
     },
     packed::{Byte32, ProposalShortId},
 };
@@ -44,6 +47,8 @@ use crate::{component::entry::TxEntry, process::PlugTarget};
 
 pub(crate) const DEFAULT_CHANNEL_SIZE: usize = 512;
 pub(crate) const BLOCK_ASSEMBLER_CHANNEL_SIZE: usize = 100;
+/// The max count of transactions in a package
+pub const MAX_PACKAGE_TXS_COUNT: usize = 25;
 
 pub(crate) struct Request<A, R> {
     pub responder: oneshot::Sender<R>,
@@ -89,6 +94,8 @@ pub(crate) enum Message {
     BlockTemplate(Request<BlockTemplateArgs, BlockTemplateResult>),
     SubmitLocalTx(Request<TransactionView, SubmitTxResult>),
     RemoveLocalTx(Request<Byte32, bool>),
+    SubmitLocalPackage(Request<Vec<TransactionView>, SubmitTxResult>),
+    SubmitRemotePackage(Request<(Vec<(TransactionView, Cycle)>, PeerIndex), ()>),
     SubmitRemoteTx(Request<(TransactionView, Cycle, PeerIndex), ()>),
     NotifyTxs(Notify<Vec<TransactionView>>),
     FreshProposalsFilter(Request<Vec<ProposalShortId>, Vec<ProposalShortId>>),
@@ -101,8 +108,12 @@ pub(crate) enum Message {
     ClearPool(Request<Arc<Snapshot>, ()>),
     GetAllEntryInfo(Request<(), TxPoolEntryInfo>),
     GetAllIds(Request<(), TxPoolIds>),
+    GetAllEntryInfoWithSequence(Request<(), (u64, TxPoolEntryInfo)>),
+    GetAllIdsWithSequence(Request<(), (u64, TxPoolIds)>),
     SavePool(Request<(), ()>),
     GetPoolTxDetails(Request<Byte32, PoolTxDetailInfo>),
+    EstimateFeeRate(Request<BlockNumber, FeeRate>),
+    GetRemovedTransactions(Request<(BlockNumber, BlockNumber), Vec<RemovedTransaction>>),
 
     // test
     #[cfg(feature = "internal")]
@@ -240,6 +251,23 @@ impl TxPoolController {
         send_message!(self, SubmitRemoteTx, (tx, declared_cycles, peer))
     }
 
+    /// Submit local package to tx-pool, the txs must be sorted topologically
+    pub fn submit_local_package(
+        &self,
+        txs: Vec<TransactionView>,
+    ) -> Result<SubmitTxResult, AnyError> {
+        send_message!(self, SubmitLocalPackage, txs)
+    }
+
+    /// Submit remote package with declared cycles and origin to tx-pool
+    pub async fn submit_remote_package(
+        &self,
+        txs: Vec<(TransactionView, Cycle)>,
+        peer: PeerIndex,
+    ) -> Result<(), AnyError> {
+        send_message!(self, SubmitRemotePackage, (txs, peer))
+    }
+
     /// Receive txs from network, try to add txs to tx-pool
     pub fn notify_txs(&self, txs: Vec<TransactionView>) -> Result<(), AnyError> {
         send_notify!(self, NotifyTxs, txs)
@@ -302,11 +330,35 @@ impl TxPoolController {
         send_message!(self, GetAllIds, ())
     }
 
+    /// Return the records of the transactions removed from the pool when the tip block number is in `[start, end)`
+    pub fn get_removed_transactions(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> Result<Vec<RemovedTransaction>, AnyError> {
+        send_message!(self, GetRemovedTransactions, (start, end))
+    }
+
+    /// Return the sequence of the last tx-pool change together with all entries info
+    pub fn get_all_entry_info_with_sequence(&self) -> Result<(u64, TxPoolEntryInfo), AnyError> {
+        send_message!(self, GetAllEntryInfoWithSequence, ())
+    }
+
+    /// Return the sequence of the last tx-pool change together with all ids
+    pub fn get_all_ids_with_sequence(&self) -> Result<(u64, TxPoolIds), AnyError> {
+        send_message!(self, GetAllIdsWithSequence, ())
+    }
+
     /// query the details of a transaction in the pool
     pub fn get_tx_detail(&self, tx_hash: Byte32) -> Result<PoolTxDetailInfo, AnyError> {
         send_message!(self, GetPoolTxDetails, tx_hash)
     }
 
+    /// Estimate the fee rate for a tx to get committed within `target_blocks` blocks
+    pub fn estimate_fee_rate(&self, target_blocks: BlockNumber) -> Result<FeeRate, AnyError> {
+        send_message!(self, EstimateFeeRate, target_blocks)
+    }
+
     /// Saves tx pool into disk.
     pub fn save_pool(&self) -> Result<(), AnyError> {
         info!("Please be patient, tx-pool are saving data into disk ...");
@@ -455,12 +507,17 @@ impl TxPoolServiceBuilder {
         self.callbacks.register_reject(callback);
     }
 
+    /// Register new diff callback
+    pub fn register_diff(&mut self, callback: DiffCallback) {
+        self.callbacks.register_diff(callback);
+    }
+
     /// Start a background thread tx-pool service by taking ownership of the Builder, and returns a TxPoolController.
     pub fn start(self, network: NetworkController) {
         let consensus = self.snapshot.cloned_consensus();
         let after_delay_window = after_delay_window(&self.snapshot);
 
-        let tx_pool = TxPool::new(self.tx_pool_config, self.snapshot);
+        let mut tx_pool = TxPool::new(self.tx_pool_config, self.snapshot);
         let txs = match tx_pool.load_from_file() {
             Ok(txs) => txs,
             Err(e) => {
@@ -469,6 +526,10 @@ impl TxPoolServiceBuilder {
                 Vec::new()
             }
         };
+        if let Err(e) = tx_pool.load_fee_estimator_from_file() {
+            error!("{}", e.to_string());
+            error!("Failed to load fee estimator persistent data file, the history is ignored");
+        }
 
         let (block_assembler_sender, mut block_assembler_receiver) = self.block_assembler_channel;
         let service = TxPoolService {
@@ -653,6 +714,15 @@ pub enum TxVerificationResult {
         /// transaction hash
         tx_hash: Byte32,
     },
+    /// package is verified, the txs should be relayed together
+    PackageOk {
+        /// original peer
+        original_peer: Option<PeerIndex>,
+        /// verified by ckb vm version
+        with_vm_2023: bool,
+        /// transaction hashes, sorted topologically
+        tx_hashes: Vec<Byte32>,
+    },
     /// tx is rejected
     Reject {
         /// transaction hash
@@ -714,6 +784,29 @@ async fn process(mut service: TxPoolService, message: Message) {
                 };
             }
         }
+        Message::SubmitLocalPackage(Request {
+            responder,
+            arguments: txs,
+        }) => {
+            let package = txs.into_iter().map(|tx| (tx, None)).collect();
+            let result = service.process_package(package, None).await;
+            if let Err(e) = responder.send(result) {
+                error!("Responder sending submit_package result failed {:?}", e);
+            };
+        }
+        Message::SubmitRemotePackage(Request {
+            responder,
+            arguments: (txs, peer),
+        }) => {
+            let package = txs
+                .into_iter()
+                .map(|(tx, declared_cycles)| (tx, Some(declared_cycles)))
+                .collect();
+            let _result = service.process_package(package, Some(peer)).await;
+            if let Err(e) = responder.send(()) {
+                error!("Responder sending submit_package result failed {:?}", e);
+            };
+        }
         Message::NotifyTxs(Notify { arguments: txs }) => {
             for tx in txs {
                 let _ret = service.resumeble_process_tx(tx, None).await;
@@ -747,6 +840,8 @@ async fn process(mut service: TxPoolService, message: Message) {
                     TxStatus::Pending
                 };
                 Ok((status, Some(entry.cycles)))
+            } else if let Some(removal) = tx_pool.removal_history.get(&hash) {
+                Ok((TxStatus::Rejected(removal.description.clone()), None))
             } else if let Some(ref recent_reject_db) = tx_pool.recent_reject {
                 let recent_reject_result = recent_reject_db.get(&hash);
                 if let Ok(recent_reject) = recent_reject_result {
@@ -791,6 +886,8 @@ async fn process(mut service: TxPoolService, message: Message) {
                     Some(entry.fee),
                     min_replace_fee,
                 ))
+            } else if let Some(removal) = tx_pool.removal_history.get(&hash) {
+                Ok(TransactionWithStatus::with_removed(removal.clone()))
             } else if let Some(ref recent_reject_db) = tx_pool.recent_reject {
                 match recent_reject_db.get(&hash) {
                     Ok(Some(record)) => Ok(TransactionWithStatus::with_rejected(record)),
@@ -864,6 +961,27 @@ async fn process(mut service: TxPoolService, message: Message) {
                 error!("responder send get_pool_tx_details failed {:?}", e)
             };
         }
+        Message::EstimateFeeRate(Request {
+            responder,
+            arguments: target_blocks,
+        }) => {
+            let max_block_bytes = service.consensus.max_block_bytes();
+            let tx_pool = service.tx_pool.read().await;
+            let fee_rate = tx_pool.estimate_fee_rate(target_blocks, max_block_bytes);
+            if let Err(e) = responder.send(fee_rate) {
+                error!("Responder sending estimate_fee_rate failed {:?}", e)
+            };
+        }
+        Message::GetRemovedTransactions(Request {
+            responder,
+            arguments: (start, end),
+        }) => {
+            let tx_pool = service.tx_pool.read().await;
+            let removed = tx_pool.removal_history.range(start, end).cloned().collect();
+            if let Err(e) = responder.send(removed) {
+                error!("Responder sending get_removed_transactions failed {:?}", e)
+            };
+        }
         Message::GetAllEntryInfo(Request { responder, .. }) => {
             let tx_pool = service.tx_pool.read().await;
             let info = tx_pool.get_all_entry_info();
@@ -878,6 +996,26 @@ async fn process(mut service: TxPoolService, message: Message) {
                 error!("Responder sending get_ids failed {:?}", e)
             };
         }
+        Message::GetAllEntryInfoWithSequence(Request { responder, .. }) => {
+            let tx_pool = service.tx_pool.read().await;
+            // the changes are made under the write lock, so the sequence matches the entries
+            let sequence = service.callbacks.sequence();
+            let info = tx_pool.get_all_entry_info();
+            if let Err(e) = responder.send((sequence, info)) {
+                error!(
+                    "Responder sending get_all_entry_info_with_sequence failed {:?}",
+                    e
+                )
+            };
+        }
+        Message::GetAllIdsWithSequence(Request { responder, .. }) => {
+            let tx_pool = service.tx_pool.read().await;
+            let sequence = service.callbacks.sequence();
+            let ids = tx_pool.get_ids();
+            if let Err(e) = responder.send((sequence, ids)) {
+                error!("Responder sending get_ids_with_sequence failed {:?}", e)
+            };
+        }
         Message::SavePool(Request { responder, .. }) => {
             service.save_pool().await;
             if let Err(e) = responder.send(()) {
@@ -978,7 +1116,10 @@ impl TxPoolService {
 
     pub async fn update_block_assembler_after_tx_pool_reorg(&self) {
         if let Some(ref block_assembler) = self.block_assembler {
-            if let Err(e) = block_assembler.update_full(&self.tx_pool).await {
+            if let Err(e) = block_assembler
+                .update_full(&self.tx_pool, &self.callbacks)
+                .await
+            {
                 error!("block_assembler update failed {:?}", e);
             }
             block_assembler.notify().await;
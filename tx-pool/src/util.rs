@@ -1,19 +1,24 @@
 use crate::error::Reject;
 use crate::pool::TxPool;
+use crate::service::MAX_PACKAGE_TXS_COUNT;
 use ckb_chain_spec::consensus::Consensus;
 use ckb_dao::DaoCalculator;
 use ckb_snapshot::Snapshot;
 use ckb_store::data_loader_wrapper::AsDataLoader;
 use ckb_store::ChainStore;
-use ckb_types::core::{
-    cell::ResolvedTransaction, tx_pool::TRANSACTION_SIZE_LIMIT, Capacity, Cycle, EpochNumber,
-    TransactionView,
+use ckb_types::{
+    core::{
+        cell::ResolvedTransaction, tx_pool::TRANSACTION_SIZE_LIMIT, Capacity, Cycle, EpochNumber,
+        TransactionView,
+    },
+    packed::Byte32,
 };
 use ckb_verification::{
     cache::{CacheEntry, Completed},
     ContextualTransactionVerifier, DaoScriptSizeVerifier, NonContextualTransactionVerifier,
     TimeRelativeTransactionVerifier, TxVerifyEnv,
 };
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::task::block_in_place;
 
@@ -25,20 +30,27 @@ pub(crate) fn check_txid_collision(tx_pool: &TxPool, tx: &TransactionView) -> Re
     Ok(())
 }
 
-pub(crate) fn check_tx_fee(
-    tx_pool: &TxPool,
+pub(crate) fn calculate_tx_fee(
     snapshot: &Snapshot,
     rtx: &ResolvedTransaction,
-    tx_size: usize,
 ) -> Result<Capacity, Reject> {
-    let fee = DaoCalculator::new(snapshot.consensus(), &snapshot.borrow_as_data_loader())
+    DaoCalculator::new(snapshot.consensus(), &snapshot.borrow_as_data_loader())
         .transaction_fee(rtx)
         .map_err(|err| {
             Reject::Malformed(
                 format!("{err}"),
                 "expect (outputs capacity) <= (inputs capacity)".to_owned(),
             )
-        })?;
+        })
+}
+
+pub(crate) fn check_tx_fee(
+    tx_pool: &TxPool,
+    snapshot: &Snapshot,
+    rtx: &ResolvedTransaction,
+    tx_size: usize,
+) -> Result<Capacity, Reject> {
+    let fee = calculate_tx_fee(snapshot, rtx)?;
     // Theoretically we cannot use size as weight directly to calculate fee_rate,
     // here min fee rate is used as a cheap check,
     // so we will use size to calculate fee_rate directly
@@ -53,6 +65,120 @@ pub(crate) fn check_tx_fee(
     Ok(fee)
 }
 
+/// Checks that the package is a non-empty set of distinct transactions, connected by the
+/// parent/child relationships and sorted topologically.
+pub(crate) fn check_package(txs: &[TransactionView]) -> Result<(), Reject> {
+    if txs.is_empty() || txs.len() > MAX_PACKAGE_TXS_COUNT {
+        return Err(Reject::Malformed(
+            format!("package of {} transactions", txs.len()),
+            format!("expect 1 to {MAX_PACKAGE_TXS_COUNT} transactions in a package"),
+        ));
+    }
+
+    let positions: HashMap<Byte32, usize> = txs
+        .iter()
+        .enumerate()
+        .map(|(i, tx)| (tx.hash(), i))
+        .collect();
+    if positions.len() != txs.len() {
+        return Err(Reject::Malformed(
+            "package with duplicated transactions".to_owned(),
+            "expect distinct transactions in a package".to_owned(),
+        ));
+    }
+
+    // Every tx is linked to the first tx of its connected group, which is sorted before it
+    let mut roots: Vec<usize> = (0..txs.len()).collect();
+    for (i, tx) in txs.iter().enumerate() {
+        let parents = tx
+            .input_pts_iter()
+            .chain(tx.cell_deps_iter().map(|dep| dep.out_point()))
+            .filter_map(|out_point| positions.get(&out_point.tx_hash()));
+        for parent in parents {
+            if *parent >= i {
+                return Err(Reject::Malformed(
+                    format!("package with unsorted transaction {}", tx.hash()),
+                    "expect parents before children in a package".to_owned(),
+                ));
+            }
+            let (a, b) = (find_root(&mut roots, i), find_root(&mut roots, *parent));
+            roots[a.max(b)] = a.min(b);
+        }
+    }
+    if let Some(i) = (0..txs.len()).find(|i| find_root(&mut roots, *i) != 0) {
+        return Err(Reject::Malformed(
+            format!("package with unrelated transaction {}", txs[i].hash()),
+            "expect every transaction to have a parent or a child in a package".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Splits the relayed txs into packages, each of which is a set of txs connected by the
+/// parent/child relationships and sorted topologically, so that a child can pay for its parents.
+pub fn split_packages(txs: Vec<(TransactionView, Cycle)>) -> Vec<Vec<(TransactionView, Cycle)>> {
+    let positions: HashMap<Byte32, usize> = txs
+        .iter()
+        .enumerate()
+        .map(|(i, (tx, _))| (tx.hash(), i))
+        .collect();
+    let parents: Vec<Vec<usize>> = txs
+        .iter()
+        .map(|(tx, _)| {
+            tx.input_pts_iter()
+                .chain(tx.cell_deps_iter().map(|dep| dep.out_point()))
+                .filter_map(|out_point| positions.get(&out_point.tx_hash()).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut roots: Vec<usize> = (0..txs.len()).collect();
+    for (i, tx_parents) in parents.iter().enumerate() {
+        for parent in tx_parents {
+            let (a, b) = (find_root(&mut roots, i), find_root(&mut roots, *parent));
+            roots[a] = b;
+        }
+    }
+
+    let mut sorted = Vec::with_capacity(txs.len());
+    let mut visited = vec![false; txs.len()];
+    while sorted.len() < txs.len() {
+        let count = sorted.len();
+        for (i, tx_parents) in parents.iter().enumerate() {
+            if !visited[i] && tx_parents.iter().all(|parent| visited[*parent]) {
+                visited[i] = true;
+                sorted.push(i);
+            }
+        }
+        if count == sorted.len() {
+            // unreachable as a tx can't spend the outputs of its descendants
+            break;
+        }
+    }
+
+    let mut txs: Vec<_> = txs.into_iter().map(Some).collect();
+    let mut packages: Vec<Vec<(TransactionView, Cycle)>> = Vec::new();
+    let mut package_indexes: HashMap<usize, usize> = HashMap::default();
+    for i in sorted {
+        let root = find_root(&mut roots, i);
+        let index = *package_indexes.entry(root).or_insert_with(|| {
+            packages.push(Vec::new());
+            packages.len() - 1
+        });
+        packages[index].push(txs[i].take().expect("visited once"));
+    }
+    packages
+}
+
+fn find_root(roots: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while roots[root] != root {
+        root = roots[root];
+    }
+    roots[i] = root;
+    root
+}
+
 pub(crate) fn non_contextual_verify(
     consensus: &Consensus,
     tx: &TransactionView,
@@ -285,6 +285,12 @@ impl CKBAppConfig {
         }))?;
 
         self.network.path = self.data_dir.join("network");
+        if let Some(asmap) = self.network.asmap.take() {
+            self.network.asmap = Some(canonicalize_data_dir(asmap, root_dir));
+        }
+        if let Some(cell_snapshot) = self.network.sync.cell_snapshot.as_mut() {
+            cell_snapshot.path = canonicalize_data_dir(cell_snapshot.path.clone(), root_dir);
+        }
         if self.tmp_dir.is_none() {
             self.tmp_dir = Some(self.data_dir.join("tmp"));
         }
@@ -4,6 +4,7 @@ use ckb_jsonrpc_types::ScriptHashType;
 use ckb_pow::PowEngine;
 use ckb_systemtime::unix_time_as_millis;
 use ckb_types::packed::Byte32;
+use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -15,6 +16,124 @@ pub struct ExportArgs {
     pub consensus: Consensus,
     /// The target directory to save the exported file.
     pub target: PathBuf,
+    /// Hash of serialized configured chain spec, recorded in the binary format.
+    pub chain_spec_hash: Byte32,
+    /// The format of the exported file.
+    pub format: ExportFormat,
+    /// The first block to export, the genesis block by default.
+    pub from: Option<u64>,
+    /// The last block to export, the tip block by default.
+    pub to: Option<u64>,
+}
+
+/// Parsed command line arguments for `ckb export-cell-snapshot`.
+pub struct ExportCellSnapshotArgs {
+    /// Parsed `ckb.toml`.
+    pub config: Box<CKBAppConfig>,
+    /// Loaded consensus.
+    pub consensus: Consensus,
+    /// The target directory to save the snapshot file.
+    pub target: PathBuf,
+    /// The block which the snapshot is expected to be at, it must be the tip block.
+    pub block: Option<BlockNumberOrHash>,
+}
+
+/// A block specified by the number or the hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockNumberOrHash {
+    /// The block number.
+    Number(u64),
+    /// The block hash.
+    Hash(Byte32),
+}
+
+impl fmt::Display for BlockNumberOrHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockNumberOrHash::Number(number) => write!(f, "{number}"),
+            BlockNumberOrHash::Hash(hash) => write!(f, "{hash}"),
+        }
+    }
+}
+
+/// Parsed command line arguments for `ckb backup`.
+pub struct BackupArgs {
+    /// Parsed `ckb.toml`.
+    pub config: Box<CKBAppConfig>,
+    /// Loaded consensus.
+    pub consensus: Consensus,
+    /// The backup directory.
+    pub target: PathBuf,
+}
+
+/// Parsed command line arguments for `ckb db-stats`.
+pub struct DBStatsArgs {
+    /// Parsed `ckb.toml`.
+    pub config: Box<CKBAppConfig>,
+    /// Loaded consensus.
+    pub consensus: Consensus,
+}
+
+/// Parsed command line arguments for `ckb compact`.
+pub struct CompactArgs {
+    /// Parsed `ckb.toml`.
+    pub config: Box<CKBAppConfig>,
+    /// Loaded consensus.
+    pub consensus: Consensus,
+    /// The compacted column families, all the column families if empty.
+    pub columns: Vec<String>,
+    /// The first key of the compacted range.
+    pub start_key: Option<Vec<u8>>,
+    /// The key after the compacted range.
+    pub end_key: Option<Vec<u8>>,
+    /// The pause between the compaction steps in milliseconds.
+    pub throttle: Option<u64>,
+}
+
+/// Parsed command line arguments for `ckb freezer`.
+pub struct FreezerArgs {
+    /// Parsed `ckb.toml`.
+    pub config: Box<CKBAppConfig>,
+    /// Loaded consensus.
+    pub consensus: Consensus,
+    /// Truncates the corrupted blocks if true, otherwise only reports them.
+    pub repair: bool,
+}
+
+/// Parsed command line arguments for `ckb peer-store`.
+pub struct PeerStoreArgs {
+    /// The peer store directory.
+    pub peer_store_path: PathBuf,
+    /// The action on the peer store.
+    pub action: PeerStoreAction,
+}
+
+/// The subcommands of `ckb peer-store`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PeerStoreAction {
+    /// Prints the peer store as JSON.
+    Dump,
+    /// Imports the addresses and the bans from the JSON file.
+    Import {
+        /// The JSON file in the dump format.
+        source: PathBuf,
+    },
+    /// Removes the addresses matching any of the criteria.
+    Prune {
+        /// Removes the addresses not connected in the recent days.
+        older_than_days: Option<u64>,
+        /// Removes the addresses lacking any of the flags.
+        without_flags: Vec<String>,
+    },
+}
+
+/// The format of the exported blocks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// JSON Lines of the blocks.
+    Json,
+    /// Length-prefixed molecule serialized blocks with periodic checksums.
+    Bin,
 }
 
 #[derive(Debug)]
@@ -36,6 +155,8 @@ pub struct ImportArgs {
     pub consensus: Consensus,
     /// The path to the file to be imported.
     pub source: PathBuf,
+    /// Hash of serialized configured chain spec, checked against the binary format.
+    pub chain_spec_hash: Byte32,
 }
 
 /// Parsed command line arguments for `ckb run`.
@@ -72,10 +193,15 @@ pub struct ReplayArgs {
     pub config: Box<CKBAppConfig>,
     /// Loaded consensus.
     pub consensus: Consensus,
-    /// The directory to store the temporary files during the replay.
-    pub tmp_target: PathBuf,
+    /// The directory to store the temporary files during the replay, not required by `verify`.
+    pub tmp_target: Option<PathBuf>,
     /// Enable profile on blocks in the range `[from, to]`.
     pub profile: ProfileArgs,
+    /// Enable verifying replay on blocks in the range `[from, to]`, the stored blocks are
+    /// re-verified in parallel against the database opened in read-only mode.
+    pub verify: ProfileArgs,
+    /// The file to write the mismatches found by the verifying replay.
+    pub report: Option<PathBuf>,
     /// Enable sanity check.
     pub sanity_check: bool,
     /// Enable full verification.
@@ -14,6 +14,8 @@ pub const CMD_MINER: &str = "miner";
 pub const CMD_EXPORT: &str = "export";
 /// Subcommand `import`.
 pub const CMD_IMPORT: &str = "import";
+/// Subcommand `export-cell-snapshot`.
+pub const CMD_EXPORT_CELL_SNAPSHOT: &str = "export-cell-snapshot";
 /// Subcommand `init`.
 pub const CMD_INIT: &str = "init";
 /// Subcommand `replay`.
@@ -34,6 +36,26 @@ pub const CMD_FROM_SECRET: &str = "from-secret";
 pub const CMD_MIGRATE: &str = "migrate";
 /// Subcommand `daemon`
 pub const CMD_DAEMON: &str = "daemon";
+/// Subcommand `freezer`.
+pub const CMD_FREEZER: &str = "freezer";
+/// Subcommand `backup`.
+pub const CMD_BACKUP: &str = "backup";
+/// Subcommand `db-stats`.
+pub const CMD_DB_STATS: &str = "db-stats";
+/// Subcommand `compact`.
+pub const CMD_COMPACT: &str = "compact";
+/// Subcommand `freezer check`.
+pub const CMD_FREEZER_CHECK: &str = "check";
+/// Subcommand `freezer repair`.
+pub const CMD_FREEZER_REPAIR: &str = "repair";
+/// Subcommand `peer-store`.
+pub const CMD_PEER_STORE: &str = "peer-store";
+/// Subcommand `peer-store dump`.
+pub const CMD_PEER_STORE_DUMP: &str = "dump";
+/// Subcommand `peer-store import`.
+pub const CMD_PEER_STORE_IMPORT: &str = "import";
+/// Subcommand `peer-store prune`.
+pub const CMD_PEER_STORE_PRUNE: &str = "prune";
 /// Command line argument `--config-dir`.
 pub const ARG_CONFIG_DIR: &str = "config-dir";
 /// Command line argument `--format`.
@@ -114,6 +136,8 @@ pub const ARG_FULL_VERIFICATION: &str = "full-verification";
 pub const ARG_SKIP_CHAIN_SPEC_CHECK: &str = "skip-spec-check";
 /// Present `overwrite-spec` arg to force overriding the chain spec in the database with the present configured chain spec
 pub const ARG_OVERWRITE_CHAIN_SPEC: &str = "overwrite-spec";
+/// Command line argument `export-cell-snapshot --block`.
+pub const ARG_BLOCK: &str = "block";
 /// Command line argument `--assume-valid-target`.
 pub const ARG_ASSUME_VALID_TARGET: &str = "assume-valid-target";
 /// Command line argument `--check`.
@@ -122,6 +146,22 @@ pub const ARG_MIGRATE_CHECK: &str = "check";
 pub const ARG_DAEMON_CHECK: &str = "check";
 /// Command line argument `daemon --stop`
 pub const ARG_DAEMON_STOP: &str = "stop";
+/// Command line argument `--column`.
+pub const ARG_COLUMN: &str = "column";
+/// Command line argument `--start-key`.
+pub const ARG_START_KEY: &str = "start-key";
+/// Command line argument `--end-key`.
+pub const ARG_END_KEY: &str = "end-key";
+/// Command line argument `--throttle`.
+pub const ARG_THROTTLE: &str = "throttle";
+/// Command line argument `--older-than`.
+pub const ARG_OLDER_THAN: &str = "older-than";
+/// Command line argument `--without-flag`.
+pub const ARG_WITHOUT_FLAG: &str = "without-flag";
+/// Command line argument `--verify`.
+pub const ARG_VERIFY: &str = "verify";
+/// Command line argument `--report`.
+pub const ARG_REPORT: &str = "report";
 
 /// Command line arguments group `ba` for block assembler.
 const GROUP_BA: &str = "ba";
@@ -148,13 +188,19 @@ pub fn basic_app() -> Command {
         .subcommand(miner())
         .subcommand(export())
         .subcommand(import())
+        .subcommand(export_cell_snapshot())
         .subcommand(list_hashes())
         .subcommand(init())
         .subcommand(replay())
         .subcommand(stats())
         .subcommand(reset_data())
         .subcommand(peer_id())
-        .subcommand(migrate());
+        .subcommand(migrate())
+        .subcommand(freezer())
+        .subcommand(backup())
+        .subcommand(db_stats())
+        .subcommand(compact())
+        .subcommand(peer_store());
 
     #[cfg(not(target_os = "windows"))]
     let command = command.subcommand(daemon());
@@ -343,8 +389,9 @@ fn replay() -> Command {
         .override_help("
             --tmp-target <tmp> --profile 1 10,\n
             --tmp-target <tmp> --sanity-check,\n
+            --verify 1 10 --report <file>,\n
         ")
-        .arg(Arg::new(ARG_TMP_TARGET).long(ARG_TMP_TARGET).value_parser(clap::builder::PathBufValueParser::new()).action(clap::ArgAction::Set).required(true).help(
+        .arg(Arg::new(ARG_TMP_TARGET).long(ARG_TMP_TARGET).value_parser(clap::builder::PathBufValueParser::new()).action(clap::ArgAction::Set).required_unless_present(ARG_VERIFY).help(
             "Specify a target path. The profile command makes a temporary directory within the specified target path. This temporary directory will be automatically deleted when the command completes.",
         ))
         .arg(Arg::new(ARG_PROFILE).long(ARG_PROFILE).action(clap::ArgAction::SetTrue).help(
@@ -366,23 +413,62 @@ fn replay() -> Command {
         .arg(
             Arg::new(ARG_FULL_VERIFICATION).long(ARG_FULL_VERIFICATION).action(clap::ArgAction::SetTrue).help("Enable sanity check")
         )
+        .arg(
+            Arg::new(ARG_VERIFY).long(ARG_VERIFY).action(clap::ArgAction::SetTrue).help(
+                "Re-verify the stored blocks in parallel, reading the database in read-only mode, and report the mismatches of the cycles, fees and DAO fields. \
+                 The inputs are not checked against double spending, and the blocks spending the consumed cells created in the pruned blocks are skipped",
+            )
+        )
+        .arg(
+            Arg::new(ARG_REPORT)
+                .long(ARG_REPORT)
+                .value_name("file")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .action(clap::ArgAction::Set)
+                .requires(ARG_VERIFY)
+                .help("Write the mismatches found by --verify into the file as JSON lines, print them if absent"),
+        )
         .group(
             ArgGroup::new("mode")
-                .args([ARG_PROFILE, ARG_SANITY_CHECK])
+                .args([ARG_PROFILE, ARG_SANITY_CHECK, ARG_VERIFY])
                 .required(true)
         )
 }
 
 fn export() -> Command {
-    Command::new(CMD_EXPORT).about("Export CKB data").arg(
-        Arg::new(ARG_TARGET)
-            .short('t')
-            .long(ARG_TARGET)
-            .value_name("path")
-            .value_parser(clap::builder::PathBufValueParser::new())
-            .required(true)
-            .help("Specify the export target path"),
-    )
+    Command::new(CMD_EXPORT)
+        .about("Export CKB data")
+        .arg(
+            Arg::new(ARG_TARGET)
+                .short('t')
+                .long(ARG_TARGET)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .required(true)
+                .help("Specify the export target path"),
+        )
+        .arg(
+            Arg::new(ARG_FORMAT)
+                .short('f')
+                .long(ARG_FORMAT)
+                .value_parser(["json", "bin"])
+                .default_value("json")
+                .help("Set the format of the exported file"),
+        )
+        .arg(
+            Arg::new(ARG_FROM)
+                .long(ARG_FROM)
+                .value_parser(clap::value_parser!(u64))
+                .action(clap::ArgAction::Set)
+                .help("Specify the first block number to export, the genesis block by default"),
+        )
+        .arg(
+            Arg::new(ARG_TO)
+                .long(ARG_TO)
+                .value_parser(clap::value_parser!(u64))
+                .action(clap::ArgAction::Set)
+                .help("Specify the last block number to export, the tip block by default"),
+        )
 }
 
 fn import() -> Command {
@@ -392,10 +478,176 @@ fn import() -> Command {
             .value_name("path")
             .value_parser(clap::builder::PathBufValueParser::new())
             .required(true)
-            .help("Specify the exported data path"),
+            .help("Specify the exported data path, the blocks already imported are skipped"),
+    )
+}
+
+fn export_cell_snapshot() -> Command {
+    Command::new(CMD_EXPORT_CELL_SNAPSHOT)
+        .about(
+            "Export the live cell snapshot at the tip block for bootstrapping new nodes, \
+             the snapshot at an older block is not supported since only the live cells of \
+             the tip block are kept",
+        )
+        .arg(
+            Arg::new(ARG_TARGET)
+                .short('t')
+                .long(ARG_TARGET)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .required(true)
+                .help("Specify the export target path"),
+        )
+        .arg(
+            Arg::new(ARG_BLOCK)
+                .long(ARG_BLOCK)
+                .value_name("number or hash")
+                .value_parser(is_block_number_or_hash)
+                .action(clap::ArgAction::Set)
+                .help(
+                    "Specify the block number or the block hash of the snapshot, \
+                     the export fails unless it's the tip block",
+                ),
+        )
+}
+
+fn backup() -> Command {
+    Command::new(CMD_BACKUP)
+        .about(
+            "Back up the chain database and the freezer, \
+             use the RPC `backup_database` to back up a running node",
+        )
+        .arg(
+            Arg::new(ARG_TARGET)
+                .short('t')
+                .long(ARG_TARGET)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .required(true)
+                .help("Specify the backup directory, which must not exist"),
+        )
+}
+
+fn db_stats() -> Command {
+    Command::new(CMD_DB_STATS).about(
+        "Report the disk usage of the database column families and the freezer tables, \
+         use the RPC `get_db_stats` for a running node",
     )
 }
 
+fn compact() -> Command {
+    Command::new(CMD_COMPACT)
+        .about(
+            "Compact the database column families to reclaim the disk space, \
+             use the RPC `compact_database` for a running node",
+        )
+        .arg(
+            Arg::new(ARG_COLUMN)
+                .long(ARG_COLUMN)
+                .value_name("name")
+                .action(clap::ArgAction::Append)
+                .help(
+                    "Specify the column family by name, e.g. block_body, or by id, \
+                     all the column families are compacted if absent",
+                ),
+        )
+        .arg(
+            Arg::new(ARG_START_KEY)
+                .long(ARG_START_KEY)
+                .value_name("hex")
+                .value_parser(parse_hex_bytes)
+                .action(clap::ArgAction::Set)
+                .help("Specify the first key of the compacted range"),
+        )
+        .arg(
+            Arg::new(ARG_END_KEY)
+                .long(ARG_END_KEY)
+                .value_name("hex")
+                .value_parser(parse_hex_bytes)
+                .action(clap::ArgAction::Set)
+                .help("Specify the key after the compacted range"),
+        )
+        .arg(
+            Arg::new(ARG_THROTTLE)
+                .long(ARG_THROTTLE)
+                .value_name("ms")
+                .value_parser(clap::value_parser!(u64))
+                .action(clap::ArgAction::Set)
+                .help("Pause between the compaction steps in milliseconds"),
+        )
+}
+
+fn freezer() -> Command {
+    Command::new(CMD_FREEZER)
+        .about("Verify or repair the ancient block data in the freezer")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new(CMD_FREEZER_CHECK)
+                .about("Verify every block in the freezer without modifying it"),
+        )
+        .subcommand(Command::new(CMD_FREEZER_REPAIR).about(
+            "Truncate the freezer to the last good block, \
+             then re-freeze the dropped blocks which are still in the database",
+        ))
+}
+
+fn peer_store() -> Command {
+    Command::new(CMD_PEER_STORE)
+        .about("Inspect, seed or prune the peer store, stop the node before modifying it")
+        .subcommand_required(true)
+        .subcommand(Command::new(CMD_PEER_STORE_DUMP).about(
+            "Print the addresses with their flags, scores and connection times, \
+             and the ban list as JSON",
+        ))
+        .subcommand(
+            Command::new(CMD_PEER_STORE_IMPORT)
+                .about("Import the addresses and the bans from a JSON file in the dump format")
+                .arg(
+                    Arg::new(ARG_SOURCE)
+                        .index(1)
+                        .value_name("path")
+                        .value_parser(clap::builder::PathBufValueParser::new())
+                        .required(true)
+                        .help(
+                            "Specify the JSON file, the addresses without a peer id \
+                             or in the ban list are skipped",
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new(CMD_PEER_STORE_PRUNE)
+                .about("Remove the addresses matching any of the criteria")
+                .arg(
+                    Arg::new(ARG_OLDER_THAN)
+                        .long(ARG_OLDER_THAN)
+                        .value_name("days")
+                        .value_parser(clap::value_parser!(u64))
+                        .action(clap::ArgAction::Set)
+                        .help("Remove the addresses not connected in the recent days"),
+                )
+                .arg(
+                    Arg::new(ARG_WITHOUT_FLAG)
+                        .long(ARG_WITHOUT_FLAG)
+                        .value_name("flag")
+                        .value_parser([
+                            "discovery",
+                            "sync",
+                            "relay",
+                            "light-client",
+                            "block-filter",
+                        ])
+                        .action(clap::ArgAction::Append)
+                        .help("Remove the addresses without the flag, can be repeated"),
+                )
+                .group(
+                    ArgGroup::new("criteria")
+                        .args([ARG_OLDER_THAN, ARG_WITHOUT_FLAG])
+                        .multiple(true)
+                        .required(true),
+                ),
+        )
+}
+
 fn migrate() -> Command {
     Command::new(CMD_MIGRATE)
         .about("Run CKB migration")
@@ -643,6 +895,25 @@ fn is_hex(hex: &str) -> Result<String, String> {
     }
 }
 
+fn parse_hex_bytes(hex: &str) -> Result<Vec<u8>, String> {
+    let hex = is_hex(hex)?;
+    (2..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|err| err.to_string()))
+        .collect()
+}
+
+fn is_block_number_or_hash(value: &str) -> Result<String, String> {
+    if value.starts_with("0x") {
+        is_h256(value)
+    } else {
+        value
+            .parse::<u64>()
+            .map(|_| value.to_owned())
+            .map_err(|_| "Must be a block number or a 0x-prefixed block hash".to_owned())
+    }
+}
+
 fn is_h256(hex: &str) -> Result<String, String> {
     if hex.len() != 66 {
         Err("Must be 0x-prefixed hexadecimal string and string length is 66".to_owned())
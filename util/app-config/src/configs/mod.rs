@@ -18,13 +18,17 @@ pub use miner::{
     ExtraHashFunction, WorkerConfig as MinerWorkerConfig,
 };
 pub use network::{
-    default_support_all_protocols, Config as NetworkConfig, HeaderMapConfig, SupportProtocol,
+    default_support_all_protocols, format_multiaddr, is_onion_name, parse_multiaddr,
+    CellSnapshotConfig, Config as NetworkConfig, HeaderMapConfig, ProxyConfig, SupportProtocol,
     SyncConfig,
 };
 pub use network_alert::Config as NetworkAlertConfig;
 pub use notify::Config as NotifyConfig;
 pub use rich_indexer::{DBDriver, RichIndexerConfig};
-pub use rpc::{Config as RpcConfig, Module as RpcModule};
+pub use rpc::{
+    AuthConfig as RpcAuthConfig, Config as RpcConfig, Credential as RpcCredential,
+    Module as RpcModule, RateLimitConfig as RpcRateLimitConfig,
+};
 pub use store::Config as StoreConfig;
 pub use tx_pool::{BlockAssemblerConfig, TxPoolConfig};
 
@@ -55,16 +55,17 @@ pub struct Config {
     /// Public addresses.
     ///
     /// Set this if this is different from `listen_addresses`.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_multiaddrs")]
     pub public_addresses: Vec<Multiaddr>,
     /// A list of peers used to boot the node discovery.
     ///
     /// Bootnodes are used to bootstrap the discovery when local peer storage is empty.
+    #[serde(deserialize_with = "deserialize_multiaddrs")]
     pub bootnodes: Vec<Multiaddr>,
     /// A list of peers added in the whitelist.
     ///
     /// When `whitelist_only` is enabled, the node will only connect to peers in this list.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_multiaddrs")]
     pub whitelist_peers: Vec<Multiaddr>,
     /// Enable UPNP when the router supports it.
     #[serde(default)]
@@ -88,6 +89,105 @@ pub struct Config {
     pub sync: SyncConfig,
     /// Tentacle inner channel_size.
     pub channel_size: Option<usize>,
+    /// The ASmap file which maps IP prefixes to autonomous system numbers.
+    ///
+    /// Inbound peers are grouped by their autonomous systems instead of the /16 prefixes when
+    /// evicting, each line of the file is an IP prefix and the ASN, e.g., `1.0.0.0/24 AS13335`.
+    #[serde(default)]
+    pub asmap: Option<PathBuf>,
+    /// The SOCKS5 proxy to dial outbound peers through.
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+}
+
+/// SOCKS5 proxy config options.
+///
+/// Onion services, e.g., `/onion3/<name>:8115`, are only reachable through a Tor proxy.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyConfig {
+    /// The address of the SOCKS5 proxy, e.g., `127.0.0.1:9050`
+    pub proxy_address: String,
+    /// Dial all outbound peers through the proxy instead of only the onion services.
+    ///
+    /// The DNS seeding is disabled as well to avoid leaking the DNS queries.
+    #[serde(default)]
+    pub only_proxy: bool,
+}
+
+const ONION3_PROTOCOL: &str = "/onion3/";
+const ONION_SUFFIX: &str = ".onion";
+// The length of the base32 encoded public key of a version 3 onion service
+const ONION_V3_NAME_LEN: usize = 56;
+
+/// Whether the name is a version 3 onion service name, e.g., `<name>.onion`.
+pub fn is_onion_name(name: &str) -> bool {
+    name.strip_suffix(ONION_SUFFIX).map_or(false, |name| {
+        name.len() == ONION_V3_NAME_LEN
+            && name.bytes().all(|b| matches!(b, b'a'..=b'z' | b'2'..=b'7'))
+    })
+}
+
+/// Parses the multiaddr, in which an onion service is addressed by the `onion3` protocol, e.g.,
+/// `/onion3/<name>:8115/p2p/<peer-id>`.
+///
+/// The multiaddr library has no `onion3` protocol, an onion service is carried as
+/// `/dns4/<name>.onion/tcp/<port>` inside the node and between the peers instead, see
+/// [`format_multiaddr`].
+pub fn parse_multiaddr(addr: &str) -> Result<Multiaddr, multiaddr::Error> {
+    let onion = match addr.strip_prefix(ONION3_PROTOCOL) {
+        Some(onion) => onion,
+        None => return addr.parse(),
+    };
+    let (service, rest) = match onion.find('/') {
+        Some(index) => onion.split_at(index),
+        None => (onion, ""),
+    };
+    let (name, port) = service
+        .split_once(':')
+        .ok_or(multiaddr::Error::InvalidMultiaddr)?;
+    let name = format!("{name}{ONION_SUFFIX}");
+    let port: u16 = port
+        .parse()
+        .map_err(|_| multiaddr::Error::InvalidMultiaddr)?;
+    if !is_onion_name(&name) {
+        return Err(multiaddr::Error::InvalidMultiaddr);
+    }
+    format!("/dns4/{name}/tcp/{port}{rest}").parse()
+}
+
+/// Formats the multiaddr, in which an onion service is formatted by the `onion3` protocol, see
+/// [`parse_multiaddr`].
+pub fn format_multiaddr(addr: &Multiaddr) -> String {
+    let mut formatted = String::new();
+    let mut protocols = addr.iter().peekable();
+    while let Some(protocol) = protocols.next() {
+        match (&protocol, protocols.peek()) {
+            (
+                multiaddr::Protocol::Dns4(name) | multiaddr::Protocol::Dns6(name),
+                Some(multiaddr::Protocol::Tcp(port)),
+            ) if is_onion_name(name) => {
+                let name = name.strip_suffix(ONION_SUFFIX).expect("onion name checked");
+                formatted.push_str(&format!("{ONION3_PROTOCOL}{name}:{port}"));
+                protocols.next();
+            }
+            _ => formatted.push_str(&protocol.to_string()),
+        }
+    }
+    formatted
+}
+
+fn deserialize_multiaddrs<'de, D>(deserializer: D) -> Result<Vec<Multiaddr>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|addr| {
+            parse_multiaddr(addr)
+                .map_err(|err| serde::de::Error::custom(format!("invalid multiaddr {addr}: {err}")))
+        })
+        .collect()
 }
 
 /// Chain synchronization config options.
@@ -103,6 +203,23 @@ pub struct SyncConfig {
     /// Proof of minimum work during synchronization
     #[serde(skip, default)]
     pub min_chain_work: U256,
+    /// The live cell snapshot to bootstrap an empty database from
+    #[serde(default)]
+    pub cell_snapshot: Option<CellSnapshotConfig>,
+}
+
+/// Live cell snapshot config options.
+///
+/// A node with an empty database imports the snapshot and starts syncing from the snapshot tip
+/// block instead of the genesis block. The snapshot is trusted only if its hash matches.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CellSnapshotConfig {
+    /// The path of the snapshot file exported by `ckb export-cell-snapshot`, relative to the root
+    /// directory if it is not absolute
+    pub path: PathBuf,
+    /// The expected hash of the snapshot file
+    pub hash: H256,
 }
 
 /// Header map config options.
@@ -320,7 +437,12 @@ impl Config {
 
     /// Checks whether the DNS seeding service should be enabled.
     pub fn dns_seeding_service_enabled(&self) -> bool {
-        !self.dns_seeds.is_empty()
+        !self.dns_seeds.is_empty() && !self.only_proxy()
+    }
+
+    /// Checks whether all outbound peers must be dialed through the proxy.
+    pub fn only_proxy(&self) -> bool {
+        self.proxy.as_ref().map_or(false, |proxy| proxy.only_proxy)
     }
 }
 
@@ -1,5 +1,6 @@
 use ckb_jsonrpc_types::Script;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// RPC modules.
 #[derive(Clone, Debug, Copy, Eq, PartialEq, Serialize, Deserialize)]
@@ -17,6 +18,7 @@ pub enum Module {
     Debug,
     Indexer,
     RichIndexer,
+    Admin,
 }
 
 /// RPC config options.
@@ -55,6 +57,84 @@ pub struct Config {
     /// Customized extra well known type scripts.
     #[serde(default)]
     pub extra_well_known_type_scripts: Vec<Script>,
+    /// Authentication and access control options.
+    ///
+    /// All the enabled modules are accessible without credentials when it is absent.
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    /// Rate limiting options.
+    ///
+    /// The calls are not limited when it is absent.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Logs the RPC calls which take longer than this threshold, in milliseconds.
+    ///
+    /// The slow calls are not logged when it is absent.
+    #[serde(default)]
+    pub slow_call_threshold_ms: Option<u64>,
+}
+
+/// RPC authentication and access control options.
+///
+/// It applies to the HTTP, TCP and WebSocket listeners. HTTP and WebSocket clients send the
+/// credential in the HTTP header `Authorization`, either `Bearer <token>` or
+/// `Basic <base64(username:password)>`. TCP clients send the same header as the first line of the
+/// connection, e.g. `Authorization: Bearer <token>`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Modules accessible without credentials.
+    #[serde(default)]
+    pub public_modules: Vec<Module>,
+    /// Credentials and the modules they are granted to access.
+    #[serde(default)]
+    pub credentials: Vec<Credential>,
+}
+
+/// RPC credential.
+///
+/// Either `token` or both `username` and `password` must be set.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Credential {
+    /// Bearer token.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// User name of HTTP basic authentication.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Password of HTTP basic authentication.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Modules accessible with this credential.
+    ///
+    /// Modules not enabled in `rpc.modules` are still disabled.
+    pub modules: Vec<Module>,
+}
+
+/// RPC rate limiting options.
+///
+/// The limits apply to each client separately. Clients sending a credential configured in
+/// `rpc.auth` are identified by the credential, and the other clients are identified by the IP
+/// address. The calls exceeding the limits are rejected with the error `RateLimitExceeded`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Max total cost of the calls per second.
+    pub requests_per_second: u32,
+    /// Max total cost of the calls in a burst.
+    ///
+    /// Default is `requests_per_second`.
+    #[serde(default)]
+    pub burst: Option<u32>,
+    /// Max number of the calls in processing at the same time.
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,
+    /// The cost of the methods, keyed by the method name.
+    ///
+    /// The cost of the methods not listed is 1.
+    #[serde(default)]
+    pub method_costs: HashMap<String, u32>,
 }
 
 impl Config {
@@ -117,4 +197,9 @@ impl Config {
     pub fn rich_indexer_enable(&self) -> bool {
         self.modules.contains(&Module::RichIndexer)
     }
+
+    /// Checks whether the Admin module is enabled.
+    pub fn admin_enable(&self) -> bool {
+        self.modules.contains(&Module::Admin)
+    }
 }
@@ -18,4 +18,7 @@ pub struct Config {
     pub block_extensions_cache_size: usize,
     /// whether enable freezer
     pub freezer_enable: bool,
+    /// Prunes the bodies and the transaction infos of the main chain blocks which are deeper than
+    /// this number, `None` keeps all the blocks. It cannot be used together with the freezer.
+    pub prune_depth: Option<u64>,
 }
@@ -15,8 +15,10 @@ pub use app_config::{
     AppConfig, CKBAppConfig, ChainConfig, LogConfig, MetricsConfig, MinerAppConfig,
 };
 pub use args::{
-    DaemonArgs, ExportArgs, ImportArgs, InitArgs, MigrateArgs, MinerArgs, PeerIDArgs, ReplayArgs,
-    ResetDataArgs, RunArgs, StatsArgs,
+    BackupArgs, BlockNumberOrHash, CompactArgs, DBStatsArgs, DaemonArgs, ExportArgs,
+    ExportCellSnapshotArgs, ExportFormat, FreezerArgs, ImportArgs, InitArgs, MigrateArgs,
+    MinerArgs, PeerIDArgs, PeerStoreAction, PeerStoreArgs, ReplayArgs, ResetDataArgs, RunArgs,
+    StatsArgs,
 };
 pub use configs::*;
 pub use exit_code::ExitCode;
@@ -26,7 +28,7 @@ pub use url::Url;
 
 use ckb_chain_spec::{consensus::Consensus, ChainSpec};
 use ckb_jsonrpc_types::ScriptHashType;
-use ckb_types::{u256, H256, U256};
+use ckb_types::{prelude::*, u256, H256, U256};
 use clap::ArgMatches;
 use std::{path::PathBuf, str::FromStr};
 
@@ -143,20 +145,23 @@ impl Setup {
     pub fn replay(self, matches: &ArgMatches) -> Result<ReplayArgs, ExitCode> {
         let consensus = self.consensus()?;
         let config = self.config.into_ckb()?;
-        let tmp_target = matches
-            .get_one::<PathBuf>(cli::ARG_TMP_TARGET)
-            .ok_or_else(|| {
-                eprintln!("Args Error: {:?} no found", cli::ARG_TMP_TARGET);
-                ExitCode::Cli
-            })?
-            .clone();
-        let profile = if matches.get_flag(cli::ARG_PROFILE) {
+        let tmp_target = matches.get_one::<PathBuf>(cli::ARG_TMP_TARGET).cloned();
+        let range = || {
             let from = matches.get_one::<u64>(cli::ARG_FROM).cloned();
             let to = matches.get_one::<u64>(cli::ARG_TO).cloned();
             Some((from, to))
+        };
+        let profile = if matches.get_flag(cli::ARG_PROFILE) {
+            range()
         } else {
             None
         };
+        let verify = if matches.get_flag(cli::ARG_VERIFY) {
+            range()
+        } else {
+            None
+        };
+        let report = matches.get_one::<PathBuf>(cli::ARG_REPORT).cloned();
         let sanity_check = matches.get_flag(cli::ARG_SANITY_CHECK);
         let full_verification = matches.get_flag(cli::ARG_FULL_VERIFICATION);
         Ok(ReplayArgs {
@@ -164,6 +169,8 @@ impl Setup {
             consensus,
             tmp_target,
             profile,
+            verify,
+            report,
             sanity_check,
             full_verification,
         })
@@ -188,6 +195,7 @@ impl Setup {
     /// Executes `ckb import`.
     pub fn import(self, matches: &ArgMatches) -> Result<ImportArgs, ExitCode> {
         let consensus = self.consensus()?;
+        let chain_spec_hash = self.chain_spec()?.hash;
         let config = self.config.into_ckb()?;
         let source = matches
             .get_one::<PathBuf>(cli::ARG_SOURCE)
@@ -201,12 +209,14 @@ impl Setup {
             config,
             consensus,
             source,
+            chain_spec_hash,
         })
     }
 
     /// Executes `ckb export`.
     pub fn export(self, matches: &ArgMatches) -> Result<ExportArgs, ExitCode> {
         let consensus = self.consensus()?;
+        let chain_spec_hash = self.chain_spec()?.hash;
         let config = self.config.into_ckb()?;
         let target = matches
             .get_one::<PathBuf>(cli::ARG_TARGET)
@@ -216,10 +226,144 @@ impl Setup {
             })?
             .clone();
 
+        let format = match matches
+            .get_one::<String>(cli::ARG_FORMAT)
+            .map(String::as_str)
+        {
+            Some("bin") => ExportFormat::Bin,
+            _ => ExportFormat::Json,
+        };
+        let from = matches.get_one::<u64>(cli::ARG_FROM).cloned();
+        let to = matches.get_one::<u64>(cli::ARG_TO).cloned();
+
         Ok(ExportArgs {
             config,
             consensus,
             target,
+            chain_spec_hash,
+            format,
+            from,
+            to,
+        })
+    }
+
+    /// Executes `ckb freezer`.
+    pub fn freezer(self, matches: &ArgMatches) -> Result<FreezerArgs, ExitCode> {
+        let consensus = self.consensus()?;
+        let config = self.config.into_ckb()?;
+        let repair = matches!(matches.subcommand_name(), Some(cli::CMD_FREEZER_REPAIR));
+
+        Ok(FreezerArgs {
+            config,
+            consensus,
+            repair,
+        })
+    }
+
+    /// Executes `ckb peer-store`.
+    pub fn peer_store(self, matches: &ArgMatches) -> Result<PeerStoreArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let action = match matches.subcommand() {
+            Some((cli::CMD_PEER_STORE_IMPORT, matches)) => {
+                let source = matches
+                    .get_one::<PathBuf>(cli::ARG_SOURCE)
+                    .ok_or_else(|| {
+                        eprintln!("Args Error: {:?} no found", cli::ARG_SOURCE);
+                        ExitCode::Cli
+                    })?
+                    .clone();
+                PeerStoreAction::Import { source }
+            }
+            Some((cli::CMD_PEER_STORE_PRUNE, matches)) => PeerStoreAction::Prune {
+                older_than_days: matches.get_one::<u64>(cli::ARG_OLDER_THAN).cloned(),
+                without_flags: matches
+                    .get_many::<String>(cli::ARG_WITHOUT_FLAG)
+                    .unwrap_or_default()
+                    .cloned()
+                    .collect(),
+            },
+            _ => PeerStoreAction::Dump,
+        };
+
+        Ok(PeerStoreArgs {
+            peer_store_path: config.network.peer_store_path(),
+            action,
+        })
+    }
+
+    /// Executes `ckb backup`.
+    pub fn backup(self, matches: &ArgMatches) -> Result<BackupArgs, ExitCode> {
+        let consensus = self.consensus()?;
+        let config = self.config.into_ckb()?;
+        let target = matches
+            .get_one::<PathBuf>(cli::ARG_TARGET)
+            .ok_or_else(|| {
+                eprintln!("Args Error: {:?} no found", cli::ARG_TARGET);
+                ExitCode::Cli
+            })?
+            .clone();
+
+        Ok(BackupArgs {
+            config,
+            consensus,
+            target,
+        })
+    }
+
+    /// Executes `ckb db-stats`.
+    pub fn db_stats(self) -> Result<DBStatsArgs, ExitCode> {
+        let consensus = self.consensus()?;
+        let config = self.config.into_ckb()?;
+
+        Ok(DBStatsArgs { config, consensus })
+    }
+
+    /// Executes `ckb compact`.
+    pub fn compact(self, matches: &ArgMatches) -> Result<CompactArgs, ExitCode> {
+        let consensus = self.consensus()?;
+        let config = self.config.into_ckb()?;
+        let columns = matches
+            .get_many::<String>(cli::ARG_COLUMN)
+            .unwrap_or_default()
+            .cloned()
+            .collect();
+        let start_key = matches.get_one::<Vec<u8>>(cli::ARG_START_KEY).cloned();
+        let end_key = matches.get_one::<Vec<u8>>(cli::ARG_END_KEY).cloned();
+        let throttle = matches.get_one::<u64>(cli::ARG_THROTTLE).cloned();
+
+        Ok(CompactArgs {
+            config,
+            consensus,
+            columns,
+            start_key,
+            end_key,
+            throttle,
+        })
+    }
+
+    /// Executes `ckb export-cell-snapshot`.
+    pub fn export_cell_snapshot(
+        self,
+        matches: &ArgMatches,
+    ) -> Result<ExportCellSnapshotArgs, ExitCode> {
+        let consensus = self.consensus()?;
+        let config = self.config.into_ckb()?;
+        let target = matches
+            .get_one::<PathBuf>(cli::ARG_TARGET)
+            .ok_or_else(|| {
+                eprintln!("Args Error: {:?} no found", cli::ARG_TARGET);
+                ExitCode::Cli
+            })?
+            .clone();
+        let block = matches
+            .get_one::<String>(cli::ARG_BLOCK)
+            .map(|value| parse_block_number_or_hash(value));
+
+        Ok(ExportCellSnapshotArgs {
+            config,
+            consensus,
+            target,
+            block,
         })
     }
 
@@ -462,3 +606,11 @@ fn consensus_from_spec(spec: &ChainSpec) -> Result<Consensus, ExitCode> {
         ExitCode::Config
     })
 }
+
+// The value is checked by the cli, see `cli::is_block_number_or_hash`.
+fn parse_block_number_or_hash(value: &str) -> BlockNumberOrHash {
+    match value.strip_prefix("0x") {
+        Some(hash) => BlockNumberOrHash::Hash(H256::from_str(hash).expect("checked hash").pack()),
+        None => BlockNumberOrHash::Number(value.parse().expect("checked number")),
+    }
+}
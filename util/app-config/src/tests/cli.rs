@@ -139,3 +139,22 @@ fn h256_as_validator() {
     let err = err_matches.err().unwrap();
     assert_eq!(clap::error::ErrorKind::ValueValidation, err.kind());
 }
+
+#[test]
+fn export_cell_snapshot_block_arg() {
+    let matches = |block: &str| {
+        basic_app().try_get_matches_from([
+            BIN_NAME,
+            CMD_EXPORT_CELL_SNAPSHOT,
+            "-t",
+            "snapshots",
+            "--block",
+            block,
+        ])
+    };
+
+    assert!(matches("100").is_ok());
+    assert!(matches(&format!("0x{}", "ab".repeat(32))).is_ok());
+    assert!(matches("0x1234").is_err());
+    assert!(matches("tip").is_err());
+}
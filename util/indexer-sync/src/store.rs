@@ -1,7 +1,7 @@
 use crate::error::Error;
 
 use ckb_db_schema::Col;
-use ckb_store::{ChainStore, Freezer, StoreCache};
+use ckb_store::{ChainStore, DBValue, Freezer, KeyValueIter, StoreCache};
 use rocksdb::{
     ops::OpenCF, prelude::*, ColumnFamilyDescriptor, DBIterator, DBPinnableSlice, IteratorMode,
     SecondaryDB as SecondaryRocksDB, SecondaryOpenDescriptor,
@@ -112,12 +112,13 @@ impl ChainStore for SecondaryDB {
         None
     }
 
-    fn get(&self, col: Col, key: &[u8]) -> Option<DBPinnableSlice> {
+    fn get(&self, col: Col, key: &[u8]) -> Option<DBValue> {
         self.get_pinned(col, key)
             .expect("db operation should be ok")
+            .map(DBValue::Pinned)
     }
 
-    fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIterator {
-        self.iter(col, mode).expect("db operation should be ok")
+    fn get_iter(&self, col: Col, mode: IteratorMode) -> KeyValueIter {
+        Box::new(self.iter(col, mode).expect("db operation should be ok"))
     }
 }
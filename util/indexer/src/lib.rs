@@ -6,4 +6,4 @@ pub(crate) mod store;
 /// The indexer service.
 pub mod service;
 
-pub use service::{IndexerHandle, IndexerService};
+pub use service::{IndexerHandle, IndexerService, SearchKeyMatcher};
@@ -152,18 +152,6 @@ impl IndexerHandle {
         limit: Uint32,
         after_cursor: Option<JsonBytes>,
     ) -> Result<IndexerPagination<IndexerCell>, Error> {
-        if search_key
-            .script_search_mode
-            .as_ref()
-            .map(|mode| *mode == IndexerSearchMode::Partial)
-            .unwrap_or(false)
-        {
-            return Err(Error::invalid_params(
-                "the CKB indexer doesn't support search_key.script_search_mode partial search mode, \
-                please use the CKB rich-indexer for such search",
-            ));
-        }
-
         let limit = limit.value() as usize;
         if limit == 0 {
             return Err(Error::invalid_params("limit should be greater than 0"));
@@ -176,16 +164,9 @@ impl IndexerHandle {
             order,
             after_cursor,
         )?;
+        // the script is matched by the key prefix, the matcher checks the filters of the cells
+        let matcher = SearchKeyMatcher::new(search_key)?;
 
-        let filter_script_type = match search_key.script_type {
-            IndexerScriptType::Lock => IndexerScriptType::Type,
-            IndexerScriptType::Type => IndexerScriptType::Lock,
-        };
-        let script_search_exact = matches!(
-            search_key.script_search_mode,
-            Some(IndexerSearchMode::Exact)
-        );
-        let filter_options: FilterOptions = search_key.try_into()?;
         let mode = IteratorMode::From(from_key.as_ref(), direction);
         let snapshot = self.store.inner().snapshot();
         let iter = snapshot.iterator(mode).skip(skip);
@@ -198,7 +179,7 @@ impl IndexerHandle {
         let cells = iter
             .take_while(|(key, _value)| key.starts_with(&prefix))
             .filter_map(|(key, value)| {
-                if script_search_exact {
+                if matcher.script_search_exact {
                     // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + OutputIndex (4)
                     if key.len() != prefix.len() + 16 {
                         return None;
@@ -222,91 +203,17 @@ impl IndexerHandle {
                         .expect("stored OutPoint"),
                 );
 
-                if let Some(prefix) = filter_options.script_prefix.as_ref() {
-                    match filter_script_type {
-                        IndexerScriptType::Lock => {
-                            if !extract_raw_data(&output.lock())
-                                .as_slice()
-                                .starts_with(prefix)
-                            {
-                                return None;
-                            }
-                        }
-                        IndexerScriptType::Type => {
-                            if output.type_().is_none()
-                                || !extract_raw_data(&output.type_().to_opt().unwrap())
-                                    .as_slice()
-                                    .starts_with(prefix)
-                            {
-                                return None;
-                            }
-                        }
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.script_len_range {
-                    match filter_script_type {
-                        IndexerScriptType::Lock => {
-                            let script_len = extract_raw_data(&output.lock()).len();
-                            if script_len < r0 || script_len >= r1 {
-                                return None;
-                            }
-                        }
-                        IndexerScriptType::Type => {
-                            let script_len = output
-                                .type_()
-                                .to_opt()
-                                .map(|script| extract_raw_data(&script).len())
-                                .unwrap_or_default();
-                            if script_len < r0 || script_len >= r1 {
-                                return None;
-                            }
-                        }
-                    }
-                }
-
-                if let Some((data, mode)) = &filter_options.output_data {
-                    match mode {
-                        IndexerSearchMode::Prefix => {
-                            if !output_data.raw_data().starts_with(data) {
-                                return None;
-                            }
-                        }
-                        IndexerSearchMode::Exact => {
-                            if output_data.raw_data() != data {
-                                return None;
-                            }
-                        }
-                        IndexerSearchMode::Partial => {
-                            memmem::find(&output_data.raw_data(), data)?;
-                        }
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.output_data_len_range {
-                    if output_data.len() < r0 || output_data.len() >= r1 {
-                        return None;
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.output_capacity_range {
-                    let capacity: core::Capacity = output.capacity().unpack();
-                    if capacity < r0 || capacity >= r1 {
-                        return None;
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.block_range {
-                    if block_number < r0 || block_number >= r1 {
-                        return None;
-                    }
+                if !matcher.match_filters(&output, &output_data.raw_data())
+                    || !matcher.match_block_number(block_number)
+                {
+                    return None;
                 }
 
                 last_key = key.to_vec();
 
                 Some(IndexerCell {
                     output: output.into(),
-                    output_data: if filter_options.with_data {
+                    output_data: if matcher.filter_options.with_data {
                         Some(output_data.into())
                     } else {
                         None
@@ -615,18 +522,6 @@ impl IndexerHandle {
         &self,
         search_key: IndexerSearchKey,
     ) -> Result<Option<IndexerCellsCapacity>, Error> {
-        if search_key
-            .script_search_mode
-            .as_ref()
-            .map(|mode| *mode == IndexerSearchMode::Partial)
-            .unwrap_or(false)
-        {
-            return Err(Error::invalid_params(
-                "the CKB indexer doesn't support search_key.script_search_mode partial search mode, \
-                please use the CKB rich-indexer for such search",
-            ));
-        }
-
         let (prefix, from_key, direction, skip) = build_query_options(
             &search_key,
             KeyPrefix::CellLockScript,
@@ -634,15 +529,8 @@ impl IndexerHandle {
             IndexerOrder::Asc,
             None,
         )?;
-        let filter_script_type = match search_key.script_type {
-            IndexerScriptType::Lock => IndexerScriptType::Type,
-            IndexerScriptType::Type => IndexerScriptType::Lock,
-        };
-        let script_search_exact = matches!(
-            search_key.script_search_mode,
-            Some(IndexerSearchMode::Exact)
-        );
-        let filter_options: FilterOptions = search_key.try_into()?;
+        // the script is matched by the key prefix, the matcher checks the filters of the cells
+        let matcher = SearchKeyMatcher::new(search_key)?;
         let mode = IteratorMode::From(from_key.as_ref(), direction);
         let snapshot = self.store.inner().snapshot();
         let iter = snapshot.iterator(mode).skip(skip);
@@ -654,7 +542,7 @@ impl IndexerHandle {
         let capacity: u64 = iter
             .take_while(|(key, _value)| key.starts_with(&prefix))
             .filter_map(|(key, value)| {
-                if script_search_exact {
+                if matcher.script_search_exact {
                     // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + OutputIndex (4)
                     if key.len() != prefix.len() + 16 {
                         return None;
@@ -678,84 +566,10 @@ impl IndexerHandle {
                         .expect("stored OutPoint"),
                 );
 
-                if let Some(prefix) = filter_options.script_prefix.as_ref() {
-                    match filter_script_type {
-                        IndexerScriptType::Lock => {
-                            if !extract_raw_data(&output.lock())
-                                .as_slice()
-                                .starts_with(prefix)
-                            {
-                                return None;
-                            }
-                        }
-                        IndexerScriptType::Type => {
-                            if output.type_().is_none()
-                                || !extract_raw_data(&output.type_().to_opt().unwrap())
-                                    .as_slice()
-                                    .starts_with(prefix)
-                            {
-                                return None;
-                            }
-                        }
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.script_len_range {
-                    match filter_script_type {
-                        IndexerScriptType::Lock => {
-                            let script_len = extract_raw_data(&output.lock()).len();
-                            if script_len < r0 || script_len > r1 {
-                                return None;
-                            }
-                        }
-                        IndexerScriptType::Type => {
-                            let script_len = output
-                                .type_()
-                                .to_opt()
-                                .map(|script| extract_raw_data(&script).len())
-                                .unwrap_or_default();
-                            if script_len < r0 || script_len > r1 {
-                                return None;
-                            }
-                        }
-                    }
-                }
-
-                if let Some((data, mode)) = &filter_options.output_data {
-                    match mode {
-                        IndexerSearchMode::Prefix => {
-                            if !output_data.raw_data().starts_with(data) {
-                                return None;
-                            }
-                        }
-                        IndexerSearchMode::Exact => {
-                            if output_data.raw_data() != data {
-                                return None;
-                            }
-                        }
-                        IndexerSearchMode::Partial => {
-                            memmem::find(&output_data.raw_data(), data)?;
-                        }
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.output_data_len_range {
-                    if output_data.len() < r0 || output_data.len() >= r1 {
-                        return None;
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.output_capacity_range {
-                    let capacity: core::Capacity = output.capacity().unpack();
-                    if capacity < r0 || capacity >= r1 {
-                        return None;
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.block_range {
-                    if block_number < r0 || block_number >= r1 {
-                        return None;
-                    }
+                if !matcher.match_filters(&output, &output_data.raw_data())
+                    || !matcher.match_block_number(block_number)
+                {
+                    return None;
                 }
 
                 Some(Unpack::<core::Capacity>::unpack(&output.capacity()).as_u64())
@@ -898,6 +712,144 @@ impl TryInto<FilterOptions> for IndexerSearchKey {
     }
 }
 
+/// Matches cells against an `IndexerSearchKey` in memory, following the same rules as
+/// `get_cells`, so that a search key can be applied to cells which are not indexed yet,
+/// e.g. the cells of a newly committed block.
+pub struct SearchKeyMatcher {
+    script_type: IndexerScriptType,
+    script: Vec<u8>,
+    script_search_exact: bool,
+    filter_options: FilterOptions,
+}
+
+impl SearchKeyMatcher {
+    /// Construct new matcher from the search key
+    pub fn new(search_key: IndexerSearchKey) -> Result<Self, Error> {
+        if search_key
+            .script_search_mode
+            .as_ref()
+            .map(|mode| *mode == IndexerSearchMode::Partial)
+            .unwrap_or(false)
+        {
+            return Err(Error::invalid_params(
+                "the CKB indexer doesn't support search_key.script_search_mode partial search mode, \
+                please use the CKB rich-indexer for such search",
+            ));
+        }
+        let script: packed::Script = search_key.script.clone().into();
+        if script.args().len() > MAX_PREFIX_SEARCH_SIZE {
+            return Err(Error::invalid_params(format!(
+                "search_key.script.args len should be less than {MAX_PREFIX_SEARCH_SIZE}"
+            )));
+        }
+        let script_type = match search_key.script_type {
+            IndexerScriptType::Lock => IndexerScriptType::Lock,
+            IndexerScriptType::Type => IndexerScriptType::Type,
+        };
+        let script_search_exact = matches!(
+            search_key.script_search_mode,
+            Some(IndexerSearchMode::Exact)
+        );
+        let filter_options: FilterOptions = search_key.try_into()?;
+        Ok(Self {
+            script_type,
+            script: extract_raw_data(&script),
+            script_search_exact,
+            filter_options,
+        })
+    }
+
+    /// Returns whether the block number is in the `filter.block_range` of the search key
+    pub fn match_block_number(&self, block_number: core::BlockNumber) -> bool {
+        match self.filter_options.block_range {
+            Some([r0, r1]) => block_number >= r0 && block_number < r1,
+            None => true,
+        }
+    }
+
+    /// Returns whether the cell matches the script and the cell filters of the search key
+    pub fn match_cell(&self, output: &packed::CellOutput, output_data: &[u8]) -> bool {
+        let script = match self.script_type {
+            IndexerScriptType::Lock => Some(output.lock()),
+            IndexerScriptType::Type => output.type_().to_opt(),
+        };
+        let script = match script {
+            Some(script) => extract_raw_data(&script),
+            None => return false,
+        };
+        if self.script_search_exact {
+            if script != self.script {
+                return false;
+            }
+        } else if !script.starts_with(&self.script) {
+            return false;
+        }
+        self.match_filters(output, output_data)
+    }
+
+    /// Returns whether the cell matches the cell filters of the search key, the script of the
+    /// search key is not checked, e.g., the cells found by the script in `get_cells`
+    pub fn match_filters(&self, output: &packed::CellOutput, output_data: &[u8]) -> bool {
+        let filter_options = &self.filter_options;
+        let filter_script = match self.script_type {
+            IndexerScriptType::Lock => output.type_().to_opt(),
+            IndexerScriptType::Type => Some(output.lock()),
+        }
+        .map(|script| extract_raw_data(&script));
+        if let Some(prefix) = filter_options.script_prefix.as_ref() {
+            match filter_script.as_ref() {
+                Some(script) if script.starts_with(prefix) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some([r0, r1]) = filter_options.script_len_range {
+            let script_len = filter_script
+                .as_ref()
+                .map(|script| script.len())
+                .unwrap_or_default();
+            if script_len < r0 || script_len >= r1 {
+                return false;
+            }
+        }
+
+        if let Some((data, mode)) = &filter_options.output_data {
+            match mode {
+                IndexerSearchMode::Prefix => {
+                    if !output_data.starts_with(data) {
+                        return false;
+                    }
+                }
+                IndexerSearchMode::Exact => {
+                    if output_data != data.as_slice() {
+                        return false;
+                    }
+                }
+                IndexerSearchMode::Partial => {
+                    if memmem::find(output_data, data).is_none() {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        if let Some([r0, r1]) = filter_options.output_data_len_range {
+            if output_data.len() < r0 || output_data.len() >= r1 {
+                return false;
+            }
+        }
+
+        if let Some([r0, r1]) = filter_options.output_capacity_range {
+            let capacity: core::Capacity = output.capacity().unpack();
+            if capacity < r0 || capacity >= r1 {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2013,4 +1965,87 @@ mod tests {
         let capacity: u64 = cells_capacity.unwrap().capacity.into();
         assert_eq!(200000000000, capacity);
     }
+
+    #[test]
+    fn search_key_matcher() {
+        let lock_script = ScriptBuilder::default()
+            .code_hash(H256(rand::random()).pack())
+            .hash_type(ScriptHashType::Data.into())
+            .args(Bytes::from(b"lock_script1".to_vec()).pack())
+            .build();
+        let type_script = ScriptBuilder::default()
+            .code_hash(H256(rand::random()).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(b"type_script1".to_vec()).pack())
+            .build();
+        let output = CellOutputBuilder::default()
+            .capacity(capacity_bytes!(1000).pack())
+            .lock(lock_script.clone())
+            .type_(Some(type_script.clone()).pack())
+            .build();
+        let data = b"cell data";
+
+        let prefix_script = lock_script
+            .clone()
+            .as_builder()
+            .args(Bytes::from(b"lock_script".to_vec()).pack())
+            .build();
+
+        // prefix search mode
+        let matcher = SearchKeyMatcher::new(IndexerSearchKey {
+            script: prefix_script.clone().into(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(matcher.match_cell(&output, data));
+
+        // exact search mode
+        let matcher = SearchKeyMatcher::new(IndexerSearchKey {
+            script: prefix_script.into(),
+            script_search_mode: Some(IndexerSearchMode::Exact),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!matcher.match_cell(&output, data));
+        let matcher = SearchKeyMatcher::new(IndexerSearchKey {
+            script: lock_script.clone().into(),
+            script_search_mode: Some(IndexerSearchMode::Exact),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(matcher.match_cell(&output, data));
+
+        // type script with filters
+        let matcher = SearchKeyMatcher::new(IndexerSearchKey {
+            script: type_script.into(),
+            script_type: IndexerScriptType::Type,
+            filter: Some(IndexerSearchKeyFilter {
+                script: Some(lock_script.into()),
+                output_data: Some(JsonBytes::from_vec(b"data".to_vec())),
+                output_data_filter_mode: Some(IndexerSearchMode::Partial),
+                output_capacity_range: Some(IndexerRange::new(0u64, 100_000_000_000u64)),
+                block_range: Some(IndexerRange::new(10u64, 20u64)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!matcher.match_cell(&output, data));
+        let output = output
+            .as_builder()
+            .capacity(capacity_bytes!(100).pack())
+            .build();
+        assert!(matcher.match_cell(&output, data));
+        assert!(!matcher.match_cell(&output, b"cell"));
+        assert!(!matcher.match_block_number(9));
+        assert!(matcher.match_block_number(10));
+        assert!(!matcher.match_block_number(20));
+
+        // partial script search mode is not supported
+        assert!(SearchKeyMatcher::new(IndexerSearchKey {
+            script_search_mode: Some(IndexerSearchMode::Partial),
+            ..Default::default()
+        })
+        .is_err());
+    }
 }
@@ -0,0 +1,173 @@
+//! The binary format of the exported blocks.
+//!
+//! The file starts with the magic bytes, the version and the hash of the chain spec, followed by
+//! the records. A record is a tag byte followed by its payload:
+//!
+//! - `TAG_BLOCK`: the length (u32 LE) and the molecule serialized `packed::Block`.
+//! - `TAG_CHECKSUM`: the blake2b hash of all the bytes since the previous checksum, the first
+//!   checksum also covers the file header.
+//!
+//! A checksum is written after every `CHECKSUM_INTERVAL` blocks and at the end of the file, so a
+//! truncated or corrupted file is detected before the blocks in the broken chunk are imported.
+use ckb_hash::{new_blake2b, Blake2b};
+use ckb_types::{packed, prelude::*};
+use std::error::Error;
+use std::io::{self, Read, Write};
+
+pub(crate) const MAGIC: &[u8] = b"CKBBLOCK";
+const VERSION: u8 = 1;
+const CHECKSUM_INTERVAL: usize = 100;
+const TAG_BLOCK: u8 = 0;
+const TAG_CHECKSUM: u8 = 1;
+
+pub(crate) struct BinWriter<W: Write> {
+    inner: W,
+    hasher: Blake2b,
+    // the number of the blocks since the previous checksum
+    pending: usize,
+    // whether there are bytes not covered by a checksum
+    dirty: bool,
+}
+
+impl<W: Write> BinWriter<W> {
+    pub(crate) fn new(inner: W, chain_spec_hash: &packed::Byte32) -> io::Result<Self> {
+        let mut writer = BinWriter {
+            inner,
+            hasher: new_blake2b(),
+            pending: 0,
+            dirty: false,
+        };
+        writer.write(MAGIC)?;
+        writer.write(&[VERSION])?;
+        writer.write(chain_spec_hash.as_slice())?;
+        Ok(writer)
+    }
+
+    pub(crate) fn write_block(&mut self, block: &packed::Block) -> io::Result<()> {
+        let data = block.as_slice();
+        self.write(&[TAG_BLOCK])?;
+        self.write(&(data.len() as u32).to_le_bytes())?;
+        self.write(data)?;
+        self.pending += 1;
+        if self.pending >= CHECKSUM_INTERVAL {
+            self.write_checksum()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        if self.dirty {
+            self.write_checksum()?;
+        }
+        self.inner.flush()
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.hasher.update(data);
+        self.dirty = true;
+        self.inner.write_all(data)
+    }
+
+    fn write_checksum(&mut self) -> io::Result<()> {
+        let checksum = finalize(&mut self.hasher);
+        self.inner.write_all(&[TAG_CHECKSUM])?;
+        self.inner.write_all(&checksum)?;
+        self.pending = 0;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+pub(crate) struct BinReader<R: Read> {
+    inner: R,
+    hasher: Blake2b,
+    chain_spec_hash: packed::Byte32,
+    // the max length of a serialized block, checked before the block is read
+    max_block_len: u64,
+}
+
+impl<R: Read> BinReader<R> {
+    pub(crate) fn new(mut inner: R, max_block_len: u64) -> Result<Self, Box<dyn Error>> {
+        let mut hasher = new_blake2b();
+        let mut header = [0u8; 8 + 1 + 32];
+        inner.read_exact(&mut header)?;
+        if &header[..MAGIC.len()] != MAGIC {
+            return Err("not a binary block export file".into());
+        }
+        if header[MAGIC.len()] != VERSION {
+            return Err(
+                format!("unsupported binary format version {}", header[MAGIC.len()]).into(),
+            );
+        }
+        hasher.update(&header);
+        let chain_spec_hash = packed::Byte32::from_slice(&header[MAGIC.len() + 1..])?;
+        Ok(BinReader {
+            inner,
+            hasher,
+            chain_spec_hash,
+            max_block_len,
+        })
+    }
+
+    /// The hash of the chain spec which the blocks belong to.
+    pub(crate) fn chain_spec_hash(&self) -> &packed::Byte32 {
+        &self.chain_spec_hash
+    }
+
+    /// Reads the serialized blocks till the next checksum, returns an empty vector at the end of
+    /// the file.
+    pub(crate) fn next_chunk(&mut self) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        let mut blocks = Vec::new();
+        loop {
+            let mut tag = [0u8; 1];
+            match self.inner.read_exact(&mut tag) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    if blocks.is_empty() {
+                        return Ok(blocks);
+                    }
+                    return Err("the file is truncated, the last checksum is missing".into());
+                }
+                Err(err) => return Err(err.into()),
+            }
+            match tag[0] {
+                TAG_BLOCK => {
+                    let mut len = [0u8; 4];
+                    self.inner.read_exact(&mut len)?;
+                    let block_len = u32::from_le_bytes(len);
+                    if u64::from(block_len) > self.max_block_len {
+                        return Err(format!(
+                            "the block length {block_len} exceeds the max block length {}, the file is corrupted",
+                            self.max_block_len
+                        )
+                        .into());
+                    }
+                    let mut data = vec![0u8; block_len as usize];
+                    self.inner.read_exact(&mut data)?;
+                    self.hasher.update(&tag);
+                    self.hasher.update(&len);
+                    self.hasher.update(&data);
+                    blocks.push(data);
+                }
+                TAG_CHECKSUM => {
+                    let mut checksum = [0u8; 32];
+                    self.inner.read_exact(&mut checksum)?;
+                    if checksum != finalize(&mut self.hasher) {
+                        return Err("checksum mismatch, the file is corrupted".into());
+                    }
+                    if !blocks.is_empty() {
+                        return Ok(blocks);
+                    }
+                }
+                tag => return Err(format!("unknown record tag {tag}").into()),
+            }
+        }
+    }
+}
+
+// Returns the hash of the bytes so far and resets the hasher.
+fn finalize(hasher: &mut Blake2b) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    std::mem::replace(hasher, new_blake2b()).finalize(&mut hash);
+    hash
+}
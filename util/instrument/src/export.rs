@@ -1,6 +1,12 @@
-use ckb_chain_iter::ChainIterator;
+use crate::bin_format::BinWriter;
+use ckb_app_config::ExportFormat;
 use ckb_jsonrpc_types::BlockView as JsonBlock;
 use ckb_shared::shared::Shared;
+use ckb_store::ChainStore;
+use ckb_types::{
+    core::{BlockNumber, BlockView},
+    packed,
+};
 #[cfg(feature = "progress_bar")]
 use indicatif::{ProgressBar, ProgressStyle};
 use std::error::Error;
@@ -15,69 +21,149 @@ pub struct Export {
     pub target: PathBuf,
     /// CKB shared data.
     pub shared: Shared,
+    /// The hash of the chain spec, recorded in the binary format.
+    pub chain_spec_hash: packed::Byte32,
+    /// export format
+    pub format: ExportFormat,
+    /// The first block to export, the genesis block by default.
+    pub from: Option<BlockNumber>,
+    /// The last block to export, the tip block by default.
+    pub to: Option<BlockNumber>,
 }
 
 impl Export {
     /// Creates the export job.
-    pub fn new(shared: Shared, target: PathBuf) -> Self {
-        Export { shared, target }
+    pub fn new(shared: Shared, target: PathBuf, chain_spec_hash: packed::Byte32) -> Self {
+        Export {
+            shared,
+            target,
+            chain_spec_hash,
+            format: ExportFormat::Json,
+            from: None,
+            to: None,
+        }
+    }
+
+    /// Sets the export format.
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Exports the blocks in the range `[from, to]` only.
+    pub fn range(mut self, from: Option<BlockNumber>, to: Option<BlockNumber>) -> Self {
+        self.from = from;
+        self.to = to;
+        self
     }
 
     /// export file name
     fn file_name(&self) -> String {
-        format!("{}.{}", self.shared.consensus().id, "json")
+        let extension = match self.format {
+            ExportFormat::Json => "json",
+            ExportFormat::Bin => "bin",
+        };
+        if self.from.is_none() && self.to.is_none() {
+            format!("{}.{}", self.shared.consensus().id, extension)
+        } else {
+            format!(
+                "{}-{}-{}.{}",
+                self.shared.consensus().id,
+                self.from.unwrap_or(0),
+                self.to
+                    .map(|to| to.to_string())
+                    .unwrap_or_else(|| "tip".to_owned()),
+                extension
+            )
+        }
     }
 
     /// Executes the export job.
     pub fn execute(self) -> Result<(), Box<dyn Error>> {
-        fs::create_dir_all(&self.target)?;
-        self.write_to_json()
-    }
+        let tip = self.shared.snapshot().tip_number();
+        let from = self.from.unwrap_or(0);
+        let to = self.to.unwrap_or(tip);
+        if from > to || to > tip {
+            return Err(format!("invalid range [{from}, {to}], the tip is {tip}").into());
+        }
 
-    #[cfg(not(feature = "progress_bar"))]
-    pub fn write_to_json(self) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.target)?;
         let f = fs::OpenOptions::new()
             .create_new(true)
             .read(true)
             .write(true)
             .open(self.target.join(self.file_name()))?;
-        let mut writer = io::BufWriter::new(f);
-        let snapshot = self.shared.snapshot();
+        let writer = io::BufWriter::new(f);
+        match self.format {
+            ExportFormat::Json => self.write_to_json(writer, from, to),
+            ExportFormat::Bin => self.write_to_bin(writer, from, to),
+        }
+    }
 
-        for block in ChainIterator::new(snapshot.as_ref()) {
+    /// Export the chain into JSON.
+    fn write_to_json<W: Write>(
+        &self,
+        mut writer: W,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<(), Box<dyn Error>> {
+        self.for_each_block(from, to, |block| {
             let block: JsonBlock = block.into();
             let encoded = serde_json::to_vec(&block)?;
             writer.write_all(&encoded)?;
             writer.write_all(b"\n")?;
-        }
+            Ok(())
+        })?;
+        writer.flush()?;
         Ok(())
     }
 
-    /// Export the chain into JSON.
-    #[cfg(feature = "progress_bar")]
-    pub fn write_to_json(self) -> Result<(), Box<dyn Error>> {
-        let f = fs::OpenOptions::new()
-            .create_new(true)
-            .read(true)
-            .write(true)
-            .open(self.target.join(self.file_name()))?;
+    /// Export the chain into the binary format.
+    fn write_to_bin<W: Write>(
+        &self,
+        writer: W,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut writer = BinWriter::new(writer, &self.chain_spec_hash)?;
+        self.for_each_block(from, to, |block| {
+            writer.write_block(&block.data())?;
+            Ok(())
+        })?;
+        writer.finish()?;
+        Ok(())
+    }
 
-        let mut writer = io::BufWriter::new(f);
+    fn for_each_block<F>(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+        mut f: F,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: FnMut(BlockView) -> Result<(), Box<dyn Error>>,
+    {
         let snapshot = self.shared.snapshot();
-        let blocks_iter = ChainIterator::new(snapshot.as_ref());
-        let progress_bar = ProgressBar::new(blocks_iter.len());
-        progress_bar.set_style(
-            ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] {bar:50.cyan/blue} {pos:>6}/{len:6} {msg}")
-                .progress_chars("##-"),
-        );
-        for block in blocks_iter {
-            let block: JsonBlock = block.into();
-            let encoded = serde_json::to_vec(&block)?;
-            writer.write_all(&encoded)?;
-            writer.write_all(b"\n")?;
+        #[cfg(feature = "progress_bar")]
+        let progress_bar = {
+            let progress_bar = ProgressBar::new(to - from + 1);
+            progress_bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] {bar:50.cyan/blue} {pos:>6}/{len:6} {msg}")
+                    .progress_chars("##-"),
+            );
+            progress_bar
+        };
+        for number in from..=to {
+            let block = snapshot
+                .get_block_hash(number)
+                .and_then(|hash| snapshot.get_block(&hash))
+                .ok_or_else(|| format!("block {number} is not found"))?;
+            f(block)?;
+            #[cfg(feature = "progress_bar")]
             progress_bar.inc(1);
         }
+        #[cfg(feature = "progress_bar")]
         progress_bar.finish_with_message("done!");
         Ok(())
     }
@@ -1,75 +1,147 @@
+use crate::bin_format::{BinReader, MAGIC};
 use ckb_chain::chain::ChainController;
 use ckb_jsonrpc_types::BlockView as JsonBlock;
-use ckb_types::core;
+use ckb_shared::shared::Shared;
+use ckb_store::ChainStore;
+use ckb_types::{core, packed, prelude::*};
 #[cfg(feature = "progress_bar")]
 use indicatif::{ProgressBar, ProgressStyle};
 use std::error::Error;
 use std::fs;
 use std::io;
-use std::io::BufRead;
+use std::io::{BufRead, Read};
 use std::path::PathBuf;
 use std::sync::Arc;
 
 /// Export block date from file to database.
+///
+/// The blocks which are already in the main chain are skipped, so an interrupted import can be
+/// resumed by importing the same file again.
 pub struct Import {
     /// source file contains block data
     source: PathBuf,
     chain: ChainController,
+    shared: Shared,
+    chain_spec_hash: packed::Byte32,
 }
 
 impl Import {
     /// Creates a new import job.
-    pub fn new(chain: ChainController, source: PathBuf) -> Self {
-        Import { chain, source }
+    pub fn new(
+        chain: ChainController,
+        shared: Shared,
+        source: PathBuf,
+        chain_spec_hash: packed::Byte32,
+    ) -> Self {
+        Import {
+            chain,
+            shared,
+            source,
+            chain_spec_hash,
+        }
     }
 
-    /// Executes the import job.
+    /// Executes the import job, the format of the file is detected automatically.
     pub fn execute(self) -> Result<(), Box<dyn Error>> {
-        self.read_from_json()
+        let mut magic = Vec::with_capacity(MAGIC.len());
+        fs::File::open(&self.source)?
+            .take(MAGIC.len() as u64)
+            .read_to_end(&mut magic)?;
+        if magic == MAGIC {
+            self.read_from_bin()?;
+        } else {
+            self.read_from_json()?;
+        }
+        Ok(())
     }
 
-    #[cfg(not(feature = "progress_bar"))]
-    pub fn read_from_json(&self) -> Result<(), Box<dyn Error>> {
+    /// Imports the chain from the JSON file, returns the number of the processed blocks.
+    pub(crate) fn read_from_json(&self) -> Result<u64, Box<dyn Error>> {
         let f = fs::File::open(&self.source)?;
         let reader = io::BufReader::new(f);
+        #[cfg(feature = "progress_bar")]
+        let progress_bar = self.progress_bar()?;
 
+        let mut processed = 0;
         for line in reader.lines() {
             let s = line?;
             let block: JsonBlock = serde_json::from_str(&s)?;
-            let block: Arc<core::BlockView> = Arc::new(block.into());
-            if !block.is_genesis() {
-                self.chain
-                    .process_block(block)
-                    .expect("import occur malformation data");
+            let block: core::BlockView = block.into();
+            if !self.is_imported(block.number(), &block.hash()) {
+                self.chain.process_block(Arc::new(block))?;
+                processed += 1;
             }
+            #[cfg(feature = "progress_bar")]
+            progress_bar.inc(s.as_bytes().len() as u64);
         }
-        Ok(())
+        #[cfg(feature = "progress_bar")]
+        progress_bar.finish_with_message("done!");
+        Ok(processed)
+    }
+
+    /// Imports the chain from the binary file, returns the number of the processed blocks.
+    pub(crate) fn read_from_bin(&self) -> Result<u64, Box<dyn Error>> {
+        let f = fs::File::open(&self.source)?;
+        let mut reader = BinReader::new(io::BufReader::new(f), self.max_block_len())?;
+        if reader.chain_spec_hash() != &self.chain_spec_hash {
+            return Err(format!(
+                "the blocks are exported from chain spec {}, but the configured chain spec is {}",
+                reader.chain_spec_hash(),
+                self.chain_spec_hash
+            )
+            .into());
+        }
+        #[cfg(feature = "progress_bar")]
+        let progress_bar = self.progress_bar()?;
+
+        let mut processed = 0;
+        loop {
+            let chunk = reader.next_chunk()?;
+            if chunk.is_empty() {
+                break;
+            }
+            for data in chunk {
+                let block = packed::BlockReader::from_compatible_slice(&data)?;
+                let header = block.header();
+                if !self.is_imported(header.raw().number().unpack(), &header.calc_header_hash()) {
+                    let block = block.to_entity().into_view();
+                    self.chain.process_block(Arc::new(block))?;
+                    processed += 1;
+                }
+                #[cfg(feature = "progress_bar")]
+                progress_bar.inc(data.len() as u64 + 5);
+            }
+        }
+        #[cfg(feature = "progress_bar")]
+        progress_bar.finish_with_message("done!");
+        Ok(processed)
+    }
+
+    // Returns the max length of a serialized block, the uncle proposals are not counted in the
+    // block bytes limit, and the genesis block is not limited.
+    fn max_block_len(&self) -> u64 {
+        let consensus = self.shared.consensus();
+        let uncle_proposals_len = consensus.max_uncles_num() as u64
+            * consensus.max_block_proposals_limit()
+            * packed::ProposalShortId::TOTAL_SIZE as u64;
+        (consensus.max_block_bytes() + uncle_proposals_len)
+            .max(consensus.genesis_block().data().as_slice().len() as u64)
+    }
+
+    // Returns true if the block is already in the main chain.
+    pub(crate) fn is_imported(&self, number: core::BlockNumber, hash: &packed::Byte32) -> bool {
+        number == 0 || self.shared.snapshot().get_block_hash(number).as_ref() == Some(hash)
     }
 
-    /// Imports the chain from the JSON file.
     #[cfg(feature = "progress_bar")]
-    pub fn read_from_json(&self) -> Result<(), Box<dyn Error>> {
+    fn progress_bar(&self) -> Result<ProgressBar, Box<dyn Error>> {
         let metadata = fs::metadata(&self.source)?;
-        let f = fs::File::open(&self.source)?;
-        let reader = io::BufReader::new(f);
         let progress_bar = ProgressBar::new(metadata.len());
         progress_bar.set_style(
             ProgressStyle::default_bar()
                 .template("[{elapsed_precise}] {bar:50.cyan/blue} {bytes:>6}/{total_bytes:6} {msg}")
                 .progress_chars("##-"),
         );
-        for line in reader.lines() {
-            let s = line?;
-            let block: JsonBlock = serde_json::from_str(&s)?;
-            let block: Arc<core::BlockView> = Arc::new(block.into());
-            if !block.is_genesis() {
-                self.chain
-                    .process_block(block)
-                    .expect("import occur malformation data");
-            }
-            progress_bar.inc(s.as_bytes().len() as u64);
-        }
-        progress_bar.finish_with_message("done!");
-        Ok(())
+        Ok(progress_bar)
     }
 }
@@ -4,9 +4,15 @@
 //!
 //! - [`Export`] provides block data export function.
 //! - [`Import`] imports block data which export from `Export`.
+//!
+//! Blocks can be exported as JSON lines or in a checksummed binary format, the format of an
+//! imported file is detected automatically.
 
+mod bin_format;
 mod export;
 mod import;
+#[cfg(test)]
+mod tests;
 
 pub use crate::export::Export;
 pub use crate::import::Import;
@@ -0,0 +1,178 @@
+use crate::bin_format::{BinReader, BinWriter};
+use crate::Import;
+use ckb_chain::chain::ChainService;
+use ckb_shared::SharedBuilder;
+use ckb_store::ChainStore;
+use ckb_test_chain_utils::{MockChain, MockStore};
+use ckb_types::{core, packed, prelude::*};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const MAX_BLOCK_LEN: u64 = 1_000;
+
+fn block(number: u64) -> packed::Block {
+    let raw = packed::RawHeader::new_builder()
+        .number(number.pack())
+        .build();
+    packed::Block::new_builder()
+        .header(packed::Header::new_builder().raw(raw).build())
+        .build()
+}
+
+fn write_bin(chain_spec_hash: &packed::Byte32, count: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut writer = BinWriter::new(&mut bytes, chain_spec_hash).unwrap();
+    for number in 1..=count {
+        writer.write_block(&block(number)).unwrap();
+    }
+    writer.finish().unwrap();
+    bytes
+}
+
+fn read_bin(bytes: Vec<u8>) -> Result<Vec<Vec<u8>>, String> {
+    let mut reader =
+        BinReader::new(Cursor::new(bytes), MAX_BLOCK_LEN).map_err(|err| err.to_string())?;
+    let mut blocks = Vec::new();
+    loop {
+        let chunk = reader.next_chunk().map_err(|err| err.to_string())?;
+        if chunk.is_empty() {
+            return Ok(blocks);
+        }
+        blocks.extend(chunk);
+    }
+}
+
+#[test]
+fn bin_format_round_trip() {
+    let chain_spec_hash = packed::Byte32::new([1u8; 32]);
+    let bytes = write_bin(&chain_spec_hash, 150);
+
+    let reader = BinReader::new(Cursor::new(bytes.clone()), MAX_BLOCK_LEN).unwrap();
+    assert_eq!(reader.chain_spec_hash(), &chain_spec_hash);
+    let blocks = read_bin(bytes).unwrap();
+    assert_eq!(blocks.len(), 150);
+    for (i, data) in blocks.iter().enumerate() {
+        assert_eq!(data.as_slice(), block(i as u64 + 1).as_slice());
+    }
+
+    // an empty export has the header only
+    assert!(read_bin(write_bin(&chain_spec_hash, 0)).unwrap().is_empty());
+}
+
+#[test]
+fn bin_format_checksum_mismatch() {
+    let mut bytes = write_bin(&packed::Byte32::zero(), 150);
+    // corrupt the last byte of the first block
+    let pos = 8 + 1 + 32 + 1 + 4 + block(1).as_slice().len() - 1;
+    bytes[pos] ^= 1;
+    let err = read_bin(bytes).unwrap_err();
+    assert!(err.contains("checksum mismatch"), "{err}");
+}
+
+#[test]
+fn bin_format_truncated_final_chunk() {
+    let bytes = write_bin(&packed::Byte32::zero(), 150);
+
+    // the final checksum is missing
+    let mut reader = BinReader::new(
+        Cursor::new(bytes[..bytes.len() - 33].to_vec()),
+        MAX_BLOCK_LEN,
+    )
+    .unwrap();
+    assert_eq!(reader.next_chunk().unwrap().len(), 100);
+    let err = reader.next_chunk().unwrap_err().to_string();
+    assert!(err.contains("truncated"), "{err}");
+
+    // the final block is cut in the middle
+    let mut reader = BinReader::new(
+        Cursor::new(bytes[..bytes.len() - 40].to_vec()),
+        MAX_BLOCK_LEN,
+    )
+    .unwrap();
+    assert_eq!(reader.next_chunk().unwrap().len(), 100);
+    assert!(reader.next_chunk().is_err());
+}
+
+#[test]
+fn bin_format_block_too_long() {
+    let mut bytes = write_bin(&packed::Byte32::zero(), 1);
+    // the length of the first block
+    let pos = 8 + 1 + 32 + 1;
+    bytes[pos..pos + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = read_bin(bytes).unwrap_err();
+    assert!(err.contains("exceeds the max block length"), "{err}");
+}
+
+#[test]
+fn import_rejects_wrong_chain_spec() {
+    let (shared, mut pack) = SharedBuilder::with_temp_db().build().unwrap();
+    let chain = ChainService::new(shared.clone(), pack.take_proposal_table()).start::<&str>(None);
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let source = tmp_dir.path().join("blocks.bin");
+    std::fs::write(&source, write_bin(&packed::Byte32::zero(), 1)).unwrap();
+
+    let import = Import::new(
+        chain,
+        shared.clone(),
+        source,
+        packed::Byte32::new([1u8; 32]),
+    );
+    let err = import.execute().unwrap_err().to_string();
+    assert!(err.contains("chain spec"), "{err}");
+    assert_eq!(shared.snapshot().tip_number(), 0);
+}
+
+#[test]
+fn import_resumes_from_the_imported_blocks() {
+    let (shared, mut pack) = SharedBuilder::with_temp_db().build().unwrap();
+    let chain = ChainService::new(shared.clone(), pack.take_proposal_table()).start::<&str>(None);
+    let genesis = shared
+        .store()
+        .get_block_header(&shared.store().get_block_hash(0).unwrap())
+        .unwrap();
+    let mock_store = MockStore::new(&genesis, shared.store());
+    let mut mock_chain = MockChain::new(genesis.clone(), shared.consensus());
+    for _ in 0..6 {
+        mock_chain.gen_empty_block(&mock_store);
+    }
+    let chain_spec_hash = packed::Byte32::zero();
+    let write_blocks = |path: &PathBuf, blocks: &[core::BlockView]| {
+        let mut bytes = Vec::new();
+        let mut writer = BinWriter::new(&mut bytes, &chain_spec_hash).unwrap();
+        for block in blocks {
+            writer.write_block(&block.data()).unwrap();
+        }
+        writer.finish().unwrap();
+        std::fs::write(path, bytes).unwrap();
+    };
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let source = tmp_dir.path().join("blocks.bin");
+    let import = Import::new(
+        chain,
+        shared.clone(),
+        source.clone(),
+        chain_spec_hash.clone(),
+    );
+
+    // the import was interrupted after 3 blocks
+    write_blocks(&source, &mock_chain.blocks()[..3]);
+    assert_eq!(import.read_from_bin().unwrap(), 3);
+    assert_eq!(shared.snapshot().tip_number(), 3);
+    assert!(import.is_imported(0, &genesis.hash()));
+    for (i, block) in mock_chain.blocks().iter().enumerate() {
+        assert_eq!(import.is_imported(block.number(), &block.hash()), i < 3);
+    }
+
+    // the second run skips the imported blocks
+    write_blocks(&source, mock_chain.blocks());
+    assert_eq!(import.read_from_bin().unwrap(), 3);
+    assert_eq!(shared.snapshot().tip_number(), 6);
+    assert_eq!(import.read_from_bin().unwrap(), 0);
+
+    // a block at an imported height but not in the main chain is not skipped
+    let fork: core::BlockView = block(1).into_view();
+    assert!(!import.is_imported(1, &fork.hash()));
+}
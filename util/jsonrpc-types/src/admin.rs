@@ -0,0 +1,80 @@
+use crate::{BlockNumber, JsonBytes, Timestamp, Uint64};
+use ckb_types::H256;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The summary of a database backup.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BackupInfo {
+    /// The backup directory in the server running the CKB node.
+    pub path: String,
+    /// The number of the tip block in the backup.
+    pub tip_number: BlockNumber,
+    /// The hash of the tip block in the backup.
+    pub tip_hash: H256,
+    /// The number of the items in the freezer copy, zero if the freezer is disabled.
+    ///
+    /// The blocks below it are read from the freezer copy after restoring.
+    pub frozen_number: Uint64,
+    /// The creation time of the backup.
+    pub created_at: Timestamp,
+}
+
+/// The parameters of a manual database compaction.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CompactionParams {
+    /// The column families to compact, referred by their names, e.g. "block_body", or their ids.
+    ///
+    /// All the column families are compacted if it is absent or empty.
+    pub columns: Option<Vec<String>>,
+    /// The first key of the compacted range, unbounded if absent.
+    pub start_key: Option<JsonBytes>,
+    /// The key after the compacted range, unbounded if absent.
+    pub end_key: Option<JsonBytes>,
+    /// The pause between the compaction steps in milliseconds, not throttled if absent.
+    ///
+    /// A throttled compaction splits the key range of every column family by the first byte of
+    /// the keys, into at most 256 sub-ranges.
+    pub throttle: Option<Uint64>,
+    /// Whether to run the compaction in the background, default is false.
+    ///
+    /// The RPC returns immediately for a background compaction, the progress is queried by
+    /// `get_compaction_status`.
+    pub background: Option<bool>,
+}
+
+/// The state of a manual database compaction.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionState {
+    /// The compaction is running.
+    Running,
+    /// The compaction has completed all the steps.
+    Done,
+    /// The compaction is cancelled because the node is shutting down.
+    Cancelled,
+    /// The compaction has failed, see the field `error`.
+    Failed,
+}
+
+/// The progress of a manual database compaction.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CompactionStatus {
+    /// The state of the compaction.
+    pub state: CompactionState,
+    /// The names of the compacted column families.
+    pub columns: Vec<String>,
+    /// The number of the compaction steps.
+    pub total_steps: Uint64,
+    /// The number of the completed steps.
+    pub completed_steps: Uint64,
+    /// The name of the column family being compacted, absent if the compaction is not running.
+    pub current_column: Option<String>,
+    /// The start time of the compaction.
+    pub started_at: Timestamp,
+    /// The finish time of the compaction, absent if the compaction is running.
+    pub finished_at: Option<Timestamp>,
+    /// The error message if the compaction has failed.
+    pub error: Option<String>,
+}
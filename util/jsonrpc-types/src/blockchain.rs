@@ -1,8 +1,8 @@
 use crate::bytes::JsonBytes;
 use crate::{
     BlockNumber, Byte32, Capacity, Cycle, DeploymentPos, EpochNumber, EpochNumberWithFraction,
-    ProposalShortId, ResponseFormat, ResponseFormatInnerType, Timestamp, Uint128, Uint32, Uint64,
-    Version,
+    ProposalShortId, RemovedTransaction, ResponseFormat, ResponseFormatInnerType, Timestamp,
+    Uint128, Uint32, Uint64, Version,
 };
 use ckb_types::core::tx_pool;
 use ckb_types::utilities::MerkleProof as RawMerkleProof;
@@ -535,6 +535,9 @@ pub struct TransactionWithStatusResponse {
     pub fee: Option<Capacity>,
     /// The minimal fee required to replace this transaction
     pub min_replace_fee: Option<Capacity>,
+    /// If the transaction has been recently removed from the tx-pool, the record of the removal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removal: Option<RemovedTransaction>,
 }
 
 impl TransactionWithStatusResponse {
@@ -551,6 +554,7 @@ impl TransactionWithStatusResponse {
                 time_added_to_pool: t.time_added_to_pool.map(Into::into),
                 fee: t.fee.map(Into::into),
                 min_replace_fee: t.min_replace_fee.map(Into::into),
+                removal: t.removal.map(Into::into),
             },
             ResponseFormatInnerType::Json => TransactionWithStatusResponse {
                 transaction: t
@@ -561,6 +565,7 @@ impl TransactionWithStatusResponse {
                 time_added_to_pool: t.time_added_to_pool.map(Into::into),
                 fee: t.fee.map(Into::into),
                 min_replace_fee: t.min_replace_fee.map(Into::into),
+                removal: t.removal.map(Into::into),
             },
         }
     }
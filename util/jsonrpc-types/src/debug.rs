@@ -1,3 +1,4 @@
+use crate::Uint64;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -56,3 +57,48 @@ pub struct MainLoggerConfig {
     /// `null` means keeping the current option unchanged.
     pub color: Option<bool>,
 }
+
+/// The disk usage statistics of the chain database and the freezer.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct DBStats {
+    /// The statistics of all the column families.
+    pub columns: Vec<ColumnFamilyStats>,
+    /// The statistics of the freezer tables, empty if the freezer is disabled.
+    pub freezer: Vec<FreezerTableStats>,
+}
+
+/// The disk usage statistics of a RocksDB column family.
+///
+/// The compression ratio of the column family is `raw_data_size / data_blocks_size`.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct ColumnFamilyStats {
+    /// The column family id, such as "1".
+    pub column: String,
+    /// The column family name, such as "block_header".
+    pub name: String,
+    /// The estimated number of keys.
+    pub estimate_num_keys: Uint64,
+    /// The total size of the live SST files, in bytes.
+    pub live_sst_files_size: Uint64,
+    /// The estimated bytes which compactions need to rewrite.
+    pub estimate_pending_compaction_bytes: Uint64,
+    /// The uncompressed size of the keys and the values in the SST files, in bytes.
+    pub raw_data_size: Uint64,
+    /// The size of the data blocks in the SST files after compression, in bytes.
+    pub data_blocks_size: Uint64,
+}
+
+/// The disk usage statistics of a freezer table.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct FreezerTableStats {
+    /// The table name, "blocks" for the frozen blocks.
+    pub name: String,
+    /// The total item number in the table.
+    pub number: Uint64,
+    /// The number of the data files.
+    pub files: Uint64,
+    /// The total size of the data files, in bytes.
+    pub data_size: Uint64,
+    /// The size of the index file, in bytes.
+    pub index_size: Uint64,
+}
@@ -1,4 +1,4 @@
-use crate::{Cycle, OutPoint};
+use crate::{Cycle, OutPoint, Uint32};
 use ckb_types::H256;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -10,6 +10,76 @@ pub struct EstimateCycles {
     pub cycles: Cycle,
 }
 
+/// Response result of the RPC method `simulate_transaction`.
+///
+/// ## Examples
+///
+/// ```
+/// # serde_json::from_str::<ckb_jsonrpc_types::TransactionSimulation>(r#"
+/// {
+///   "cycles": "0x219",
+///   "script_groups": [
+///     {
+///       "group_type": "lock",
+///       "script_hash": "0x6e4ac4bd2bb3e6bb5c6ae67cbd4fbf8fb4a1e3a8ac8d2c4b1b1c6fa1d7a1f9a6",
+///       "input_indices": ["0x0"],
+///       "output_indices": [],
+///       "cycles": "0x219",
+///       "exit_code": 0,
+///       "error": null,
+///       "debug_output": ["verifying the signature"]
+///     }
+///   ]
+/// }
+/// # "#).unwrap();
+/// ```
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+pub struct TransactionSimulation {
+    /// The total cycles consumed by the script groups.
+    pub cycles: Cycle,
+    /// The simulation results of the script groups, the lock script groups first.
+    pub script_groups: Vec<ScriptGroupSimulation>,
+}
+
+/// The simulation result of a script group.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+pub struct ScriptGroupSimulation {
+    /// The script group type.
+    pub group_type: ScriptGroupType,
+    /// The hash of the script shared by the group.
+    pub script_hash: H256,
+    /// The indices of the inputs in the group.
+    pub input_indices: Vec<Uint32>,
+    /// The indices of the outputs in the group.
+    pub output_indices: Vec<Uint32>,
+    /// The cycles consumed by the script.
+    ///
+    /// It is null if the script is aborted by an error, e.g., exceeding the maximum cycles.
+    pub cycles: Option<Cycle>,
+    /// The exit code of the script.
+    ///
+    /// It is null if the script is aborted by an error, e.g., exceeding the maximum cycles.
+    pub exit_code: Option<i8>,
+    /// The error message, null if the script succeeds.
+    pub error: Option<String>,
+    /// The messages printed by the script via the debug syscall.
+    pub debug_output: Vec<String>,
+}
+
+/// The script group type.
+///
+/// It's an enum value from one of:
+///   - lock : Lock script group.
+///   - type : Type script group.
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptGroupType {
+    /// Lock script group.
+    Lock,
+    /// Type script group.
+    Type,
+}
+
 /// An enum to represent the two kinds of dao withdrawal amount calculation option.
 /// `DaoWithdrawingCalculationKind` is equivalent to [`H256`] `|` [`OutPoint`].
 ///
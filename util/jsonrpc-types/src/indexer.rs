@@ -253,7 +253,7 @@ pub struct IndexerTxWithCells {
 }
 
 /// Cell type
-#[derive(Serialize, Clone, JsonSchema, Debug)]
+#[derive(Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum IndexerCellType {
     /// Input
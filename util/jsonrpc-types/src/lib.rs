@@ -1,4 +1,5 @@
 //! Wrappers for JSON serialization.
+mod admin;
 mod alert;
 mod block_template;
 mod blockchain;
@@ -21,6 +22,7 @@ mod uints;
 #[cfg(test)]
 mod tests;
 
+pub use self::admin::{BackupInfo, CompactionParams, CompactionState, CompactionStatus};
 pub use self::alert::{Alert, AlertId, AlertMessage, AlertPriority};
 pub use self::block_template::{
     BlockTemplate, CellbaseTemplate, TransactionTemplate, UncleTemplate,
@@ -35,8 +37,13 @@ pub use self::blockchain::{
 };
 pub use self::bytes::JsonBytes;
 pub use self::cell::{CellData, CellInfo, CellWithStatus};
-pub use self::debug::{ExtraLoggerConfig, MainLoggerConfig};
-pub use self::experiment::{DaoWithdrawingCalculationKind, EstimateCycles};
+pub use self::debug::{
+    ColumnFamilyStats, DBStats, ExtraLoggerConfig, FreezerTableStats, MainLoggerConfig,
+};
+pub use self::experiment::{
+    DaoWithdrawingCalculationKind, EstimateCycles, ScriptGroupSimulation, ScriptGroupType,
+    TransactionSimulation,
+};
 pub use self::fee_rate::FeeRateDef;
 pub use self::fixed_bytes::Byte32;
 pub use self::info::{ChainInfo, DeploymentInfo, DeploymentPos, DeploymentState, DeploymentsInfo};
@@ -46,10 +53,13 @@ pub use self::net::{
 };
 pub use self::pool::{
     AncestorsScoreSortKey, OutputsValidator, PoolTransactionEntry, PoolTransactionReject,
-    PoolTxDetailInfo, RawTxPool, TxPoolEntries, TxPoolEntry, TxPoolIds, TxPoolInfo,
+    PoolTxDetailInfo, RawTxPool, RawTxPoolWithSequence, RemovalReason, RemovedTransaction,
+    TxPoolEntries, TxPoolEntry, TxPoolIds, TxPoolInfo,
 };
 pub use self::proposal_short_id::ProposalShortId;
-pub use self::subscription::Topic;
+pub use self::subscription::{
+    ChainReorg, CommittedTransaction, PoolTransactionChange, PoolTransactionDiff, Topic,
+};
 pub use self::uints::{Uint128, Uint32, Uint64};
 pub use ckb_types::core::RationalU256;
 pub use indexer::{
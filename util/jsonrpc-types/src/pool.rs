@@ -2,7 +2,8 @@ use crate::{BlockNumber, Capacity, Cycle, Timestamp, TransactionView, Uint64};
 use ckb_types::core::service::PoolTransactionEntry as CorePoolTransactionEntry;
 use ckb_types::core::tx_pool::{
     AncestorsScoreSortKey as CoreAncestorsScoreSortKey, PoolTxDetailInfo as CorePoolTxDetailInfo,
-    Reject, TxEntryInfo, TxPoolEntryInfo, TxPoolIds as CoreTxPoolIds, TxPoolInfo as CoreTxPoolInfo,
+    Reject, RemovalReason as CoreRemovalReason, RemovedTransaction as CoreRemovedTransaction,
+    TxEntryInfo, TxPoolEntryInfo, TxPoolIds as CoreTxPoolIds, TxPoolInfo as CoreTxPoolInfo,
 };
 use ckb_types::prelude::Unpack;
 use ckb_types::H256;
@@ -225,6 +226,18 @@ pub enum RawTxPool {
     Verbose(TxPoolEntries),
 }
 
+/// The raw tx-pool together with the sequence of the last change applied to it.
+///
+/// Together with the subscription topic `pool_diff`, it can be used to keep a copy of the
+/// tx-pool: apply the changes whose sequence is greater than `sequence` in order.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, JsonSchema)]
+pub struct RawTxPoolWithSequence {
+    /// The sequence of the last change applied to the pool.
+    pub sequence: Uint64,
+    /// The transactions in the pool.
+    pub pool: RawTxPool,
+}
+
 /// A struct as a sorted key for tx-pool
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, JsonSchema)]
 pub struct AncestorsScoreSortKey {
@@ -350,3 +363,104 @@ impl From<Reject> for PoolTransactionReject {
         }
     }
 }
+
+/// The reason why a transaction was removed from the tx-pool.
+///
+/// It's an enum value from one of:
+///
+/// * "expired" - Stayed in the pool longer than the config option `tx_pool.expiry_hours`.
+/// * "evicted" - Evicted to keep the pool size under the config option `tx_pool.max_tx_pool_size`.
+/// * "replaced" - Replaced by a transaction paying a higher fee (RBF).
+/// * "conflicted" - Its input or cell dep was consumed by a committed transaction.
+/// * "invalidated" - Its cell dep was consumed by a transaction entering the pool.
+/// * "detached" - Dropped by a reorg which detached the block it depended on.
+/// * "removed" - Removed manually, or dropped by the block assembler.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalReason {
+    /// Stayed in the pool longer than the config option `tx_pool.expiry_hours`.
+    Expired,
+    /// Evicted to keep the pool size under the config option `tx_pool.max_tx_pool_size`.
+    Evicted,
+    /// Replaced by a transaction paying a higher fee (RBF).
+    Replaced,
+    /// Its input or cell dep was consumed by a committed transaction.
+    Conflicted,
+    /// Its cell dep was consumed by a transaction entering the pool.
+    Invalidated,
+    /// Dropped by a reorg which detached the block it depended on.
+    Detached,
+    /// Removed manually, or dropped by the block assembler.
+    Removed,
+}
+
+impl From<CoreRemovalReason> for RemovalReason {
+    fn from(reason: CoreRemovalReason) -> Self {
+        match reason {
+            CoreRemovalReason::Expired => RemovalReason::Expired,
+            CoreRemovalReason::Evicted => RemovalReason::Evicted,
+            CoreRemovalReason::Replaced => RemovalReason::Replaced,
+            CoreRemovalReason::Conflicted => RemovalReason::Conflicted,
+            CoreRemovalReason::Invalidated => RemovalReason::Invalidated,
+            CoreRemovalReason::Detached => RemovalReason::Detached,
+            CoreRemovalReason::Removed => RemovalReason::Removed,
+        }
+    }
+}
+
+/// The record of a transaction removed from the tx-pool.
+///
+/// ## Examples
+///
+/// ```
+/// # serde_json::from_str::<ckb_jsonrpc_types::RemovedTransaction>(r#"
+/// {
+///   "tx_hash": "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3",
+///   "reason": "replaced",
+///   "description": "RBF rejected: replaced by tx Byte32(0x0e081e2bb2717be90798e553574a37f6ef03c217a7ffdbc3edf5823cb694f702)",
+///   "conflicting_tx": "0x0e081e2bb2717be90798e553574a37f6ef03c217a7ffdbc3edf5823cb694f702",
+///   "removed_descendants": [],
+///   "block_number": "0x400",
+///   "fee_rate": "0x52607f1408",
+///   "timestamp": "0x17c983e6e44"
+/// }
+/// # "#).unwrap();
+/// ```
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+pub struct RemovedTransaction {
+    /// The removed transaction hash.
+    pub tx_hash: H256,
+    /// The reason code.
+    pub reason: RemovalReason,
+    /// The detailed description of the reason.
+    pub description: String,
+    /// The transaction which replaced, conflicted with or invalidated the removed one.
+    pub conflicting_tx: Option<H256>,
+    /// The descendants of the removed transaction which were removed together with it.
+    pub removed_descendants: Vec<H256>,
+    /// The tip block number when the transaction was removed.
+    pub block_number: BlockNumber,
+    /// The fee rate of the transaction in shannons per kilo-weight.
+    pub fee_rate: Uint64,
+    /// The unix timestamp when the transaction was removed, unit: Millisecond.
+    pub timestamp: Timestamp,
+}
+
+impl From<CoreRemovedTransaction> for RemovedTransaction {
+    fn from(removed: CoreRemovedTransaction) -> Self {
+        RemovedTransaction {
+            tx_hash: removed.tx_hash.unpack(),
+            reason: removed.reason.into(),
+            description: removed.description,
+            conflicting_tx: removed.conflicting_tx.map(|hash| hash.unpack()),
+            removed_descendants: removed
+                .removed_descendants
+                .iter()
+                .map(Unpack::unpack)
+                .collect(),
+            block_number: removed.block_number.into(),
+            fee_rate: removed.fee_rate.as_u64().into(),
+            timestamp: removed.timestamp.into(),
+        }
+    }
+}
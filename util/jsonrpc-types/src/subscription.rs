@@ -1,3 +1,9 @@
+use crate::{
+    BlockNumber, HeaderView, IndexerCellType, PoolTransactionEntry, PoolTransactionReject,
+    TransactionView, Uint32, Uint64,
+};
+use ckb_types::{core, prelude::Unpack, H256};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Specifies the topic which to be added as active subscription.
@@ -14,4 +20,123 @@ pub enum Topic {
     ProposedTransaction,
     /// Subscribe transactions which are abandoned by tx-pool.
     RejectedTransaction,
+    /// Subscribe committed transactions which touch the cells matching a search key.
+    CommittedTransaction,
+    /// Subscribe chain reorganizations which detach blocks from the canonical chain.
+    ChainReorg,
+    /// Subscribe all the changes of the tx-pool, ordered by sequence.
+    PoolDiff,
+}
+
+/// A committed transaction which matches the search key of a `committed_transaction` subscription.
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct CommittedTransaction {
+    /// The committed transaction.
+    pub transaction: TransactionView,
+    /// The hash of the block which commits the transaction.
+    pub block_hash: H256,
+    /// The number of the block which commits the transaction.
+    pub block_number: BlockNumber,
+    /// The position index of the transaction committed in the block.
+    pub tx_index: Uint32,
+    /// Array [(io_type, io_index)] of the cells matching the search key.
+    pub cells: Vec<(IndexerCellType, Uint32)>,
+    /// The indices of the inputs whose previous outputs are unknown, because the transactions
+    /// creating them have been pruned. These inputs are not matched against the search key.
+    pub unresolved_inputs: Vec<Uint32>,
+}
+
+/// A chain reorganization, which switches the canonical chain to another fork.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+pub struct ChainReorg {
+    /// The header of the latest block shared by the old and the new canonical chain.
+    pub common_ancestor: HeaderView,
+    /// The hashes of the blocks removed from the canonical chain, in ascending order of block number.
+    pub detached_blocks: Vec<H256>,
+    /// The hashes of the blocks added to the canonical chain, in ascending order of block number.
+    pub attached_blocks: Vec<H256>,
+}
+
+impl From<core::service::ChainReorg> for ChainReorg {
+    fn from(reorg: core::service::ChainReorg) -> Self {
+        ChainReorg {
+            common_ancestor: reorg.common_ancestor.into(),
+            detached_blocks: reorg.detached_blocks.iter().map(Unpack::unpack).collect(),
+            attached_blocks: reorg.attached_blocks.iter().map(Unpack::unpack).collect(),
+        }
+    }
+}
+
+/// The change of a transaction in the tx-pool.
+///
+/// ## Examples
+///
+/// ```
+/// # serde_json::from_str::<ckb_jsonrpc_types::PoolTransactionChange>(r#"
+/// {
+///   "type": "pending"
+/// }
+/// # "#).unwrap();
+/// ```
+///
+/// ```
+/// # serde_json::from_str::<ckb_jsonrpc_types::PoolTransactionChange>(r#"
+/// {
+///   "type": "rejected",
+///   "reason": {
+///     "type": "Expiry",
+///     "description": "Expiry transaction, timestamp 1681207305000"
+///   }
+/// }
+/// # "#).unwrap();
+/// ```
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+#[serde(tag = "type", content = "reason", rename_all = "snake_case")]
+pub enum PoolTransactionChange {
+    /// The transaction entered the pending pool.
+    Pending,
+    /// The transaction was proposed but can't be committed yet.
+    Gap,
+    /// The transaction entered the proposed pool.
+    Proposed,
+    /// The transaction was removed from the pool because it has been committed.
+    Committed,
+    /// The transaction was removed from the pool with the reject reason.
+    Rejected(PoolTransactionReject),
+    /// The transaction was removed from the pool manually or by clearing the pool.
+    Removed,
+}
+
+impl From<core::service::PoolTransactionChange> for PoolTransactionChange {
+    fn from(change: core::service::PoolTransactionChange) -> Self {
+        match change {
+            core::service::PoolTransactionChange::Pending => Self::Pending,
+            core::service::PoolTransactionChange::Gap => Self::Gap,
+            core::service::PoolTransactionChange::Proposed => Self::Proposed,
+            core::service::PoolTransactionChange::Committed => Self::Committed,
+            core::service::PoolTransactionChange::Rejected(reject) => Self::Rejected(reject.into()),
+            core::service::PoolTransactionChange::Removed => Self::Removed,
+        }
+    }
+}
+
+/// A change of the tx-pool published to the `pool_diff` subscription.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct PoolTransactionDiff {
+    /// The sequence of the change, which increases by one for each change of the tx-pool.
+    pub sequence: Uint64,
+    /// The changed transaction.
+    pub transaction: PoolTransactionEntry,
+    /// The change.
+    pub change: PoolTransactionChange,
+}
+
+impl From<core::service::PoolTransactionDiff> for PoolTransactionDiff {
+    fn from(diff: core::service::PoolTransactionDiff) -> Self {
+        PoolTransactionDiff {
+            sequence: diff.sequence.into(),
+            transaction: diff.entry.into(),
+            change: diff.change.into(),
+        }
+    }
 }
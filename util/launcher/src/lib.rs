@@ -12,7 +12,7 @@ use ckb_chain::chain::{ChainController, ChainService};
 use ckb_channel::Receiver;
 use ckb_jsonrpc_types::ScriptHashType;
 use ckb_light_client_protocol_server::LightClientProtocol;
-use ckb_logger::info;
+use ckb_logger::{info, warn};
 use ckb_network::{
     observe_listen_port_occupancy, CKBProtocol, Flags, NetworkController, NetworkService,
     NetworkState, SupportProtocols,
@@ -203,6 +203,7 @@ impl Launcher {
             .notify_config(self.args.config.notify.clone())
             .store_config(self.args.config.store)
             .block_assembler_config(block_assembler_config)
+            .cell_snapshot(self.args.config.network.sync.cell_snapshot.clone())
             .build()?;
 
         // internal check migrate_version
@@ -255,13 +256,30 @@ impl Launcher {
         Ok(())
     }
 
+    fn is_pruned(&self, shared: &Shared) -> bool {
+        self.args.config.store.prune_depth.is_some()
+            || shared.store().get_pruned_block_number().is_some()
+    }
+
+    // The block filter and the light client protocols are built on the block bodies, they are
+    // disabled when the bodies are pruned.
+    fn support_protocols(&self, shared: &Shared) -> Vec<SupportProtocol> {
+        let mut support_protocols = self.args.config.network.support_protocols.clone();
+        if self.is_pruned(shared) {
+            support_protocols.retain(|protocol| {
+                !matches!(
+                    protocol,
+                    SupportProtocol::Filter | SupportProtocol::LightClient
+                )
+            });
+        }
+        support_protocols
+    }
+
     /// start block filter service
     pub fn start_block_filter(&self, shared: &Shared) {
         if self
-            .args
-            .config
-            .network
-            .support_protocols
+            .support_protocols(shared)
             .contains(&SupportProtocol::Filter)
         {
             BlockFilterService::new(shared.clone()).start();
@@ -304,7 +322,7 @@ impl Launcher {
             Arc::clone(&network_state),
         )];
 
-        let support_protocols = &self.args.config.network.support_protocols;
+        let support_protocols = &self.support_protocols(shared);
         let mut flags = Flags::all();
 
         if support_protocols.contains(&SupportProtocol::Relay) {
@@ -358,6 +376,12 @@ impl Launcher {
             flags.remove(Flags::LIGHT_CLIENT);
         }
 
+        if self.is_pruned(shared) {
+            warn!("The Filter and LightClient protocols are disabled since the block bodies are pruned");
+        } else {
+            flags.remove(Flags::PRUNED);
+        }
+
         let alert_signature_config = self.args.config.alert_signature.clone().unwrap_or_default();
         let alert_relayer = AlertRelayer::new(
             self.version.short(),
@@ -422,12 +446,13 @@ impl Launcher {
                 &self.args.config.db,
                 &self.args.config.indexer,
             )
-            .enable_debug();
+            .enable_debug(shared.clone())
+            .enable_admin(shared.clone());
         builder.enable_subscription(shared.clone());
-        let io_handler = builder.build();
+        let access_control = builder.build();
 
         let async_handle = shared.async_handle();
-        let _rpc = RpcServer::new(rpc_config, io_handler, async_handle.clone());
+        let _rpc = RpcServer::new(rpc_config, access_control, async_handle.clone());
 
         network_controller
     }
@@ -7,8 +7,9 @@
 //! [`ckb-metrics-service`]: ../ckb_metrics_service/index.html
 
 use prometheus::{
-    register_histogram, register_histogram_vec, register_int_counter, register_int_gauge,
-    register_int_gauge_vec, Histogram, HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+    register_gauge_vec, register_histogram, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, register_int_gauge, register_int_gauge_vec, GaugeVec, Histogram,
+    HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use prometheus_static_metric::make_static_metric;
 use std::cell::Cell;
@@ -81,8 +82,22 @@ pub struct Metrics {
     pub ckb_message_bytes: HistogramVec,
     /// Gauge for CKB rocksdb statistics
     pub ckb_sys_mem_rocksdb: IntGaugeVec,
+    /// Gauge for the disk usage statistics of the CKB rocksdb column families
+    pub ckb_db_cf_stats: IntGaugeVec,
+    /// Gauge for the compression ratio of the CKB rocksdb column families
+    pub ckb_db_cf_compression_ratio: GaugeVec,
+    /// Gauge for the disk usage statistics of the CKB freezer tables
+    pub ckb_freezer_table_stats: IntGaugeVec,
     /// Counter for CKB network ban peers
     pub ckb_network_ban_peer: IntCounter,
+    /// Histogram for RPC call duration, in seconds
+    pub ckb_rpc_call_duration: HistogramVec,
+    /// Counter for RPC calls, the code is "0" when the call succeeds
+    pub ckb_rpc_calls: IntCounterVec,
+    /// Histogram for RPC response bytes
+    pub ckb_rpc_response_bytes: HistogramVec,
+    /// Histogram for the number of calls in an RPC request
+    pub ckb_rpc_batch_size: HistogramVec,
 }
 
 static METRICS: once_cell::sync::Lazy<Metrics> = once_cell::sync::Lazy::new(|| Metrics {
@@ -169,11 +184,59 @@ static METRICS: once_cell::sync::Lazy<Metrics> = once_cell::sync::Lazy::new(|| M
         &["type", "cf"]
     )
     .unwrap(),
+    ckb_db_cf_stats: register_int_gauge_vec!(
+        "ckb_db_cf_stats",
+        "CKB rocksdb column family disk usage statistics",
+        &["type", "cf"]
+    )
+    .unwrap(),
+    ckb_db_cf_compression_ratio: register_gauge_vec!(
+        "ckb_db_cf_compression_ratio",
+        "CKB rocksdb column family compression ratio of the SST files",
+        &["cf"]
+    )
+    .unwrap(),
+    ckb_freezer_table_stats: register_int_gauge_vec!(
+        "ckb_freezer_table_stats",
+        "CKB freezer table disk usage statistics",
+        &["type", "table"]
+    )
+    .unwrap(),
     ckb_network_ban_peer: register_int_counter!(
         "ckb_network_ban_peer",
         "CKB network baned peer count"
     )
     .unwrap(),
+    ckb_rpc_call_duration: register_histogram_vec!(
+        "ckb_rpc_call_duration",
+        "The CKB RPC call duration in seconds",
+        &["method"],
+        vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0]
+    )
+    .unwrap(),
+    ckb_rpc_calls: register_int_counter_vec!(
+        "ckb_rpc_calls",
+        "The CKB RPC calls count",
+        &["method", "code"]
+    )
+    .unwrap(),
+    ckb_rpc_response_bytes: register_histogram_vec!(
+        "ckb_rpc_response_bytes",
+        "The CKB RPC response bytes",
+        &["method"],
+        vec![
+            100.0, 500.0, 1000.0, 5000.0, 10000.0, 50000.0, 100000.0, 500000.0, 1000000.0,
+            5000000.0
+        ]
+    )
+    .unwrap(),
+    ckb_rpc_batch_size: register_histogram_vec!(
+        "ckb_rpc_batch_size",
+        "The number of calls in a CKB RPC request",
+        &["transport"],
+        vec![1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0]
+    )
+    .unwrap(),
 });
 
 /// Indicate whether the metrics service is enabled.
@@ -5,10 +5,7 @@ use ckb_chain_spec::{
     consensus::{Consensus, ConsensusProvider},
     versionbits::{DeploymentPos, ThresholdState, VersionbitsIndexer},
 };
-use ckb_db::{
-    iter::{DBIter, IteratorMode},
-    DBPinnableSlice,
-};
+use ckb_db::{iter::IteratorMode, DBValue, KeyValueIter};
 use ckb_db_schema::Col;
 use ckb_freezer::Freezer;
 use ckb_merkle_mountain_range::{
@@ -189,7 +186,7 @@ impl ChainStore for Snapshot {
         self.store.cache()
     }
 
-    fn get(&self, col: Col, key: &[u8]) -> Option<DBPinnableSlice> {
+    fn get(&self, col: Col, key: &[u8]) -> Option<DBValue> {
         self.store.get(col, key)
     }
 
@@ -197,7 +194,7 @@ impl ChainStore for Snapshot {
         self.store.freezer()
     }
 
-    fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter {
+    fn get_iter(&self, col: Col, mode: IteratorMode) -> KeyValueIter {
         self.store.get_iter(col, mode)
     }
 
@@ -2,7 +2,8 @@
 //!
 //! A CKB service acts as an actor, which processes requests from a channel and sends back the
 //! response via one shot channel.
-use crate::core::{Capacity, Cycle, TransactionView};
+use crate::core::{tx_pool::Reject, Capacity, Cycle, HeaderView, TransactionView};
+use crate::packed::Byte32;
 use ckb_channel::Sender;
 use std::sync::mpsc;
 /// Default channel size to send control signals.
@@ -44,3 +45,45 @@ pub struct PoolTransactionEntry {
     /// The unix timestamp when entering the Txpool, unit: Millisecond
     pub timestamp: u64,
 }
+
+/// The change of a transaction in the tx-pool
+#[derive(Debug, Clone)]
+pub enum PoolTransactionChange {
+    /// Entered the pending pool
+    Pending,
+    /// Moved into the gap, i.e. proposed but can't be committed yet
+    Gap,
+    /// Moved into the proposed pool
+    Proposed,
+    /// Removed from the pool because it has been committed
+    Committed,
+    /// Removed from the pool with the reject reason
+    Rejected(Reject),
+    /// Removed from the pool manually or by the pool clearing
+    Removed,
+}
+
+/// Notify tx-pool change
+///
+/// The sequence increases by one for each change, so it can be used to apply the changes upon
+/// a tx-pool snapshot in order and to detect the missed ones.
+#[derive(Debug, Clone)]
+pub struct PoolTransactionDiff {
+    /// The sequence of the change
+    pub sequence: u64,
+    /// The changed transaction
+    pub entry: PoolTransactionEntry,
+    /// The change
+    pub change: PoolTransactionChange,
+}
+
+/// Notify chain reorganization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainReorg {
+    /// The latest block shared by the old and the new main chain
+    pub common_ancestor: HeaderView,
+    /// Hashes of the blocks removed from the main chain, in ascending order of block number
+    pub detached_blocks: Vec<Byte32>,
+    /// Hashes of the blocks added to the main chain, in ascending order of block number
+    pub attached_blocks: Vec<Byte32>,
+}
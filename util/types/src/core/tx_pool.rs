@@ -126,6 +126,46 @@ pub enum TxStatus {
     Rejected(String),
 }
 
+/// The reason why a transaction was removed from the tx-pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RemovalReason {
+    /// Stayed in the pool longer than the config option `tx_pool.expiry_hours`
+    Expired,
+    /// Evicted to keep the pool size under the config option `tx_pool.max_tx_pool_size`
+    Evicted,
+    /// Replaced by a transaction paying a higher fee (RBF)
+    Replaced,
+    /// Its input or cell dep was consumed by a committed transaction
+    Conflicted,
+    /// Its cell dep was consumed by a transaction entering the pool
+    Invalidated,
+    /// Dropped by a reorg which detached the block it depended on
+    Detached,
+    /// Removed manually, or dropped by the block assembler
+    Removed,
+}
+
+/// The record of a transaction removed from the tx-pool
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemovedTransaction {
+    /// The removed transaction hash
+    pub tx_hash: Byte32,
+    /// The reason code
+    pub reason: RemovalReason,
+    /// The detailed description of the reason
+    pub description: String,
+    /// The transaction which replaced, conflicted with or invalidated the removed one
+    pub conflicting_tx: Option<Byte32>,
+    /// The descendants of the removed transaction which were removed together with it
+    pub removed_descendants: Vec<Byte32>,
+    /// The tip block number when the transaction was removed
+    pub block_number: BlockNumber,
+    /// The fee rate of the transaction
+    pub fee_rate: FeeRate,
+    /// The unix timestamp when the transaction was removed, unit: Millisecond
+    pub timestamp: u64,
+}
+
 /// Tx-pool entry info
 #[derive(Debug, PartialEq, Eq)]
 pub struct TxEntryInfo {
@@ -184,6 +224,8 @@ pub struct TransactionWithStatus {
     pub min_replace_fee: Option<Capacity>,
     /// If the transaction is in tx-pool, `time_added_to_pool` represent when it enters the tx-pool. unit: Millisecond
     pub time_added_to_pool: Option<u64>,
+    /// If the transaction has been recently removed from the tx-pool, the record of the removal
+    pub removal: Option<RemovedTransaction>,
 }
 
 impl TransactionWithStatus {
@@ -203,6 +245,7 @@ impl TransactionWithStatus {
             transaction: tx,
             cycles: Some(cycles),
             time_added_to_pool: Some(time_added_to_pool),
+            removal: None,
         }
     }
 
@@ -221,6 +264,7 @@ impl TransactionWithStatus {
             fee,
             min_replace_fee: None,
             time_added_to_pool: None,
+            removal: None,
         }
     }
 
@@ -233,6 +277,20 @@ impl TransactionWithStatus {
             fee: None,
             min_replace_fee: None,
             time_added_to_pool: None,
+            removal: None,
+        }
+    }
+
+    /// Build with rejected status from the record of the removal
+    pub fn with_removed(removal: RemovedTransaction) -> Self {
+        Self {
+            tx_status: TxStatus::Rejected(removal.description.clone()),
+            transaction: None,
+            cycles: None,
+            fee: None,
+            min_replace_fee: None,
+            time_added_to_pool: None,
+            removal: Some(removal),
         }
     }
 
@@ -245,6 +303,7 @@ impl TransactionWithStatus {
             fee: None,
             min_replace_fee: None,
             time_added_to_pool: None,
+            removal: None,
         }
     }
 
@@ -257,6 +316,7 @@ impl TransactionWithStatus {
             fee: None,
             min_replace_fee: None,
             time_added_to_pool: None,
+            removal: None,
         }
     }
 